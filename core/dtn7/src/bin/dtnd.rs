@@ -129,6 +129,14 @@ async fn main() -> Result<(), std::io::Error> {
                 .help("Sets listening port for IPND node discovery (default = 3003)")
                 .value_parser(value_parser!(u16))
                 .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("discoverybackend")
+                .long("discovery-backend")
+                .value_name("BACKEND")
+                .help("Neighbour discovery mechanism to use, can be given multiple times (ipnd, mdns; default = ipnd)")
+                .value_parser(["ipnd", "mdns"])
+                .action(ArgAction::Append),
         ).arg(
             Arg::new("webport")
                 .short('w')
@@ -351,6 +359,15 @@ Tag 255 takes 5 arguments and is interpreted as address. Usage: -S 255:'Samplest
                 humantime::parse_duration(i).expect("Could not parse interval parameter!");
         }
     }
+    if let Some(backends) = matches.get_many::<String>("discoverybackend") {
+        cfg.discovery_backends.clear();
+        for backend in backends {
+            let backend = backend.parse().expect("Invalid discovery backend");
+            if !cfg.discovery_backends.contains(&backend) {
+                cfg.discovery_backends.push(backend);
+            }
+        }
+    }
     if let Some(i) = matches.get_one::<u16>("discoveryport") {
         cfg.discovery_listen_port = *i;
     }
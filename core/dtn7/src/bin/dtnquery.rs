@@ -1,4 +1,7 @@
+use bp7::dtntime::DtnTimeHelpers;
 use clap::{Parser, Subcommand};
+use dtn7::client::{ClientError, DtnClient};
+use std::collections::HashMap;
 
 /// A simple Bundle Protocol 7 Query Utility for Delay Tolerant Networking
 #[derive(Parser, Debug)]
@@ -40,26 +43,73 @@ enum Commands {
     Info,
     /// Local node id
     Nodeid,
+    /// Lifecycle journal of a bundle, exports the journals of all bundles as JSON if omitted
+    Journal {
+        /// Bundle ID
+        bid: Option<String>,
+    },
+    /// Delivery timeline of own bundles from received status reports
+    Reports {
+        /// Bundle ID, lists all bundles with status reports if omitted
+        bid: Option<String>,
+    },
+    /// Active routing agent, replaces it at runtime if a strategy is given
+    Routing {
+        /// New routing strategy, e.g., sprayandwait
+        strategy: Option<String>,
+        /// Settings for the new agent, e.g., sprayandwait.num_copies=4
+        #[clap(short, long)]
+        set: Vec<String>,
+    },
 }
 
-fn main() {
+/// Parses `agent.key=value` settings
+fn parse_settings(
+    settings: &[String],
+) -> Result<HashMap<String, HashMap<String, String>>, ClientError> {
+    let mut parsed: HashMap<String, HashMap<String, String>> = HashMap::new();
+    for setting in settings {
+        let (agent, key, value) = setting
+            .split_once('=')
+            .and_then(|(name, value)| {
+                let (agent, key) = name.split_once('.')?;
+                Some((agent, key, value))
+            })
+            .ok_or_else(|| {
+                ClientError::Protocol(format!("expected agent.key=value, got {}", setting))
+            })?;
+        parsed
+            .entry(agent.trim().to_string())
+            .or_default()
+            .insert(key.trim().to_string(), value.trim().to_string());
+    }
+    Ok(parsed)
+}
+
+fn pretty<T: serde::Serialize>(value: T) -> String {
+    serde_json::to_string_pretty(&value).unwrap()
+}
+
+#[tokio::main]
+async fn main() -> Result<(), ClientError> {
     let args = Args::parse();
     let port = if let Ok(env_port) = std::env::var("DTN_WEB_PORT") {
-        env_port // string is fine, no need to parse number
+        env_port.parse::<u16>().expect("invalid port number")
     } else {
-        args.port.to_string()
+        args.port
     };
 
     let localhost = if args.ipv6 { "[::1]" } else { "127.0.0.1" };
+    let client = DtnClient::with_host_and_port(localhost, port);
 
-    let url = match &args.cmd {
+    let res = match &args.cmd {
         Commands::Eids => {
             println!("Listing registered endpoint IDs:");
-            format!("http://{}:{}/status/eids", localhost, port)
+            pretty(client.endpoints().await?)
         }
         Commands::Peers => {
             println!("Listing of known peers:");
-            format!("http://{}:{}/status/peers", localhost, port)
+            pretty(client.peers().await?)
         }
         Commands::Bundles {
             verbose,
@@ -68,42 +118,72 @@ fn main() {
         } => {
             println!("Listing of bundles in store:");
             if *verbose {
-                format!("http://{}:{}/status/bundles/verbose", localhost, port)
-            } else if let Some(addr) = addr {
-                if *digest {
-                    format!(
-                        "http://{}:{}/status/bundles/filtered/digest?addr={}",
-                        localhost, port, addr
-                    )
-                } else {
-                    format!(
-                        "http://{}:{}/status/bundles/filtered?addr={}",
-                        localhost, port, addr
-                    )
-                }
+                pretty(client.bundles_verbose().await?)
             } else if *digest {
-                format!("http://{}:{}/status/bundles/digest", localhost, port)
+                client.bundles_digest(addr.as_deref()).await?
+            } else if let Some(addr) = addr {
+                pretty(client.bundles_filtered(addr).await?)
             } else {
-                format!("http://{}:{}/status/bundles", localhost, port)
+                pretty(client.bundles().await?)
             }
         }
         Commands::Store => {
             println!("Listing of bundles status in store:");
-            format!("http://{}:{}/status/store", localhost, port)
+            pretty(client.store().await?)
         }
         Commands::Info => {
             println!("Daemon info:");
-            format!("http://{}:{}/status/info", localhost, port)
+            pretty(client.info().await?)
         }
         Commands::Nodeid => {
             println!("Local node ID:");
-            format!("http://{}:{}/status/nodeid", localhost, port)
+            client.local_node_id().await?.to_string()
+        }
+        Commands::Journal { bid: None } => pretty(client.journals().await?),
+        Commands::Journal { bid: Some(bid) } => pretty(client.journal(bid).await?),
+        Commands::Reports { bid: None } => {
+            println!("Listing of bundles with status reports:");
+            pretty(client.reported_bundles().await?)
+        }
+        Commands::Reports { bid: Some(bid) } => {
+            println!("Status reports of {}:", bid);
+            client
+                .status_reports(bid)
+                .await?
+                .iter()
+                .map(|report| {
+                    let mut line = format!("{:<10} {}", report.status, report.node);
+                    if report.reason != "no_information" {
+                        line.push_str(&format!(" ({})", report.reason));
+                    }
+                    if let Some(time) = report.time {
+                        line.push_str(&format!(" at {}", time.string()));
+                    }
+                    line
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+        }
+        Commands::Routing {
+            strategy: None,
+            set,
+        } => {
+            if !set.is_empty() {
+                return Err(ClientError::Protocol(
+                    "settings require a routing strategy".into(),
+                ));
+            }
+            println!("Routing agent:");
+            pretty(client.routing().await?)
+        }
+        Commands::Routing {
+            strategy: Some(strategy),
+            set,
+        } => {
+            println!("Replacing routing agent with {}:", strategy);
+            pretty(client.set_routing(strategy, parse_settings(set)?).await?)
         }
     };
-    let res = attohttpc::get(url)
-        .send()
-        .expect("error connecting to local dtnd")
-        .text()
-        .unwrap();
     println!("{}", res);
+    Ok(())
 }
@@ -1,6 +1,6 @@
 use bp7::*;
 use clap::Parser;
-use std::convert::TryFrom;
+use dtn7::client::DtnClient;
 use std::fs;
 use std::io::prelude::*;
 use std::process;
@@ -69,89 +69,78 @@ fn write_bytes(data: &[u8], possible_file: Option<String>, verbose: bool) {
             .expect("Error writing binary.");
     }
 }
-fn main() {
+fn write_payload(bndl: &Bundle, possible_file: Option<String>, verbose: bool) {
+    match bndl
+        .extension_block_by_type(bp7::canonical::PAYLOAD_BLOCK)
+        .expect("Payload block missing!")
+        .data()
+    {
+        bp7::canonical::CanonicalData::Data(data) => {
+            write_bytes(data, possible_file, verbose);
+        }
+        _ => {
+            panic!("No data in payload block!");
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
     let args = Args::parse();
 
     let port = if let Ok(env_port) = std::env::var("DTN_WEB_PORT") {
-        env_port // string is fine, no need to parse number
+        env_port.parse::<u16>().expect("invalid port number")
     } else {
-        args.port.to_string()
+        args.port
     };
     let localhost = if args.ipv6 { "[::1]" } else { "127.0.0.1" };
-    let local_url = if let Some(endpoint) = args.endpoint {
-        format!("http://{}:{}/endpoint?{}", localhost, port, endpoint)
-    } else if args.delete.is_some() {
-        format!(
-            "http://{}:{}/delete?{}",
-            localhost,
-            port,
-            args.delete.clone().unwrap()
-        )
-    } else if args.register.is_some() {
-        format!(
-            "http://{}:{}/register?{}",
-            localhost,
-            port,
-            args.register.clone().unwrap()
-        )
-    } else if args.unregister.is_some() {
-        format!(
-            "http://{}:{}/unregister?{}",
-            localhost,
-            port,
-            args.unregister.clone().unwrap()
-        )
-    } else {
-        format!(
-            "http://{}:{}/download?{}",
-            localhost,
-            port,
-            args.bid.unwrap()
-        )
-    };
-    let res = attohttpc::get(local_url)
-        .send()
-        .expect("error connecting to local dtnd");
+    let client = DtnClient::with_host_and_port(localhost, port);
 
-    if res.status() != attohttpc::StatusCode::OK {
-        if args.verbose {
-            println!("Unexpected response from server! {:?}", res);
-        }
-        process::exit(23);
-    }
-    let buf: Vec<u8> = res.bytes().expect("No bundle bytes received");
-    if args.delete.is_some() {
-        println!("Deleted bundle {}", args.delete.unwrap());
-        process::exit(0);
-    } else if args.register.is_some() {
-        println!("Registered endpoint {}", args.register.unwrap());
-        process::exit(0);
-    } else if args.unregister.is_some() {
-        println!("Unregistered endpoint {}", args.unregister.unwrap());
-        process::exit(0);
-    } else if buf.len() > 50 {
-        // TODO: very arbitrary number, should check return code
-        if args.hex {
-            println!("{}", hexify(&buf));
-        } else if args.raw {
-            write_bytes(&buf, args.outfile, args.verbose);
+    let result = if let Some(delete) = args.delete {
+        client
+            .delete_bundle(&delete)
+            .await
+            .map(|_| println!("Deleted bundle {}", delete))
+    } else if let Some(register) = args.register {
+        client
+            .register_application_endpoint(&register)
+            .await
+            .map(|_| println!("Registered endpoint {}", register))
+    } else if let Some(unregister) = args.unregister {
+        client
+            .unregister_application_endpoint(&unregister)
+            .await
+            .map(|_| println!("Unregistered endpoint {}", unregister))
+    } else {
+        let bndl = if let Some(endpoint) = args.endpoint {
+            client.fetch_bundle(&endpoint).await
         } else {
-            let bndl: Bundle = Bundle::try_from(buf).expect("Error decoding bundle");
-            match bndl
-                .extension_block_by_type(bp7::canonical::PAYLOAD_BLOCK)
-                .expect("Payload block missing!")
-                .data()
-            {
-                bp7::canonical::CanonicalData::Data(data) => {
-                    write_bytes(data, args.outfile, args.verbose);
+            client.download_bundle(&args.bid.unwrap()).await.map(Some)
+        };
+        match bndl {
+            Ok(Some(mut bndl)) => {
+                if args.hex {
+                    println!("{}", hexify(&bndl.to_cbor()));
+                } else if args.raw {
+                    write_bytes(&bndl.to_cbor(), args.outfile, args.verbose);
+                } else {
+                    write_payload(&bndl, args.outfile, args.verbose);
                 }
-                _ => {
-                    panic!("No data in payload block!");
+                Ok(())
+            }
+            Ok(None) => {
+                if args.verbose {
+                    println!("Nothing to fetch.");
                 }
+                process::exit(23);
             }
+            Err(err) => Err(err),
+        }
+    };
+    if let Err(err) = result {
+        if args.verbose {
+            println!("Unexpected response from server! {}", err);
         }
-    } else if args.verbose {
-        println!("Nothing to fetch.");
         process::exit(23);
     }
 }
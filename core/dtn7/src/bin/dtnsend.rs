@@ -1,8 +1,10 @@
-use bp7::bundle::*;
-use bp7::flags::{BundleControlFlags, BundleValidation};
+use bp7::canonical::CanonicalBlockType;
+use bp7::crc::CrcRawType;
+use bp7::flags::BundleControlFlags;
 use bp7::*;
 use clap::Parser;
-use dtn7_plus::client::DtnClient;
+use dtn7::client::send::{crc_type, parse_extension_block, status_report_flag};
+use dtn7::client::{DtnClient, SendOptions};
 use std::io;
 use std::{convert::TryInto, io::prelude::*};
 
@@ -41,33 +43,99 @@ struct Args {
     /// Bundle lifetime in seconds (default = 3600)
     #[clap(short, long, default_value_t = 3600)]
     lifetime: u64,
+
+    /// Request status reports, comma separated list of reception, forwarding, delivery, deletion or none
+    #[clap(short = 'R', long, value_delimiter = ',', default_value = "delivery")]
+    report: Vec<String>,
+
+    /// Endpoint receiving status reports (default = sender)
+    #[clap(long)]
+    report_to: Option<String>,
+
+    /// Allow fragmentation of the bundle
+    #[clap(long)]
+    fragment: bool,
+
+    /// Request an acknowledgement by the receiving application
+    #[clap(long)]
+    ack: bool,
+
+    /// Hop limit of the bundle (default = 32)
+    #[clap(long, default_value_t = 32, value_parser = clap::value_parser!(u8).range(1..))]
+    hop_limit: u8,
+
+    /// Add a bundle age block
+    #[clap(long)]
+    bundle_age: bool,
+
+    /// Number of copies spray and wait routing may spread, carried in the bundle
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    copies: Option<u64>,
+
+    /// Additional extension block as <TYPE>:<HEXDATA>, can be given multiple times
+    #[clap(short, long, value_name = "TYPE:HEXDATA", value_parser = parse_extension_block)]
+    block: Vec<(CanonicalBlockType, Vec<u8>)>,
+
+    /// CRC type of all blocks: none, 16 or 32 (default = none)
+    #[clap(long, default_value = "none", value_parser = parse_crc)]
+    crc: CrcRawType,
 }
 
-fn main() {
+fn parse_crc(name: &str) -> Result<CrcRawType, &'static str> {
+    crc_type(name).ok_or("expected one of none, 16, 32")
+}
+
+fn send_options(args: &Args) -> SendOptions {
+    let mut flags = BundleControlFlags::empty();
+    if !args.fragment {
+        flags |= BundleControlFlags::BUNDLE_MUST_NOT_FRAGMENTED;
+    }
+    if args.ack {
+        flags |= BundleControlFlags::BUNDLE_REQUEST_USER_APPLICATION_ACK;
+    }
+    for report in args.report.iter().filter(|r| r.as_str() != "none") {
+        flags |= status_report_flag(report).unwrap_or_else(|| {
+            eprintln!("Invalid status report type: {}", report);
+            std::process::exit(1);
+        });
+    }
+    SendOptions {
+        lifetime: std::time::Duration::from_secs(args.lifetime),
+        flags: flags.bits(),
+        report_to: args
+            .report_to
+            .as_ref()
+            .map(|eid| eid.as_str().try_into().expect("invalid report-to endpoint")),
+        hop_limit: args.hop_limit,
+        bundle_age: args.bundle_age,
+        copies: args.copies,
+        extension_blocks: args.block.clone(),
+        crc: args.crc,
+    }
+}
+
+#[tokio::main]
+async fn main() {
     let args = Args::parse();
+    let opts = send_options(&args);
     let localhost = if args.ipv6 { "[::1]" } else { "127.0.0.1" };
     let port = if let Ok(env_port) = std::env::var("DTN_WEB_PORT") {
-        env_port // string is fine, no need to parse number
+        env_port.parse::<u16>().expect("invalid port number")
     } else {
-        args.port.to_string()
+        args.port
+    };
+    let client = DtnClient::with_host_and_port(localhost, port);
+    let sender: EndpointID = match args.sender {
+        Some(sender) => sender.try_into().unwrap(),
+        None => client
+            .local_node_id()
+            .await
+            .expect("error getting node id from local dtnd"),
     };
-    let client = DtnClient::with_host_and_port(
-        localhost.into(),
-        port.parse::<u16>().expect("invalid port number"),
-    );
-    let sender: EndpointID = args
-        .sender
-        .unwrap_or_else(|| {
-            client
-                .local_node_id()
-                .expect("error getting node id from local dtnd")
-                .to_string()
-        })
-        .try_into()
-        .unwrap();
     let receiver: EndpointID = args.receiver.try_into().unwrap();
     let cts = client
         .creation_timestamp()
+        .await
         .expect("error getting creation timestamp from local dtnd");
     let mut buffer = Vec::new();
     if let Some(infile) = args.infile {
@@ -87,28 +155,18 @@ fn main() {
         println!("Sending {} bytes.", buffer.len());
     }
 
-    let mut bndl = new_std_payload_bundle(sender, receiver, buffer);
-    let flags = BundleControlFlags::BUNDLE_MUST_NOT_FRAGMENTED
-        | BundleControlFlags::BUNDLE_STATUS_REQUEST_DELIVERY;
-    bndl.primary.bundle_control_flags.set(flags);
-    bndl.primary.creation_timestamp = cts;
-    bndl.primary.lifetime = std::time::Duration::from_secs(args.lifetime);
-    let binbundle = bndl.to_cbor();
+    let mut bndl = opts.build_bundle(sender, receiver, cts, buffer);
     println!("Bundle-Id: {}", bndl.id());
     if args.verbose || args.dryrun {
-        let hexstr = bp7::helpers::hexify(&binbundle);
+        let hexstr = bp7::helpers::hexify(&bndl.to_cbor());
         println!("{}", hexstr);
     }
 
-    //let local_url = format!("http://127.0.0.1:3000/send?bundle={}", hexstr);
-    //let res = reqwest::get(&local_url).expect("error connecting to local dtnd").text().unwrap();
     if !args.dryrun {
-        let res = attohttpc::post(format!("http://{}:{}/insert", localhost, port))
-            .bytes(binbundle)
-            .send()
-            .expect("error send bundle to dtnd")
-            .text()
-            .unwrap();
+        let res = client
+            .send_bundle(&mut bndl)
+            .await
+            .expect("error send bundle to dtnd");
         println!("Result: {}", res);
         let now = std::time::SystemTime::now();
         println!("Time: {}", humantime::format_rfc3339(now));
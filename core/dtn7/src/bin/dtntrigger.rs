@@ -1,13 +1,11 @@
-use anyhow::{bail, Result};
+use anyhow::Result;
 use bp7::*;
 use clap::{crate_authors, crate_version, Parser};
-use dtn7_plus::client::DtnClient;
-use std::convert::TryFrom;
+use dtn7::client::DtnClient;
+use futures::StreamExt;
 use std::io::prelude::*;
 use std::process::Command;
 use tempfile::NamedTempFile;
-use tungstenite::protocol::WebSocketConfig;
-use tungstenite::Message;
 
 fn write_temp_file(data: &[u8], verbose: bool) -> Result<NamedTempFile> {
     let mut data_file = NamedTempFile::new()?;
@@ -76,103 +74,53 @@ struct Args {
     #[clap(short, long, default_value = "echo")]
     command: String,
 }
-fn main() -> anyhow::Result<()> {
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
     let port = if let Ok(env_port) = std::env::var("DTN_WEB_PORT") {
-        env_port // string is fine, no need to parse number
+        env_port.parse::<u16>().expect("invalid port number")
     } else {
-        args.port.to_string()
+        args.port
     };
     let localhost = if args.ipv6 { "[::1]" } else { "127.0.0.1" };
 
-    let client = DtnClient::with_host_and_port(
-        localhost.into(),
-        port.parse::<u16>().expect("invalid port number"),
-    );
+    let client = DtnClient::with_host_and_port(localhost, port);
 
-    client.register_application_endpoint(&args.endpoint)?;
-    let config = WebSocketConfig {
-        max_message_size: Some(1024 * 1024 * 128), // 128 MB
-        max_frame_size: Some(1024 * 1024 * 128),   // 128 MB
-        ..Default::default()
-    };
-    let mut wscon = client.ws_with_config(config)?;
-
-    wscon.write_text("/bundle")?;
-    let msg = wscon.read_text()?;
-    if msg.starts_with("200 tx mode: bundle") {
-        eprintln!("[*] {}", msg);
-    } else {
-        bail!("[!] Failed to set mode to `bundle`");
-    }
-
-    wscon.write_text(&format!("/subscribe {}", args.endpoint))?;
-    let msg = wscon.read_text()?;
-    if msg.starts_with("200 subscribed") {
-        eprintln!("[*] {}", msg);
-    } else {
-        bail!("[!] Failed to subscribe to service");
-    }
+    client.register_application_endpoint(&args.endpoint).await?;
+    let mut bundles = client.subscribe(&args.endpoint).await?;
+    eprintln!("[*] subscribed to {}", args.endpoint);
 
-    loop {
-        let msg = wscon.read_message()?;
-        match msg {
-            Message::Text(txt) => {
-                eprintln!("Unexpected response: {}", txt);
-                break;
+    while let Some(bndl) = bundles.next().await {
+        let bndl = bndl?;
+        if bndl.is_administrative_record() {
+            eprintln!("[!] Handling of administrative records not yet implemented!");
+        } else if let Some(data) = bndl.payload() {
+            if args.verbose {
+                eprintln!("[<] Received Bundle-Id: {}", bndl.id());
             }
-            Message::Binary(bin) => {
-                let bndl: Bundle =
-                    Bundle::try_from(bin).expect("Error decoding bundle from server");
-                if bndl.is_administrative_record() {
-                    eprintln!("[!] Handling of administrative records not yet implemented!");
-                } else if let Some(data) = bndl.payload() {
-                    if args.verbose {
-                        eprintln!("[<] Received Bundle-Id: {}", bndl.id());
-                    }
-                    if args.print {
-                        let now = humantime::format_rfc3339(std::time::SystemTime::now());
-                        println!(
-                            "[{}] {} → {}",
-                            now,
-                            bndl.primary.source.to_string(),
-                            String::from_utf8_lossy(data)
-                        );
-                    } else {
-                        let data_file = write_temp_file(data, args.verbose)?;
-                        if args.verbose {
-                            eprintln!("[*] wrote tmp data file, now executing...");
-                        }
-                        execute_cmd(&args.command, data_file, &bndl, args.verbose)?;
-                    }
-                } else if args.verbose {
-                    eprintln!("[!] Unexpected payload!");
-                    break;
-                }
-            }
-            Message::Ping(_) => {
+            if args.print {
+                let now = humantime::format_rfc3339(std::time::SystemTime::now());
+                println!(
+                    "[{}] {} → {}",
+                    now,
+                    bndl.primary.source,
+                    String::from_utf8_lossy(data)
+                );
+            } else {
+                let data_file = write_temp_file(data, args.verbose)?;
                 if args.verbose {
-                    eprintln!("[<] Ping")
-                }
-            }
-            Message::Pong(_) => {
-                if args.verbose {
-                    eprintln!("[<] Pong")
-                }
-            }
-            Message::Close(_) => {
-                if args.verbose {
-                    eprintln!("[<] Close")
-                }
-                break;
-            }
-            Message::Frame(_) => {
-                if args.verbose {
-                    eprintln!("[!] Received raw frame, not supported!")
+                    eprintln!("[*] wrote tmp data file, now executing...");
                 }
+                execute_cmd(&args.command, data_file, &bndl, args.verbose)?;
             }
+        } else if args.verbose {
+            eprintln!("[!] Unexpected payload!");
+            break;
         }
     }
+    if args.verbose {
+        eprintln!("[<] Close")
+    }
     Ok(())
 }
@@ -1,12 +1,155 @@
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 use super::TransferResult;
 use super::{ConvergenceLayerAgent, HelpStr};
+use crate::node::{DtnNode, WeakDtnNode};
 use async_trait::async_trait;
+use bp7::{Bundle, ByteBuffer};
 use dtn7_codegen::cla;
-use log::debug;
+use log::{debug, warn};
+use parking_lot::Mutex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::convert::TryFrom;
 use tokio::sync::mpsc;
 
+/// Properties of a directed link between two nodes of a [`DummyNetwork`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DummyLink {
+    pub up: bool,
+    /// Time a bundle takes to reach the other node
+    pub delay: Duration,
+    /// Probability of a bundle getting lost on the way, between 0.0 and 1.0
+    pub loss: f64,
+}
+
+impl Default for DummyLink {
+    fn default() -> Self {
+        DummyLink {
+            up: true,
+            delay: Duration::ZERO,
+            loss: 0.0,
+        }
+    }
+}
+
+struct NetworkState {
+    nodes: HashMap<String, WeakDtnNode>,
+    links: HashMap<(String, String), DummyLink>,
+    rng: StdRng,
+}
+
+/// In-memory medium connecting the dummy CLAs of nodes running in the same process.
+///
+/// Nodes are addressed by name, a peer reachable via the network uses the node name as
+/// generic peer address. Transfers fail if there is no link or the link is down when the
+/// transfer starts or ends. Lost bundles are reported as sent successfully, like on an
+/// unreliable medium. Losses are drawn from a random generator seeded on creation.
+#[derive(Clone)]
+pub struct DummyNetwork {
+    state: Arc<Mutex<NetworkState>>,
+}
+
+impl std::fmt::Debug for DummyNetwork {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let state = self.state.lock();
+        f.debug_struct("DummyNetwork")
+            .field("nodes", &state.nodes.keys())
+            .field("links", &state.links)
+            .finish()
+    }
+}
+
+impl DummyNetwork {
+    pub fn new(seed: u64) -> DummyNetwork {
+        DummyNetwork {
+            state: Arc::new(Mutex::new(NetworkState {
+                nodes: HashMap::new(),
+                links: HashMap::new(),
+                rng: StdRng::seed_from_u64(seed),
+            })),
+        }
+    }
+    /// Delivers bundles sent to `name` to the given node
+    pub fn attach(&self, name: &str, node: &DtnNode) {
+        self.state
+            .lock()
+            .nodes
+            .insert(name.to_string(), node.downgrade());
+    }
+    /// Sets the link from one node to another, links are directed
+    pub fn set_link(&self, from: &str, to: &str, link: DummyLink) {
+        self.state
+            .lock()
+            .links
+            .insert((from.to_string(), to.to_string()), link);
+    }
+    pub fn link(&self, from: &str, to: &str) -> Option<DummyLink> {
+        self.state
+            .lock()
+            .links
+            .get(&(from.to_string(), to.to_string()))
+            .copied()
+    }
+    /// Returns the delay of a transfer and if the bundle gets lost, `None` if the link is down
+    fn start_transfer(&self, from: &str, to: &str) -> Option<(Duration, bool)> {
+        let mut state = self.state.lock();
+        let link = state
+            .links
+            .get(&(from.to_string(), to.to_string()))
+            .copied()
+            .filter(|link| link.up)?;
+        let lost = link.loss > 0.0 && state.rng.gen_bool(link.loss.min(1.0));
+        Some((link.delay, lost))
+    }
+    fn finish_transfer(
+        &self,
+        from: &str,
+        to: &str,
+        data: ByteBuffer,
+        lost: bool,
+    ) -> TransferResult {
+        let node = {
+            let state = self.state.lock();
+            let up = state
+                .links
+                .get(&(from.to_string(), to.to_string()))
+                .is_some_and(|link| link.up);
+            if !up {
+                debug!("DummyNetwork: link {} -> {} went down", from, to);
+                return TransferResult::Failure;
+            }
+            match state.nodes.get(to).and_then(WeakDtnNode::upgrade) {
+                Some(node) => node,
+                None => return TransferResult::Failure,
+            }
+        };
+        if lost {
+            debug!("DummyNetwork: bundle from {} to {} lost", from, to);
+            return TransferResult::Successful;
+        }
+        match Bundle::try_from(data) {
+            Ok(bndl) => {
+                let from = from.to_string();
+                node.spawn(async move {
+                    if let Err(err) =
+                        crate::core::processing::receive_from(bndl, "dummy", Some(from)).await
+                    {
+                        warn!("DummyNetwork: error processing bundle: {}", err);
+                    }
+                });
+                TransferResult::Successful
+            }
+            Err(err) => {
+                warn!("DummyNetwork: invalid bundle from {}: {}", from, err);
+                TransferResult::Failure
+            }
+        }
+    }
+}
+
 #[cla(dummy)]
 #[derive(Debug, Clone)]
 pub struct DummyConvergenceLayer {
@@ -16,7 +159,7 @@ pub struct DummyConvergenceLayer {
 impl DummyConvergenceLayer {
     pub fn new(_local_settings: Option<&HashMap<String, String>>) -> DummyConvergenceLayer {
         let (tx, mut rx) = mpsc::channel(1);
-        tokio::spawn(async move {
+        crate::node::spawn(async move {
             while let Some(cmd) = rx.recv().await {
                 match cmd {
                     super::ClaCmd::Transfer(remote, _, reply) => {
@@ -35,6 +178,44 @@ impl DummyConvergenceLayer {
         });
         DummyConvergenceLayer { tx }
     }
+
+    /// Creates a dummy CLA sending bundles as node `name` via the given network
+    pub fn connected(network: DummyNetwork, name: &str) -> DummyConvergenceLayer {
+        let (tx, mut rx) = mpsc::channel(100);
+        let name = name.to_string();
+        crate::node::spawn(async move {
+            while let Some(cmd) = rx.recv().await {
+                match cmd {
+                    super::ClaCmd::Transfer(remote, data, reply) => {
+                        // strip the port, peers are addressed by node name
+                        let to = remote
+                            .rsplit_once(':')
+                            .map_or(remote.as_str(), |(addr, _)| addr)
+                            .to_string();
+                        debug!("DummyConvergenceLayer: transfer from {} to {}", name, to);
+                        let (delay, lost) = match network.start_transfer(&name, &to) {
+                            Some(transfer) => transfer,
+                            None => {
+                                let _ = reply.send(TransferResult::Failure);
+                                continue;
+                            }
+                        };
+                        let network = network.clone();
+                        let from = name.clone();
+                        crate::node::spawn(async move {
+                            tokio::time::sleep(delay).await;
+                            let _ = reply.send(network.finish_transfer(&from, &to, data, lost));
+                        });
+                    }
+                    super::ClaCmd::Shutdown => {
+                        debug!("DummyConvergenceLayer: received shutdown command");
+                        break;
+                    }
+                }
+            }
+        });
+        DummyConvergenceLayer { tx }
+    }
 }
 
 #[async_trait]
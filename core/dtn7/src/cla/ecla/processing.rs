@@ -6,29 +6,29 @@ use crate::cla::external::ExternalConvergenceLayer;
 use crate::cla::{ConvergenceLayerAgent, TransferResult};
 use crate::core::PeerType;
 use crate::ipnd::services::ServiceBlock;
-use crate::routing::RoutingAgent;
-use crate::{cla_add, cla_remove, PeerAddress, RoutingCmd, CONFIG};
+use crate::node::node_local;
+use crate::RoutingNotifcation;
+use crate::{cla_add, cla_remove, PeerAddress, CONFIG};
 use crate::{cla_names, CLAS, DTNCORE};
-use crate::{lazy_static, RoutingNotifcation};
 use crate::{peers_add, DtnPeer};
 use bp7::{Bundle, ByteBuffer};
 use log::{debug, error, info};
+use parking_lot::Mutex;
 use serde::__private::TryFrom;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
 use tokio::time::interval;
 
 /// Specifies the maximum length for a name of ECLA modules.
 const ECLA_NAME_MAX_LEN: usize = 64;
 
-type ModuleMap = Arc<Mutex<HashMap<String, Module>>>;
-type LayerMap = Arc<Mutex<HashMap<String, ConnectorEnum>>>;
+type ModuleMap = Mutex<HashMap<String, Module>>;
+type LayerMap = Mutex<HashMap<String, ConnectorEnum>>;
 
-lazy_static! {
+node_local! {
     /// Tracks the registered connectors over which clients can connect to dtnd (e.g. WebSocket or TCP).
-    static ref CONNECTORS_MAP: LayerMap = LayerMap::new(Mutex::new(HashMap::new()));
+    static ref CONNECTORS_MAP: LayerMap = Mutex::new(HashMap::new());
     /// Tracks the registered modules that are connected over a connector.
-    static ref MODULE_MAP: ModuleMap = ModuleMap::new(Mutex::new(HashMap::new()));
+    static ref MODULE_MAP: ModuleMap = Mutex::new(HashMap::new());
 }
 
 /// Represents in which state the Module connection is.
@@ -87,16 +87,12 @@ async fn announcer() {
     loop {
         task.tick().await;
 
-        MODULE_MAP.lock().unwrap().iter().for_each(|(addr, value)| {
+        MODULE_MAP.lock().iter().for_each(|(addr, value)| {
             if !value.enable_beacon {
                 return;
             }
 
-            if let Some(connector) = CONNECTORS_MAP
-                .lock()
-                .unwrap()
-                .get_mut(value.connector.as_str())
-            {
+            if let Some(connector) = CONNECTORS_MAP.lock().get_mut(value.connector.as_str()) {
                 debug!("Sending Beacon to addr={} ({})", addr, value.connector);
                 connector.send_packet(addr, &Packet::Beacon(generate_beacon()));
             }
@@ -106,7 +102,7 @@ async fn announcer() {
 
 /// Handles packets from a connector
 pub fn handle_packet(connector_name: String, addr: String, packet: Packet) {
-    let mut module_map = MODULE_MAP.lock().unwrap();
+    let mut module_map = MODULE_MAP.lock();
 
     // Check if the module exists.
     let mod_opt = module_map.get_mut(&addr);
@@ -116,7 +112,7 @@ pub fn handle_packet(connector_name: String, addr: String, packet: Packet) {
 
     // Check if the corresponding layer exists without holding the lock for the whole function.
     {
-        let connectors_map = CONNECTORS_MAP.lock().unwrap();
+        let connectors_map = CONNECTORS_MAP.lock();
         if connectors_map.get(&connector_name).is_none() {
             return;
         }
@@ -132,7 +128,7 @@ pub fn handle_packet(connector_name: String, addr: String, packet: Packet) {
                     addr, connector_name, ident.name
                 );
 
-                let mut connectors_map = CONNECTORS_MAP.lock().unwrap();
+                let mut connectors_map = CONNECTORS_MAP.lock();
                 let connector = connectors_map.get_mut(&connector_name).unwrap();
 
                 if ident.name.is_empty() || ident.name.len() > ECLA_NAME_MAX_LEN {
@@ -192,8 +188,12 @@ pub fn handle_packet(connector_name: String, addr: String, packet: Packet) {
                 if let Ok(bndl) = Bundle::try_from(fwd.data) {
                     info!("Received bundle: {} from {}", bndl.id(), me.name);
                     {
-                        tokio::spawn(async move {
-                            if let Err(err) = crate::core::processing::receive(bndl).await {
+                        let cla = me.name.clone();
+                        let peer = Some(fwd.src).filter(|src| !src.is_empty());
+                        crate::node::spawn(async move {
+                            if let Err(err) =
+                                crate::core::processing::receive_from(bndl, &cla, peer).await
+                            {
                                 error!("Failed to process bundle: {}", err);
                             }
                         });
@@ -210,21 +210,27 @@ pub fn handle_packet(connector_name: String, addr: String, packet: Packet) {
 
                 debug!("Received beacon: ecla={} eid={} addr={} service_block={}", me.name, pdp.eid, pdp.addr, service_block);
 
-                peers_add(DtnPeer::new(
+                let mut peer = DtnPeer::new(
                     pdp.eid.clone(),
                     PeerAddress::Generic(pdp.addr),
                     PeerType::Dynamic,
                     None,
                     service_block.clas().clone(),
                     service_block.convert_services(),
-                ));
-
-                let cmd_channel = DTNCORE.lock().routing_agent.channel();
-                if let Err(err) = cmd_channel.try_send(RoutingCmd::Notify(
-                    RoutingNotifcation::EncounteredPeer(pdp.eid),
-                )) {
-                    error!("Failed to send encountered peer notification: {}", err);
+                );
+                if !CONFIG.lock().admission.admit_peer(&mut peer) {
+                    debug!("Peer refused by admission policy: {}", pdp.eid);
+                    return;
                 }
+                peers_add(peer);
+
+                crate::node::spawn(async move {
+                    if let Err(err) =
+                        crate::routing_notify(RoutingNotifcation::EncounteredPeer(pdp.eid)).await
+                    {
+                        error!("Failed to send encountered peer notification: {}", err);
+                    }
+                });
             }
             _ => {}
         },
@@ -234,7 +240,7 @@ pub fn handle_packet(connector_name: String, addr: String, packet: Packet) {
 /// When a module connects in a connector, this function should be called.
 /// It will initialize the information about the new module.
 pub fn handle_connect(connector_name: String, from: String) {
-    MODULE_MAP.lock().unwrap().insert(
+    MODULE_MAP.lock().insert(
         from,
         Module {
             state: ModuleState::WaitingForIdent,
@@ -250,13 +256,13 @@ pub fn handle_connect(connector_name: String, from: String) {
 pub fn handle_disconnect(addr: String) {
     info!("ECLA {} disconnected", &addr);
 
-    if let Some(module) = MODULE_MAP.lock().unwrap().get(&addr) {
+    if let Some(module) = MODULE_MAP.lock().get(&addr) {
         if let ModuleState::Active = module.state {
             cla_remove(module.name.clone());
         }
     }
 
-    MODULE_MAP.lock().unwrap().remove(&addr);
+    MODULE_MAP.lock().remove(&addr);
 }
 
 /// Will schedule a submission to a module by name
@@ -267,8 +273,8 @@ pub fn scheduled_submission(name: String, dest: String, ready: &ByteBuffer) -> T
         );
 
     let mut was_sent = TransferResult::Failure;
-    let mut connectors_map = CONNECTORS_MAP.lock().unwrap();
-    let module_map = MODULE_MAP.lock().unwrap();
+    let mut connectors_map = CONNECTORS_MAP.lock();
+    let module_map = MODULE_MAP.lock();
     module_map.iter().for_each(|(addr, value)| {
         if value.name == name {
             if let Ok(bndl) = Bundle::try_from(ready.as_slice()) {
@@ -294,7 +300,6 @@ pub fn scheduled_submission(name: String, dest: String, ready: &ByteBuffer) -> T
 pub fn add_connector(connector: ConnectorEnum) {
     CONNECTORS_MAP
         .lock()
-        .unwrap()
         .insert(connector.name().to_string(), connector);
 }
 
@@ -312,5 +317,5 @@ pub async fn start_ecla(tcp_port: u16) {
         add_connector(tcp_layer.into());
     }
 
-    tokio::spawn(announcer());
+    crate::node::spawn(announcer());
 }
@@ -1,14 +1,14 @@
 use super::Connector;
 use crate::cla::ecla::processing::{handle_connect, handle_disconnect, handle_packet};
 use crate::cla::ecla::Packet;
-use crate::lazy_static;
+use crate::node::node_local;
 use async_trait::async_trait;
 use futures_util::{future, stream::TryStreamExt};
 use log::info;
 use log::{debug, error};
+use parking_lot::Mutex;
 use std::collections::HashMap;
 use std::net::SocketAddr;
-use std::sync::{Arc, Mutex};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
@@ -16,10 +16,10 @@ use tokio_serde::formats::SymmetricalJson;
 use tokio_util::codec::{FramedRead, LengthDelimitedCodec};
 
 type TCPConnection = super::Connection<Vec<u8>>;
-type PeerMap = Arc<Mutex<HashMap<String, TCPConnection>>>;
+type PeerMap = Mutex<HashMap<String, TCPConnection>>;
 
-lazy_static! {
-    static ref PEER_MAP: PeerMap = PeerMap::new(Mutex::new(HashMap::new()));
+node_local! {
+    static ref PEER_MAP: PeerMap = Mutex::new(HashMap::new());
 }
 
 // Handles the TCP connection.
@@ -30,7 +30,7 @@ async fn handle_connection(raw_stream: TcpStream, addr: SocketAddr) {
     let (tx_close, rx_close) = oneshot::channel();
 
     // Insert the write part of this peer to the peer map.
-    PEER_MAP.lock().unwrap().insert(
+    PEER_MAP.lock().insert(
         addr.to_string(),
         TCPConnection {
             tx,
@@ -55,7 +55,7 @@ async fn handle_connection(raw_stream: TcpStream, addr: SocketAddr) {
         future::ok(())
     });
 
-    let outgoing = tokio::spawn(async move {
+    let outgoing = crate::node::spawn(async move {
         while let Some(cmd) = rx.recv().await {
             if let Err(err) = outgoing.try_write(cmd.as_slice()) {
                 error!("err while sending to outgoing channel: {}", err);
@@ -67,7 +67,7 @@ async fn handle_connection(raw_stream: TcpStream, addr: SocketAddr) {
     // until a close command for this connection is received.
     future::select(rx_close, future::select(incoming, outgoing)).await;
 
-    if PEER_MAP.lock().unwrap().remove(&addr.to_string()).is_some() {
+    if PEER_MAP.lock().remove(&addr.to_string()).is_some() {
         info!("ECLA (TCP) {} disconnected", &addr);
         handle_disconnect(addr.to_string());
     }
@@ -89,7 +89,7 @@ impl Connector for TCPConnector {
     async fn setup(&mut self) {
         let port = self.port;
 
-        tokio::spawn(async move {
+        crate::node::spawn(async move {
             let addr = String::from("127.0.0.1:") + port.to_string().as_str();
 
             // Create the event loop and TCP listener we'll accept connections on.
@@ -99,7 +99,7 @@ impl Connector for TCPConnector {
 
             // Let's spawn the handling of each connection in a separate task.
             while let Ok((stream, addr)) = listener.accept().await {
-                tokio::spawn(handle_connection(stream, addr));
+                crate::node::spawn(handle_connection(stream, addr));
             }
         });
     }
@@ -111,7 +111,7 @@ impl Connector for TCPConnector {
     fn send_packet(&self, dest: &str, packet: &Packet) -> bool {
         debug!("Sending Packet to {} ({})", dest, self.name());
 
-        let peer_map = PEER_MAP.lock().unwrap();
+        let peer_map = PEER_MAP.lock();
         let target = peer_map.get(dest);
         if target.is_some() {
             // Build the packet frame [ len: u32 | frame payload (data) ]
@@ -128,7 +128,7 @@ impl Connector for TCPConnector {
     }
 
     fn close(&self, addr: &str) {
-        if let Some(conn) = PEER_MAP.lock().unwrap().get_mut(addr) {
+        if let Some(conn) = PEER_MAP.lock().get_mut(addr) {
             let close = conn.close.take();
             if let Err(_err) = close.unwrap().send(()) {
                 debug!("Error while sending close to {}", addr);
@@ -1,24 +1,24 @@
 use super::Connector;
 use crate::cla::ecla::processing::{handle_connect, handle_disconnect, handle_packet};
 use crate::cla::ecla::Packet;
-use crate::lazy_static;
+use crate::node::node_local;
 use async_trait::async_trait;
 use axum::extract::ws::{Message, WebSocket};
 use futures_util::{future, stream::TryStreamExt, SinkExt, StreamExt};
 use log::{error, warn, info, debug, trace};
+use parking_lot::Mutex;
 use serde_json::Result;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 
 type WebSocketConnection = super::Connection<Message>;
-type PeerMap = Arc<Mutex<HashMap<String, WebSocketConnection>>>;
+type PeerMap = Mutex<HashMap<String, WebSocketConnection>>;
 
-lazy_static! {
+node_local! {
     /// Tracks the connected peers (modules)
-    static ref PEER_MAP: PeerMap = PeerMap::new(Mutex::new(HashMap::new()));
+    static ref PEER_MAP: PeerMap = Mutex::new(HashMap::new());
 }
 
 static ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
@@ -32,7 +32,7 @@ pub async fn handle_connection(ws: WebSocket) {
     let (tx, mut rx) = mpsc::channel(100);
     let (tx_close, rx_close) = oneshot::channel();
 
-    PEER_MAP.lock().unwrap().insert(
+    PEER_MAP.lock().insert(
         id.to_string(),
         WebSocketConnection {
             tx,
@@ -49,7 +49,7 @@ pub async fn handle_connection(ws: WebSocket) {
         let packet: Result<Packet>;
         {
             // Get own peer
-            let mut peer_map = PEER_MAP.lock().unwrap();
+            let mut peer_map = PEER_MAP.lock();
 
             let me_opt = peer_map.get_mut(&id.to_string());
             if me_opt.is_none() {
@@ -85,7 +85,7 @@ pub async fn handle_connection(ws: WebSocket) {
     });
 
     // Pass the received messages to the websocket client.
-    let receive_from_others = tokio::spawn(async move {
+    let receive_from_others = crate::node::spawn(async move {
         while let Some(cmd) = rx.recv().await {
             if let Err(err) = outgoing.send(cmd).await {
                 error!("err while sending to outgoing channel: {}", err);
@@ -103,7 +103,7 @@ pub async fn handle_connection(ws: WebSocket) {
 
     info!("ECLA (WS) {} disconnected", id);
     handle_disconnect(id.to_string());
-    PEER_MAP.lock().unwrap().remove(&id.to_string());
+    PEER_MAP.lock().remove(&id.to_string());
 }
 
 #[derive(Clone, Default)]
@@ -128,7 +128,7 @@ impl Connector for WebsocketConnector {
     fn send_packet(&self, dest: &str, packet: &Packet) -> bool {
         debug!("Sending Packet to dest={} ({})", dest, self.name());
 
-        let peer_map = PEER_MAP.lock().unwrap();
+        let peer_map = PEER_MAP.lock();
         if let Some(target) = peer_map.get(dest) {
             let data = serde_json::to_string(&packet);
             return target.tx.try_send(Message::Text(data.unwrap())).is_ok();
@@ -138,7 +138,7 @@ impl Connector for WebsocketConnector {
     }
 
     fn close(&self, addr: &str) {
-        if let Some(conn) = PEER_MAP.lock().unwrap().get_mut(addr) {
+        if let Some(conn) = PEER_MAP.lock().get_mut(addr) {
             let close = conn.close.take();
             if let Err(_err) = close.unwrap().send(()) {
                 debug!("Error while sending close to {}", addr);
@@ -104,7 +104,7 @@ impl Client {
 
         // Pass rx to write
         let mut cmd_receiver = std::mem::replace(&mut self.cmd_receiver, mpsc::channel(1).1);
-        let to_ws = tokio::spawn(async move {
+        let to_ws = crate::node::spawn(async move {
             while let Some(command) = cmd_receiver.recv().await {
                 match command {
                     Command::SendPacket(packet) => {
@@ -3,11 +3,11 @@ use crate::cla::ecla::processing::scheduled_submission;
 use crate::cla::{ClaCmd, HelpStr};
 use async_trait::async_trait;
 use dtn7_codegen::cla;
+use log::debug;
 use std::collections::HashMap;
 use std::str::FromStr;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::Sender;
-use log::debug;
 
 #[cla(external)]
 #[derive(Debug, Clone)]
@@ -34,13 +34,13 @@ impl ExternalConvergenceLayer {
         let name = settings.get("name").expect("name missing").to_string();
         let task_name = name.clone();
         let (tx, mut rx) = mpsc::channel(100);
-        tokio::spawn(async move {
+        crate::node::spawn(async move {
             while let Some(cmd) = rx.recv().await {
                 match cmd {
                     super::ClaCmd::Transfer(dest, ready, reply) => {
                         if !discovery_only {
                             let name = task_name.clone();
-                            tokio::spawn(async move {
+                            crate::node::spawn(async move {
                                 debug!("ExternalConvergenceLayer will schedule submission");
                                 reply
                                     .send(scheduled_submission(name, dest, &ready))
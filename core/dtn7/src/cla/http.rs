@@ -66,7 +66,7 @@ pub async fn http_send_bundles(
 impl HttpConvergenceLayer {
     pub fn new(_local_settings: Option<&HashMap<String, String>>) -> HttpConvergenceLayer {
         let (tx, mut rx) = mpsc::channel(100);
-        tokio::spawn(async move {
+        crate::node::spawn(async move {
             let client = hyper::client::Client::new();
             /*let client = hyper::client::Client::builder()
             .pool_idle_timeout(Duration::from_secs(15))
@@ -82,7 +82,7 @@ impl HttpConvergenceLayer {
                             remote
                         );
                         let client2 = client.clone();
-                        tokio::spawn(async move {
+                        crate::node::spawn(async move {
                             reply
                                 .send(http_send_bundles(client2, remote, ready).await)
                                 .unwrap();
@@ -31,7 +31,7 @@ async fn http_pull_from_node(
     let mut transfers = 0;
 
     debug!("pulling bundles from {} / {}", eid, addr);
-    tokio::task::spawn_blocking(move || {
+    crate::node::spawn_blocking(move || {
         // get digest of remote node
         let response =
             attohttpc::get(format!("http://{}:{}/status/bundles/digest", addr, port)).send();
@@ -95,8 +95,11 @@ async fn http_pull_from_node(
             };
             info!("Downloaded bundle: {} from {}", bundle.id(), addr);
             {
-                tokio::spawn(async move {
-                    if let Err(err) = crate::core::processing::receive(bundle).await {
+                let peer = Some(eid.to_string());
+                crate::node::spawn(async move {
+                    if let Err(err) =
+                        crate::core::processing::receive_from(bundle, "httppull", peer).await
+                    {
                         error!("Failed to process bundle: {}", err);
                     }
                 });
@@ -133,7 +136,7 @@ async fn http_pull_bundles() {
             }
         }
         if CONFIG.lock().parallel_bundle_processing {
-            tokio::spawn(async move {
+            crate::node::spawn(async move {
                 http_pull_from_node(peer.eid, peer.addr.to_string(), port, local_digest).await;
             });
         } else {
@@ -163,11 +166,11 @@ async fn http_puller_loop(rx: mpsc::Receiver<bool>) {
 impl HttpPullConvergenceLayer {
     pub fn new(_local_settings: Option<&HashMap<String, String>>) -> HttpPullConvergenceLayer {
         let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
-        tokio::spawn(async move {
+        crate::node::spawn(async move {
             http_puller_loop(shutdown_rx).await;
         });
         let (tx, mut rx) = mpsc::channel(1);
-        tokio::spawn(async move {
+        crate::node::spawn(async move {
             while let Some(cmd) = rx.recv().await {
                 match cmd {
                     super::ClaCmd::Transfer(remote, _, reply) => {
@@ -53,6 +53,8 @@ pub struct ClaSenderTask {
     pub dest: String,
     pub cla_name: String,
     pub next_hop: EndpointID,
+    /// Copy budget handed to the next hop, carried in the copy count block of the sent bundle
+    pub copies: Option<u64>,
 }
 
 impl ClaSenderTask {
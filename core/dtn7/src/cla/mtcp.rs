@@ -1,4 +1,5 @@
 use crate::cla::{ConvergenceLayerAgent, TransferResult};
+use crate::node::node_local;
 use async_trait::async_trait;
 use bp7::{Bundle, ByteBuffer};
 use bytes::buf::Buf;
@@ -6,7 +7,6 @@ use bytes::{BufMut, BytesMut};
 use core::convert::TryFrom;
 use dtn7_codegen::cla;
 use futures_util::stream::StreamExt;
-use lazy_static::lazy_static;
 use log::{debug, error, info};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
@@ -23,7 +23,7 @@ use tokio_util::codec::{Decoder, Encoder, Framed};
 
 use super::HelpStr;
 
-lazy_static! {
+node_local! {
     pub static ref MTCP_CONNECTIONS: Mutex<HashMap<SocketAddr, TcpStream>> =
         Mutex::new(HashMap::new());
 }
@@ -206,7 +206,7 @@ async fn mtcp_listener(addr: String, port: u16) -> Result<(), io::Error> {
     loop {
         let (socket, _) = listener.accept().await.unwrap();
 
-        tokio::spawn(MtcpConvergenceLayer::handle_connection(socket));
+        crate::node::spawn(MtcpConvergenceLayer::handle_connection(socket));
     }
 }
 
@@ -280,7 +280,7 @@ impl MtcpConvergenceLayer {
             .and_then(|port_str| port_str.parse::<u16>().ok())
             .unwrap_or(16162);
         let (tx, mut rx) = mpsc::channel(100);
-        tokio::spawn(async move {
+        crate::node::spawn(async move {
             while let Some(cmd) = rx.recv().await {
                 match cmd {
                     super::ClaCmd::Transfer(remote, data, reply) => {
@@ -291,7 +291,7 @@ impl MtcpConvergenceLayer {
                         if !data.is_empty() {
                             let peeraddr: SocketAddr = remote.parse().unwrap();
                             debug!("forwarding to {:?}", peeraddr);
-                            tokio::spawn(async move {
+                            crate::node::spawn(async move {
                                 reply.send(mtcp_send_bundles(peeraddr, vec![data])).unwrap();
                             });
                         } else {
@@ -322,8 +322,14 @@ impl MtcpConvergenceLayer {
                     if let Ok(bndl) = Bundle::try_from(frame) {
                         info!("Received bundle: {} from {}", bndl.id(), peer_addr);
                         {
-                            tokio::spawn(async move {
-                                if let Err(err) = crate::core::processing::receive(bndl).await {
+                            crate::node::spawn(async move {
+                                if let Err(err) = crate::core::processing::receive_from(
+                                    bndl,
+                                    "mtcp",
+                                    Some(peer_addr.to_string()),
+                                )
+                                .await
+                                {
                                     error!("Failed to process bundle: {}", err);
                                 }
                             });
@@ -346,7 +352,7 @@ impl MtcpConvergenceLayer {
 
     pub async fn spawn_listener(&self) -> std::io::Result<()> {
         // TODO: bubble up errors from run
-        tokio::spawn(mtcp_listener(self.local_addr.clone(), self.local_port)); /*.await.unwrap()*/
+        crate::node::spawn(mtcp_listener(self.local_addr.clone(), self.local_port)); /*.await.unwrap()*/
         Ok(())
     }
     pub fn send_bundles(&self, addr: SocketAddr, bundles: Vec<ByteBuffer>) -> bool {
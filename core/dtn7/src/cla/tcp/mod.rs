@@ -12,6 +12,8 @@ use log::{debug, error, info, trace, warn};
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Instant;
 use thiserror::Error;
 use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
@@ -22,13 +24,14 @@ use tokio::sync::Mutex;
 use tokio::time::{self};
 //use std::net::TcpStream;
 use super::tcp::proto::*;
+use crate::core::admission::{admitted, Candidate};
 use crate::core::store::BundleStore;
 use crate::core::PeerType;
-use crate::{peers_add, peers_known, STORE};
+use crate::node::node_local;
+use crate::{peers_add, peers_known, peers_record_rtt, STORE};
 use crate::{DtnPeer, CONFIG};
 use anyhow::bail;
 use bytes::Bytes;
-use lazy_static::lazy_static;
 use tokio::io::{AsyncReadExt, BufReader, BufWriter};
 use tokio::net::TcpListener;
 use tokio::net::TcpStream;
@@ -55,8 +58,70 @@ const SEGMENT_MRU: u64 = 64000;
 const TRANSFER_MRU: u64 = 64000;
 const INTERNAL_CHANNEL_BUFFER: usize = 200;
 
-lazy_static! {
+node_local! {
     pub static ref TCP_CONNECTIONS: Mutex<SessionMap> = Mutex::new(HashMap::new());
+    /// Remote node of every established session
+    static ref SESSION_NODES: parking_lot::Mutex<HashMap<SocketAddr, String>> =
+        parking_lot::Mutex::new(HashMap::new());
+    /// Connections establishing or running a session
+    static ref RUNNING_CONNECTIONS: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+}
+
+/// Counts a connection as running until dropped
+struct RunningConnection(Arc<AtomicUsize>);
+
+impl RunningConnection {
+    fn new() -> Self {
+        let running = Arc::clone(&RUNNING_CONNECTIONS.get());
+        running.fetch_add(1, Ordering::SeqCst);
+        RunningConnection(running)
+    }
+}
+
+impl Drop for RunningConnection {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Terminates the sessions of nodes the admission policy no longer admits.
+///
+/// Closing the session queue makes the session send a SESS_TERM to the remote node.
+pub async fn close_refused_sessions() {
+    let refused: Vec<SocketAddr> = SESSION_NODES
+        .lock()
+        .iter()
+        .filter(|(addr, node)| {
+            !admitted(Candidate {
+                node: Some(node),
+                addr: Some(addr.ip()),
+                cla: Some("tcp"),
+            })
+        })
+        .map(|(addr, _)| *addr)
+        .collect();
+    let mut sessions = TCP_CONNECTIONS.lock().await;
+    for addr in refused {
+        info!(
+            "Closing TCP session with {} refused by admission policy",
+            addr
+        );
+        sessions.remove(&addr);
+    }
+}
+
+/// Terminates all sessions, e.g., when the node shuts down, and waits up to `timeout` for
+/// them to send their SESS_TERM.
+pub async fn close_sessions(timeout: Duration) {
+    TCP_CONNECTIONS.lock().await.clear();
+    let closed = async {
+        while RUNNING_CONNECTIONS.get().load(Ordering::SeqCst) > 0 {
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    if time::timeout(timeout, closed).await.is_err() {
+        warn!("TCP sessions did not terminate within {:?}", timeout);
+    }
 }
 
 #[derive(Error, Debug)]
@@ -103,7 +168,8 @@ enum ReceiveState {
 
 enum SendState {
     Idle,
-    Sending(u64, tokio::sync::oneshot::Sender<TransferResult>),
+    /// Bundle length, time the last segment was written and result channel
+    Sending(u64, Instant, tokio::sync::oneshot::Sender<TransferResult>),
     TransferRequest(Vec<u8>, tokio::sync::oneshot::Sender<TransferResult>),
     Terminated,
 }
@@ -225,8 +291,11 @@ impl TcpSession {
     async fn process_bundle(&mut self, vec: Vec<u8>, tid: u64) -> anyhow::Result<ReceiveState> {
         match Bundle::try_from(vec) {
             Ok(bundle) => {
-                tokio::spawn(async move {
-                    if let Err(err) = crate::core::processing::receive(bundle).await {
+                let peer = self.remote_session_data.node_id.clone();
+                crate::node::spawn(async move {
+                    if let Err(err) =
+                        crate::core::processing::receive_from(bundle, "tcp", Some(peer)).await
+                    {
                         error!("Failed to process bundle: {}", err);
                     }
                 });
@@ -358,13 +427,23 @@ impl TcpSession {
                     }
                     Ok((receive_state, self.send_bundle(data, response).await?))
                 }
-                SendState::Sending(len, response) => {
+                SendState::Sending(len, sent, response) => {
                     if ack_data.tid != self.last_tid {
                         return Err(TcpSessionError::Protocol(packet).into());
                     }
                     if ack_data.len < len {
-                        Ok((receive_state, SendState::Sending(len, response)))
+                        Ok((receive_state, SendState::Sending(len, sent, response)))
                     } else {
+                        // the final ack follows the last segment immediately, see receive()
+                        if let Ok(remote_eid) =
+                            EndpointID::try_from(self.remote_session_data.node_id.as_ref())
+                        {
+                            peers_record_rtt(
+                                &remote_eid.node().unwrap_or_default(),
+                                "tcp",
+                                sent.elapsed(),
+                            );
+                        }
                         if let Err(err) = response.send(TransferResult::Successful) {
                             error!("Failed to send response: {:?}", err);
                             return Err(TcpSessionError::Protocol(packet).into());
@@ -387,7 +466,7 @@ impl TcpSession {
                     }
                     Ok((receive_state, SendState::Idle))
                 }
-                SendState::Sending(_, response) => {
+                SendState::Sending(_, _, response) => {
                     if refuse_data.tid != self.last_tid {
                         return Err(TcpSessionError::Protocol(packet).into());
                     }
@@ -483,7 +562,11 @@ impl TcpSession {
             bndl_buf.len(),
             self.addr
         );
-        Ok(SendState::Sending(bndl_buf.len() as u64, tx_result))
+        Ok(SendState::Sending(
+            bndl_buf.len() as u64,
+            Instant::now(),
+            tx_result,
+        ))
     }
 }
 
@@ -545,6 +628,7 @@ impl TcpConnection {
         rx_session_queue: mpsc::Receiver<(Vec<u8>, Sender<TransferResult>)>,
         active: bool,
     ) -> anyhow::Result<()> {
+        let _running = RunningConnection::new();
         // Phase 1
         debug!("Exchanging contact header, {}", self.addr);
         if let Err(err) = self.exchange_contact_header().await {
@@ -561,6 +645,27 @@ impl TcpConnection {
                 // TODO: validate node id
                 let remote_eid = EndpointID::try_from(remote_parameters.node_id.as_ref())
                     .expect("Invalid node id in tcpcl session");
+                let remote_node = remote_eid.node().unwrap_or_default();
+                if !admitted(Candidate {
+                    node: Some(&remote_node),
+                    addr: Some(self.addr.ip()),
+                    cla: Some("tcp"),
+                }) {
+                    if let Err(err) = TcpClPacket::SessTerm(SessTermData {
+                        flags: SessTermFlags::empty(),
+                        reason: SessTermReasonCode::Unknown,
+                    })
+                    .write(&mut self.writer)
+                    .await
+                    {
+                        error!("error while sending session terminate: {:?}", err);
+                    }
+                    bail!(
+                        "Session with {} @ {} refused by admission policy",
+                        remote_node,
+                        self.addr
+                    );
+                }
                 if !active && !peers_known(remote_eid.node().unwrap().as_ref()) {
                     let peer = DtnPeer::new(
                         remote_eid.clone(),
@@ -577,6 +682,7 @@ impl TcpConnection {
                     "Started TCP session for {} @ {} | refuse existing bundles: {}",
                     remote_parameters.node_id, self.addr, self.refuse_existing_bundles
                 );
+                SESSION_NODES.lock().insert(self.addr, remote_node);
                 let session = TcpSession {
                     reader: self.reader,
                     writer: self.writer,
@@ -588,6 +694,7 @@ impl TcpConnection {
                     rx_session_queue,
                 };
                 session.run().await;
+                SESSION_NODES.lock().remove(&self.addr);
             }
             Err(err) => bail!("Failed to negotiate session for {}: {}", self.addr, err),
         }
@@ -619,7 +726,7 @@ impl Listener {
                             INTERNAL_CHANNEL_BUFFER,
                         );
                     (*TCP_CONNECTIONS.lock().await).insert(addr, tx_session_queue);
-                    tokio::spawn(async move {
+                    crate::node::spawn(async move {
                         if let Err(err) = connection.connect(rx_session_queue, false).await {
                             error!("Failed to establish TCP session with {}: {}", addr, err);
                         }
@@ -682,7 +789,7 @@ async fn tcp_send_bundles(
                     addr,
                     refuse_existing_bundles,
                 };
-                tokio::spawn(connection.connect(rx_session_queue, true));
+                crate::node::spawn(connection.connect(rx_session_queue, true));
             }
             Ok(Err(_)) => {
                 if let Err(e) = reply.send(TransferResult::Failure) {
@@ -733,7 +840,7 @@ impl TcpConvergenceLayer {
         );
         let (tx, mut rx) = mpsc::channel(INTERNAL_CHANNEL_BUFFER);
 
-        tokio::spawn(async move {
+        crate::node::spawn(async move {
             while let Some(cmd) = rx.recv().await {
                 match cmd {
                     super::ClaCmd::Transfer(remote, data, reply) => {
@@ -741,7 +848,7 @@ impl TcpConvergenceLayer {
                             "TcpConvergenceLayer: received transfer command for {}",
                             remote
                         );
-                        tokio::spawn(async move {
+                        crate::node::spawn(async move {
                             if let Err(e) = tcp_send_bundles(
                                 remote.clone(),
                                 data,
@@ -789,7 +896,7 @@ impl ConvergenceLayerAgent for TcpConvergenceLayer {
             tcp_listener,
             refuse_existing_bundles: self.refuse_existing_bundles,
         };
-        tokio::spawn(listener.run());
+        crate::node::spawn(listener.run());
     }
 
     fn port(&self) -> u16 {
@@ -845,7 +952,7 @@ mod tests {
         if buf.len() > config.transfer_mru as usize {
             bail!("bundle too big");
         }
-        let fitting = u64::from(buf.len() as u64 % config.segment_mru != 0);
+        let fitting = u64::from(!(buf.len() as u64).is_multiple_of(config.segment_mru));
         let num_segs = (buf.len() as u64 / config.segment_mru) + fitting;
 
         for i in 0..num_segs {
@@ -894,7 +1001,7 @@ mod tests {
         //        let data_raw: [u8; data_len] = [0; data_len];
         let data_raw: Vec<u8> = vec![0x90; data_len as usize];
 
-        let fitting = u64::from(data_len % segment_mru != 0);
+        let fitting = u64::from(!data_len.is_multiple_of(segment_mru));
         let num_expected_segs = ((data_len / segment_mru) + fitting) as usize;
 
         //let data = Bytes::copy_from_slice(&data_raw);
@@ -27,8 +27,11 @@ async fn udp_listener(addr: String, port: u16) -> Result<(), io::Error> {
         if let Ok(bndl) = Bundle::try_from(buf.to_vec()) {
             info!("Received bundle: {} from {}", bndl.id(), src);
             {
-                tokio::spawn(async move {
-                    if let Err(err) = crate::core::processing::receive(bndl).await {
+                crate::node::spawn(async move {
+                    if let Err(err) =
+                        crate::core::processing::receive_from(bndl, "udp", Some(src.to_string()))
+                            .await
+                    {
                         error!("Failed to process bundle: {}", err);
                     }
                 });
@@ -98,7 +101,7 @@ impl UdpConvergenceLayer {
             .and_then(|port_str| port_str.parse::<u16>().ok())
             .unwrap_or(4556);
         let (tx, mut rx) = mpsc::channel(100);
-        tokio::spawn(async move {
+        crate::node::spawn(async move {
             while let Some(cmd) = rx.recv().await {
                 match cmd {
                     super::ClaCmd::Transfer(remote, data, reply) => {
@@ -109,7 +112,7 @@ impl UdpConvergenceLayer {
                         if !data.is_empty() {
                             let peeraddr: SocketAddr = remote.parse().unwrap();
                             debug!("forwarding to {:?}", peeraddr);
-                            tokio::spawn(async move {
+                            crate::node::spawn(async move {
                                 reply
                                     .send(udp_send_bundles(peeraddr, vec![data]).await)
                                     .unwrap();
@@ -135,7 +138,7 @@ impl UdpConvergenceLayer {
 
     pub async fn spawn_listener(&self) -> std::io::Result<()> {
         // TODO: bubble up errors from run
-        tokio::spawn(udp_listener(self.local_addr.clone(), self.local_port)); /*.await.unwrap()*/
+        crate::node::spawn(udp_listener(self.local_addr.clone(), self.local_port)); /*.await.unwrap()*/
         Ok(())
     }
 }
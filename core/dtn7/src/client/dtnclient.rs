@@ -0,0 +1,418 @@
+//! Async client for the HTTP and WebSocket interfaces of dtnd
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dtn7::client::DtnClient;
+//! use futures::StreamExt;
+//!
+//! # async fn run() -> Result<(), dtn7::client::ClientError> {
+//! let client = DtnClient::new();
+//!
+//! let local_node = client.local_node_id().await?;
+//! client.register_application_endpoint("incoming").await?;
+//!
+//! let mut bundles = client.subscribe("incoming").await?;
+//! while let Some(bndl) = bundles.next().await {
+//!     println!("received {} on {}", bndl?.id(), local_node);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use super::SendOptions;
+use crate::core::journal::JournalEntry;
+use crate::core::status_reports::ReportedStatus;
+use crate::core::DtnStatistics;
+use crate::dtnd::api::{RoutingChange, RoutingResource};
+use crate::DtnPeer;
+use bp7::{Bundle, CreationTimestamp, EndpointID};
+use bytes::Bytes;
+use futures::{SinkExt, Stream, StreamExt};
+use http::{header, Method, Request, StatusCode};
+use hyper::client::HttpConnector;
+use hyper::Body;
+use log::debug;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::convert::TryFrom;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::protocol::{Message, WebSocketConfig};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// Maximum size of bundles received via WebSocket
+const WS_MAX_MESSAGE_SIZE: usize = 128 * 1024 * 1024;
+
+/// Reply of dtnd if an endpoint has no bundles queued
+const NOTHING_TO_RECEIVE: &[u8] = b"Nothing to receive";
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("http connection error: {0}")]
+    Http(#[from] hyper::Error),
+    #[error("invalid http request: {0}")]
+    Request(#[from] http::Error),
+    #[error("unexpected response ({status}): {message}")]
+    Status { status: StatusCode, message: String },
+    #[error("websocket error: {0}")]
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+    #[error("unexpected websocket reply: {0}")]
+    Protocol(String),
+    #[error("message not utf8: {0}")]
+    NonUtf8(#[from] std::string::FromUtf8Error),
+    #[error("serde json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid endpoint id: {0}")]
+    EndpointIdInvalid(#[from] bp7::eid::EndpointIdError),
+    #[error("invalid bundle: {0}")]
+    Bundle(#[from] bp7::error::Error),
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for ClientError {
+    fn from(err: tokio_tungstenite::tungstenite::Error) -> Self {
+        ClientError::WebSocket(Box::new(err))
+    }
+}
+
+/// Query string filtering bundles by an address, e.g., an endpoint ID
+fn addr_query(addr: &str) -> String {
+    url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("addr", addr)
+        .finish()
+}
+
+/// Async client for a dtnd instance
+#[derive(Debug, Clone)]
+pub struct DtnClient {
+    host: String,
+    port: u16,
+    token: Option<String>,
+    http: hyper::Client<HttpConnector>,
+}
+
+impl Default for DtnClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DtnClient {
+    /// Constructs a new client for `127.0.0.1` on port `3000`.
+    pub fn new() -> Self {
+        Self::with_host_and_port("127.0.0.1", 3000)
+    }
+    /// New client with custom host and port, IPv6 addresses must be given in brackets
+    pub fn with_host_and_port(host: impl Into<String>, port: u16) -> Self {
+        DtnClient {
+            host: host.into(),
+            port,
+            token: None,
+            http: hyper::Client::new(),
+        }
+    }
+    /// Authenticate all requests with the given bearer token
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    fn url(&self, scheme: &str, path: &str) -> String {
+        format!("{}://{}:{}{}", scheme, self.host, self.port, path)
+    }
+
+    fn builder(&self, method: Method, path: &str) -> http::request::Builder {
+        let mut req = Request::builder()
+            .method(method)
+            .uri(self.url("http", path));
+        if let Some(token) = &self.token {
+            req = req.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        req
+    }
+    async fn request(&self, method: Method, path: &str, body: Body) -> Result<Bytes, ClientError> {
+        self.execute(self.builder(method, path).body(body)?).await
+    }
+    async fn execute(&self, req: Request<Body>) -> Result<Bytes, ClientError> {
+        let res = self.http.request(req).await?;
+        let status = res.status();
+        let body = hyper::body::to_bytes(res.into_body()).await?;
+        if !status.is_success() {
+            return Err(ClientError::Status {
+                status,
+                message: String::from_utf8_lossy(&body).into(),
+            });
+        }
+        Ok(body)
+    }
+    async fn get(&self, path: &str) -> Result<Bytes, ClientError> {
+        self.request(Method::GET, path, Body::empty()).await
+    }
+    async fn get_text(&self, path: &str) -> Result<String, ClientError> {
+        Ok(String::from_utf8(self.get(path).await?.to_vec())?)
+    }
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        Ok(serde_json::from_slice(&self.get(path).await?)?)
+    }
+    async fn put_json<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        value: &impl serde::Serialize,
+    ) -> Result<T, ClientError> {
+        let req = self
+            .builder(Method::PUT, path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(value)?))?;
+        Ok(serde_json::from_slice(&self.execute(req).await?)?)
+    }
+
+    /// Return the local node ID
+    pub async fn local_node_id(&self) -> Result<EndpointID, ClientError> {
+        Ok(EndpointID::try_from(
+            self.get_text("/status/nodeid").await?,
+        )?)
+    }
+    /// Get a new node-wide unique creation timestamp
+    pub async fn creation_timestamp(&self) -> Result<CreationTimestamp, ClientError> {
+        self.get_json("/cts").await
+    }
+    /// Register a new application endpoint at the node, e.g., `incoming` or `dtn://helpers/~incoming`
+    pub async fn register_application_endpoint(&self, path: &str) -> Result<(), ClientError> {
+        self.get(&format!("/register?{}", path)).await?;
+        Ok(())
+    }
+    /// Unregister an application endpoint at the node
+    pub async fn unregister_application_endpoint(&self, path: &str) -> Result<(), ClientError> {
+        self.get(&format!("/unregister?{}", path)).await?;
+        Ok(())
+    }
+
+    /// Hand a complete bundle to the node for sending, returns the reply of dtnd
+    pub async fn send_bundle(&self, bndl: &mut Bundle) -> Result<String, ClientError> {
+        let res = self
+            .request(Method::POST, "/insert", Body::from(bndl.to_cbor()))
+            .await?;
+        Ok(String::from_utf8(res.to_vec())?)
+    }
+    /// Send data in a new bundle created by the node, returns the reply of dtnd
+    pub async fn send_data(
+        &self,
+        dst: &str,
+        lifetime: Duration,
+        data: Vec<u8>,
+    ) -> Result<String, ClientError> {
+        let opts = SendOptions {
+            lifetime,
+            ..Default::default()
+        };
+        self.send_data_with_options(dst, &opts, data).await
+    }
+    /// Send data in a new bundle created by the node with the given bundle options
+    pub async fn send_data_with_options(
+        &self,
+        dst: &str,
+        opts: &SendOptions,
+        data: Vec<u8>,
+    ) -> Result<String, ClientError> {
+        let query = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("dst", dst)
+            .extend_pairs(opts.to_query())
+            .finish();
+        let res = self
+            .request(Method::POST, &format!("/send?{}", query), Body::from(data))
+            .await?;
+        Ok(String::from_utf8(res.to_vec())?)
+    }
+    /// Fetch the next bundle queued for a registered endpoint, if any
+    pub async fn fetch_bundle(&self, endpoint: &str) -> Result<Option<Bundle>, ClientError> {
+        let res = self.get(&format!("/endpoint?{}", endpoint)).await?;
+        if res.as_ref() == NOTHING_TO_RECEIVE {
+            return Ok(None);
+        }
+        Ok(Some(Bundle::try_from(res.as_ref())?))
+    }
+    /// Download any bundle in the store by its ID
+    pub async fn download_bundle(&self, bid: &str) -> Result<Bundle, ClientError> {
+        let res = self.get(&format!("/download?{}", bid)).await?;
+        Ok(Bundle::try_from(res.as_ref())?)
+    }
+    /// Delete any bundle in the store by its ID
+    pub async fn delete_bundle(&self, bid: &str) -> Result<(), ClientError> {
+        self.request(Method::DELETE, &format!("/delete?{}", bid), Body::empty())
+            .await?;
+        Ok(())
+    }
+
+    /// Known peers by node name
+    pub async fn peers(&self) -> Result<BTreeMap<String, DtnPeer>, ClientError> {
+        self.get_json("/status/peers").await
+    }
+    /// Registered endpoint IDs
+    pub async fn endpoints(&self) -> Result<Vec<String>, ClientError> {
+        self.get_json("/status/eids").await
+    }
+    /// IDs of all bundles in the store
+    pub async fn bundles(&self) -> Result<Vec<String>, ClientError> {
+        self.get_json("/status/bundles").await
+    }
+    /// IDs of all bundles with a source or destination containing `addr`
+    pub async fn bundles_filtered(&self, addr: &str) -> Result<Vec<String>, ClientError> {
+        self.get_json(&format!("/status/bundles/filtered?{}", addr_query(addr)))
+            .await
+    }
+    /// Source, destination and creation time of all bundles in the store
+    pub async fn bundles_verbose(&self) -> Result<Vec<String>, ClientError> {
+        self.get_json("/status/bundles/verbose").await
+    }
+    /// Digest over all bundle IDs, optionally only those matching `addr`
+    pub async fn bundles_digest(&self, addr: Option<&str>) -> Result<String, ClientError> {
+        match addr {
+            Some(addr) => {
+                self.get_text(&format!(
+                    "/status/bundles/filtered/digest?{}",
+                    addr_query(addr)
+                ))
+                .await
+            }
+            None => self.get_text("/status/bundles/digest").await,
+        }
+    }
+    /// Bundle IDs with their current constraints
+    pub async fn store(&self) -> Result<Vec<String>, ClientError> {
+        self.get_json("/status/store").await
+    }
+    /// Statistics of the node
+    pub async fn info(&self) -> Result<DtnStatistics, ClientError> {
+        self.get_json("/status/info").await
+    }
+    /// Journal of lifecycle events of a bundle at the node
+    pub async fn journal(&self, bid: &str) -> Result<Vec<JournalEntry>, ClientError> {
+        self.get_json(&format!("/status/journal/{}", bid)).await
+    }
+    /// Journals of all bundles known to the node by bundle ID
+    pub async fn journals(&self) -> Result<BTreeMap<String, Vec<JournalEntry>>, ClientError> {
+        self.get_json("/status/journal").await
+    }
+    /// IDs of own bundles for which status reports were received
+    pub async fn reported_bundles(&self) -> Result<Vec<String>, ClientError> {
+        self.get_json("/status/reports").await
+    }
+    /// Delivery timeline of an own bundle from its received status reports
+    pub async fn status_reports(&self, bid: &str) -> Result<Vec<ReportedStatus>, ClientError> {
+        self.get_json(&format!("/status/reports/{}", bid)).await
+    }
+
+    /// Active routing agent, its settings and the available agents
+    pub async fn routing(&self) -> Result<RoutingResource, ClientError> {
+        self.get_json("/api/v1/routing").await
+    }
+    /// Replace the routing agent of the node at runtime.
+    ///
+    /// `settings` replace the current settings of the respective agents, e.g.,
+    /// `sprayandwait` => `num_copies` => `4`.
+    pub async fn set_routing(
+        &self,
+        strategy: &str,
+        settings: HashMap<String, HashMap<String, String>>,
+    ) -> Result<RoutingResource, ClientError> {
+        let change = RoutingChange {
+            strategy: strategy.into(),
+            settings,
+        };
+        self.put_json("/api/v1/routing", &change).await
+    }
+
+    /// Opens a WebSocket session receiving complete bundles for the given endpoint.
+    ///
+    /// The endpoint must be registered beforehand.
+    pub async fn subscribe(&self, endpoint: &str) -> Result<BundleSubscription, ClientError> {
+        let mut request = self.url("ws", "/ws").into_client_request()?;
+        if let Some(token) = &self.token {
+            request.headers_mut().insert(
+                header::AUTHORIZATION.as_str(),
+                format!("Bearer {}", token)
+                    .parse()
+                    .map_err(|_| ClientError::Protocol("invalid token".into()))?,
+            );
+        }
+        let config = WebSocketConfig {
+            max_message_size: Some(WS_MAX_MESSAGE_SIZE),
+            max_frame_size: Some(WS_MAX_MESSAGE_SIZE),
+            ..Default::default()
+        };
+        let (ws, _) =
+            tokio_tungstenite::connect_async_with_config(request, Some(config), false).await?;
+        let mut subscription = BundleSubscription {
+            ws,
+            pending: VecDeque::new(),
+        };
+        subscription
+            .command("/bundle", "200 tx mode: bundle")
+            .await?;
+        subscription.subscribe(endpoint).await?;
+        Ok(subscription)
+    }
+}
+
+/// WebSocket session in bundle mode, yields all bundles delivered to the subscribed endpoints.
+pub struct BundleSubscription {
+    ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    /// Bundles received while waiting for the reply to a command
+    pending: VecDeque<Bundle>,
+}
+
+impl BundleSubscription {
+    async fn command(&mut self, cmd: &str, expected: &str) -> Result<(), ClientError> {
+        self.ws.send(Message::Text(cmd.into())).await?;
+        while let Some(msg) = self.ws.next().await {
+            match msg? {
+                Message::Text(txt) if txt.starts_with(expected) => return Ok(()),
+                Message::Text(txt) => return Err(ClientError::Protocol(txt)),
+                Message::Binary(bin) => match Bundle::try_from(bin.as_slice()) {
+                    Ok(bndl) => self.pending.push_back(bndl),
+                    Err(err) => debug!("dropping invalid bundle from dtnd: {}", err),
+                },
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+        Err(ClientError::Protocol("connection closed".into()))
+    }
+    /// Additionally subscribe to another registered endpoint
+    pub async fn subscribe(&mut self, endpoint: &str) -> Result<(), ClientError> {
+        self.command(&format!("/subscribe {}", endpoint), "200 subscribed")
+            .await
+    }
+    /// Send a bundle through this session
+    pub async fn send_bundle(&mut self, bndl: &mut Bundle) -> Result<(), ClientError> {
+        self.ws.send(Message::Binary(bndl.to_cbor())).await?;
+        Ok(())
+    }
+    pub async fn close(mut self) -> Result<(), ClientError> {
+        self.ws.close(None).await?;
+        Ok(())
+    }
+}
+
+impl Stream for BundleSubscription {
+    type Item = Result<Bundle, ClientError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(bndl) = self.pending.pop_front() {
+            return Poll::Ready(Some(Ok(bndl)));
+        }
+        loop {
+            match futures::ready!(self.ws.poll_next_unpin(cx)) {
+                Some(Ok(Message::Binary(bin))) => {
+                    return Poll::Ready(Some(Bundle::try_from(bin.as_slice()).map_err(Into::into)))
+                }
+                Some(Ok(Message::Text(txt))) => debug!("dtnd: {}", txt),
+                Some(Ok(Message::Close(_))) | None => return Poll::Ready(None),
+                Some(Ok(_)) => {}
+                Some(Err(err)) => return Poll::Ready(Some(Err(err.into()))),
+            }
+        }
+    }
+}
@@ -3,3 +3,10 @@ pub mod ecla;
 pub mod erouting;
 
 pub mod data;
+
+pub mod dtnclient;
+
+pub mod send;
+
+pub use dtnclient::{BundleSubscription, ClientError, DtnClient};
+pub use send::SendOptions;
@@ -0,0 +1,267 @@
+use crate::routing::sprayandwait::{new_copy_count_block, COPY_COUNT_BLOCK};
+use bp7::canonical::{
+    new_bundle_age_block, new_canonical_block, new_hop_count_block, new_payload_block,
+    CanonicalBlockType, CanonicalData, BUNDLE_AGE_BLOCK, HOP_COUNT_BLOCK, PAYLOAD_BLOCK,
+    PREVIOUS_NODE_BLOCK,
+};
+use bp7::crc::{CrcRawType, CRC_16, CRC_32, CRC_NO};
+use bp7::flags::{BlockControlFlags, BundleControlFlags, BundleControlFlagsType};
+use bp7::{Bundle, CreationTimestamp, EndpointID};
+use std::convert::TryFrom;
+use std::time::Duration;
+
+/// Options for bundles constructed by `/send` or `dtnsend`
+///
+/// As query parameters:
+/// - `lifetime=<DURATION>`: bundle lifetime, e.g., `5m` (default: `1h`)
+/// - `flags=<BPCF>`: replace all bundle processing control flags by the given integer
+/// - `report=<reception,forwarding,delivery,deletion>`: request status reports
+/// - `dnf=<true|false>`: bundle must not be fragmented (default: `true`)
+/// - `ack=<true|false>`: request an acknowledgement by the application agent
+/// - `report_to=<EID>`: endpoint receiving status reports (default: source)
+/// - `hop_limit=<1-255>`: hop limit of the hop count block (default: `32`)
+/// - `age=<true|false>`: add a bundle age block
+/// - `copies=<int>`: copy budget for spray and wait routing, carried in a copy count block
+/// - `block=<TYPE>:<HEXDATA>`: additional extension block, may be repeated
+/// - `crc=<none|16|32>`: CRC type of all blocks (default: `none`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendOptions {
+    pub lifetime: Duration,
+    /// Bundle processing control flags
+    pub flags: BundleControlFlagsType,
+    pub report_to: Option<EndpointID>,
+    pub hop_limit: u8,
+    pub bundle_age: bool,
+    /// Number of copies spray and wait routing may spread
+    pub copies: Option<u64>,
+    pub extension_blocks: Vec<(CanonicalBlockType, Vec<u8>)>,
+    pub crc: CrcRawType,
+}
+
+impl Default for SendOptions {
+    fn default() -> Self {
+        SendOptions {
+            lifetime: Duration::from_secs(60 * 60),
+            flags: BundleControlFlags::BUNDLE_MUST_NOT_FRAGMENTED.bits(),
+            report_to: None,
+            hop_limit: 32,
+            bundle_age: false,
+            copies: None,
+            extension_blocks: Vec::new(),
+            crc: CRC_NO,
+        }
+    }
+}
+
+/// Status report flag by name as used in the `report` parameter
+pub fn status_report_flag(name: &str) -> Option<BundleControlFlags> {
+    match name {
+        "reception" => Some(BundleControlFlags::BUNDLE_STATUS_REQUEST_RECEPTION),
+        "forwarding" => Some(BundleControlFlags::BUNDLE_STATUS_REQUEST_FORWARD),
+        "delivery" => Some(BundleControlFlags::BUNDLE_STATUS_REQUEST_DELIVERY),
+        "deletion" => Some(BundleControlFlags::BUNDLE_STATUS_REQUEST_DELETION),
+        _ => None,
+    }
+}
+
+/// CRC type by name as used in the `crc` parameter
+pub fn crc_type(name: &str) -> Option<CrcRawType> {
+    match name {
+        "none" | "0" => Some(CRC_NO),
+        "16" | "crc16" => Some(CRC_16),
+        "32" | "crc32" => Some(CRC_32),
+        _ => None,
+    }
+}
+
+fn crc_name(crc: CrcRawType) -> &'static str {
+    match crc {
+        CRC_16 => "16",
+        CRC_32 => "32",
+        _ => "none",
+    }
+}
+
+/// Parses an extension block given as `<TYPE>:<HEXDATA>`
+pub fn parse_extension_block(value: &str) -> Result<(CanonicalBlockType, Vec<u8>), &'static str> {
+    let (block_type, data) = value
+        .split_once(':')
+        .ok_or("Extension blocks must be given as <TYPE>:<HEXDATA>!")?;
+    let block_type: CanonicalBlockType = block_type
+        .trim()
+        .parse()
+        .map_err(|_| "Invalid extension block type!")?;
+    if matches!(
+        block_type,
+        0 | PAYLOAD_BLOCK
+            | PREVIOUS_NODE_BLOCK
+            | BUNDLE_AGE_BLOCK
+            | HOP_COUNT_BLOCK
+            | COPY_COUNT_BLOCK
+    ) {
+        return Err("Extension block type is reserved or set by dedicated option!");
+    }
+    let data = bp7::helpers::unhexify(data.trim()).map_err(|_| "Invalid extension block data!")?;
+    Ok((block_type, data))
+}
+
+fn parse_bool(value: &str) -> Result<bool, &'static str> {
+    match value {
+        "" | "1" | "true" | "yes" => Ok(true),
+        "0" | "false" | "no" => Ok(false),
+        _ => Err("Invalid boolean value!"),
+    }
+}
+
+fn set_flag(
+    flags: &mut BundleControlFlagsType,
+    flag: BundleControlFlags,
+    value: &str,
+) -> Result<(), &'static str> {
+    if parse_bool(value)? {
+        *flags |= flag.bits();
+    } else {
+        *flags &= !flag.bits();
+    }
+    Ok(())
+}
+
+impl SendOptions {
+    /// Parses the options from query parameters, unknown parameters are ignored.
+    pub fn from_query(params: &[(String, String)]) -> Result<Self, &'static str> {
+        let mut opts = SendOptions::default();
+        // raw flags are applied first, so named options can be combined with them
+        if let Some((_, v)) = params.iter().rev().find(|(k, _)| k == "flags") {
+            let param_flags: u64 = v
+                .parse()
+                .map_err(|_| "Invalid Bundle Processing Control Flags!")?;
+            opts.flags = BundleControlFlags::from_bits(param_flags)
+                .ok_or("Invalid Bundle Processing Control Flags!")?
+                .bits();
+        }
+        for (k, v) in params {
+            match k.as_str() {
+                "lifetime" => {
+                    opts.lifetime =
+                        humantime::parse_duration(v).map_err(|_| "Invalid lifetime!")?;
+                }
+                "report" => {
+                    for name in v.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                        opts.flags |= status_report_flag(name)
+                            .ok_or("Invalid status report type!")?
+                            .bits();
+                    }
+                }
+                "dnf" => set_flag(
+                    &mut opts.flags,
+                    BundleControlFlags::BUNDLE_MUST_NOT_FRAGMENTED,
+                    v,
+                )?,
+                "ack" => set_flag(
+                    &mut opts.flags,
+                    BundleControlFlags::BUNDLE_REQUEST_USER_APPLICATION_ACK,
+                    v,
+                )?,
+                "report_to" => {
+                    opts.report_to = Some(
+                        EndpointID::try_from(v.as_str())
+                            .map_err(|_| "Invalid report-to endpoint id!")?,
+                    );
+                }
+                "hop_limit" => {
+                    opts.hop_limit = v
+                        .parse()
+                        .ok()
+                        .filter(|limit| *limit > 0)
+                        .ok_or("Hop limit must be between 1 and 255!")?;
+                }
+                "age" => opts.bundle_age = parse_bool(v)?,
+                "copies" => {
+                    opts.copies = Some(
+                        v.parse()
+                            .ok()
+                            .filter(|copies| *copies > 0)
+                            .ok_or("Number of copies must be a positive integer!")?,
+                    );
+                }
+                "block" => opts.extension_blocks.push(parse_extension_block(v)?),
+                "crc" => opts.crc = crc_type(v).ok_or("Invalid CRC type!")?,
+                _ => {}
+            }
+        }
+        Ok(opts)
+    }
+
+    /// Query parameters representing these options, counterpart to [`SendOptions::from_query`]
+    pub fn to_query(&self) -> Vec<(String, String)> {
+        let mut params = vec![
+            (
+                "lifetime".into(),
+                format!("{}ms", self.lifetime.as_millis()),
+            ),
+            ("flags".into(), self.flags.to_string()),
+            ("hop_limit".into(), self.hop_limit.to_string()),
+            ("age".into(), self.bundle_age.to_string()),
+            ("crc".into(), crc_name(self.crc).into()),
+        ];
+        if let Some(report_to) = &self.report_to {
+            params.push(("report_to".into(), report_to.to_string()));
+        }
+        if let Some(copies) = self.copies {
+            params.push(("copies".into(), copies.to_string()));
+        }
+        for (block_type, data) in &self.extension_blocks {
+            params.push((
+                "block".into(),
+                format!("{}:{}", block_type, bp7::helpers::hexify(data)),
+            ));
+        }
+        params
+    }
+
+    /// Constructs a new bundle with these options.
+    pub fn build_bundle(
+        &self,
+        src: EndpointID,
+        dst: EndpointID,
+        creation_timestamp: CreationTimestamp,
+        payload: Vec<u8>,
+    ) -> Bundle {
+        let report_to = self.report_to.clone().unwrap_or_else(|| src.clone());
+        let pblock = bp7::primary::PrimaryBlockBuilder::default()
+            .bundle_control_flags(self.flags)
+            .destination(dst)
+            .source(src)
+            .report_to(report_to)
+            .creation_timestamp(creation_timestamp)
+            .lifetime(self.lifetime)
+            .build()
+            .unwrap();
+
+        let mut bndl = Bundle::new(
+            pblock,
+            vec![new_payload_block(BlockControlFlags::empty(), payload)],
+        );
+        bndl.add_canonical_block(new_hop_count_block(
+            0,
+            BlockControlFlags::empty(),
+            self.hop_limit,
+        ));
+        if self.bundle_age {
+            bndl.add_canonical_block(new_bundle_age_block(0, BlockControlFlags::empty(), 0));
+        }
+        if let Some(copies) = self.copies {
+            bndl.add_canonical_block(new_copy_count_block(0, BlockControlFlags::empty(), copies));
+        }
+        for (block_type, data) in &self.extension_blocks {
+            bndl.add_canonical_block(new_canonical_block(
+                *block_type,
+                0,
+                BlockControlFlags::empty().bits(),
+                CanonicalData::Unknown(data.clone()),
+            ));
+        }
+        bndl.set_crc(self.crc);
+        bndl
+    }
+}
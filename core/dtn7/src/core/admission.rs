@@ -0,0 +1,329 @@
+use crate::core::peer::PeerAddress;
+use crate::core::DtnPeer;
+use crate::node::node_local;
+use crate::routing::RoutingNotifcation;
+use crate::CONFIG;
+use anyhow::Context;
+use glob_match::glob_match;
+use log::{debug, error, info};
+use parking_lot::Mutex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::net::IpAddr;
+use std::path::Path;
+use std::str::FromStr;
+
+/*
+    Admission policy for peers and bundles.
+
+    An ordered list of rules decides whether a node may peer with this node and whether bundles
+    received from it are accepted. The first rule matching a node ID, source address and CLA
+    wins, if none matches the default action applies. The policy is checked on discovery of a
+    peer, when a TCP session is established and for every received bundle.
+
+    Changes at runtime are written back to the `[admission]` section of the config file and
+    applied to the known peers and established TCP sessions.
+*/
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    #[default]
+    Allow,
+    Deny,
+}
+
+impl FromStr for Action {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "allow" => Ok(Self::Allow),
+            "deny" => Ok(Self::Deny),
+            _ => Err(format!("unknown admission action: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Allow => write!(f, "allow"),
+            Self::Deny => write!(f, "deny"),
+        }
+    }
+}
+
+/// Range of IP addresses in CIDR notation, a plain address matches only itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddrRange {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl FromStr for AddrRange {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid address: {}", s))?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix| *prefix <= max)
+                .ok_or_else(|| format!("invalid prefix length: {}", s))?,
+            None => max,
+        };
+        Ok(AddrRange { addr, prefix })
+    }
+}
+
+impl fmt::Display for AddrRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+impl Serialize for AddrRange {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AddrRange {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        String::deserialize(d)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl AddrRange {
+    /// Example
+    ///
+    /// ```
+    /// use dtn7::core::admission::AddrRange;
+    ///
+    /// let range: AddrRange = "10.1.0.0/16".parse().unwrap();
+    /// assert!(range.contains("10.1.2.3".parse().unwrap()));
+    /// assert!(!range.contains("10.2.0.1".parse().unwrap()));
+    /// assert!(!range.contains("::ffff:10.1.2.3".parse().unwrap()));
+    /// ```
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        fn masked(bits: u128, prefix: u8, width: u8) -> u128 {
+            if prefix == 0 {
+                0
+            } else {
+                bits >> (width - prefix)
+            }
+        }
+        match (self.addr, addr) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                masked(u32::from(net) as u128, self.prefix, 32)
+                    == masked(u32::from(addr) as u128, self.prefix, 32)
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                masked(u128::from(net), self.prefix, 128)
+                    == masked(u128::from(addr), self.prefix, 128)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A single admission rule, unset conditions match everything
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdmissionRule {
+    pub action: Action,
+    /// Glob pattern of node names, e.g., `node*`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub addr: Option<AddrRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cla: Option<String>,
+}
+
+/// What is known about a node asking for admission
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Candidate<'a> {
+    pub node: Option<&'a str>,
+    pub addr: Option<IpAddr>,
+    pub cla: Option<&'a str>,
+}
+
+impl AdmissionRule {
+    /// A condition can only match if the candidate provides the corresponding information
+    pub fn matches(&self, candidate: &Candidate) -> bool {
+        let node = match (&self.node, candidate.node) {
+            (Some(pattern), Some(node)) => glob_match(pattern, node),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let addr = match (&self.addr, candidate.addr) {
+            (Some(range), Some(addr)) => range.contains(addr),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let cla = match (&self.cla, candidate.cla) {
+            (Some(name), Some(cla)) => name == cla,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        node && addr && cla
+    }
+}
+
+/// Settings of the `[admission]` config section
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdmissionPolicy {
+    #[serde(default)]
+    pub default: Action,
+    #[serde(default)]
+    pub rules: Vec<AdmissionRule>,
+}
+
+impl AdmissionPolicy {
+    /// Action of the first matching rule or the default action
+    pub fn action(&self, candidate: &Candidate) -> Action {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(candidate))
+            .map(|rule| rule.action)
+            .unwrap_or(self.default)
+    }
+    pub fn admits(&self, candidate: &Candidate) -> bool {
+        self.action(candidate) == Action::Allow
+    }
+    /// Removes the CLAs the policy denies from a discovered peer.
+    ///
+    /// Returns false if the peer is not admitted at all.
+    pub fn admit_peer(&self, peer: &mut DtnPeer) -> bool {
+        let node = peer.node_name();
+        let addr = match peer.addr() {
+            PeerAddress::Ip(addr) => Some(*addr),
+            _ => None,
+        };
+        let candidate = Candidate {
+            node: Some(&node),
+            addr,
+            cla: None,
+        };
+        if peer.cla_list.is_empty() {
+            return self.admits(&candidate);
+        }
+        peer.cla_list.retain(|(cla, _)| {
+            self.admits(&Candidate {
+                cla: Some(cla),
+                ..candidate
+            })
+        });
+        !peer.cla_list.is_empty()
+    }
+
+    /// Writes the policy to the `[admission]` section of a TOML config file,
+    /// keeping the rest of the file as it is
+    pub fn persist(&self, path: &Path) -> anyhow::Result<()> {
+        use toml_edit::{value, ArrayOfTables, DocumentMut, Item, Table};
+
+        let content = std::fs::read_to_string(path).unwrap_or_default();
+        let mut doc: DocumentMut = content
+            .parse()
+            .with_context(|| format!("parsing {}", path.display()))?;
+        let mut section = Table::new();
+        section["default"] = value(self.default.to_string());
+        let mut rules = ArrayOfTables::new();
+        for rule in &self.rules {
+            let mut table = Table::new();
+            table["action"] = value(rule.action.to_string());
+            if let Some(node) = &rule.node {
+                table["node"] = value(node);
+            }
+            if let Some(addr) = &rule.addr {
+                table["addr"] = value(addr.to_string());
+            }
+            if let Some(cla) = &rule.cla {
+                table["cla"] = value(cla);
+            }
+            rules.push(table);
+        }
+        section["rules"] = Item::ArrayOfTables(rules);
+        doc["admission"] = Item::Table(section);
+
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        let mut file = tempfile::NamedTempFile::new_in(dir)?;
+        std::io::Write::write_all(&mut file, doc.to_string().as_bytes())?;
+        file.persist(path)?;
+        Ok(())
+    }
+}
+
+/// Checks a node against the admission policy of this node
+pub fn admitted(candidate: Candidate) -> bool {
+    let admitted = CONFIG.lock().admission.admits(&candidate);
+    if !admitted {
+        debug!("Refused by admission policy: {:?}", candidate);
+    }
+    admitted
+}
+
+node_local! {
+    /// Serializes runtime changes of the policy and their persistence
+    static ref UPDATE: Mutex<()> = Mutex::new(());
+}
+
+/// Changes the admission policy at runtime.
+///
+/// The change is persisted first if the node was started with a config file. If `change`
+/// returns `None`, e.g., for an unknown rule, the policy is left untouched.
+pub fn update_policy<T>(
+    change: impl FnOnce(&mut AdmissionPolicy) -> Option<T>,
+) -> anyhow::Result<Option<T>> {
+    let _update = UPDATE.lock();
+    let mut policy = CONFIG.lock().admission.clone();
+    let result = match change(&mut policy) {
+        Some(result) => result,
+        None => return Ok(None),
+    };
+    let config_file = CONFIG.lock().config_file.clone();
+    if let Some(path) = config_file {
+        policy.persist(&path)?;
+    }
+    CONFIG.lock().admission = policy;
+    Ok(Some(result))
+}
+
+/// Applies the current policy to the known peers and established sessions.
+///
+/// Peers no longer admitted are dropped, the denied CLAs of the remaining peers are removed.
+pub async fn enforce_policy() {
+    let policy = CONFIG.lock().admission.clone();
+    let mut dropped = Vec::new();
+    (*crate::PEERS.lock()).retain(|_, peer| {
+        let mut admitted = peer.clone();
+        if !policy.admit_peer(&mut admitted) {
+            dropped.push(admitted);
+            return false;
+        }
+        peer.cla_list = admitted.cla_list;
+        true
+    });
+    for peer in dropped {
+        info!("Dropping peer {} refused by admission policy", peer.eid);
+        crate::core::peer::archive_metrics(&peer);
+        if let Err(err) = crate::routing_notify(RoutingNotifcation::DroppedPeer(peer.eid)).await {
+            error!("Failed to notify routing agent about dropped peer: {}", err);
+        }
+    }
+    crate::cla::tcp::close_refused_sessions().await;
+}
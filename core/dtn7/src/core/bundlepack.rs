@@ -1,3 +1,4 @@
+use crate::core::journal::JournalEntry;
 use crate::store_remove;
 use crate::store_update_metadata;
 use anyhow::Result;
@@ -54,6 +55,9 @@ pub struct BundlePack {
     pub administrative: bool,
     pub size: usize,
     pub constraints: HashSet<Constraint>,
+    /// lifecycle events of this bundle at this node
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub journal: Vec<JournalEntry>,
 }
 
 impl fmt::Display for BundlePack {
@@ -83,6 +87,7 @@ impl From<Bundle> for BundlePack {
             administrative: bundle.is_administrative_record(),
             size,
             constraints: HashSet::new(),
+            journal: Vec::new(),
         }
     }
 }
@@ -108,6 +113,7 @@ impl From<&Bundle> for BundlePack {
             administrative: bundle.is_administrative_record(),
             size,
             constraints: HashSet::new(),
+            journal: Vec::new(),
         }
     }
 }
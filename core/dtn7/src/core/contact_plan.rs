@@ -0,0 +1,352 @@
+use crate::core::{DtnPeer, PeerType};
+use crate::routing::RoutingNotifcation;
+use crate::{peers_add, peers_remove_if_type, routing_notify, CONFIG};
+use log::{error, info};
+use serde::{Serialize, Serializer};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/*
+    Contact plan of scheduled peers.
+
+    Scheduled peers are only known during their configured contact windows, e.g., the passes
+    of a ground station. A window starts at an absolute time or at every match of a cron-like
+    expression and lasts for a fixed duration. At the start of a window the peer is added and
+    routing is notified of an encountered peer, at the end it is removed again.
+
+    All times are UTC.
+*/
+
+/// Upper bound of the time to sleep between two checks of the contact plan
+pub const CONTACT_PLAN_MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// How far ahead the next start of a recurring window is searched
+const CRON_SEARCH_HORIZON: u64 = 4 * 366 * 86400;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("time went backwards")
+        .as_secs()
+}
+
+/// Converts days since the unix epoch to (year, month, day)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// One field of a cron expression as bit set of the matching values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CronField {
+    values: u64,
+    any: bool,
+}
+
+impl CronField {
+    fn parse(field: &str, min: u32, max: u32) -> Result<Self, String> {
+        let mut values = 0u64;
+        for item in field.split(',') {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (
+                    range,
+                    step.parse::<u32>()
+                        .ok()
+                        .filter(|step| *step > 0)
+                        .ok_or_else(|| format!("invalid step: {}", item))?,
+                ),
+                None => (item, 1),
+            };
+            let (from, to) = if range == "*" {
+                (min, max)
+            } else if let Some((from, to)) = range.split_once('-') {
+                (parse_cron_value(from)?, parse_cron_value(to)?)
+            } else {
+                let value = parse_cron_value(range)?;
+                // "5/10" starts at 5 and repeats until the end of the range
+                (value, if item.contains('/') { max } else { value })
+            };
+            if from < min || to > max || from > to {
+                return Err(format!("value out of range {}-{}: {}", min, max, item));
+            }
+            for value in (from..=to).step_by(step as usize) {
+                values |= 1 << value;
+            }
+        }
+        Ok(CronField {
+            values,
+            any: field == "*",
+        })
+    }
+    fn matches(&self, value: u32) -> bool {
+        self.values & (1 << value) != 0
+    }
+}
+
+fn parse_cron_value(value: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value: {}", value))
+}
+
+/// Recurrence in the classic cron format `minute hour day-of-month month day-of-week`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minute: CronField,
+    hour: CronField,
+    day: CronField,
+    month: CronField,
+    weekday: CronField,
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "expected 5 cron fields, got {}: {}",
+                fields.len(),
+                s
+            ));
+        }
+        let mut weekday = CronField::parse(fields[4], 0, 7)?;
+        // both 0 and 7 are sunday
+        if weekday.matches(7) {
+            weekday.values |= 1;
+        }
+        Ok(CronSchedule {
+            minute: CronField::parse(fields[0], 0, 59)?,
+            hour: CronField::parse(fields[1], 0, 23)?,
+            day: CronField::parse(fields[2], 1, 31)?,
+            month: CronField::parse(fields[3], 1, 12)?,
+            weekday,
+        })
+    }
+}
+
+impl CronSchedule {
+    fn matches_day(&self, time: u64) -> bool {
+        let days = (time / 86400) as i64;
+        let (_, month, day) = civil_from_days(days);
+        // 1970-01-01 was a thursday
+        let weekday = ((days + 4) % 7) as u32;
+        if !self.month.matches(month) {
+            return false;
+        }
+        // like cron, restricting both day fields matches either of them
+        match (self.day.any, self.weekday.any) {
+            (false, false) => self.day.matches(day) || self.weekday.matches(weekday),
+            _ => self.day.matches(day) && self.weekday.matches(weekday),
+        }
+    }
+    /// Returns true if the schedule fires in the minute of the given unix timestamp
+    pub fn matches(&self, time: u64) -> bool {
+        self.matches_day(time)
+            && self.hour.matches(((time % 86400) / 3600) as u32)
+            && self.minute.matches(((time % 3600) / 60) as u32)
+    }
+    /// First time the schedule fires at or after the given unix timestamp
+    pub fn next(&self, time: u64) -> Option<u64> {
+        // start of the minute, the next one if already within
+        let mut t = time.div_ceil(60) * 60;
+        let horizon = time + CRON_SEARCH_HORIZON;
+        while t < horizon {
+            if !self.matches_day(t) {
+                t = (t / 86400 + 1) * 86400;
+            } else if !self.hour.matches(((t % 86400) / 3600) as u32) {
+                t = (t / 3600 + 1) * 3600;
+            } else if !self.minute.matches(((t % 3600) / 60) as u32) {
+                t += 60;
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+    /// Latest time the schedule fired in the interval (`after`, `time`]
+    fn last(&self, time: u64, after: u64) -> Option<u64> {
+        let mut t = time / 60 * 60;
+        while t > after {
+            if self.matches(t) {
+                return Some(t);
+            }
+            t -= 60;
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowStart {
+    /// Unix timestamp
+    At(u64),
+    Recurring(CronSchedule),
+}
+
+/// A contact window, written as `<start> for <duration>`
+///
+/// The start is either a RFC 3339 timestamp, e.g., `2024-06-01T12:00:00Z for 10m`,
+/// or a cron expression, e.g., `30 */2 * * * for 8m`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContactWindow {
+    pub start: WindowStart,
+    pub duration: Duration,
+    spec: String,
+}
+
+impl FromStr for ContactWindow {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, duration) = s
+            .rsplit_once(" for ")
+            .ok_or_else(|| format!("expected '<start> for <duration>': {}", s))?;
+        let duration: Duration = duration
+            .trim()
+            .parse::<humantime::Duration>()
+            .map_err(|err| format!("invalid duration in {}: {}", s, err))?
+            .into();
+        if duration.as_secs() == 0 {
+            return Err(format!("empty contact window: {}", s));
+        }
+        let start = start.trim();
+        let start = if let Ok(time) = humantime::parse_rfc3339_weak(start) {
+            WindowStart::At(
+                time.duration_since(UNIX_EPOCH)
+                    .map_err(|_| format!("start before 1970: {}", s))?
+                    .as_secs(),
+            )
+        } else {
+            WindowStart::Recurring(start.parse()?)
+        };
+        Ok(ContactWindow {
+            start,
+            duration,
+            spec: s.trim().to_string(),
+        })
+    }
+}
+
+impl fmt::Display for ContactWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.spec)
+    }
+}
+
+impl Serialize for ContactWindow {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl ContactWindow {
+    /// End of the window if it is open at the given unix timestamp
+    pub fn open_at(&self, time: u64) -> Option<u64> {
+        let duration = self.duration.as_secs();
+        let start = match &self.start {
+            WindowStart::At(start) => Some(*start).filter(|start| *start <= time),
+            WindowStart::Recurring(cron) => cron.last(time, time.saturating_sub(duration)),
+        }?;
+        Some(start + duration).filter(|end| *end > time)
+    }
+    /// Next start of the window after the given unix timestamp
+    pub fn next_start(&self, time: u64) -> Option<u64> {
+        match &self.start {
+            WindowStart::At(start) => Some(*start).filter(|start| *start > time),
+            WindowStart::Recurring(cron) => cron.next(time + 1),
+        }
+    }
+}
+
+/// A peer that is only reachable during its contact windows
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledPeer {
+    pub peer: DtnPeer,
+    pub windows: Vec<ContactWindow>,
+}
+
+impl ScheduledPeer {
+    pub fn new(mut peer: DtnPeer, windows: Vec<ContactWindow>) -> Self {
+        peer.con_type = PeerType::Scheduled;
+        ScheduledPeer { peer, windows }
+    }
+    /// End of the current contact if any window is open at the given unix timestamp
+    pub fn open_until(&self, time: u64) -> Option<u64> {
+        self.windows.iter().filter_map(|w| w.open_at(time)).max()
+    }
+    /// Next time the peer becomes reachable or unreachable
+    pub fn next_change(&self, time: u64) -> Option<u64> {
+        let next_start = self.windows.iter().filter_map(|w| w.next_start(time)).min();
+        match (self.open_until(time), next_start) {
+            (Some(end), Some(start)) => Some(end.min(start)),
+            (end, start) => end.or(start),
+        }
+    }
+}
+
+/// Adds and removes scheduled peers according to their contact windows
+pub async fn contact_plan() {
+    let plan = CONFIG.lock().scheduled.clone();
+    let mut reachable: BTreeSet<String> = BTreeSet::new();
+    loop {
+        let now = now();
+        for scheduled in &plan {
+            let node = scheduled.peer.node_name();
+            let open = scheduled.open_until(now);
+            if let Some(end) = open {
+                if reachable.insert(node.clone()) {
+                    info!(
+                        "Contact window of scheduled peer {} opened, closes in {}s",
+                        node,
+                        end - now
+                    );
+                    let mut peer = scheduled.peer.clone();
+                    peer.touch();
+                    peers_add(peer);
+                    if let Err(err) = routing_notify(RoutingNotifcation::EncounteredPeer(
+                        scheduled.peer.eid.clone(),
+                    ))
+                    .await
+                    {
+                        error!("Error while encountered peer notification: {}", err);
+                    }
+                }
+            } else if reachable.remove(&node) {
+                info!("Contact window of scheduled peer {} closed", node);
+                // the peer might have been replaced by a static or dynamic one
+                if !peers_remove_if_type(&node, PeerType::Scheduled) {
+                    continue;
+                }
+                if let Err(err) =
+                    routing_notify(RoutingNotifcation::DroppedPeer(scheduled.peer.eid.clone()))
+                        .await
+                {
+                    error!("Error while dropping peer: {}", err);
+                }
+            }
+        }
+        let sleep = plan
+            .iter()
+            .filter_map(|scheduled| scheduled.next_change(now))
+            .min()
+            .map(|next| Duration::from_secs(next.saturating_sub(now)))
+            .unwrap_or(CONTACT_PLAN_MAX_SLEEP)
+            .min(CONTACT_PLAN_MAX_SLEEP)
+            .max(Duration::from_millis(100));
+        tokio::time::sleep(sleep).await;
+    }
+}
+
+pub fn spawn_contact_plan() {
+    crate::node::spawn(contact_plan());
+}
@@ -0,0 +1,190 @@
+use crate::routing::RoutingNotifcation;
+use crate::EVENTS;
+use bp7::administrative_record::*;
+use glob_match::glob_match;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of events buffered for slow subscribers before they start missing events
+pub const EVENT_BUFFER_SIZE: usize = 1024;
+
+/// Deletion reason of bundles removed through the HTTP interfaces
+pub const REASON_USER_REQUEST: &str = "user_request";
+
+/// Observable state change of the node, published to all event stream subscribers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NodeEvent {
+    PeerEncountered {
+        eid: String,
+    },
+    PeerDropped {
+        eid: String,
+    },
+    PeerChanged {
+        eid: String,
+        /// Names of the changed fields, e.g., `addr` or `cla_list`
+        changes: Vec<String>,
+    },
+    BundleReceived {
+        bid: String,
+        source: String,
+        destination: String,
+    },
+    BundleForwarded {
+        bid: String,
+        next_hop: String,
+        cla: String,
+    },
+    BundleForwardingFailed {
+        bid: String,
+        next_hop: String,
+        cla: String,
+    },
+    BundleDelivered {
+        bid: String,
+        destination: String,
+    },
+    BundleDeleted {
+        bid: String,
+        reason: String,
+    },
+    ClaUp {
+        name: String,
+        port: u16,
+    },
+    ClaDown {
+        name: String,
+    },
+    Routing {
+        notification: String,
+        /// Bundle ID or peer the notification is about
+        subject: String,
+    },
+}
+
+impl NodeEvent {
+    /// Name of the event type as used in the serialized `type` field
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NodeEvent::PeerEncountered { .. } => "peer_encountered",
+            NodeEvent::PeerDropped { .. } => "peer_dropped",
+            NodeEvent::PeerChanged { .. } => "peer_changed",
+            NodeEvent::BundleReceived { .. } => "bundle_received",
+            NodeEvent::BundleForwarded { .. } => "bundle_forwarded",
+            NodeEvent::BundleForwardingFailed { .. } => "bundle_forwarding_failed",
+            NodeEvent::BundleDelivered { .. } => "bundle_delivered",
+            NodeEvent::BundleDeleted { .. } => "bundle_deleted",
+            NodeEvent::ClaUp { .. } => "cla_up",
+            NodeEvent::ClaDown { .. } => "cla_down",
+            NodeEvent::Routing { .. } => "routing",
+        }
+    }
+    /// All endpoint IDs and bundle IDs the event refers to
+    fn subjects(&self) -> Vec<&str> {
+        match self {
+            NodeEvent::PeerEncountered { eid }
+            | NodeEvent::PeerDropped { eid }
+            | NodeEvent::PeerChanged { eid, .. } => vec![eid],
+            NodeEvent::BundleReceived {
+                bid,
+                source,
+                destination,
+            } => vec![bid, source, destination],
+            NodeEvent::BundleForwarded { bid, next_hop, .. }
+            | NodeEvent::BundleForwardingFailed { bid, next_hop, .. } => vec![bid, next_hop],
+            NodeEvent::BundleDelivered { bid, destination } => vec![bid, destination],
+            NodeEvent::BundleDeleted { bid, .. } => vec![bid],
+            NodeEvent::ClaUp { .. } | NodeEvent::ClaDown { .. } => vec![],
+            NodeEvent::Routing { subject, .. } => vec![subject],
+        }
+    }
+}
+
+impl From<&RoutingNotifcation> for NodeEvent {
+    fn from(notification: &RoutingNotifcation) -> Self {
+        let (notification, subject) = match notification {
+            RoutingNotifcation::SendingFailed(bid, _) => ("sending_failed", bid.clone()),
+            RoutingNotifcation::SendingSucceeded(bid, _) => ("sending_succeeded", bid.clone()),
+            RoutingNotifcation::IncomingBundle(bndl) => ("incoming_bundle", bndl.id()),
+            RoutingNotifcation::IncomingBundleWithoutPreviousNode(bid, _) => {
+                ("incoming_bundle_without_previous_node", bid.clone())
+            }
+            RoutingNotifcation::EncounteredPeer(eid) => ("encountered_peer", eid.to_string()),
+            RoutingNotifcation::DroppedPeer(eid) => ("dropped_peer", eid.to_string()),
+            RoutingNotifcation::PeerChanged(eid, _) => ("peer_changed", eid.to_string()),
+        };
+        NodeEvent::Routing {
+            notification: notification.into(),
+            subject,
+        }
+    }
+}
+
+/// Event as delivered to subscribers
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimedEvent {
+    /// Unix timestamp in milliseconds
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub event: NodeEvent,
+}
+
+/// Server side filter for event subscriptions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventFilter {
+    /// Comma separated list of event types, e.g., `peer_encountered,bundle_delivered`
+    pub types: Option<String>,
+    /// Glob pattern matched against the endpoint and bundle IDs of an event
+    pub eid: Option<String>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &NodeEvent) -> bool {
+        if let Some(types) = &self.types {
+            if !types.split(',').any(|t| t.trim() == event.kind()) {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.eid {
+            return event
+                .subjects()
+                .iter()
+                .any(|subject| glob_match(pattern, subject));
+        }
+        true
+    }
+}
+
+/// Human readable name of a status report reason code
+pub fn reason_name(reason: StatusReportReason) -> &'static str {
+    match reason {
+        NO_INFORMATION => "no_information",
+        LIFETIME_EXPIRED => "lifetime_expired",
+        FORWARD_UNIDIRECTIONAL_LINK => "forward_unidirectional_link",
+        TRANSMISSION_CANCELED => "transmission_canceled",
+        DEPLETED_STORAGE => "depleted_storage",
+        DEST_ENDPOINT_UNINTELLIGIBLE => "dest_endpoint_unintelligible",
+        NO_ROUTE_TO_DESTINATION => "no_route_to_destination",
+        NO_NEXT_NODE_CONTACT => "no_next_node_contact",
+        BLOCK_UNINTELLIGIBLE => "block_unintelligible",
+        HOP_LIMIT_EXCEEDED => "hop_limit_exceeded",
+        TRAFFIC_PARED => "traffic_pared",
+        BLOCK_UNSUPPORTED => "block_unsupported",
+        _ => "unknown",
+    }
+}
+
+/// Publishes an event to all current subscribers.
+pub fn publish(event: NodeEvent) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    // an error only means that nobody is listening
+    let _ = EVENTS.get().send(TimedEvent { timestamp, event });
+}
+
+pub fn subscribe() -> tokio::sync::broadcast::Receiver<TimedEvent> {
+    EVENTS.get().subscribe()
+}
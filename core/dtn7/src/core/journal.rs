@@ -0,0 +1,124 @@
+use crate::cla::ClaSenderTask;
+use crate::core::bundlepack::Constraint;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/*
+    Per-bundle journal of lifecycle events.
+
+    The journal is part of the bundle metadata in the store. It is only appended to through
+    `store_journal` and merged on every metadata update, so stale copies of a `BundlePack`
+    cannot overwrite entries recorded in the meantime.
+*/
+
+/// Maximum number of entries per bundle, the oldest are dropped except for the first one
+pub const MAX_JOURNAL_ENTRIES: usize = 64;
+
+/// Next hop chosen by the routing agent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NextHop {
+    pub cla: String,
+    pub dest: String,
+    pub next_hop: String,
+    /// Copy budget handed to the next hop by spray and wait routing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copies: Option<u64>,
+}
+
+impl From<&ClaSenderTask> for NextHop {
+    fn from(task: &ClaSenderTask) -> Self {
+        NextHop {
+            cla: task.cla_name.clone(),
+            dest: task.dest.clone(),
+            next_hop: task.next_hop.to_string(),
+            copies: task.copies,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalEvent {
+    /// Bundle was created by a local application
+    Created,
+    /// Bundle was received via a convergence layer
+    Received {
+        cla: String,
+        peer: Option<String>,
+    },
+    /// Forwarding decision of the routing agent
+    Routed {
+        agent: String,
+        next_hops: Vec<NextHop>,
+        delete_afterwards: bool,
+    },
+    TransferSucceeded {
+        cla: String,
+        next_hop: String,
+        duration_ms: u64,
+    },
+    TransferFailed {
+        cla: String,
+        next_hop: String,
+        duration_ms: u64,
+        error: String,
+    },
+    /// Retention constraints of the bundle changed
+    Constraints {
+        constraints: Vec<Constraint>,
+    },
+    Delivered {
+        endpoint: String,
+    },
+    Deleted {
+        reason: String,
+    },
+}
+
+impl JournalEvent {
+    pub fn constraints(constraints: &HashSet<Constraint>) -> Self {
+        let mut constraints: Vec<Constraint> = constraints.iter().copied().collect();
+        constraints.sort_by_key(|c| c.to_string());
+        JournalEvent::Constraints { constraints }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Unix time in milliseconds of the latest occurrence
+    pub timestamp: u64,
+    /// Number of identical consecutive occurrences
+    #[serde(default = "one")]
+    pub count: u32,
+    #[serde(flatten)]
+    pub event: JournalEvent,
+}
+
+fn one() -> u32 {
+    1
+}
+
+/// Appends an event to a journal, repetitions of the last event are only counted.
+pub fn append(journal: &mut Vec<JournalEntry>, event: JournalEvent) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    if let Some(last) = journal.last_mut() {
+        if last.event == event {
+            last.timestamp = timestamp;
+            last.count += 1;
+            return;
+        }
+    }
+    if journal.len() >= MAX_JOURNAL_ENTRIES {
+        // keep how the bundle entered this node
+        journal.remove(1);
+    }
+    journal.push(JournalEntry {
+        timestamp,
+        count: 1,
+        event,
+    });
+}
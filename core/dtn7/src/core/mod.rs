@@ -1,9 +1,15 @@
+pub mod admission;
 pub mod application_agent;
 pub mod bundlepack;
+pub mod contact_plan;
+pub mod events;
 pub mod helpers;
+pub mod journal;
 pub mod peer;
 pub mod processing;
+pub mod scheduler;
 pub mod stats;
+pub mod status_reports;
 pub mod store;
 
 use crate::cla::ConvergenceLayerAgent;
@@ -20,7 +26,7 @@ use crate::{RoutingNotifcation, CONFIG};
 use crate::{PEERS, STORE};
 use application_agent::ApplicationAgent;
 use bp7::EndpointID;
-use log::{error, warn, info, trace};
+use log::{error, info, trace, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Instant;
@@ -67,7 +73,7 @@ impl DtnStatistics {
                 let registration = RegistrationInformation {
                     eid: eid.clone(),
                     active: aa.delivery_addr().is_some(),
-                    singleton: singleton,
+                    singleton,
                     default_failure_action: stats::FailureAction::Defer,
                 };
                 self.node.registrations.push(registration);
@@ -160,9 +166,10 @@ pub async fn process_peers() {
                 v.eid, v.addr
             );
 
+            crate::core::peer::archive_metrics(v);
             dropped.push(v.eid.clone());
         }
-        v.con_type == PeerType::Static || val
+        v.con_type != PeerType::Dynamic || val
     });
 
     for eid in dropped {
@@ -176,7 +183,7 @@ pub async fn process_peers() {
 pub async fn process_bundles() {
     let now_total = Instant::now();
 
-    store_delete_expired();
+    store_delete_expired().await;
 
     let active_cla = (*CLAS.lock()).iter().any(|p| p.accepting());
     if !active_cla {
@@ -194,7 +201,7 @@ pub async fn process_bundles() {
         .collect();
 
     // process them in chronological order
-    forwarding_bundles.sort_unstable_by(|a, b| a.creation_time.cmp(&b.creation_time));
+    forwarding_bundles.sort_unstable_by_key(|bp| bp.creation_time);
 
     let num_bundles = forwarding_bundles.len();
 
@@ -202,7 +209,7 @@ pub async fn process_bundles() {
         let mut tasks = Vec::new();
         for bp in forwarding_bundles {
             let bpid = bp.id().to_string();
-            let task_handle = tokio::spawn(async move {
+            let task_handle = crate::node::spawn(async move {
                 let now = Instant::now();
                 if let Err(err) = forward(bp).await {
                     error!("Error forwarding bundle: {}", err);
@@ -1,8 +1,10 @@
 use crate::cla::{ClaSenderTask, ConvergenceLayerAgent};
+use crate::node::node_local;
 use crate::{CLAS, CONFIG};
 use bp7::EndpointID;
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
 use std::net::IpAddr;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -11,6 +13,8 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 pub enum PeerType {
     Static,
     Dynamic,
+    /// Only known during configured contact windows, see `core::contact_plan`
+    Scheduled,
 }
 
 impl std::convert::TryFrom<&str> for PeerType {
@@ -19,6 +23,7 @@ impl std::convert::TryFrom<&str> for PeerType {
         match dbg!(value.to_lowercase().as_str()) {
             "static" => Ok(Self::Static),
             "dynamic" => Ok(Self::Dynamic),
+            "scheduled" => Ok(Self::Scheduled),
             _ => Err(()),
         }
     }
@@ -65,6 +70,8 @@ pub struct DtnPeer {
     pub services: HashMap<u8, String>,
     pub last_contact: u64,
     pub fails: u16,
+    #[serde(default)]
+    pub metrics: PeerMetrics,
 }
 
 impl DtnPeer {
@@ -76,6 +83,12 @@ impl DtnPeer {
         cla_list: Vec<(String, Option<u16>)>,
         services: HashMap<u8, String>,
     ) -> DtnPeer {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let mut metrics = PeerMetrics::default();
+        metrics.contacts.begin(now);
         DtnPeer {
             eid,
             addr,
@@ -83,11 +96,9 @@ impl DtnPeer {
             period,
             cla_list,
             services,
-            last_contact: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            last_contact: now,
             fails: 0,
+            metrics,
         }
     }
     /// Example
@@ -125,7 +136,8 @@ impl DtnPeer {
     /// assert_eq!(peer.still_valid(), false);
     /// ```
     pub fn still_valid(&self) -> bool {
-        if self.con_type == PeerType::Static {
+        // scheduled peers are removed by the contact plan at the end of their window
+        if self.con_type != PeerType::Dynamic {
             return true;
         }
         // If a custom peer timeout was specified force remove all peers after specified amount of time
@@ -133,20 +145,61 @@ impl DtnPeer {
         // that didn't advertise a BeaconPeriod
         let timeout = CONFIG.lock().peer_timeout.as_secs();
         let custom = CONFIG.lock().custom_timeout;
-        if (custom && timeout > 0) || self.period.is_none() {
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .expect("time went backwards")
-                .as_secs();
-            now - self.last_contact < timeout
-        // Else if a received beacon contains a BeaconPeriod remove this peer after 2 * received BeaconPeriod
-        } else {
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .expect("Time went backwards")
-                .as_secs();
-            now - self.last_contact < self.period.unwrap().as_secs() * 2
+        match self.period {
+            Some(period) if !(custom && timeout > 0) => {
+                // Else if a received beacon contains a BeaconPeriod remove this peer after 2 * received BeaconPeriod
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .expect("Time went backwards")
+                    .as_secs();
+                now - self.last_contact < period.as_secs() * 2
+            }
+            _ => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .expect("time went backwards")
+                    .as_secs();
+                now - self.last_contact < timeout
+            }
+        }
+    }
+
+    /// Takes over the advertised attributes of a newer description of the same peer,
+    /// e.g., from a received beacon, and returns the names of the changed fields.
+    ///
+    /// The peer type and failure count are kept.
+    ///
+    /// Example
+    ///
+    /// ```
+    /// use dtn7::core::*;
+    ///
+    /// let mut peer = helpers::rnd_peer();
+    /// let mut beacon = peer.clone();
+    /// beacon.cla_list.push(("tcp".into(), Some(4223)));
+    /// assert_eq!(peer.update_from(&beacon), vec!["cla_list"]);
+    /// assert!(peer.update_from(&beacon).is_empty());
+    /// ```
+    pub fn update_from(&mut self, other: &DtnPeer) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.addr != other.addr {
+            self.addr = other.addr.clone();
+            changed.push("addr");
         }
+        if self.cla_list != other.cla_list {
+            self.cla_list = other.cla_list.clone();
+            changed.push("cla_list");
+        }
+        if self.services != other.services {
+            self.services = other.services.clone();
+            changed.push("services");
+        }
+        if self.period != other.period {
+            self.period = other.period;
+            changed.push("period");
+        }
+        self.touch();
+        changed
     }
 
     /// Return the peers DTN node name (without URI scheme)
@@ -168,6 +221,7 @@ impl DtnPeer {
                         dest,
                         cla_name: cla_instance.name().into(),
                         next_hop: self.eid.clone(),
+                        copies: None,
                     });
                 }
             }
@@ -191,3 +245,163 @@ impl DtnPeer {
         self.fails > 3
     }
 }
+
+/// Weight of a new sample in the smoothed throughput and round-trip time
+pub const METRICS_SMOOTHING: f64 = 0.25;
+
+/// Maximum number of peers whose metrics are remembered after they were dropped
+pub const MAX_METRICS_HISTORY: usize = 1024;
+
+fn smooth(old: Option<f64>, sample: f64) -> Option<f64> {
+    Some(match old {
+        Some(old) => old + METRICS_SMOOTHING * (sample - old),
+        None => sample,
+    })
+}
+
+/// Quality of the link to a peer as observed on a single CLA
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LinkMetrics {
+    pub transfers_succeeded: u64,
+    pub transfers_failed: u64,
+    /// Share of successful transfers, between 0 and 1
+    pub success_ratio: f64,
+    pub bytes_sent: u64,
+    /// Smoothed throughput of successful transfers in bytes per second
+    pub throughput: Option<f64>,
+    /// Smoothed round-trip time in milliseconds, only measured by some CLAs, e.g., tcp
+    pub rtt_ms: Option<f64>,
+    /// Unix timestamp of the last transfer
+    pub last_transfer: u64,
+}
+
+impl LinkMetrics {
+    /// Example
+    ///
+    /// ```
+    /// use dtn7::core::peer::LinkMetrics;
+    /// use std::time::Duration;
+    ///
+    /// let mut link = LinkMetrics::default();
+    /// link.record_transfer(1000, Duration::from_millis(500), true);
+    /// link.record_transfer(1000, Duration::from_millis(500), false);
+    /// assert_eq!(link.success_ratio, 0.5);
+    /// assert_eq!(link.throughput, Some(2000.0));
+    /// assert_eq!(link.bytes_sent, 1000);
+    /// ```
+    pub fn record_transfer(&mut self, bytes: usize, duration: Duration, success: bool) {
+        if success {
+            self.transfers_succeeded += 1;
+            self.bytes_sent += bytes as u64;
+            let secs = duration.as_secs_f64();
+            if secs > 0.0 {
+                self.throughput = smooth(self.throughput, bytes as f64 / secs);
+            }
+        } else {
+            self.transfers_failed += 1;
+        }
+        self.success_ratio = self.transfers_succeeded as f64
+            / (self.transfers_succeeded + self.transfers_failed) as f64;
+        self.last_transfer = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+    }
+    pub fn record_rtt(&mut self, rtt: Duration) {
+        self.rtt_ms = smooth(self.rtt_ms, rtt.as_secs_f64() * 1000.0);
+    }
+}
+
+/// Durations of the contacts with a peer in seconds
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ContactStats {
+    /// Number of contacts, including the current one
+    pub count: u64,
+    /// Unix timestamp of the start of the current contact
+    pub current_start: Option<u64>,
+    pub last_duration: Option<u64>,
+    /// Mean duration of all completed contacts
+    pub mean_duration: Option<f64>,
+    pub total_duration: u64,
+}
+
+impl ContactStats {
+    pub fn begin(&mut self, now: u64) {
+        if self.current_start.is_none() {
+            self.count += 1;
+            self.current_start = Some(now);
+        }
+    }
+    /// Completes the current contact, `last_seen` being the time the peer was last heard of
+    ///
+    /// Example
+    ///
+    /// ```
+    /// use dtn7::core::peer::ContactStats;
+    ///
+    /// let mut contacts = ContactStats::default();
+    /// contacts.begin(100);
+    /// contacts.end(130);
+    /// contacts.begin(200);
+    /// contacts.end(210);
+    /// assert_eq!(contacts.count, 2);
+    /// assert_eq!(contacts.last_duration, Some(10));
+    /// assert_eq!(contacts.mean_duration, Some(20.0));
+    /// ```
+    pub fn end(&mut self, last_seen: u64) {
+        if let Some(start) = self.current_start.take() {
+            let duration = last_seen.saturating_sub(start);
+            self.last_duration = Some(duration);
+            self.total_duration += duration;
+            self.mean_duration = Some(self.total_duration as f64 / self.count as f64);
+        }
+    }
+}
+
+/// Link metrics of a peer, exposed via `/status/peers` and the erouting `PeerState`
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PeerMetrics {
+    pub contacts: ContactStats,
+    /// Metrics by CLA name
+    pub clas: BTreeMap<String, LinkMetrics>,
+}
+
+impl PeerMetrics {
+    pub fn cla_mut(&mut self, cla: &str) -> &mut LinkMetrics {
+        self.clas.entry(cla.to_string()).or_default()
+    }
+    /// Continues the history of an earlier acquaintance with the current contact
+    pub fn resume(&mut self, history: PeerMetrics) {
+        let current_start = self.contacts.current_start;
+        self.contacts = history.contacts;
+        if let Some(start) = current_start {
+            self.contacts.begin(start);
+        }
+        for (cla, link) in history.clas {
+            self.clas.entry(cla).or_insert(link);
+        }
+    }
+}
+
+node_local! {
+    static ref METRICS_HISTORY: Mutex<BTreeMap<String, PeerMetrics>> = Mutex::new(BTreeMap::new());
+}
+
+/// Ends the current contact of a dropped peer and remembers its metrics for the next encounter
+pub fn archive_metrics(peer: &DtnPeer) {
+    let mut metrics = peer.metrics.clone();
+    metrics.contacts.end(peer.last_contact);
+    let node = peer.node_name();
+    let mut history = METRICS_HISTORY.lock();
+    if history.len() >= MAX_METRICS_HISTORY && !history.contains_key(&node) {
+        return;
+    }
+    history.insert(node, metrics);
+}
+
+/// Restores the remembered metrics of a peer that was encountered before
+pub fn restore_metrics(peer: &mut DtnPeer) {
+    if let Some(history) = METRICS_HISTORY.lock().remove(&peer.node_name()) {
+        peer.metrics.resume(history);
+    }
+}
@@ -1,11 +1,17 @@
+use crate::core::admission::{admitted, Candidate};
 use crate::core::bundlepack::*;
+use crate::core::events::NodeEvent;
+use crate::core::journal::{JournalEvent, NextHop};
 use crate::core::*;
+use crate::routing::sprayandwait::set_copy_count;
 use crate::routing::RoutingNotifcation;
+use crate::store_journal;
 use crate::store_push_bundle;
 use crate::store_remove;
 use crate::CONFIG;
 use crate::DTNCORE;
-use crate::{is_local_node_id, STATS};
+use crate::STATUS_REPORTS;
+use crate::{is_local_node_id, peers_record_transfer, peers_remove, STATS};
 use crate::{routing_notify, routing_sender_for_bundle, store_add_bundle_if_unknown};
 
 use bp7::administrative_record::*;
@@ -17,6 +23,7 @@ use bp7::BUNDLE_AGE_BLOCK;
 use anyhow::{bail, Result};
 use log::trace;
 use log::{debug, info, warn};
+use std::net::{IpAddr, SocketAddr};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::SystemTime;
@@ -25,11 +32,12 @@ use tokio::sync::mpsc::channel;
 
 // transmit an outbound bundle.
 pub async fn send_bundle(bndl: Bundle) {
-    tokio::spawn(async move {
+    crate::node::spawn(async move {
         if let Err(err) = store_push_bundle(&bndl) {
             warn!("Transmission failed: {}", err);
             return;
         }
+        store_journal(&bndl.id(), JournalEvent::Created);
         if let Err(err) = transmit(bndl.into()).await {
             warn!("Transmission failed: {}", err);
         }
@@ -37,16 +45,15 @@ pub async fn send_bundle(bndl: Bundle) {
 }
 
 pub fn send_through_task(bndl: Bundle) {
-    let rt = tokio::runtime::Handle::current();
     let mut stask = crate::SENDERTASK.lock();
     if stask.is_none() {
         let (tx, rx) = channel(50);
-        tokio::spawn(sender_task(rx));
+        crate::node::spawn(sender_task(rx));
         *stask = Some(tx);
     }
     let tx = stask.as_ref().unwrap().clone();
     //let mut rt = tokio::runtime::Runtime::new().unwrap();
-    rt.spawn(async move { tx.send(bndl).await });
+    crate::node::spawn(async move { tx.send(bndl).await });
 }
 
 pub async fn send_through_task_async(bndl: Bundle) {
@@ -54,7 +61,7 @@ pub async fn send_through_task_async(bndl: Bundle) {
         let mut stask = crate::SENDERTASK.lock();
         if stask.is_none() {
             let (tx, rx) = channel(50);
-            tokio::spawn(sender_task(rx));
+            crate::node::spawn(sender_task(rx));
             *stask = Some(tx);
         }
         stask.as_ref().unwrap().clone()
@@ -93,11 +100,83 @@ pub async fn transmit(mut bp: BundlePack) -> Result<()> {
     Ok(())
 }
 
-// handle received/incoming bundles.
-pub async fn receive(mut bndl: Bundle) -> Result<()> {
-    if store_add_bundle_if_unknown(&bndl)? {
+// handle received/incoming bundles without a known convergence layer.
+pub async fn receive(bndl: Bundle) -> Result<()> {
+    receive_from(bndl, "unknown", None).await
+}
+
+// checks the node a bundle was received from against the admission policy. The sender is
+// taken from the previous node block, the peer reported by the CLA or the bundle's source.
+fn sender_admitted(bndl: &Bundle, cla: &str, peer: Option<&str>) -> bool {
+    let addr = peer.and_then(|peer| {
+        peer.parse::<SocketAddr>()
+            .map(|addr| addr.ip())
+            .or_else(|_| peer.parse::<IpAddr>())
+            .ok()
+    });
+    let node = bndl
+        .extension_block_by_type(bp7::canonical::PREVIOUS_NODE_BLOCK)
+        .and_then(|pnb| pnb.previous_node_get().cloned())
+        .or_else(|| {
+            peer.filter(|_| addr.is_none())
+                .and_then(|peer| EndpointID::try_from(peer).ok())
+        })
+        .unwrap_or_else(|| bndl.primary.source.clone())
+        .node();
+    admitted(Candidate {
+        node: node.as_deref(),
+        addr,
+        cla: Some(cla),
+    })
+}
+
+// handle bundles received via the given convergence layer, optionally from a known peer.
+pub async fn receive_from(mut bndl: Bundle, cla: &str, peer: Option<String>) -> Result<()> {
+    if !sender_admitted(&bndl, cla, peer.as_deref()) {
+        info!(
+            "Refusing bundle {} received via {} from {:?} due to admission policy",
+            bndl.id(),
+            cla,
+            peer
+        );
+        return Ok(());
+    }
+    if store_full() && !store_has_item(&bndl.id()) {
+        info!(
+            "Store holds the maximum number of bundles, paring bundle {} received via {}",
+            bndl.id(),
+            cla
+        );
+        STATS.lock().node.error_info.discarded_bundle_count += 1;
+        events::publish(NodeEvent::BundleDeleted {
+            bid: bndl.id(),
+            reason: events::reason_name(TRAFFIC_PARED).into(),
+        });
+        report_deleted(&bndl, TRAFFIC_PARED).await;
+        return Ok(());
+    }
+    let is_new = match store_add_bundle_if_unknown(&bndl) {
+        Ok(is_new) => is_new,
+        Err(err) => {
+            report_deleted(&bndl, DEPLETED_STORAGE).await;
+            return Err(err);
+        }
+    };
+    if is_new {
         info!("Received new bundle: {}", bndl.id());
+        store_journal(
+            &bndl.id(),
+            JournalEvent::Received {
+                cla: cla.to_string(),
+                peer,
+            },
+        );
         STATS.lock().incoming += 1;
+        events::publish(NodeEvent::BundleReceived {
+            bid: bndl.id(),
+            source: bndl.primary.source.to_string(),
+            destination: bndl.primary.destination.to_string(),
+        });
     } else {
         debug!(
             "Received an already known bundle, skip processing: {}",
@@ -114,15 +193,7 @@ pub async fn receive(mut bndl: Bundle) -> Result<()> {
     bp.add_constraint(Constraint::DispatchPending);
     bp.sync()?;
 
-    if bndl
-        .primary
-        .bundle_control_flags
-        .contains(BundleControlFlags::BUNDLE_STATUS_REQUEST_RECEPTION)
-        && !bndl.is_administrative_record()
-        && CONFIG.lock().generate_status_reports
-    {
-        send_status_report(&bp, RECEIVED_BUNDLE, NO_INFORMATION).await;
-    }
+    report_if_requested(&bndl, RECEIVED_BUNDLE, NO_INFORMATION).await;
     let mut remove_idx = Vec::new();
     let mut index = 0;
     for cb in bndl.canonicals.iter() {
@@ -142,10 +213,10 @@ pub async fn receive(mut bndl: Bundle) -> Result<()> {
                 bp.id(),
                 cb.block_type
             );
-            if CONFIG.lock().generate_status_reports {
-                send_status_report(&bp, RECEIVED_BUNDLE, BLOCK_UNINTELLIGIBLE).await;
-            } else {
-                info!("Generation of status reports disabled, ignoring request");
+            if !bndl.is_administrative_record()
+                && status_reports::permit(&bndl, RECEIVED_BUNDLE, BLOCK_UNINTELLIGIBLE)
+            {
+                send_status_report(&bndl, RECEIVED_BUNDLE, BLOCK_UNINTELLIGIBLE).await;
             }
         }
         if flags.contains(BlockControlFlags::BLOCK_DELETE_BUNDLE) {
@@ -173,6 +244,7 @@ pub async fn receive(mut bndl: Bundle) -> Result<()> {
         bndl.canonicals.remove(i);
     }
     if let Err(err) = store_push_bundle(&bndl) {
+        report_deleted(&bndl, DEPLETED_STORAGE).await;
         bail!("error adding received bundle: {} {}", bndl.id(), err);
     }
     if let Err(err) = dispatch(bp).await {
@@ -181,6 +253,12 @@ pub async fn receive(mut bndl: Bundle) -> Result<()> {
     Ok(())
 }
 
+// checks whether the store reached the configured maximum number of bundles.
+fn store_full() -> bool {
+    let max_bundles = CONFIG.lock().max_bundles;
+    max_bundles > 0 && (*crate::STORE.lock()).count() >= max_bundles
+}
+
 // handle the dispatching of received bundles.
 pub async fn dispatch(bp: BundlePack) -> Result<()> {
     info!("Dispatching bundle: {}", bp.id());
@@ -196,12 +274,18 @@ pub async fn dispatch(bp: BundlePack) -> Result<()> {
     if (*DTNCORE.lock()).is_in_endpoints(&bp.destination)
     // TODO: lookup here AND in local delivery, optimize for just one
     {
-        trace!("Destination for {} is local endpoint → local_delivery", bp.id());
+        trace!(
+            "Destination for {} is local endpoint → local_delivery",
+            bp.id()
+        );
         local_delivery(bp.clone()).await?;
     }
     if !is_local_node_id(&bp.destination) {
-        trace!("Destination for {} is NOT local endpoint → forward", bp.id());
-        tokio::spawn(forward(bp));
+        trace!(
+            "Destination for {} is NOT local endpoint → forward",
+            bp.id()
+        );
+        crate::node::spawn(forward(bp));
     }
     Ok(())
 }
@@ -298,7 +382,16 @@ async fn handle_previous_node_block(mut bundle: Bundle) -> Result<Bundle> {
     Ok(bundle)
 }
 // forward a bundle pack's bundle to another node.
-pub async fn forward(mut bp: BundlePack) -> Result<()> {
+pub async fn forward(bp: BundlePack) -> Result<()> {
+    let bpid = bp.id().to_string();
+    let res = try_forward(bp).await;
+    // park the bundle for a retry if it is still waiting for forwarding
+    scheduler::forwarding_done(&bpid, matches!(res, Ok(true)));
+    res.map(|_| ())
+}
+
+// returns whether the transfers to all next hops failed
+async fn try_forward(mut bp: BundlePack) -> Result<bool> {
     let bpid = bp.id().to_string();
 
     trace!("Forward request for bundle: {}", bpid);
@@ -313,6 +406,15 @@ pub async fn forward(mut bp: BundlePack) -> Result<()> {
     trace!("Check delivery");
 
     let (nodes, delete_afterwards) = routing_sender_for_bundle(bp.clone()).await?;
+    let agent = CONFIG.lock().routing.clone();
+    store_journal(
+        &bpid,
+        JournalEvent::Routed {
+            agent,
+            next_hops: nodes.iter().map(NextHop::from).collect(),
+            delete_afterwards,
+        },
+    );
     if !nodes.is_empty() {
         debug!("Attempting forwarding of {} to nodes: {:?}", bp.id(), nodes);
     }
@@ -320,7 +422,8 @@ pub async fn forward(mut bp: BundlePack) -> Result<()> {
     if nodes.is_empty() {
         trace!("No new peers for forwarding of bundle {}", &bp.id());
         if delete_afterwards {
-            store_remove(&bpid)?;
+            // routing agent gave up on this bundle without any next hop
+            delete(bp, NO_ROUTE_TO_DESTINATION).await?;
         }
     } else {
         debug!("Attempting forwarding of {} to nodes: {:?}", bp.id(), nodes);
@@ -348,11 +451,19 @@ pub async fn forward(mut bp: BundlePack) -> Result<()> {
         let mut wg = Vec::new();
         let bundle_data = bndl.to_cbor();
         for n in nodes {
-            let bd = bundle_data.clone(); // TODO: optimize cloning away, reference should do
+            let bd = match n.copies {
+                Some(copies) => {
+                    let mut copy = bndl.clone();
+                    set_copy_count(&mut copy, copies);
+                    copy.to_cbor()
+                }
+                None => bundle_data.clone(), // TODO: optimize cloning away, reference should do
+            };
             let bpid = bpid.clone();
             let bundle_sent = std::sync::Arc::clone(&bundle_sent);
             let n = n.clone();
-            let task_handle = tokio::spawn(async move {
+            let task_handle = crate::node::spawn(async move {
+                let size = bd.len();
                 let start_time = Instant::now();
                 debug!(
                     "Sending bundle to a CLA: {} {} {}",
@@ -368,6 +479,27 @@ pub async fn forward(mut bp: BundlePack) -> Result<()> {
                         start_time.elapsed()
                     );
                     STATS.lock().failed += 1;
+                    peers_record_transfer(
+                        &n.next_hop.node().unwrap_or_default(),
+                        &n.cla_name,
+                        size,
+                        start_time.elapsed(),
+                        false,
+                    );
+                    store_journal(
+                        &bpid,
+                        JournalEvent::TransferFailed {
+                            cla: n.cla_name.clone(),
+                            next_hop: n.next_hop.to_string(),
+                            duration_ms: start_time.elapsed().as_millis() as u64,
+                            error: err.to_string(),
+                        },
+                    );
+                    events::publish(NodeEvent::BundleForwardingFailed {
+                        bid: bpid.clone(),
+                        next_hop: n.next_hop.to_string(),
+                        cla: n.cla_name.clone(),
+                    });
                     debug!("Error while transferring bundle {}: {}", &bpid, err);
                     let mut failed_peer = None;
 
@@ -394,14 +526,12 @@ pub async fn forward(mut bp: BundlePack) -> Result<()> {
                     }
                     if let Some(peer) = failed_peer {
                         let peers_before = (*PEERS.lock()).len();
-                        (*PEERS.lock()).remove(&peer);
+                        peers_remove(&peer);
                         let peers_after = (*PEERS.lock()).len();
                         debug!("Removing peer {} from list of neighbors due to too many failed transmissions ({}/{})", peer, peers_before, peers_after);
                     }
-                    // TODO: send status report?
-                    // if (*CONFIG.lock()).generate_service_reports {
-                    //    send_status_report(&bp2, FORWARDED_BUNDLE, TRANSMISSION_CANCELED);
-                    // }
+                    // no status report yet: the bundle is retained and retried by the
+                    // forwarding scheduler until `max-transfer-failures` is reached
                 } else {
                     info!(
                         "Bundle send success: id={} dest={} cla={} in {:?}",
@@ -411,6 +541,26 @@ pub async fn forward(mut bp: BundlePack) -> Result<()> {
                         start_time.elapsed()
                     );
                     STATS.lock().outgoing += 1;
+                    peers_record_transfer(
+                        &n.next_hop.node().unwrap_or_default(),
+                        &n.cla_name,
+                        size,
+                        start_time.elapsed(),
+                        true,
+                    );
+                    store_journal(
+                        &bpid,
+                        JournalEvent::TransferSucceeded {
+                            cla: n.cla_name.clone(),
+                            next_hop: n.next_hop.to_string(),
+                            duration_ms: start_time.elapsed().as_millis() as u64,
+                        },
+                    );
+                    events::publish(NodeEvent::BundleForwarded {
+                        bid: bpid.clone(),
+                        next_hop: n.next_hop.to_string(),
+                        cla: n.cla_name.clone(),
+                    });
                     bundle_sent.store(true, Ordering::Relaxed);
                     if let Err(err) = routing_notify(RoutingNotifcation::SendingSucceeded(
                         bpid,
@@ -440,15 +590,7 @@ pub async fn forward(mut bp: BundlePack) -> Result<()> {
             }
         }*/
         if bundle_sent.load(Ordering::Relaxed) {
-            if bndl
-                .primary
-                .bundle_control_flags
-                .contains(BundleControlFlags::BUNDLE_STATUS_REQUEST_FORWARD)
-                && !bndl.is_administrative_record()
-                && CONFIG.lock().generate_status_reports
-            {
-                send_status_report(&bp, FORWARDED_BUNDLE, NO_INFORMATION).await;
-            }
+            report_if_requested(&bndl, FORWARDED_BUNDLE, NO_INFORMATION).await;
             if delete_afterwards {
                 store_remove(&bpid)?;
             } else if bndl.is_administrative_record() {
@@ -459,11 +601,12 @@ pub async fn forward(mut bp: BundlePack) -> Result<()> {
         } else {
             info!("Failed to forward bundle to any CLA: {}", bp.id());
             // don't contraindicate if we failed to forward to any CLA
-            // retry next janitor run
+            // retried by the forwarding scheduler
             //contraindicated(bp)?;
+            return Ok(true);
         }
     }
-    Ok(())
+    Ok(false)
 }
 
 pub async fn local_delivery(mut bp: BundlePack) -> Result<()> {
@@ -482,15 +625,7 @@ pub async fn local_delivery(mut bp: BundlePack) -> Result<()> {
     bp.add_constraint(Constraint::LocalEndpoint);
     bp.sync()?;
     if is_local_node_id(&bp.destination) {
-        if bndl
-            .primary
-            .bundle_control_flags
-            .contains(BundleControlFlags::BUNDLE_STATUS_REQUEST_DELIVERY)
-            && !bndl.is_administrative_record()
-            && CONFIG.lock().generate_status_reports
-        {
-            send_status_report(&bp, DELIVERED_BUNDLE, NO_INFORMATION).await;
-        }
+        report_if_requested(&bndl, DELIVERED_BUNDLE, NO_INFORMATION).await;
         // TODO: might not be okay to clear if it was a group message, check in various setups
         bp.clear_constraints();
     } else {
@@ -504,7 +639,17 @@ pub async fn local_delivery(mut bp: BundlePack) -> Result<()> {
     if let Some(aa) = (*DTNCORE.lock()).get_endpoint_mut(&bp.destination) {
         info!("Delivering {}", bp.id());
         aa.push(&bndl);
+        store_journal(
+            bp.id(),
+            JournalEvent::Delivered {
+                endpoint: bp.destination.to_string(),
+            },
+        );
         STATS.lock().delivered += 1;
+        events::publish(NodeEvent::BundleDelivered {
+            bid: bp.id().to_string(),
+            destination: bp.destination.to_string(),
+        });
     }
     Ok(())
 }
@@ -520,23 +665,55 @@ pub async fn delete(mut bp: BundlePack, reason: StatusReportReason) -> Result<()
     if bndl.is_none() {
         bail!("bundle not found");
     }
-    (*STATS.lock()).node.error_info.discarded_bundle_count += 1;
-    let bndl = bndl.unwrap();
-    if bndl
-        .primary
-        .bundle_control_flags
-        .contains(BundleControlFlags::BUNDLE_STATUS_REQUEST_DELETION)
-        && !bndl.is_administrative_record()
-        && CONFIG.lock().generate_status_reports
-    {
-        send_status_report(&bp, DELETED_BUNDLE, reason).await;
-    }
+    STATS.lock().node.error_info.discarded_bundle_count += 1;
+    store_journal(
+        bp.id(),
+        JournalEvent::Deleted {
+            reason: events::reason_name(reason).into(),
+        },
+    );
+    events::publish(NodeEvent::BundleDeleted {
+        bid: bp.id().to_string(),
+        reason: events::reason_name(reason).into(),
+    });
+    report_deleted(&bndl.unwrap(), reason).await;
     bp.clear_constraints();
     info!("Bundle marked for deletion: {}", bp.id());
     bp.sync()?;
     Ok(())
 }
 
+/// Removes a stored bundle on behalf of the node, e.g., when its lifetime expired or a user
+/// canceled it. The deletion is journaled before the removal, so the journal keeps it, and it
+/// is published, counted and reported to the report-to endpoint once the bundle is gone.
+///
+/// User requested deletions are reported as `TRANSMISSION_CANCELED` and named
+/// [`events::REASON_USER_REQUEST`] in the journal and event stream.
+pub async fn remove(bid: &str, reason: StatusReportReason) -> Result<()> {
+    let name = if reason == TRANSMISSION_CANCELED {
+        events::REASON_USER_REQUEST
+    } else {
+        events::reason_name(reason)
+    };
+    let bndl = crate::store_get_bundle(bid);
+    store_journal(
+        bid,
+        JournalEvent::Deleted {
+            reason: name.into(),
+        },
+    );
+    store_remove(bid)?;
+    STATS.lock().node.error_info.discarded_bundle_count += 1;
+    events::publish(NodeEvent::BundleDeleted {
+        bid: bid.to_string(),
+        reason: name.into(),
+    });
+    if let Some(bndl) = bndl {
+        report_deleted(&bndl, reason).await;
+    }
+    Ok(())
+}
+
 fn is_administrative_record_valid(bundle: &Bundle) -> bool {
     if !bundle.is_administrative_record() {
         warn!(
@@ -565,7 +742,7 @@ fn is_administrative_record_valid(bundle: &Bundle) -> bool {
                     );
                     // Currently there are only status reports. This must be changed if more
                     // types of administrative records are introduced.
-                    inspect_status_report(&bundle.id(), ar);
+                    inspect_status_report(bundle, ar);
                     true
                 }
                 Err(ar) => {
@@ -588,7 +765,8 @@ fn is_administrative_record_valid(bundle: &Bundle) -> bool {
     }
 }
 
-fn inspect_status_report(bid: &str, ar: AdministrativeRecord) {
+fn inspect_status_report(bundle: &Bundle, ar: AdministrativeRecord) {
+    let bid = &bundle.id();
     if let AdministrativeRecord::BundleStatusReport(bsr) = &ar {
         let sips = &bsr.status_information;
         if sips.is_empty() {
@@ -598,6 +776,16 @@ fn inspect_status_report(bid: &str, ar: AdministrativeRecord) {
             );
             return;
         }
+        // keep track of the delivery of bundles originating from this node
+        if is_local_node_id(&bsr.source_node) {
+            let statuses = status_reports::ReportedStatus::from_report(bsr, &bundle.primary.source);
+            debug!(
+                "Recording status report for own bundle: {} {:?}",
+                bsr.refbundle(),
+                statuses
+            );
+            (*STATUS_REPORTS.lock()).record(&bsr.refbundle(), statuses);
+        }
         if !store_has_item(&bsr.refbundle()) {
             warn!("Status Report's bundle is unknown: {} {:?}", bid, ar);
             return;
@@ -643,24 +831,35 @@ fn inspect_status_report(bid: &str, ar: AdministrativeRecord) {
     }
 }
 
+// Sends a status report if requested by the bundle and permitted by the report policy.
+async fn report_if_requested(
+    bndl: &Bundle,
+    status: StatusInformationPos,
+    reason: StatusReportReason,
+) {
+    if status_reports::is_requested(bndl, status) && status_reports::permit(bndl, status, reason) {
+        send_status_report(bndl, status, reason).await;
+    }
+}
+
+/// Sends a deletion status report for a bundle if it was requested, e.g., when a bundle
+/// is removed outside of the regular bundle processing.
+pub async fn report_deleted(bndl: &Bundle, reason: StatusReportReason) {
+    report_if_requested(bndl, DELETED_BUNDLE, reason).await;
+}
+
 // SendStatusReport creates a new status report in response to the given
-// BundlePack and transmits it.
+// bundle and transmits it.
 async fn send_status_report(
-    bp: &BundlePack,
+    bndl: &Bundle,
     status: StatusInformationPos,
     reason: StatusReportReason,
 ) {
     // Don't respond to other administrative records or anonymous bundles.
-    if bp.administrative || bp.source == EndpointID::none() {
-        warn!("status report sending denied for dtn:none sources/administrative bundles themselves: {}", bp.id());
+    if bndl.is_administrative_record() || bndl.primary.source == EndpointID::none() {
+        warn!("status report sending denied for dtn:none sources/administrative bundles themselves: {}", bndl.id());
         return;
     }
-    let bndl = store_get_bundle(bp.id());
-    if bndl.is_none() {
-        warn!("bundle not found when sending status report: {}", bp.id());
-        return;
-    }
-    let bndl = bndl.unwrap();
 
     // Don't respond to ourself
     if (*DTNCORE.lock()).is_in_endpoints(&bndl.primary.report_to) {
@@ -668,14 +867,14 @@ async fn send_status_report(
     }
 
     info!(
-        "Sending a status report for a bundle: {} {:?} {:?}",
-        bp.id(),
+        "Sending a status report for a bundle: {} {} {}",
+        bndl.id(),
         status,
-        reason
+        events::reason_name(reason)
     );
 
     let out_bndl = new_status_report_bundle(
-        &bndl,
+        bndl,
         CONFIG.lock().host_eid.clone(),
         bndl.primary.crc.to_code(),
         status,
@@ -686,14 +885,13 @@ async fn send_status_report(
         warn!("Storing new status report failed: {}", err);
         return;
     }
+    store_journal(&out_bndl.id(), JournalEvent::Created);
     let mut bp: BundlePack = out_bndl.into();
     bp.add_constraint(Constraint::ForwardPending);
     if let Err(err) = bp.sync() {
         warn!("Sending status report failed: {}", err);
     }
+    // forwarded by the scheduler, dispatching it here would make the futures recursive
+    scheduler::enqueue(bp.id(), &bp.destination.node().unwrap_or_default());
     debug!("Enqueued status report: {}", bp.id());
-    // TODO: impl without cycle
-    //send_bundle(out_bndl).await;
-    //dispatch(out_bndl.into()).await;
-    //send_through_task_async(out_bndl).await;
 }
@@ -0,0 +1,374 @@
+use crate::cla::ConvergenceLayerAgent;
+use crate::core::bundlepack::{BundlePack, Constraint};
+use crate::core::processing::{delete, forward};
+use crate::core::store::BundleStore;
+use crate::{store_get_metadata, BundleID, CLAS, CONFIG, SCHEDULER, STORE};
+use bp7::administrative_record::NO_NEXT_NODE_CONTACT;
+use log::{debug, error, info, trace};
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tokio::time::Instant;
+
+/*
+    Event-driven forwarding scheduler.
+
+    Bundles that could not be forwarded are parked with an exponential backoff instead of
+    being reprocessed on every janitor run. New forwarding opportunities make parked bundles
+    due immediately while keeping their backoff: new peers, new CLAs or routing changes wake
+    all of them, a peer with a changed address or CLA list only the bundles destined for it.
+    Beacons of known peers without changes wake nothing. Bundles deferred by the routing
+    agent are not woken before their deferral expires.
+
+    Bundles whose transfers failed on all next hops `max-transfer-failures` times in a row
+    are deleted with a "no timely contact" status report instead of being parked again.
+*/
+
+/// Delay before the first retry of a bundle that could not be forwarded
+pub const RETRY_BACKOFF_MIN: Duration = Duration::from_secs(1);
+/// Upper bound for the retry delay of parked bundles
+pub const RETRY_BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+pub enum SchedulerCmd {
+    /// Forwarding attempt finished, bundle still waits for an opportunity, with the node
+    /// name of its destination and whether the transfers to all next hops failed
+    Park(BundleID, String, bool),
+    /// New bundle waiting for forwarding, with the node name of its destination
+    Enqueue(BundleID, String),
+    /// Bundle no longer needs forwarding
+    Forget(BundleID),
+    /// New forwarding opportunity, re-evaluate all parked bundles now
+    Wake(&'static str),
+    /// New forwarding opportunity for the bundles destined for the given node
+    WakeNode(String, &'static str),
+    /// The routing agent asked to retry the bundle after the given delay
+    Defer(BundleID, Duration),
+}
+
+/// Retry bookkeeping of parked bundles, ordered by due time
+#[derive(Debug, Default)]
+pub struct RetryQueue {
+    attempts: HashMap<BundleID, u32>,
+    due: HashMap<BundleID, Instant>,
+    queue: BTreeSet<(Instant, BundleID)>,
+    /// Retry times requested by the routing agent, used instead of the backoff on parking
+    deferred: HashMap<BundleID, Instant>,
+    /// Destination node names of the parked bundles
+    destinations: HashMap<BundleID, String>,
+    /// Consecutive forwarding attempts in which all transfers failed
+    failures: HashMap<BundleID, u32>,
+}
+
+impl RetryQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Retry delay after the given number of unsuccessful attempts
+    pub fn backoff(attempts: u32) -> Duration {
+        RETRY_BACKOFF_MIN
+            .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+            .min(RETRY_BACKOFF_MAX)
+    }
+    fn schedule(&mut self, bid: BundleID, at: Instant) {
+        if let Some(old) = self.due.insert(bid.clone(), at) {
+            self.queue.remove(&(old, bid.clone()));
+        }
+        self.queue.insert((at, bid));
+    }
+    /// Parks a bundle destined for the given node after an unsuccessful attempt, returns its
+    /// next due time.
+    pub fn park(&mut self, bid: &str, destination: &str, now: Instant) -> Instant {
+        let attempts = self.attempts.entry(bid.to_string()).or_insert(0);
+        *attempts += 1;
+        let at = match self.deferred.get(bid) {
+            Some(at) if *at > now => *at,
+            _ => now + Self::backoff(*attempts),
+        };
+        self.destinations
+            .insert(bid.to_string(), destination.to_string());
+        self.schedule(bid.to_string(), at);
+        at
+    }
+    /// Retries a bundle not before the given time, e.g., if the routing agent deferred its
+    /// decision. Wakes ignore the bundle until then.
+    pub fn defer(&mut self, bid: &str, at: Instant) {
+        self.deferred.insert(bid.to_string(), at);
+        if self.due.contains_key(bid) {
+            self.schedule(bid.to_string(), at);
+        }
+    }
+    /// Makes a bundle due immediately without counting an attempt.
+    pub fn enqueue(&mut self, bid: &str, destination: &str, now: Instant) {
+        self.attempts.entry(bid.to_string()).or_insert(0);
+        self.destinations
+            .insert(bid.to_string(), destination.to_string());
+        self.schedule(bid.to_string(), now);
+    }
+    /// Counts a forwarding attempt in which all transfers failed, a successful attempt
+    /// resets the count. Returns the number of consecutive failed attempts.
+    pub fn record_transfer(&mut self, bid: &str, failed: bool) -> u32 {
+        if !failed {
+            self.failures.remove(bid);
+            return 0;
+        }
+        let failures = self.failures.entry(bid.to_string()).or_insert(0);
+        *failures += 1;
+        *failures
+    }
+    pub fn forget(&mut self, bid: &str) {
+        self.attempts.remove(bid);
+        self.failures.remove(bid);
+        self.deferred.remove(bid);
+        self.destinations.remove(bid);
+        if let Some(at) = self.due.remove(bid) {
+            self.queue.remove(&(at, bid.to_string()));
+        }
+    }
+    fn wake(&mut self, candidates: Vec<BundleID>, now: Instant) -> usize {
+        let mut woken = 0;
+        for bid in candidates {
+            let deferred = self.deferred.get(&bid).is_some_and(|until| *until > now);
+            let waiting = self.due.get(&bid).is_some_and(|at| *at > now);
+            if waiting && !deferred {
+                self.schedule(bid, now);
+                woken += 1;
+            }
+        }
+        woken
+    }
+    /// Makes all parked bundles due now except deferred ones, their backoff is kept.
+    /// Returns the number of woken bundles.
+    pub fn wake_all(&mut self, now: Instant) -> usize {
+        let parked: Vec<BundleID> = self.due.keys().cloned().collect();
+        self.wake(parked, now)
+    }
+    /// Makes the parked bundles destined for the given node due now except deferred ones,
+    /// their backoff is kept. Returns the number of woken bundles.
+    pub fn wake_node(&mut self, node: &str, now: Instant) -> usize {
+        let parked: Vec<BundleID> = self
+            .destinations
+            .iter()
+            .filter(|(_, destination)| *destination == node)
+            .map(|(bid, _)| bid.clone())
+            .collect();
+        self.wake(parked, now)
+    }
+    /// Removes and returns all bundles due at `now`, their attempt counters are kept.
+    pub fn pop_due(&mut self, now: Instant) -> Vec<BundleID> {
+        let mut due = Vec::new();
+        while let Some((at, _)) = self.queue.first() {
+            if *at > now {
+                break;
+            }
+            let (_, bid) = self.queue.pop_first().unwrap();
+            self.due.remove(&bid);
+            self.deferred.remove(&bid);
+            due.push(bid);
+        }
+        due
+    }
+    /// Node name of the destination of a parked bundle
+    pub fn destination(&self, bid: &str) -> Option<&str> {
+        self.destinations.get(bid).map(|dst| dst.as_str())
+    }
+    pub fn next_due(&self) -> Option<Instant> {
+        self.queue.first().map(|(at, _)| *at)
+    }
+    pub fn attempts(&self, bid: &str) -> Option<u32> {
+        self.attempts.get(bid).copied()
+    }
+    /// Number of parked bundles
+    pub fn len(&self) -> usize {
+        self.due.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.due.is_empty()
+    }
+}
+
+fn send_cmd(cmd: SchedulerCmd) {
+    if let Some(tx) = &*SCHEDULER.lock() {
+        if tx.send(cmd).is_err() {
+            error!("Forwarding scheduler is gone");
+        }
+    }
+}
+
+/// Signals a new forwarding opportunity for all bundles, e.g., a new peer.
+pub fn wake(reason: &'static str) {
+    send_cmd(SchedulerCmd::Wake(reason));
+}
+
+/// Signals a new forwarding opportunity for the bundles destined for the given node.
+pub fn wake_node(node: &str, reason: &'static str) {
+    send_cmd(SchedulerCmd::WakeNode(node.to_string(), reason));
+}
+
+/// Retries a bundle after `delay` instead of the backoff once the current forwarding attempt is done.
+pub fn defer(bid: &str, delay: Duration) {
+    send_cmd(SchedulerCmd::Defer(bid.to_string(), delay));
+}
+
+/// Schedules a bundle created outside of the regular bundle processing for forwarding,
+/// e.g., a status report.
+pub fn enqueue(bid: &str, destination: &str) {
+    send_cmd(SchedulerCmd::Enqueue(
+        bid.to_string(),
+        destination.to_string(),
+    ));
+}
+
+/// Reports the outcome of a forwarding attempt, parks the bundle if it is still pending.
+/// `failed` tells whether the transfers to all next hops failed.
+pub fn forwarding_done(bid: &str, failed: bool) {
+    let pending = store_get_metadata(bid).filter(|bp| {
+        bp.has_constraint(Constraint::ForwardPending) && !bp.has_constraint(Constraint::Deleted)
+    });
+    match pending {
+        Some(bp) => send_cmd(SchedulerCmd::Park(
+            bid.to_string(),
+            bp.destination.node().unwrap_or_default(),
+            failed,
+        )),
+        None => send_cmd(SchedulerCmd::Forget(bid.to_string())),
+    }
+}
+
+async fn reforward(bundles: Vec<BundlePack>) {
+    for bp in bundles {
+        let bpid = bp.id().to_string();
+        let now = std::time::Instant::now();
+        if let Err(err) = forward(bp).await {
+            error!("Error forwarding bundle: {}", err);
+        }
+        trace!("Forwarding time: {:?} for {}", now.elapsed(), bpid);
+    }
+}
+
+// deletes a bundle that could not be transferred to any next hop too often
+async fn give_up(bid: BundleID, failures: u32) {
+    let Some(bp) = store_get_metadata(&bid) else {
+        return;
+    };
+    info!(
+        "Giving up on bundle {} after {} failed forwarding attempts",
+        bid, failures
+    );
+    if let Err(err) = delete(bp, NO_NEXT_NODE_CONTACT).await {
+        error!("Error deleting bundle {}: {}", bid, err);
+    }
+}
+
+fn process_due(queue: &mut RetryQueue) {
+    let now = Instant::now();
+    let due = queue.pop_due(now);
+    if due.is_empty() {
+        return;
+    }
+    if !(*CLAS.lock()).iter().any(|p| p.accepting()) {
+        debug!("No active/push CLA, postponing {} bundles", due.len());
+        for bid in due {
+            let destination = queue.destination(&bid).unwrap_or_default().to_string();
+            queue.park(&bid, &destination, now);
+        }
+        return;
+    }
+
+    let mut bundles: Vec<BundlePack> = Vec::with_capacity(due.len());
+    for bid in due {
+        match store_get_metadata(&bid) {
+            Some(bp)
+                if bp.has_constraint(Constraint::ForwardPending)
+                    && !bp.has_constraint(Constraint::Deleted) =>
+            {
+                bundles.push(bp)
+            }
+            _ => queue.forget(&bid),
+        }
+    }
+    // process them in chronological order
+    bundles.sort_unstable_by_key(|bp| bp.creation_time);
+    debug!("Re-evaluating {} parked bundles", bundles.len());
+
+    if CONFIG.lock().parallel_bundle_processing {
+        for bp in bundles {
+            crate::node::spawn(reforward(vec![bp]));
+        }
+    } else {
+        crate::node::spawn(reforward(bundles));
+    }
+}
+
+async fn scheduler_loop(mut rx: UnboundedReceiver<SchedulerCmd>, mut queue: RetryQueue) {
+    loop {
+        let next_due = queue.next_due();
+        tokio::select! {
+            cmd = rx.recv() => {
+                let Some(cmd) = cmd else {
+                    break;
+                };
+                let now = Instant::now();
+                match cmd {
+                    SchedulerCmd::Park(bid, destination, failed) => {
+                        let failures = queue.record_transfer(&bid, failed);
+                        let max_failures = CONFIG.lock().max_transfer_failures;
+                        if max_failures > 0 && failures >= max_failures {
+                            queue.forget(&bid);
+                            crate::node::spawn(give_up(bid, failures));
+                        } else {
+                            let at = queue.park(&bid, &destination, now);
+                            trace!("Parked bundle {} for {:?}", bid, at - now);
+                        }
+                    }
+                    SchedulerCmd::Enqueue(bid, destination) => {
+                        queue.enqueue(&bid, &destination, now)
+                    }
+                    SchedulerCmd::Forget(bid) => queue.forget(&bid),
+                    SchedulerCmd::Wake(reason) => {
+                        let woken = queue.wake_all(now);
+                        debug!(
+                            "Forwarding opportunity ({}), woke {} of {} bundles",
+                            reason,
+                            woken,
+                            queue.len()
+                        );
+                    }
+                    SchedulerCmd::WakeNode(node, reason) => {
+                        let woken = queue.wake_node(&node, now);
+                        debug!(
+                            "Forwarding opportunity for {} ({}), woke {} of {} bundles",
+                            node,
+                            reason,
+                            woken,
+                            queue.len()
+                        );
+                    }
+                    SchedulerCmd::Defer(bid, delay) => queue.defer(&bid, now + delay),
+                }
+            }
+            _ = tokio::time::sleep_until(next_due.unwrap_or_else(Instant::now)), if next_due.is_some() => {}
+        }
+        process_due(&mut queue);
+    }
+}
+
+/// Starts the forwarding scheduler, bundles already pending in the store are evaluated right away.
+pub fn spawn_scheduler() {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let mut queue = RetryQueue::new();
+    let now = Instant::now();
+    let forwarding = (*STORE.lock()).forwarding();
+    for bid in forwarding {
+        let destination = store_get_metadata(&bid)
+            .and_then(|bp| bp.destination.node())
+            .unwrap_or_default();
+        queue.enqueue(&bid, &destination, now);
+    }
+    debug!(
+        "Forwarding scheduler started with {} pending bundles",
+        queue.len()
+    );
+    (*SCHEDULER.lock()) = Some(tx);
+    crate::node::spawn(scheduler_loop(rx, queue));
+}
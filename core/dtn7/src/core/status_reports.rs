@@ -0,0 +1,278 @@
+use crate::node::node_local;
+use crate::BundleID;
+use crate::CONFIG;
+use bp7::administrative_record::{
+    StatusInformationPos, StatusReport, StatusReportReason, DELETED_BUNDLE, DELIVERED_BUNDLE,
+    FORWARDED_BUNDLE, RECEIVED_BUNDLE,
+};
+use bp7::dtntime::{dtn_time_now, DtnTime};
+use bp7::flags::{BundleControlFlags, BundleValidation};
+use bp7::{Bundle, EndpointID};
+use glob_match::glob_match;
+use log::{debug, warn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/*
+    Policy for the generation of RFC 9171 bundle status reports.
+
+    Reports are only generated if enabled by `generate-status-reports`, requested by the
+    bundle and addressed to an allowed report-to endpoint. Each combination of status and
+    reason is rate limited independently so that e.g. a mass expiry of bundles does not
+    flood the network with deletion reports.
+
+    Status reports received for bundles originating from this node are kept in a bounded
+    log, so the end-to-end delivery of a bundle can be traced.
+*/
+
+/// Number of bundles for which received status reports are kept
+pub const MAX_TRACKED_BUNDLES: usize = 1000;
+
+/// Settings of the `[statusreports]` config section
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatusReportConfig {
+    /// Glob patterns of report-to endpoints that may receive reports, empty allows all
+    pub report_to: Vec<String>,
+    /// Maximum number of reports per status and reason within `rate_interval`, 0 disables the limit
+    pub rate_limit: u32,
+    pub rate_interval: Duration,
+}
+
+impl Default for StatusReportConfig {
+    fn default() -> Self {
+        StatusReportConfig {
+            report_to: Vec::new(),
+            rate_limit: 100,
+            rate_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+impl StatusReportConfig {
+    /// Checks if reports may be sent to the given report-to endpoint
+    pub fn report_to_allowed(&self, report_to: &str) -> bool {
+        self.report_to.is_empty()
+            || self
+                .report_to
+                .iter()
+                .any(|pattern| glob_match(pattern, report_to))
+    }
+}
+
+/// Status report flag of the primary block matching a status information position
+pub fn request_flag(status: StatusInformationPos) -> Option<BundleControlFlags> {
+    match status {
+        RECEIVED_BUNDLE => Some(BundleControlFlags::BUNDLE_STATUS_REQUEST_RECEPTION),
+        FORWARDED_BUNDLE => Some(BundleControlFlags::BUNDLE_STATUS_REQUEST_FORWARD),
+        DELIVERED_BUNDLE => Some(BundleControlFlags::BUNDLE_STATUS_REQUEST_DELIVERY),
+        DELETED_BUNDLE => Some(BundleControlFlags::BUNDLE_STATUS_REQUEST_DELETION),
+        _ => None,
+    }
+}
+
+/// Checks if the bundle requests a status report of the given kind.
+///
+/// Administrative records never request reports.
+pub fn is_requested(bndl: &Bundle, status: StatusInformationPos) -> bool {
+    !bndl.is_administrative_record()
+        && request_flag(status)
+            .map(|flag| bndl.primary.bundle_control_flags.contains(flag))
+            .unwrap_or(false)
+}
+
+/// Fixed window rate limiter with an independent window per status and reason
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: u32,
+    interval: Duration,
+    windows: HashMap<(StatusInformationPos, StatusReportReason), (Instant, u32)>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: u32, interval: Duration) -> Self {
+        RateLimiter {
+            limit,
+            interval,
+            windows: HashMap::new(),
+        }
+    }
+    /// Counts a report and returns whether it may be sent.
+    pub fn check(
+        &mut self,
+        status: StatusInformationPos,
+        reason: StatusReportReason,
+        now: Instant,
+    ) -> bool {
+        if self.limit == 0 {
+            return true;
+        }
+        let (start, count) = self.windows.entry((status, reason)).or_insert((now, 0));
+        if now.duration_since(*start) >= self.interval {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= self.limit {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+    /// Number of reports sent in the current window of the given status and reason
+    pub fn count(&self, status: StatusInformationPos, reason: StatusReportReason) -> u32 {
+        self.windows
+            .get(&(status, reason))
+            .map(|(_, count)| *count)
+            .unwrap_or(0)
+    }
+}
+
+node_local! {
+    static ref LIMITER: Mutex<Option<RateLimiter>> = Mutex::new(None);
+}
+
+/// Decides if a requested status report for a bundle may be generated under the node's policy.
+pub fn permit(bndl: &Bundle, status: StatusInformationPos, reason: StatusReportReason) -> bool {
+    let (enabled, cfg) = {
+        let config = CONFIG.lock();
+        (
+            config.generate_status_reports,
+            config.status_reports.clone(),
+        )
+    };
+    if !enabled {
+        debug!("Generation of status reports disabled: {}", bndl.id());
+        return false;
+    }
+    let report_to = bndl.primary.report_to.to_string();
+    if !cfg.report_to_allowed(&report_to) {
+        debug!(
+            "Status report for {} not sent, report-to {} not allowed",
+            bndl.id(),
+            report_to
+        );
+        return false;
+    }
+    let mut limiter = LIMITER.lock();
+    let limiter =
+        limiter.get_or_insert_with(|| RateLimiter::new(cfg.rate_limit, cfg.rate_interval));
+    if !limiter.check(status, reason, Instant::now()) {
+        warn!(
+            "Status report rate limit reached, suppressing report for {}: {} {}",
+            bndl.id(),
+            status,
+            crate::core::events::reason_name(reason)
+        );
+        return false;
+    }
+    true
+}
+
+/// Name of a status information position, e.g., `delivered`
+pub fn status_name(status: StatusInformationPos) -> &'static str {
+    match status {
+        RECEIVED_BUNDLE => "received",
+        FORWARDED_BUNDLE => "forwarded",
+        DELIVERED_BUNDLE => "delivered",
+        DELETED_BUNDLE => "deleted",
+        _ => "unknown",
+    }
+}
+
+/// A status asserted by a received status report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportedStatus {
+    /// `received`, `forwarded`, `delivered` or `deleted`
+    pub status: String,
+    pub reason: String,
+    /// Node that generated the status report
+    pub node: String,
+    /// DTN time of the status, if the bundle requested status times
+    pub time: Option<DtnTime>,
+    /// DTN time at which the status report arrived at this node
+    pub reported: DtnTime,
+}
+
+impl ReportedStatus {
+    /// All statuses asserted by a status report generated by `node`
+    pub fn from_report(report: &StatusReport, node: &EndpointID) -> Vec<ReportedStatus> {
+        let reported = dtn_time_now();
+        report
+            .status_information
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.asserted)
+            .map(|(pos, item)| ReportedStatus {
+                status: status_name(pos as StatusInformationPos).into(),
+                reason: crate::core::events::reason_name(report.report_reason).into(),
+                node: node.node_id().unwrap_or_else(|| node.to_string()),
+                time: if item.status_requested {
+                    Some(item.time)
+                } else {
+                    None
+                },
+                reported,
+            })
+            .collect()
+    }
+    /// Time used to order the delivery timeline
+    pub fn timeline_time(&self) -> DtnTime {
+        self.time.unwrap_or(self.reported)
+    }
+}
+
+/// Received status reports by subject bundle ID, the oldest bundles are dropped first
+#[derive(Debug)]
+pub struct StatusReportLog {
+    capacity: usize,
+    reports: HashMap<BundleID, Vec<ReportedStatus>>,
+    order: VecDeque<BundleID>,
+}
+
+impl Default for StatusReportLog {
+    fn default() -> Self {
+        Self::new(MAX_TRACKED_BUNDLES)
+    }
+}
+
+impl StatusReportLog {
+    pub fn new(capacity: usize) -> Self {
+        StatusReportLog {
+            capacity,
+            reports: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+    pub fn record(&mut self, bid: &str, statuses: Vec<ReportedStatus>) {
+        if !self.reports.contains_key(bid) {
+            while self.order.len() >= self.capacity {
+                match self.order.pop_front() {
+                    Some(oldest) => self.reports.remove(&oldest),
+                    None => break,
+                };
+            }
+            self.order.push_back(bid.to_string());
+        }
+        self.reports
+            .entry(bid.to_string())
+            .or_default()
+            .extend(statuses);
+    }
+    /// Reported statuses of a bundle in chronological order
+    pub fn timeline(&self, bid: &str) -> Option<Vec<ReportedStatus>> {
+        let mut timeline = self.reports.get(bid)?.clone();
+        timeline.sort_by_key(|status| status.timeline_time());
+        Some(timeline)
+    }
+    /// IDs of all bundles with received status reports, oldest first
+    pub fn bundles(&self) -> Vec<BundleID> {
+        self.order.iter().cloned().collect()
+    }
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
@@ -73,6 +73,10 @@ pub trait BundleStore: Debug {
     }
     fn get_bundle(&self, bpid: &str) -> Option<Bundle>;
     fn get_metadata(&self, bpid: &str) -> Option<BundlePack>;
+    /// Writes pending changes to persistent storage
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
 }
 
 pub fn bundle_stores() -> Vec<&'static str> {
@@ -137,6 +137,11 @@ impl BundleStore for SledBundleStore {
             .map(|b| b.unwrap().as_ref().into())
             .ok()
     }
+    fn flush(&mut self) -> Result<()> {
+        self.metadata.flush()?;
+        self.bundles.flush()?;
+        Ok(())
+    }
 }
 
 impl SledBundleStore {
@@ -1,17 +1,20 @@
 use super::BundleStore;
 use crate::core::bundlepack::{self, BundlePack, Constraint};
+use crate::core::journal::JournalEntry;
 use crate::CONFIG;
 use anyhow::{bail, Result};
 use bp7::{Bundle, EndpointID};
 use d7sneakers::{Constraints, SneakerWorld};
 use log::debug;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::fmt::Debug;
 
 #[derive(Debug, Clone)]
 pub struct SneakersBundleStore {
     store: SneakerWorld,
+    /// bundle journals are not persisted by sneakers and only kept in memory
+    journals: HashMap<String, Vec<JournalEntry>>,
 }
 
 impl SneakersBundleStore {
@@ -54,6 +57,8 @@ impl BundleStore for SneakersBundleStore {
         }
         let c = convert_hashset_to_constraints(&bp.constraints);
         self.store.db.set_constraints(bp.id(), c)?;
+        self.journals
+            .insert(bp.id().to_string(), bp.journal.clone());
         Ok(())
     }
     fn remove(&mut self, bid: &str) -> Result<()> {
@@ -62,6 +67,8 @@ impl BundleStore for SneakersBundleStore {
             meta.add_constraint(Constraint::Deleted);
             self.update_metadata(&meta)?;
         }
+        // removed and expired bundles are gone from the db, their journals with them
+        self.journals.remove(bid);
         self.store.remove(bid)
     }
 
@@ -134,6 +141,7 @@ impl BundleStore for SneakersBundleStore {
             size: meta.size as usize,
             constraints: convert_constraints_to_hashset(constraints.unwrap()),
             creation_time: meta.creation_time,
+            journal: self.journals.get(bpid).cloned().unwrap_or_default(),
         };
         //debug_time!("get_metadata");
         // {
@@ -158,7 +166,10 @@ impl SneakersBundleStore {
             SneakerWorld::open(wd.to_string_lossy().as_ref()).expect("open sneaker bundle store");
         debug!("syncing store fs/db");
         store.sync().expect("sync sneaker bundle store failed");
-        SneakersBundleStore { store }
+        SneakersBundleStore {
+            store,
+            journals: HashMap::new(),
+        }
     }
 }
 
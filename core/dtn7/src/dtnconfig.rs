@@ -1,6 +1,12 @@
 use crate::cla::CLAsAvailable;
+use crate::core::admission::{AdmissionPolicy, AdmissionRule};
+use crate::core::contact_plan::{ContactWindow, ScheduledPeer};
 use crate::core::helpers::is_valid_node_name;
+use crate::core::status_reports::StatusReportConfig;
 use crate::core::DtnPeer;
+use crate::dtnd::auth::{AuthConfig, Scope, StaticToken};
+use crate::ipnd::security::{parse_key, BeaconSecurityConfig};
+use crate::ipnd::DiscoveryBackend;
 use bp7::EndpointID;
 use config::{Config, File};
 use log::{debug, error};
@@ -26,8 +32,10 @@ pub struct DtnConfig {
     pub webport: u16,
     pub announcement_interval: Duration,
     pub disable_neighbour_discovery: bool,
+    pub discovery_backends: Vec<DiscoveryBackend>,
     pub discovery_destinations: BTreeMap<String, u32>,
     pub discovery_listen_port: u16,
+    pub beacon_security: BeaconSecurityConfig,
     pub janitor_interval: Duration,
     pub endpoints: Vec<String>,
     pub clas: Vec<(CLAsAvailable, HashMap<String, String>)>,
@@ -37,12 +45,23 @@ pub struct DtnConfig {
     pub routing_settings: BTreeMap<String, HashMap<String, String>>,
     pub peer_timeout: Duration,
     pub statics: Vec<DtnPeer>,
+    pub scheduled: Vec<ScheduledPeer>,
     pub workdir: PathBuf,
     pub db: String,
     pub generate_status_reports: bool,
+    pub status_reports: StatusReportConfig,
+    /// Maximum number of bundles in the store, further received bundles are pared, 0 disables the limit
+    pub max_bundles: u64,
+    /// Failed forwarding attempts after which a bundle is deleted, 0 retries until it expires
+    pub max_transfer_failures: u32,
     pub ecla_tcp_port: u16,
     pub ecla_enable: bool,
     pub parallel_bundle_processing: bool,
+    #[serde(skip)]
+    pub auth: AuthConfig,
+    pub admission: AdmissionPolicy,
+    /// Config file the node was started with, runtime changes of the admission policy are saved to it
+    pub config_file: Option<PathBuf>,
 }
 
 pub fn rnd_node_name() -> String {
@@ -65,6 +84,7 @@ pub fn rnd_node_name() -> String {
 impl From<PathBuf> for DtnConfig {
     fn from(item: PathBuf) -> Self {
         let mut dtncfg = DtnConfig::new();
+        dtncfg.config_file = Some(item.clone());
         let s_default = Config::default();
 
         let configbuilder = Config::builder().add_source(s_default);
@@ -81,7 +101,10 @@ impl From<PathBuf> for DtnConfig {
         if dtncfg.debug {
             //std::env::set_var("RUST_LOG", "dtn7=debug,dtnd=debug");
         }
-        dtncfg.generate_status_reports = s.get_bool("generate_status_reports").unwrap_or(false);
+        dtncfg.generate_status_reports = s
+            .get_bool("generate-status-reports")
+            .or_else(|_| s.get_bool("generate_status_reports"))
+            .unwrap_or(false);
         dtncfg.parallel_bundle_processing =
             s.get_bool("parallel-bundle-processing").unwrap_or(false);
         dtncfg.unsafe_httpd = s.get_bool("unsafe_httpd").unwrap_or(false);
@@ -148,6 +171,11 @@ impl From<PathBuf> for DtnConfig {
             dtncfg.janitor_interval
         };
         debug!("janitor: {:?}", dtncfg.janitor_interval);
+        dtncfg.max_bundles = s.get_int("core.max-bundles").unwrap_or(0).max(0) as u64;
+        debug!("max bundles: {}", dtncfg.max_bundles);
+        dtncfg.max_transfer_failures =
+            s.get_int("core.max-transfer-failures").unwrap_or(0).max(0) as u32;
+        debug!("max transfer failures: {}", dtncfg.max_transfer_failures);
 
         dtncfg.announcement_interval = if let Ok(interval) = s.get_string("discovery.interval") {
             humantime::parse_duration(&interval).unwrap_or_else(|_| Duration::new(0, 0))
@@ -163,6 +191,52 @@ impl From<PathBuf> for DtnConfig {
         };
         debug!("discovery-peer-timeout: {:?}", dtncfg.peer_timeout);
 
+        if let Ok(backends) = s.get_array("discovery.backends") {
+            dtncfg.discovery_backends.clear();
+            for backend in backends {
+                match backend.into_string().unwrap_or_default().parse() {
+                    Ok(backend) if !dtncfg.discovery_backends.contains(&backend) => {
+                        dtncfg.discovery_backends.push(backend)
+                    }
+                    Ok(_) => {}
+                    Err(err) => error!("{}", err),
+                }
+            }
+        }
+        debug!("discovery-backends: {:?}", dtncfg.discovery_backends);
+
+        if let Ok(key) = s.get_string("discovery.security.signing-key") {
+            match parse_key(&key) {
+                Ok(key) => dtncfg.beacon_security.signing_key = Some(key),
+                Err(err) => error!("Error parsing beacon signing key: {}", err),
+            }
+        }
+        if let Ok(policy) = s.get_string("discovery.security.unauthenticated") {
+            match policy.parse() {
+                Ok(policy) => dtncfg.beacon_security.unauthenticated = policy,
+                Err(err) => error!("{}", err),
+            }
+        }
+        if let Ok(keys) = s.get_table("discovery.security.trusted-keys") {
+            for (node, key) in keys.iter() {
+                match parse_key(&key.clone().into_string().unwrap_or_default()) {
+                    Ok(key) => {
+                        dtncfg
+                            .beacon_security
+                            .trusted_keys
+                            .insert(node.clone(), key);
+                    }
+                    Err(err) => error!("Error parsing trusted beacon key of {}: {}", node, err),
+                }
+            }
+        }
+        debug!(
+            "discovery-security: signing: {:?}, trusted keys: {:?}, unauthenticated beacons: {}",
+            dtncfg.beacon_security.signing_key.is_some(),
+            dtncfg.beacon_security.trusted_keys.keys(),
+            dtncfg.beacon_security.unauthenticated
+        );
+
         if let Ok(peers) = s.get_array("statics.peers") {
             for m in peers.iter() {
                 let peer: DtnPeer =
@@ -172,6 +246,35 @@ impl From<PathBuf> for DtnConfig {
                 dtncfg.statics.push(peer);
             }
         }
+        if let Ok(scheduled) = s.get_table("statics.scheduled") {
+            for (name, v) in scheduled.iter() {
+                let table = v
+                    .clone()
+                    .into_table()
+                    .expect("error parsing scheduled peer");
+                let url = table
+                    .get("peer")
+                    .and_then(|url| url.clone().into_string().ok())
+                    .expect("scheduled peer without url");
+                let peer = crate::core::helpers::parse_peer_url(&url)
+                    .expect("error parsing scheduled peer url");
+                let windows: Vec<ContactWindow> = table
+                    .get("windows")
+                    .and_then(|windows| windows.clone().into_array().ok())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|window| {
+                        let window = window.into_string().ok()?;
+                        window
+                            .parse()
+                            .map_err(|err| error!("Invalid contact window of {}: {}", name, err))
+                            .ok()
+                    })
+                    .collect();
+                debug!("Scheduled peer {}: {:?} {:?}", name, peer, windows);
+                dtncfg.scheduled.push(ScheduledPeer::new(peer, windows));
+            }
+        }
         if let Ok(endpoints) = s.get_table("endpoints.local") {
             for (_k, v) in endpoints.iter() {
                 let eid = v.clone().into_string().unwrap();
@@ -223,6 +326,84 @@ impl From<PathBuf> for DtnConfig {
                 dtncfg.ecla_tcp_port = tcp_port.clone().into_int().unwrap_or(0) as u16;
             }
         }
+        if let Ok(reports) = s.get_table("statusreports") {
+            if let Some(report_to) = reports.get("report-to") {
+                dtncfg.status_reports.report_to = report_to
+                    .clone()
+                    .into_array()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|pattern| pattern.into_string().ok())
+                    .collect();
+            }
+            if let Some(rate_limit) = reports.get("rate-limit") {
+                dtncfg.status_reports.rate_limit =
+                    rate_limit.clone().into_int().unwrap_or(0) as u32;
+            }
+            if let Some(interval) = reports.get("rate-interval") {
+                if let Ok(interval) = interval
+                    .clone()
+                    .into_string()
+                    .unwrap_or_default()
+                    .parse::<humantime::Duration>()
+                {
+                    dtncfg.status_reports.rate_interval = interval.into();
+                }
+            }
+            debug!("status reports: {:?}", dtncfg.status_reports);
+        }
+        if let Ok(auth) = s.get_table("auth") {
+            if let Some(enabled) = auth.get("enabled") {
+                dtncfg.auth.enabled = enabled.clone().into_bool().unwrap_or(false);
+            }
+            if let Some(trust_localhost) = auth.get("trust-localhost") {
+                dtncfg.auth.trust_localhost = trust_localhost.clone().into_bool().unwrap_or(true);
+            }
+            if let Some(public_status) = auth.get("public-status") {
+                dtncfg.auth.public_status = public_status.clone().into_bool().unwrap_or(true);
+            }
+            if let Some(secret) = auth.get("hmac-secret") {
+                dtncfg.auth.hmac_secret = secret.clone().into_string().ok();
+            }
+            debug!(
+                "auth enabled: {:?}, trust localhost: {:?}, public status: {:?}",
+                dtncfg.auth.enabled, dtncfg.auth.trust_localhost, dtncfg.auth.public_status
+            );
+        }
+        if let Ok(tokens) = s.get_table("auth.tokens") {
+            for (name, v) in tokens.iter() {
+                let tab = v.clone().into_table().unwrap();
+                let token = tab["token"].clone().into_string().unwrap();
+                let mut scopes = Vec::new();
+                for scope in tab["scopes"].clone().into_array().unwrap_or_default() {
+                    match scope.into_string().unwrap_or_default().parse::<Scope>() {
+                        Ok(scope) => scopes.push(scope),
+                        Err(err) => error!("Error parsing scopes of token {}: {}", name, err),
+                    }
+                }
+                debug!("Added access token {} with scopes {:?}", name, scopes);
+                dtncfg.auth.tokens.push(StaticToken {
+                    name: name.clone(),
+                    token,
+                    scopes,
+                });
+            }
+        }
+        if let Ok(default) = s.get_string("admission.default") {
+            match default.parse() {
+                Ok(default) => dtncfg.admission.default = default,
+                Err(err) => panic!("Error parsing default admission action: {}", err),
+            }
+        }
+        if let Ok(rules) = s.get_array("admission.rules") {
+            for rule in rules {
+                match rule.try_deserialize::<AdmissionRule>() {
+                    Ok(rule) => dtncfg.admission.rules.push(rule),
+                    Err(err) => panic!("Error parsing admission rule: {}", err),
+                }
+            }
+        }
+        debug!("admission: {:?}", dtncfg.admission);
         if let Ok(services) = s.get_table("services.service") {
             for (_k, v) in services.iter() {
                 let tab = v.clone().into_table().unwrap();
@@ -277,8 +458,10 @@ impl DtnConfig {
             host_eid: local_node_id,
             announcement_interval: "2s".parse::<humantime::Duration>().unwrap().into(),
             disable_neighbour_discovery: false,
+            discovery_backends: vec![DiscoveryBackend::Ipnd],
             discovery_destinations: BTreeMap::new(),
             discovery_listen_port: 3003,
+            beacon_security: BeaconSecurityConfig::default(),
             webport: 3000,
             janitor_interval: "10s".parse::<humantime::Duration>().unwrap().into(),
             endpoints: Vec::new(),
@@ -289,12 +472,19 @@ impl DtnConfig {
             routing_settings: BTreeMap::new(),
             peer_timeout: "20s".parse::<humantime::Duration>().unwrap().into(),
             statics: Vec::new(),
+            scheduled: Vec::new(),
             workdir: std::env::current_dir().unwrap(),
             db: String::from("mem"),
             generate_status_reports: false,
+            status_reports: StatusReportConfig::default(),
+            max_bundles: 0,
+            max_transfer_failures: 0,
             ecla_enable: false,
             ecla_tcp_port: 0,
             parallel_bundle_processing: false,
+            auth: AuthConfig::default(),
+            admission: AdmissionPolicy::default(),
+            config_file: None,
         }
     }
     pub fn set(&mut self, cfg: DtnConfig) {
@@ -309,8 +499,10 @@ impl DtnConfig {
         self.webport = cfg.webport;
         self.announcement_interval = cfg.announcement_interval;
         self.disable_neighbour_discovery = cfg.disable_neighbour_discovery;
+        self.discovery_backends = cfg.discovery_backends;
         self.discovery_destinations = cfg.discovery_destinations;
         self.discovery_listen_port = cfg.discovery_listen_port;
+        self.beacon_security = cfg.beacon_security;
         self.janitor_interval = cfg.janitor_interval;
         self.endpoints = cfg.endpoints;
         self.clas = cfg.clas;
@@ -320,12 +512,19 @@ impl DtnConfig {
         self.routing_settings = cfg.routing_settings;
         self.peer_timeout = cfg.peer_timeout;
         self.statics = cfg.statics;
+        self.scheduled = cfg.scheduled;
         self.workdir = cfg.workdir;
         self.db = cfg.db;
         self.generate_status_reports = cfg.generate_status_reports;
+        self.status_reports = cfg.status_reports;
+        self.max_bundles = cfg.max_bundles;
+        self.max_transfer_failures = cfg.max_transfer_failures;
         self.ecla_enable = cfg.ecla_enable;
         self.ecla_tcp_port = cfg.ecla_tcp_port;
         self.parallel_bundle_processing = cfg.parallel_bundle_processing;
+        self.auth = cfg.auth;
+        self.admission = cfg.admission;
+        self.config_file = cfg.config_file;
     }
 
     /// Helper function that adds discovery destinations to a config struct
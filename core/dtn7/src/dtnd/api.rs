@@ -0,0 +1,1216 @@
+use super::auth::{
+    AdminAccess, AuthError, ClientAccess, Grant, ReadAccess, Require, Requirement, SendAccess,
+    StatusAccess,
+};
+use super::httpd::new_bundle_from_params;
+use crate::cla::ConvergenceLayerAgent;
+use crate::core::admission::{enforce_policy, update_policy, AdmissionPolicy, AdmissionRule};
+use crate::core::application_agent::{ApplicationAgent, SimpleApplicationAgent};
+use crate::core::bundlepack::{BundlePack, Constraint};
+use crate::core::helpers::{get_complete_digest, is_valid_service_name, parse_peer_url};
+use crate::core::journal::JournalEntry;
+use crate::core::peer::PeerType;
+use crate::core::status_reports::ReportedStatus;
+use crate::core::store::BundleStore;
+use crate::routing::static_routing::StaticRouteEntry;
+use crate::routing::CommandError;
+use crate::{
+    peers_add, peers_remove, routing_cmd, routing_get_data, routing_replace, DtnPeer, CLAS, CONFIG,
+    DTNCORE, PEERS, STATS, STATUS_REPORTS, STORE,
+};
+use async_trait::async_trait;
+use axum::extract::{self, FromRequest, Path, Query, RequestParts};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post, MethodRouter};
+use axum::{Json, Router};
+use bp7::administrative_record::TRANSMISSION_CANCELED;
+use bp7::EndpointID;
+use http::StatusCode;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+
+/*
+    Versioned JSON REST API.
+
+    All resources are served below `/api/v1` and always answer with JSON documents, errors
+    included. Access is controlled by the same scopes as the legacy routes, see `auth`.
+
+    The OpenAPI description of this interface is served at `/api/v1/openapi.json`.
+*/
+
+/// Default number of items returned by paginated listings.
+const DEFAULT_PAGE_LIMIT: usize = 100;
+/// Upper bound for the number of items returned by paginated listings.
+const MAX_PAGE_LIMIT: usize = 1000;
+
+/// Structured error body returned by all `/api/v1` resources.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiError {
+    /// HTTP status code of the response
+    pub status: u16,
+    /// Short machine readable error kind, e.g., `not_found`
+    pub error: String,
+    /// Human readable description of the problem
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        let error = status
+            .canonical_reason()
+            .unwrap_or("error")
+            .to_lowercase()
+            .replace(' ', "_");
+        ApiError {
+            status: status.as_u16(),
+            error,
+            message: message.into(),
+        }
+    }
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, message)
+    }
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, message)
+    }
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, Json(self)).into_response()
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+impl From<AuthError> for ApiError {
+    fn from(err: AuthError) -> Self {
+        ApiError::new(err.status(), err.to_string())
+    }
+}
+
+/// Extractor enforcing the access requirement `R`, answers with a structured error.
+struct Access<R>(Grant, PhantomData<R>);
+
+#[async_trait]
+impl<B, R> FromRequest<B> for Access<R>
+where
+    B: Send,
+    R: Requirement,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let grant = Require::<R>::from_request(req).await?.into_grant();
+        Ok(Access(grant, PhantomData))
+    }
+}
+
+fn check_receive(grant: &Grant, eid: &EndpointID) -> ApiResult<()> {
+    if grant.may_receive(&eid.to_string()) {
+        Ok(())
+    } else {
+        Err(AuthError::Forbidden.into())
+    }
+}
+
+// Begin of API resources
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NodeResource {
+    pub node_id: String,
+    pub version: String,
+    pub routing: String,
+    pub store: String,
+    pub peers: usize,
+    pub bundles: usize,
+    /// Digest over all known bundle IDs
+    pub bundles_digest: String,
+    pub endpoints: usize,
+    pub clas: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Page<T> {
+    /// Total number of items available
+    pub total: usize,
+    /// Index of the first returned item
+    pub offset: usize,
+    /// Maximum number of items requested
+    pub limit: usize,
+    pub items: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BundleListParams {
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+    /// Only list bundles whose source or destination contains this string
+    pub addr: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SentBundle {
+    pub bid: String,
+    pub destination: String,
+    pub size: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NewPeer {
+    /// Peer connection URL, e.g., `mtcp://192.168.2.1:2342/node2`
+    pub url: String,
+    /// Either `static` or `dynamic` (default)
+    #[serde(default)]
+    pub con_type: Option<String>,
+}
+
+impl TryFrom<NewPeer> for DtnPeer {
+    type Error = ApiError;
+
+    fn try_from(new_peer: NewPeer) -> Result<Self, Self::Error> {
+        let mut peer = parse_peer_url(&new_peer.url)
+            .map_err(|err| ApiError::bad_request(format!("malformed peer URL: {}", err)))?;
+        if let Some(con_type) = new_peer.con_type {
+            // scheduled peers are only added by the contact plan
+            peer.con_type = PeerType::try_from(con_type.as_str())
+                .ok()
+                .filter(|con_type| *con_type != PeerType::Scheduled)
+                .ok_or_else(|| ApiError::bad_request("peer type must be static or dynamic"))?;
+        }
+        Ok(peer)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EndpointResource {
+    pub eid: String,
+    pub singleton: bool,
+    /// true if an application agent is currently connected via WebSocket
+    pub active: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NewEndpoint {
+    /// Local service name (e.g., `incoming`) or fully qualified endpoint ID
+    pub endpoint: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClaResource {
+    pub name: String,
+    pub port: u16,
+    pub accepting: bool,
+    pub settings: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RoutingResource {
+    pub agent: String,
+    pub strategy: String,
+    pub settings: HashMap<String, HashMap<String, String>>,
+    pub available: Vec<String>,
+}
+
+/// Replacement of the active routing agent, settings are given per agent
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct RoutingChange {
+    pub strategy: String,
+    #[serde(default)]
+    pub settings: HashMap<String, HashMap<String, String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RoutingCommand {
+    pub command: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RoutingDataParams {
+    pub p: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdmissionRuleParams {
+    /// Index the new rule is inserted at, appended by default
+    pub position: Option<usize>,
+}
+
+// End of API resources
+
+fn endpoint_from_str(endpoint: &str) -> ApiResult<EndpointID> {
+    if is_valid_service_name(endpoint) {
+        // without url scheme assume a local DTN service name
+        CONFIG
+            .lock()
+            .host_eid
+            .new_endpoint(endpoint)
+            .map_err(|err| ApiError::bad_request(err.to_string()))
+    } else {
+        EndpointID::try_from(endpoint).map_err(|_| {
+            ApiError::bad_request(
+                "malformed endpoint, only alphanumeric strings or endpoint URIs are allowed",
+            )
+        })
+    }
+}
+
+async fn get_node(_: Access<StatusAccess>) -> Json<NodeResource> {
+    let (node_id, routing, store) = {
+        let cfg = CONFIG.lock();
+        (
+            cfg.host_eid.to_string(),
+            cfg.routing.clone(),
+            cfg.db.clone(),
+        )
+    };
+    let peers = PEERS.lock().len();
+    let bundles = DTNCORE.lock().bundle_count();
+    let endpoints = DTNCORE.lock().endpoints.len();
+    Json(NodeResource {
+        node_id,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        routing,
+        store,
+        peers,
+        bundles,
+        bundles_digest: get_complete_digest(),
+        endpoints,
+        clas: crate::cla_names(),
+    })
+}
+
+async fn get_stats(_: Access<StatusAccess>) -> Json<crate::core::DtnStatistics> {
+    STATS.lock().update_node_stats();
+    let stats = STATS.lock().clone();
+    Json(stats)
+}
+
+async fn list_bundles(
+    _: Access<StatusAccess>,
+    Query(params): Query<BundleListParams>,
+) -> ApiResult<Json<Page<BundlePack>>> {
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+    if limit == 0 || limit > MAX_PAGE_LIMIT {
+        return Err(ApiError::bad_request(format!(
+            "limit must be between 1 and {}",
+            MAX_PAGE_LIMIT
+        )));
+    }
+    let offset = params.offset.unwrap_or(0);
+
+    let mut bundles: Vec<BundlePack> = (*STORE.lock())
+        .bundles()
+        .into_iter()
+        .filter(|bp| !bp.has_constraint(Constraint::Deleted))
+        .filter(|bp| {
+            params.addr.as_ref().is_none_or(|addr| {
+                bp.source.to_string().contains(addr) || bp.destination.to_string().contains(addr)
+            })
+        })
+        .collect();
+    // stable order for pagination
+    bundles.sort_unstable_by(|a, b| {
+        a.creation_time
+            .cmp(&b.creation_time)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    let total = bundles.len();
+    let items = bundles.into_iter().skip(offset).take(limit).collect();
+    Ok(Json(Page {
+        total,
+        offset,
+        limit,
+        items,
+    }))
+}
+
+async fn send_bundle(
+    _: Access<SendAccess>,
+    Query(params): Query<Vec<(String, String)>>,
+    body: bytes::Bytes,
+) -> ApiResult<(StatusCode, Json<SentBundle>)> {
+    let size = body.len();
+    let bndl = new_bundle_from_params(&params, body.to_vec()).map_err(ApiError::bad_request)?;
+    let sent = SentBundle {
+        bid: bndl.id(),
+        destination: bndl.primary.destination.to_string(),
+        size,
+    };
+    debug!("Sending bundle {} to {}", sent.bid, sent.destination);
+    crate::core::processing::send_bundle(bndl).await;
+    STATS.lock().node.bundles.bundles_created += 1;
+    Ok((StatusCode::CREATED, Json(sent)))
+}
+
+async fn get_bundle(
+    _: Access<StatusAccess>,
+    Path(bid): Path<String>,
+) -> ApiResult<Json<BundlePack>> {
+    (*STORE.lock())
+        .get_metadata(&bid)
+        .filter(|bp| !bp.has_constraint(Constraint::Deleted))
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("bundle {} not found", bid)))
+}
+
+async fn get_bundle_cbor(_: Access<StatusAccess>, Path(bid): Path<String>) -> ApiResult<Vec<u8>> {
+    let bundle = (*STORE.lock()).get_bundle(&bid);
+    bundle
+        .map(|mut bndl| bndl.to_cbor())
+        .ok_or_else(|| ApiError::not_found(format!("bundle {} not found", bid)))
+}
+
+async fn delete_bundle(_: Access<AdminAccess>, Path(bid): Path<String>) -> ApiResult<StatusCode> {
+    info!("Requested deleting of bundle {}", bid);
+    if !crate::store_has_item(&bid) {
+        return Err(ApiError::not_found(format!("bundle {} not found", bid)));
+    }
+    crate::core::processing::remove(&bid, TRANSMISSION_CANCELED)
+        .await
+        .map_err(|err| ApiError::internal(err.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_bundle_journal(
+    _: Access<StatusAccess>,
+    Path(bid): Path<String>,
+) -> ApiResult<Json<Vec<JournalEntry>>> {
+    (*STORE.lock())
+        .get_metadata(&bid)
+        .map(|bp| Json(bp.journal))
+        .ok_or_else(|| ApiError::not_found(format!("bundle {} not found", bid)))
+}
+
+async fn get_bundle_reports(
+    _: Access<StatusAccess>,
+    Path(bid): Path<String>,
+) -> ApiResult<Json<Vec<ReportedStatus>>> {
+    (*STATUS_REPORTS.lock())
+        .timeline(&bid)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("no status reports for bundle {}", bid)))
+}
+
+async fn list_peers(_: Access<StatusAccess>) -> Json<Vec<DtnPeer>> {
+    Json(PEERS.lock().values().cloned().collect())
+}
+
+async fn add_peer(
+    _: Access<AdminAccess>,
+    extract::Json(new_peer): extract::Json<NewPeer>,
+) -> ApiResult<(StatusCode, Json<DtnPeer>)> {
+    let peer = DtnPeer::try_from(new_peer)?;
+    let status = if peers_add(peer.clone()) {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    Ok((status, Json(peer)))
+}
+
+async fn get_peer(_: Access<StatusAccess>, Path(node): Path<String>) -> ApiResult<Json<DtnPeer>> {
+    PEERS
+        .lock()
+        .get(&node)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("peer {} not found", node)))
+}
+
+async fn delete_peer(_: Access<AdminAccess>, Path(node): Path<String>) -> ApiResult<StatusCode> {
+    if !crate::peers_known(&node) {
+        return Err(ApiError::not_found(format!("peer {} not found", node)));
+    }
+    peers_remove(&node);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_endpoints(_: Access<StatusAccess>) -> Json<Vec<EndpointResource>> {
+    let endpoints = DTNCORE
+        .lock()
+        .endpoints
+        .iter()
+        .map(|aa| EndpointResource {
+            eid: aa.eid().to_string(),
+            singleton: !aa.eid().is_non_singleton(),
+            active: aa.delivery_addr().is_some(),
+        })
+        .collect();
+    Json(endpoints)
+}
+
+async fn add_endpoint(
+    Access(grant, _): Access<ClientAccess>,
+    extract::Json(new_endpoint): extract::Json<NewEndpoint>,
+) -> ApiResult<(StatusCode, Json<EndpointResource>)> {
+    let eid = endpoint_from_str(&new_endpoint.endpoint)?;
+    check_receive(&grant, &eid)?;
+    let status = if DTNCORE.lock().is_in_endpoints(&eid) {
+        StatusCode::OK
+    } else {
+        StatusCode::CREATED
+    };
+    DTNCORE
+        .lock()
+        .register_application_agent(SimpleApplicationAgent::with(eid.clone()).into());
+    Ok((
+        status,
+        Json(EndpointResource {
+            eid: eid.to_string(),
+            singleton: !eid.is_non_singleton(),
+            active: false,
+        }),
+    ))
+}
+
+async fn delete_endpoint(
+    Access(grant, _): Access<ClientAccess>,
+    Path(endpoint): Path<String>,
+) -> ApiResult<StatusCode> {
+    let eid = endpoint_from_str(&endpoint)?;
+    check_receive(&grant, &eid)?;
+    if !DTNCORE.lock().is_in_endpoints(&eid) {
+        return Err(ApiError::not_found(format!("endpoint {} not found", eid)));
+    }
+    DTNCORE
+        .lock()
+        .unregister_application_agent(SimpleApplicationAgent::with(eid).into());
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_clas(_: Access<StatusAccess>) -> Json<Vec<ClaResource>> {
+    let clas = (*CLAS.lock())
+        .iter()
+        .map(|cla| ClaResource {
+            name: cla.name().to_string(),
+            port: cla.port(),
+            accepting: cla.accepting(),
+            settings: cla.local_settings().unwrap_or_default(),
+        })
+        .collect();
+    Json(clas)
+}
+
+async fn get_routing(_: Access<StatusAccess>) -> Json<RoutingResource> {
+    Json(routing_resource())
+}
+
+async fn put_routing(
+    _: Access<AdminAccess>,
+    extract::Json(change): extract::Json<RoutingChange>,
+) -> ApiResult<Json<RoutingResource>> {
+    info!("Replacing routing agent: {:?}", change);
+    routing_replace(&change.strategy, change.settings.into_iter().collect())
+        .await
+        .map_err(|err| ApiError::bad_request(format!("{:#}", err)))?;
+    Ok(Json(routing_resource()))
+}
+
+fn routing_resource() -> RoutingResource {
+    let agent = DTNCORE.lock().routing_agent.to_string();
+    let (strategy, settings) = {
+        let cfg = CONFIG.lock();
+        (
+            cfg.routing.clone(),
+            cfg.routing_settings
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        )
+    };
+    RoutingResource {
+        agent,
+        strategy,
+        settings,
+        available: crate::routing::routing_algorithms()
+            .iter()
+            .map(|r| r.to_string())
+            .chain(crate::routing::plugin::routing_plugins())
+            .collect(),
+    }
+}
+
+/// Commands rejected by the routing agent are client errors
+fn routing_cmd_error(err: anyhow::Error) -> ApiError {
+    match err.downcast_ref::<CommandError>() {
+        Some(CommandError::Invalid(msg)) => ApiError::bad_request(msg.clone()),
+        Some(CommandError::NotFound(msg)) => ApiError::not_found(msg.clone()),
+        Some(CommandError::Conflict(msg)) => ApiError::new(StatusCode::CONFLICT, msg.clone()),
+        None => ApiError::internal(err.to_string()),
+    }
+}
+
+async fn post_routing_command(
+    _: Access<AdminAccess>,
+    extract::Json(cmd): extract::Json<RoutingCommand>,
+) -> ApiResult<StatusCode> {
+    routing_cmd(cmd.command).await.map_err(routing_cmd_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_routing_data(
+    _: Access<ReadAccess>,
+    Query(params): Query<RoutingDataParams>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let data = routing_get_data(params.p.unwrap_or_default())
+        .await
+        .map_err(|err| ApiError::internal(err.to_string()))?;
+    Ok(Json(json!({ "data": data })))
+}
+
+/// Prefix addressing the static routing agent, also as part of a composite agent
+fn static_routing_prefix() -> ApiResult<&'static str> {
+    match CONFIG.lock().routing.as_str() {
+        "static" => Ok(""),
+        "composite" => Ok("static "),
+        _ => Err(static_routing_inactive()),
+    }
+}
+
+fn static_routing_inactive() -> ApiError {
+    ApiError::new(StatusCode::CONFLICT, "static routing is not active")
+}
+
+async fn static_routes() -> ApiResult<Vec<StaticRouteEntry>> {
+    let prefix = static_routing_prefix()?;
+    let data = routing_get_data(format!("{}json", prefix))
+        .await
+        .map_err(|err| ApiError::internal(err.to_string()))?;
+    serde_json::from_str(&data).map_err(|_| static_routing_inactive())
+}
+
+/// Passes a command to the static routing agent, returns the resulting routes
+async fn static_routing_command(cmd: String) -> ApiResult<Vec<StaticRouteEntry>> {
+    let prefix = static_routing_prefix()?;
+    routing_cmd(format!("{}{}", prefix, cmd))
+        .await
+        .map_err(routing_cmd_error)?;
+    static_routes().await
+}
+
+async fn list_static_routes(_: Access<StatusAccess>) -> ApiResult<Json<Vec<StaticRouteEntry>>> {
+    Ok(Json(static_routes().await?))
+}
+
+async fn add_static_route(
+    _: Access<AdminAccess>,
+    extract::Json(route): extract::Json<StaticRouteEntry>,
+) -> ApiResult<(StatusCode, Json<Vec<StaticRouteEntry>>)> {
+    route.validate().map_err(ApiError::bad_request)?;
+    let routes = static_routing_command(format!("insert {}", route.to_line())).await?;
+    Ok((StatusCode::CREATED, Json(routes)))
+}
+
+async fn delete_static_route(
+    _: Access<AdminAccess>,
+    Path(idx): Path<u16>,
+) -> ApiResult<Json<Vec<StaticRouteEntry>>> {
+    Ok(Json(static_routing_command(format!("del {}", idx)).await?))
+}
+
+async fn reload_static_routes(_: Access<AdminAccess>) -> ApiResult<Json<Vec<StaticRouteEntry>>> {
+    // the agent keeps its routes on invalid files and reports why
+    Ok(Json(static_routing_command("reload".into()).await?))
+}
+
+async fn get_admission(_: Access<StatusAccess>) -> Json<AdmissionPolicy> {
+    Json(CONFIG.lock().admission.clone())
+}
+
+fn admission_error(err: anyhow::Error) -> ApiError {
+    ApiError::internal(format!("failed to save admission policy: {:#}", err))
+}
+
+/// Changes the admission policy and applies it to known peers and sessions
+async fn change_admission<T>(
+    change: impl FnOnce(&mut AdmissionPolicy) -> Option<T>,
+) -> ApiResult<Option<T>> {
+    let result = update_policy(change).map_err(admission_error)?;
+    if result.is_some() {
+        enforce_policy().await;
+    }
+    Ok(result)
+}
+
+async fn put_admission(
+    _: Access<AdminAccess>,
+    extract::Json(policy): extract::Json<AdmissionPolicy>,
+) -> ApiResult<Json<AdmissionPolicy>> {
+    info!("Replacing admission policy: {:?}", policy);
+    change_admission(|current| {
+        *current = policy;
+        Some(current.clone())
+    })
+    .await?
+    .map(Json)
+    .ok_or_else(|| ApiError::internal("admission policy not updated"))
+}
+
+async fn add_admission_rule(
+    _: Access<AdminAccess>,
+    Query(params): Query<AdmissionRuleParams>,
+    extract::Json(rule): extract::Json<AdmissionRule>,
+) -> ApiResult<(StatusCode, Json<AdmissionPolicy>)> {
+    info!("Adding admission rule: {:?}", rule);
+    change_admission(|policy| {
+        let position = params.position.unwrap_or(policy.rules.len());
+        if position > policy.rules.len() {
+            return None;
+        }
+        policy.rules.insert(position, rule);
+        Some(policy.clone())
+    })
+    .await?
+    .map(|policy| (StatusCode::CREATED, Json(policy)))
+    .ok_or_else(|| ApiError::bad_request("position out of range"))
+}
+
+async fn delete_admission_rule(
+    _: Access<AdminAccess>,
+    Path(index): Path<usize>,
+) -> ApiResult<Json<AdmissionPolicy>> {
+    change_admission(|policy| {
+        if index >= policy.rules.len() {
+            return None;
+        }
+        let rule = policy.rules.remove(index);
+        info!("Removed admission rule: {:?}", rule);
+        Some(policy.clone())
+    })
+    .await?
+    .map(Json)
+    .ok_or_else(|| ApiError::not_found(format!("admission rule {} not found", index)))
+}
+
+async fn get_openapi() -> Json<serde_json::Value> {
+    Json(openapi())
+}
+
+/// All `/api/v1` resources, every path must be described in [`openapi`]
+fn routes() -> Vec<(&'static str, MethodRouter)> {
+    vec![
+        ("/openapi.json", get(get_openapi)),
+        ("/node", get(get_node)),
+        ("/stats", get(get_stats)),
+        ("/bundles", get(list_bundles).post(send_bundle)),
+        ("/bundles/:bid", get(get_bundle).delete(delete_bundle)),
+        ("/bundles/:bid/cbor", get(get_bundle_cbor)),
+        ("/bundles/:bid/journal", get(get_bundle_journal)),
+        ("/bundles/:bid/reports", get(get_bundle_reports)),
+        ("/peers", get(list_peers).post(add_peer)),
+        ("/peers/:node", get(get_peer).delete(delete_peer)),
+        ("/endpoints", get(list_endpoints).post(add_endpoint)),
+        ("/endpoints/:eid", delete(delete_endpoint)),
+        ("/clas", get(list_clas)),
+        ("/routing", get(get_routing).put(put_routing)),
+        ("/routing/commands", post(post_routing_command)),
+        ("/routing/data", get(get_routing_data)),
+        (
+            "/routing/static/routes",
+            get(list_static_routes).post(add_static_route),
+        ),
+        ("/routing/static/routes/:idx", delete(delete_static_route)),
+        ("/routing/static/reload", post(reload_static_routes)),
+        ("/admission", get(get_admission).put(put_admission)),
+        ("/admission/rules", post(add_admission_rule)),
+        ("/admission/rules/:index", delete(delete_admission_rule)),
+    ]
+}
+
+/// Builds the router for all `/api/v1` resources.
+pub fn router() -> Router {
+    routes()
+        .into_iter()
+        .fold(Router::new(), |router, (path, route)| {
+            router.route(path, route)
+        })
+}
+
+/// Paths served by [`router`] in OpenAPI notation, e.g., `/bundles/{bid}`
+pub fn paths() -> Vec<String> {
+    routes()
+        .into_iter()
+        .map(|(path, _)| {
+            path.split('/')
+                .map(|segment| match segment.strip_prefix(':') {
+                    Some(param) => format!("{{{}}}", param),
+                    None => segment.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .collect()
+}
+
+fn error_responses() -> serde_json::Value {
+    let err = json!({
+        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+    });
+    let mut desc_bad = err.clone();
+    desc_bad["description"] = json!("Invalid request");
+    let mut desc_unauthorized = err.clone();
+    desc_unauthorized["description"] = json!("Missing or invalid access token");
+    let mut desc_forbidden = err.clone();
+    desc_forbidden["description"] = json!("Access token lacks the required scope");
+    let mut desc_missing = err;
+    desc_missing["description"] = json!("Resource not found");
+    json!({ "400": desc_bad, "401": desc_unauthorized, "403": desc_forbidden, "404": desc_missing })
+}
+
+fn json_response(description: &str, schema: serde_json::Value) -> serde_json::Value {
+    json!({
+        "description": description,
+        "content": { "application/json": { "schema": schema } }
+    })
+}
+
+fn schema_ref(name: &str) -> serde_json::Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+fn path_param(name: &str, description: &str) -> serde_json::Value {
+    json!({
+        "name": name,
+        "in": "path",
+        "required": true,
+        "description": description,
+        "schema": { "type": "string" }
+    })
+}
+
+fn query_param(name: &str, description: &str, ty: &str) -> serde_json::Value {
+    json!({
+        "name": name,
+        "in": "query",
+        "required": false,
+        "description": description,
+        "schema": { "type": ty }
+    })
+}
+
+/// Generates the OpenAPI 3 description of the `/api/v1` interface.
+pub fn openapi() -> serde_json::Value {
+    let errors = error_responses();
+    let with_errors = |mut responses: serde_json::Value| {
+        for (code, resp) in errors.as_object().unwrap() {
+            responses[code] = resp.clone();
+        }
+        responses
+    };
+    let bid_param = path_param("bid", "percent-encoded bundle ID");
+    let routes = json!({ "type": "array", "items": schema_ref("StaticRoute") });
+    let conflict = json!({
+        "description": "Static routing is not active or the route exists",
+        "content": { "application/json": { "schema": schema_ref("Error") } }
+    });
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "dtn7 REST API",
+            "version": "1",
+            "description": "Versioned JSON interface of the dtn7 daemon. Remote clients authenticate with bearer tokens, requests from localhost are trusted by default.",
+            "x-dtn7-version": env!("CARGO_PKG_VERSION")
+        },
+        "servers": [ { "url": "/api/v1" } ],
+        "security": [ {}, { "bearerAuth": [] } ],
+        "paths": {
+            "/openapi.json": { "get": {
+                "summary": "This OpenAPI description",
+                "responses": { "200": json_response("OpenAPI 3 document", json!({ "type": "object" })) }
+            }},
+            "/node": { "get": {
+                "summary": "General information about this node",
+                "responses": { "200": json_response("Node information", schema_ref("Node")) }
+            }},
+            "/stats": { "get": {
+                "summary": "Bundle and node statistics",
+                "responses": { "200": json_response("Statistics", json!({ "type": "object" })) }
+            }},
+            "/bundles": {
+                "get": {
+                    "summary": "Paginated list of bundles in the store",
+                    "parameters": [
+                        query_param("offset", "index of the first bundle to return", "integer"),
+                        query_param("limit", "maximum number of bundles to return (1-1000, default 100)", "integer"),
+                        query_param("addr", "only bundles with matching source or destination", "string")
+                    ],
+                    "responses": with_errors(json!({ "200": json_response("Page of bundles", schema_ref("BundlePage")) }))
+                },
+                "post": {
+                    "summary": "Send the request body as payload of a new bundle",
+                    "parameters": [
+                        query_param("dst", "destination endpoint ID", "string"),
+                        query_param("lifetime", "bundle lifetime as humantime, e.g. 5m", "string"),
+                        query_param("flags", "bundle processing control flags", "integer"),
+                        query_param("report", "requested status reports, e.g. reception,forwarding,delivery,deletion", "string"),
+                        query_param("report_to", "endpoint ID receiving status reports", "string"),
+                        query_param("dnf", "bundle must not be fragmented, default true", "boolean"),
+                        query_param("ack", "request an application acknowledgement", "boolean"),
+                        query_param("hop_limit", "hop limit, default 32", "integer"),
+                        query_param("age", "add a bundle age block", "boolean"),
+                        query_param("copies", "copy budget for spray and wait routing", "integer"),
+                        query_param("block", "additional extension block as <TYPE>:<HEXDATA>, repeatable", "string"),
+                        query_param("crc", "CRC type of all blocks: none, 16 or 32", "string")
+                    ],
+                    "requestBody": { "content": { "application/octet-stream": { "schema": { "type": "string", "format": "binary" } } } },
+                    "responses": with_errors(json!({ "201": json_response("Bundle sent", schema_ref("SentBundle")) }))
+                }
+            },
+            "/bundles/{bid}": {
+                "get": {
+                    "summary": "Metadata of a single bundle",
+                    "parameters": [ bid_param.clone() ],
+                    "responses": with_errors(json!({ "200": json_response("Bundle metadata", schema_ref("Bundle")) }))
+                },
+                "delete": {
+                    "summary": "Remove a bundle from the store",
+                    "parameters": [ bid_param.clone() ],
+                    "responses": with_errors(json!({ "204": { "description": "Bundle deleted" } }))
+                }
+            },
+            "/bundles/{bid}/cbor": { "get": {
+                "summary": "Raw CBOR encoded bundle",
+                "parameters": [ bid_param.clone() ],
+                "responses": with_errors(json!({ "200": { "description": "Bundle", "content": { "application/octet-stream": { "schema": { "type": "string", "format": "binary" } } } } }))
+            }},
+            "/bundles/{bid}/journal": { "get": {
+                "summary": "Lifecycle events of a bundle at this node, also kept for deleted bundles",
+                "parameters": [ bid_param.clone() ],
+                "responses": with_errors(json!({ "200": json_response("Journal entries", json!({ "type": "array", "items": schema_ref("JournalEntry") })) }))
+            }},
+            "/bundles/{bid}/reports": { "get": {
+                "summary": "Delivery timeline from received status reports of an own bundle",
+                "parameters": [ bid_param ],
+                "responses": with_errors(json!({ "200": json_response("Reported statuses", json!({ "type": "array", "items": schema_ref("ReportedStatus") })) }))
+            }},
+            "/peers": {
+                "get": {
+                    "summary": "Known peers",
+                    "responses": { "200": json_response("Peers", json!({ "type": "array", "items": schema_ref("Peer") })) }
+                },
+                "post": {
+                    "summary": "Add or update a peer",
+                    "requestBody": { "content": { "application/json": { "schema": schema_ref("NewPeer") } } },
+                    "responses": with_errors(json!({
+                        "200": json_response("Existing peer updated", schema_ref("Peer")),
+                        "201": json_response("New peer added", schema_ref("Peer"))
+                    }))
+                }
+            },
+            "/peers/{node}": {
+                "get": {
+                    "summary": "Single peer by node name",
+                    "parameters": [ path_param("node", "node name of the peer") ],
+                    "responses": with_errors(json!({ "200": json_response("Peer", schema_ref("Peer")) }))
+                },
+                "delete": {
+                    "summary": "Remove a peer",
+                    "parameters": [ path_param("node", "node name of the peer") ],
+                    "responses": with_errors(json!({ "204": { "description": "Peer removed" } }))
+                }
+            },
+            "/endpoints": {
+                "get": {
+                    "summary": "Registered application endpoints",
+                    "responses": { "200": json_response("Endpoints", json!({ "type": "array", "items": schema_ref("Endpoint") })) }
+                },
+                "post": {
+                    "summary": "Register an application endpoint",
+                    "requestBody": { "content": { "application/json": { "schema": schema_ref("NewEndpoint") } } },
+                    "responses": with_errors(json!({
+                        "200": json_response("Endpoint already registered", schema_ref("Endpoint")),
+                        "201": json_response("Endpoint registered", schema_ref("Endpoint"))
+                    }))
+                }
+            },
+            "/endpoints/{eid}": { "delete": {
+                "summary": "Unregister an application endpoint",
+                "parameters": [ path_param("eid", "service name or percent-encoded endpoint ID") ],
+                "responses": with_errors(json!({ "204": { "description": "Endpoint unregistered" } }))
+            }},
+            "/clas": { "get": {
+                "summary": "Active convergence layer agents",
+                "responses": { "200": json_response("CLAs", json!({ "type": "array", "items": schema_ref("Cla") })) }
+            }},
+            "/routing": { "get": {
+                "summary": "Active routing agent and its settings",
+                "responses": { "200": json_response("Routing", schema_ref("Routing")) }
+            }, "put": {
+                "summary": "Replace the routing agent at runtime",
+                "description": "The new agent learns about current peers and waiting bundles, then the old agent is shut down.",
+                "requestBody": { "content": { "application/json": { "schema": schema_ref("RoutingChange") } } },
+                "responses": with_errors(json!({ "200": json_response("New routing agent", schema_ref("Routing")) }))
+            }},
+            "/routing/commands": { "post": {
+                "summary": "Send a command to the routing agent",
+                "requestBody": { "content": { "application/json": { "schema": schema_ref("RoutingCommand") } } },
+                "responses": with_errors(json!({ "204": { "description": "Command executed by the routing agent" } }))
+            }},
+            "/routing/data": { "get": {
+                "summary": "Internal data of the routing agent",
+                "parameters": [ query_param("p", "optional parameter passed to the routing agent", "string") ],
+                "responses": with_errors(json!({ "200": json_response("Routing data", json!({ "type": "object", "properties": { "data": { "type": "string" } } })) }))
+            }},
+            "/routing/static/routes": {
+                "get": {
+                    "summary": "Route table of the static routing agent",
+                    "responses": with_errors(json!({ "200": json_response("Static routes", routes.clone()), "409": conflict.clone() }))
+                },
+                "post": {
+                    "summary": "Add a static route, not saved to the routes file",
+                    "requestBody": { "content": { "application/json": { "schema": schema_ref("StaticRoute") } } },
+                    "responses": with_errors(json!({ "201": json_response("New static routes", routes.clone()), "409": conflict.clone() }))
+                }
+            },
+            "/routing/static/routes/{idx}": { "delete": {
+                "summary": "Remove a static route",
+                "parameters": [ path_param("idx", "index of the route") ],
+                "responses": with_errors(json!({ "200": json_response("New static routes", routes.clone()), "409": conflict.clone() }))
+            }},
+            "/routing/static/reload": { "post": {
+                "summary": "Replace the static routes with those of the routes file if it is valid",
+                "responses": with_errors(json!({ "200": json_response("New static routes", routes.clone()), "409": conflict.clone() }))
+            }},
+            "/admission": {
+                "get": {
+                    "summary": "Admission policy for peers and received bundles",
+                    "responses": { "200": json_response("Admission policy", schema_ref("AdmissionPolicy")) }
+                },
+                "put": {
+                    "summary": "Replace the admission policy, saved to the config file",
+                    "requestBody": { "content": { "application/json": { "schema": schema_ref("AdmissionPolicy") } } },
+                    "responses": with_errors(json!({ "200": json_response("New admission policy", schema_ref("AdmissionPolicy")) }))
+                }
+            },
+            "/admission/rules": { "post": {
+                "summary": "Add an admission rule, saved to the config file",
+                "parameters": [ query_param("position", "index the rule is inserted at, appended by default", "integer") ],
+                "requestBody": { "content": { "application/json": { "schema": schema_ref("AdmissionRule") } } },
+                "responses": with_errors(json!({ "201": json_response("New admission policy", schema_ref("AdmissionPolicy")) }))
+            }},
+            "/admission/rules/{index}": { "delete": {
+                "summary": "Remove an admission rule, saved to the config file",
+                "parameters": [ path_param("index", "index of the rule") ],
+                "responses": with_errors(json!({ "200": json_response("New admission policy", schema_ref("AdmissionPolicy")) }))
+            }}
+        },
+        "components": {
+          "securitySchemes": {
+            "bearerAuth": { "type": "http", "scheme": "bearer" }
+          },
+          "schemas": {
+            "Error": {
+                "type": "object",
+                "required": ["status", "error", "message"],
+                "properties": {
+                    "status": { "type": "integer" },
+                    "error": { "type": "string" },
+                    "message": { "type": "string" }
+                }
+            },
+            "Node": {
+                "type": "object",
+                "properties": {
+                    "node_id": { "type": "string" },
+                    "version": { "type": "string" },
+                    "routing": { "type": "string" },
+                    "store": { "type": "string" },
+                    "peers": { "type": "integer" },
+                    "bundles": { "type": "integer" },
+                    "bundles_digest": { "type": "string" },
+                    "endpoints": { "type": "integer" },
+                    "clas": { "type": "array", "items": { "type": "string" } }
+                }
+            },
+            "Bundle": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string" },
+                    "source": { "type": "string" },
+                    "destination": { "type": "string" },
+                    "received_time": { "type": "integer" },
+                    "creation_time": { "type": "integer" },
+                    "lifetime": { "type": "integer" },
+                    "administrative": { "type": "boolean" },
+                    "size": { "type": "integer" },
+                    "constraints": { "type": "array", "items": { "type": "string" } },
+                    "journal": { "type": "array", "items": schema_ref("JournalEntry") }
+                }
+            },
+            "BundlePage": {
+                "type": "object",
+                "properties": {
+                    "total": { "type": "integer" },
+                    "offset": { "type": "integer" },
+                    "limit": { "type": "integer" },
+                    "items": { "type": "array", "items": schema_ref("Bundle") }
+                }
+            },
+            "JournalEntry": {
+                "type": "object",
+                "required": ["timestamp", "count", "event"],
+                "properties": {
+                    "timestamp": { "type": "integer" },
+                    "count": { "type": "integer" },
+                    "event": { "type": "string", "enum": ["created", "received", "routed", "transfer_succeeded", "transfer_failed", "constraints", "delivered", "deleted"] }
+                },
+                "additionalProperties": true
+            },
+            "ReportedStatus": {
+                "type": "object",
+                "properties": {
+                    "status": { "type": "string", "enum": ["received", "forwarded", "delivered", "deleted"] },
+                    "reason": { "type": "string" },
+                    "node": { "type": "string" },
+                    "time": { "type": "integer", "nullable": true },
+                    "reported": { "type": "integer" }
+                }
+            },
+            "SentBundle": {
+                "type": "object",
+                "properties": {
+                    "bid": { "type": "string" },
+                    "destination": { "type": "string" },
+                    "size": { "type": "integer" }
+                }
+            },
+            "Peer": {
+                "type": "object",
+                "properties": {
+                    "eid": { "type": "string" },
+                    "addr": { "type": "object" },
+                    "con_type": { "type": "string", "enum": ["Static", "Dynamic", "Scheduled"] },
+                    "period": { "type": "object", "nullable": true },
+                    "cla_list": { "type": "array", "items": { "type": "array" } },
+                    "services": { "type": "object" },
+                    "last_contact": { "type": "integer" },
+                    "fails": { "type": "integer" },
+                    "metrics": schema_ref("PeerMetrics")
+                }
+            },
+            "PeerMetrics": {
+                "type": "object",
+                "properties": {
+                    "contacts": {
+                        "type": "object",
+                        "properties": {
+                            "count": { "type": "integer" },
+                            "current_start": { "type": "integer", "nullable": true },
+                            "last_duration": { "type": "integer", "nullable": true },
+                            "mean_duration": { "type": "number", "nullable": true },
+                            "total_duration": { "type": "integer" }
+                        }
+                    },
+                    "clas": { "type": "object", "additionalProperties": schema_ref("LinkMetrics") }
+                }
+            },
+            "LinkMetrics": {
+                "type": "object",
+                "properties": {
+                    "transfers_succeeded": { "type": "integer" },
+                    "transfers_failed": { "type": "integer" },
+                    "success_ratio": { "type": "number" },
+                    "bytes_sent": { "type": "integer" },
+                    "throughput": { "type": "number", "nullable": true },
+                    "rtt_ms": { "type": "number", "nullable": true },
+                    "last_transfer": { "type": "integer" }
+                }
+            },
+            "NewPeer": {
+                "type": "object",
+                "required": ["url"],
+                "properties": {
+                    "url": { "type": "string", "example": "mtcp://192.168.2.1:2342/node2" },
+                    "con_type": { "type": "string", "enum": ["static", "dynamic"] }
+                }
+            },
+            "Endpoint": {
+                "type": "object",
+                "properties": {
+                    "eid": { "type": "string" },
+                    "singleton": { "type": "boolean" },
+                    "active": { "type": "boolean" }
+                }
+            },
+            "NewEndpoint": {
+                "type": "object",
+                "required": ["endpoint"],
+                "properties": { "endpoint": { "type": "string", "example": "incoming" } }
+            },
+            "Cla": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "port": { "type": "integer" },
+                    "accepting": { "type": "boolean" },
+                    "settings": { "type": "object", "additionalProperties": { "type": "string" } }
+                }
+            },
+            "Routing": {
+                "type": "object",
+                "properties": {
+                    "agent": { "type": "string" },
+                    "strategy": { "type": "string" },
+                    "settings": { "type": "object" },
+                    "available": { "type": "array", "items": { "type": "string" } }
+                }
+            },
+            "AdmissionPolicy": {
+                "type": "object",
+                "properties": {
+                    "default": { "type": "string", "enum": ["allow", "deny"] },
+                    "rules": { "type": "array", "items": schema_ref("AdmissionRule") }
+                }
+            },
+            "AdmissionRule": {
+                "type": "object",
+                "required": ["action"],
+                "properties": {
+                    "action": { "type": "string", "enum": ["allow", "deny"] },
+                    "node": { "type": "string", "example": "node*" },
+                    "addr": { "type": "string", "example": "10.0.0.0/8" },
+                    "cla": { "type": "string", "example": "tcp" }
+                }
+            },
+            "StaticRoute": {
+                "type": "object",
+                "required": ["idx", "src", "dst", "via"],
+                "properties": {
+                    "idx": { "type": "integer" },
+                    "src": { "type": "string", "example": "*" },
+                    "dst": { "type": "string", "example": "dtn://gs*/**" },
+                    "via": { "type": "string", "example": "dtn://relay1/" },
+                    "metric": { "type": "integer", "description": "lower is preferred among matching routes" }
+                }
+            },
+            "RoutingChange": {
+                "type": "object",
+                "required": ["strategy"],
+                "properties": {
+                    "strategy": { "type": "string", "example": "sprayandwait" },
+                    "settings": {
+                        "type": "object",
+                        "additionalProperties": { "type": "object", "additionalProperties": { "type": "string" } },
+                        "example": { "sprayandwait": { "num_copies": "4" } }
+                    }
+                }
+            },
+            "RoutingCommand": {
+                "type": "object",
+                "required": ["command"],
+                "properties": { "command": { "type": "string", "example": "reload" } }
+            }
+        }}
+    })
+}
@@ -0,0 +1,402 @@
+use super::httpd::is_local_request;
+use crate::CONFIG;
+use async_trait::async_trait;
+use axum::extract::{FromRequest, RequestParts};
+use axum::response::{IntoResponse, Response};
+use base64::prelude::*;
+use glob_match::glob_match;
+use hmac::{Hmac, Mac};
+use http::{header, StatusCode};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::convert::{Infallible, TryFrom};
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+
+/*
+    Token based access control for the HTTP and WebSocket interfaces.
+
+    Clients authenticate with `Authorization: Bearer <token>`. A token is either one of the
+    static tokens from the `[auth]` section of the config or a token signed with the
+    configured HMAC secret (see `issue_token`). Requests from localhost are trusted as
+    before unless `trust-localhost` is disabled, `unsafe_httpd` still disables all checks.
+*/
+
+/// Permission granted to a token.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Scope {
+    /// Read node status, web UI and routing data
+    ReadStatus,
+    /// Send bundles
+    Send,
+    /// Register and receive on endpoints matching the glob pattern
+    Receive(String),
+    /// Full access including peer, routing and bundle management
+    Admin,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("unknown scope: {0}")]
+pub struct ParseScopeError(String);
+
+impl FromStr for Scope {
+    type Err = ParseScopeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read-status" => Ok(Scope::ReadStatus),
+            "send" => Ok(Scope::Send),
+            "receive" => Ok(Scope::Receive("**".into())),
+            "admin" => Ok(Scope::Admin),
+            _ => match s.strip_prefix("receive:") {
+                Some(pattern) if !pattern.is_empty() => Ok(Scope::Receive(pattern.into())),
+                _ => Err(ParseScopeError(s.into())),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for Scope {
+    type Error = ParseScopeError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Scope::ReadStatus => write!(f, "read-status"),
+            Scope::Send => write!(f, "send"),
+            Scope::Receive(pattern) => write!(f, "receive:{}", pattern),
+            Scope::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl From<Scope> for String {
+    fn from(scope: Scope) -> Self {
+        scope.to_string()
+    }
+}
+
+/// Statically configured access token
+#[derive(Debug, Clone)]
+pub struct StaticToken {
+    pub name: String,
+    pub token: String,
+    pub scopes: Vec<Scope>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    /// Require tokens for remote access
+    pub enabled: bool,
+    /// Grant full access to requests from localhost
+    pub trust_localhost: bool,
+    /// Keep web UI and `/status` resources readable without token
+    pub public_status: bool,
+    /// Secret used to verify signed tokens
+    pub hmac_secret: Option<String>,
+    pub tokens: Vec<StaticToken>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        AuthConfig {
+            enabled: false,
+            trust_localhost: true,
+            public_status: true,
+            hmac_secret: None,
+            tokens: Vec::new(),
+        }
+    }
+}
+
+/// Payload of a signed token
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TokenClaims {
+    /// Name of the token holder, only used for logging
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    pub scopes: Vec<Scope>,
+    /// Expiration as unix timestamp in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+}
+
+/// Permissions of the client issuing a request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Grant {
+    pub subject: Option<String>,
+    pub scopes: Vec<Scope>,
+}
+
+impl Grant {
+    /// Grant without any permissions
+    pub fn anonymous() -> Self {
+        Grant::default()
+    }
+    /// Grant with full access, e.g., for trusted local clients
+    pub fn full() -> Self {
+        Grant {
+            subject: None,
+            scopes: vec![Scope::Admin],
+        }
+    }
+    pub fn is_anonymous(&self) -> bool {
+        self.scopes.is_empty()
+    }
+    pub fn is_admin(&self) -> bool {
+        self.scopes.contains(&Scope::Admin)
+    }
+    /// Checks for the given scope, admin implies all other scopes.
+    pub fn has(&self, scope: &Scope) -> bool {
+        self.is_admin() || self.scopes.contains(scope)
+    }
+    pub fn may_send(&self) -> bool {
+        self.has(&Scope::Send)
+    }
+    /// Checks whether the client may register or receive on the given endpoint.
+    pub fn may_receive(&self, eid: &str) -> bool {
+        self.is_admin()
+            || self.scopes.iter().any(|s| match s {
+                Scope::Receive(pattern) => glob_match(pattern, eid),
+                _ => false,
+            })
+    }
+    fn may_receive_any(&self) -> bool {
+        self.is_admin() || self.scopes.iter().any(|s| matches!(s, Scope::Receive(_)))
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+fn hmac_sha256(secret: &str) -> Hmac<Sha256> {
+    Hmac::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length")
+}
+
+/// Checks the HMAC-SHA256 signature of a token
+fn signature_valid(secret: &str, payload: &str, signature: &[u8]) -> bool {
+    let mut mac = hmac_sha256(secret);
+    mac.update(payload.as_bytes());
+    mac.verify_slice(signature).is_ok()
+}
+
+/// Creates a signed token of the form `<base64url(json claims)>.<base64url(hmac-sha256)>`.
+///
+/// # Example
+///
+/// ```
+/// use dtn7::dtnd::auth::{issue_token, verify_token, AuthConfig, Scope, TokenClaims};
+///
+/// let claims = TokenClaims { sub: None, scopes: vec![Scope::Send], exp: None };
+/// let token = issue_token("secret", &claims);
+///
+/// let cfg = AuthConfig { enabled: true, hmac_secret: Some("secret".into()), ..Default::default() };
+/// assert!(verify_token(&cfg, &token).unwrap().may_send());
+/// ```
+pub fn issue_token(secret: &str, claims: &TokenClaims) -> String {
+    let payload = BASE64_URL_SAFE_NO_PAD
+        .encode(serde_json::to_vec(claims).expect("error encoding token claims"));
+    let mut mac = hmac_sha256(secret);
+    mac.update(payload.as_bytes());
+    let signature = mac.finalize().into_bytes();
+    format!("{}.{}", payload, BASE64_URL_SAFE_NO_PAD.encode(signature))
+}
+
+/// Verifies a token against the configured static tokens and HMAC secret.
+pub fn verify_token(cfg: &AuthConfig, token: &str) -> Option<Grant> {
+    if let Some(st) = cfg
+        .tokens
+        .iter()
+        .find(|st| constant_time_eq(st.token.as_bytes(), token.as_bytes()))
+    {
+        return Some(Grant {
+            subject: Some(st.name.clone()),
+            scopes: st.scopes.clone(),
+        });
+    }
+    let secret = cfg.hmac_secret.as_ref()?;
+    let (payload, signature) = token.split_once('.')?;
+    let signature = BASE64_URL_SAFE_NO_PAD.decode(signature).ok()?;
+    if !signature_valid(secret, payload, &signature) {
+        return None;
+    }
+    let claims: TokenClaims =
+        serde_json::from_slice(&BASE64_URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+    if let Some(exp) = claims.exp {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if now >= exp {
+            debug!("rejecting expired token of {:?}", claims.sub);
+            return None;
+        }
+    }
+    Some(Grant {
+        subject: claims.sub,
+        scopes: claims.scopes,
+    })
+}
+
+/// Determines the permissions of a request from its origin and bearer token.
+pub(crate) fn grant_for_request(extensions: &http::Extensions, headers: &http::HeaderMap) -> Grant {
+    let local = is_local_request(extensions);
+    let (cfg, unsafe_httpd) = {
+        let config = CONFIG.lock();
+        (config.auth.clone(), config.unsafe_httpd)
+    };
+    if unsafe_httpd || (local && (!cfg.enabled || cfg.trust_localhost)) {
+        return Grant::full();
+    }
+    if !cfg.enabled {
+        return Grant::anonymous();
+    }
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(|token| verify_token(&cfg, token.trim()))
+        .unwrap_or_else(Grant::anonymous)
+}
+
+#[async_trait]
+impl<B> FromRequest<B> for Grant
+where
+    B: Send,
+{
+    type Rejection = Infallible;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        if let Some(grant) = req.extensions().get::<Grant>() {
+            return Ok(grant.clone());
+        }
+        Ok(grant_for_request(req.extensions(), req.headers()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthError {
+    /// No valid token presented
+    Unauthorized,
+    /// Token lacks the required scope
+    Forbidden,
+}
+
+impl AuthError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AuthError::Unauthorized => StatusCode::UNAUTHORIZED,
+            AuthError::Forbidden => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::Unauthorized => write!(f, "a valid access token is required"),
+            AuthError::Forbidden => write!(f, "insufficient permissions for this resource"),
+        }
+    }
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        match self {
+            AuthError::Unauthorized => {
+                (self.status(), [(header::WWW_AUTHENTICATE, "Bearer")]).into_response()
+            }
+            AuthError::Forbidden => self.status().into_response(),
+        }
+    }
+}
+
+/// Access requirement of a group of resources.
+pub trait Requirement {
+    fn permits(grant: &Grant) -> bool;
+}
+
+/// Peer, routing and bundle management
+pub struct AdminAccess;
+/// Sending bundles
+pub struct SendAccess;
+/// Application clients that send or receive, finer checks happen per endpoint
+pub struct ClientAccess;
+/// Routing data and other internals, always requires `read-status`
+pub struct ReadAccess;
+/// Web UI and status resources, public unless `public-status` is disabled
+pub struct StatusAccess;
+
+impl Requirement for AdminAccess {
+    fn permits(grant: &Grant) -> bool {
+        grant.is_admin()
+    }
+}
+impl Requirement for SendAccess {
+    fn permits(grant: &Grant) -> bool {
+        grant.may_send()
+    }
+}
+impl Requirement for ClientAccess {
+    fn permits(grant: &Grant) -> bool {
+        grant.may_send() || grant.may_receive_any()
+    }
+}
+impl Requirement for ReadAccess {
+    fn permits(grant: &Grant) -> bool {
+        grant.has(&Scope::ReadStatus)
+    }
+}
+impl Requirement for StatusAccess {
+    fn permits(grant: &Grant) -> bool {
+        let cfg = &CONFIG.lock().auth;
+        !cfg.enabled || cfg.public_status || grant.has(&Scope::ReadStatus)
+    }
+}
+
+/// Extractor rejecting requests that do not fulfill the requirement `R`.
+///
+/// The resulting grant is stored in the request extensions for later extractors.
+pub struct Require<R>(pub Grant, PhantomData<R>);
+
+impl<R> Require<R> {
+    pub fn into_grant(self) -> Grant {
+        self.0
+    }
+}
+
+#[async_trait]
+impl<B, R> FromRequest<B> for Require<R>
+where
+    B: Send,
+    R: Requirement,
+{
+    type Rejection = AuthError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let grant = match Grant::from_request(req).await {
+            Ok(grant) => grant,
+            Err(never) => match never {},
+        };
+        if R::permits(&grant) {
+            req.extensions_mut().insert(grant.clone());
+            Ok(Require(grant, PhantomData))
+        } else if grant.is_anonymous() && CONFIG.lock().auth.enabled {
+            Err(AuthError::Unauthorized)
+        } else {
+            Err(AuthError::Forbidden)
+        }
+    }
+}
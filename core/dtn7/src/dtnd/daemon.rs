@@ -2,10 +2,11 @@ use std::convert::TryFrom;
 
 use super::{httpd, janitor};
 use crate::cla::ecla::processing::start_ecla;
-use crate::cla::ConvergenceLayerAgent;
+use crate::cla::{CLAEnum, ConvergenceLayerAgent};
 use crate::core::application_agent::SimpleApplicationAgent;
+use crate::core::store::BundleStoresEnum;
 use crate::dtnconfig::DtnConfig;
-use crate::ipnd::neighbour_discovery;
+use crate::ipnd::{mdns, neighbour_discovery, DiscoveryBackend};
 use crate::{cla_add, peers_add, STATS};
 use crate::{CLAS, CONFIG, DTNCORE, STORE};
 use bp7::EndpointID;
@@ -64,6 +65,18 @@ async fn start_convergencylayers() {
 }
 
 pub async fn start_dtnd(cfg: DtnConfig) -> anyhow::Result<()> {
+    init_node(cfg, None, Vec::new()).await?;
+    httpd::spawn_httpd().await?;
+    Ok(())
+}
+
+/// Starts all services of the running node except the httpd, a given store and CLAs are
+/// used in addition to the configured ones
+pub(crate) async fn init_node(
+    cfg: DtnConfig,
+    store: Option<BundleStoresEnum>,
+    extra_clas: Vec<CLAEnum>,
+) -> anyhow::Result<()> {
     {
         (*CONFIG.lock()).set(cfg);
     }
@@ -80,7 +93,7 @@ pub async fn start_dtnd(cfg: DtnConfig) -> anyhow::Result<()> {
     let db = CONFIG.lock().db.clone();
     info!("DB Backend: {}", db);
 
-    (*STORE.lock()) = crate::core::store::new(&db);
+    (*STORE.lock()) = store.unwrap_or_else(|| crate::core::store::new(&db));
 
     info!(
         "Announcement Interval: {}",
@@ -109,9 +122,12 @@ pub async fn start_dtnd(cfg: DtnConfig) -> anyhow::Result<()> {
     );
 
     let routing = CONFIG.lock().routing.clone();
-    DTNCORE.lock().routing_agent = crate::routing::new(&routing);
+    DTNCORE.lock().routing_agent = crate::routing::try_new(&routing)?;
 
     info!("RoutingAgent: {}", routing);
+    if routing == "external" {
+        crate::routing::erouting::start_transports().await?;
+    }
 
     let routing_options = CONFIG.lock().routing_settings.clone();
     info!("RoutingOptions: {:?}", routing_options);
@@ -121,10 +137,17 @@ pub async fn start_dtnd(cfg: DtnConfig) -> anyhow::Result<()> {
         info!("Adding CLA: {:?}", cla);
         cla_add(crate::cla::new(cla, Some(local_settings)));
     }
-    if clas.is_empty() {
+    for cla in extra_clas {
+        info!("Adding CLA: {}", cla);
+        cla_add(cla);
+    }
+    if CLAS.lock().is_empty() {
         warn!("No CLAs configured!");
         if CONFIG.lock().ecla_enable {
-            info!("... but ECLA mode is enabled! (port={})", CONFIG.lock().ecla_tcp_port);
+            info!(
+                "... but ECLA mode is enabled! (port={})",
+                CONFIG.lock().ecla_tcp_port
+            );
         }
     }
 
@@ -138,6 +161,10 @@ pub async fn start_dtnd(cfg: DtnConfig) -> anyhow::Result<()> {
         peers_add(s.clone());
     }
 
+    if !CONFIG.lock().scheduled.is_empty() {
+        crate::core::contact_plan::spawn_contact_plan();
+    }
+
     let local_host_id = CONFIG.lock().host_eid.clone();
     (*DTNCORE.lock())
         .register_application_agent(SimpleApplicationAgent::with(local_host_id.clone()).into());
@@ -154,6 +181,7 @@ pub async fn start_dtnd(cfg: DtnConfig) -> anyhow::Result<()> {
         (*DTNCORE.lock()).register_application_agent(SimpleApplicationAgent::with(eid).into());
     }
     start_convergencylayers().await;
+    crate::core::scheduler::spawn_scheduler();
     if CONFIG.lock().janitor_interval.as_micros() != 0 {
         janitor::spawn_janitor();
     }
@@ -161,8 +189,15 @@ pub async fn start_dtnd(cfg: DtnConfig) -> anyhow::Result<()> {
     let dn = CONFIG.lock().disable_neighbour_discovery;
     let interval = CONFIG.lock().announcement_interval.as_micros();
     if !dn && interval != 0 {
-        if let Err(errmsg) = neighbour_discovery::spawn_neighbour_discovery().await {
-            error!("Error spawning service discovery: {:?}", errmsg);
+        let backends = CONFIG.lock().discovery_backends.clone();
+        for backend in backends {
+            let res = match backend {
+                DiscoveryBackend::Ipnd => neighbour_discovery::spawn_neighbour_discovery().await,
+                DiscoveryBackend::Mdns => mdns::spawn_mdns_discovery().await,
+            };
+            if let Err(errmsg) = res {
+                error!("Error spawning {} service discovery: {:?}", backend, errmsg);
+            }
         }
     }
 
@@ -170,7 +205,5 @@ pub async fn start_dtnd(cfg: DtnConfig) -> anyhow::Result<()> {
         let ecla_port = CONFIG.lock().ecla_tcp_port;
         start_ecla(ecla_port).await;
     }
-
-    httpd::spawn_httpd().await?;
     Ok(())
 }
@@ -0,0 +1,83 @@
+use crate::core::events::{self, EventFilter, TimedEvent};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::Query;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::IntoResponse;
+use futures::stream::{self, Stream};
+use futures::{SinkExt, StreamExt};
+use log::{debug, warn};
+use std::convert::Infallible;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+
+// Begin of node event streams, available as server-sent events and JSON WebSocket
+
+/// Waits for the next event matching the filter, `None` once the event bus is gone.
+async fn next_event(rx: &mut Receiver<TimedEvent>, filter: &EventFilter) -> Option<TimedEvent> {
+    loop {
+        match rx.recv().await {
+            Ok(event) if filter.matches(&event.event) => return Some(event),
+            Ok(_) => {}
+            Err(RecvError::Lagged(missed)) => {
+                warn!("Event subscriber too slow, skipped {} events", missed);
+            }
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
+fn event_stream(filter: EventFilter) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(
+        (events::subscribe(), filter),
+        |(mut rx, filter)| async move {
+            let event = next_event(&mut rx, &filter).await?;
+            let sse = Event::default()
+                .event(event.event.kind())
+                .json_data(&event)
+                .expect("error encoding node event");
+            Some((Ok(sse), (rx, filter)))
+        },
+    )
+}
+
+/// `GET /events`: node events as server-sent events
+pub async fn sse_handler(Query(filter): Query<EventFilter>) -> impl IntoResponse {
+    debug!("New event stream subscriber, filter: {:?}", filter);
+    Sse::new(event_stream(filter)).keep_alive(KeepAlive::default())
+}
+
+/// `GET /ws/events`: node events as JSON text messages
+pub async fn ws_handler(
+    Query(filter): Query<EventFilter>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let node = crate::node::current();
+    ws.on_upgrade(move |socket| crate::node::scope(node, handle_socket(socket, filter)))
+}
+
+async fn handle_socket(socket: WebSocket, filter: EventFilter) {
+    debug!("New event websocket subscriber, filter: {:?}", filter);
+    let (mut sender, mut receiver) = socket.split();
+    let mut rx = events::subscribe();
+    loop {
+        tokio::select! {
+            event = next_event(&mut rx, &filter) => {
+                let Some(event) = event else {
+                    break;
+                };
+                let msg = serde_json::to_string(&event).expect("error encoding node event");
+                if sender.send(Message::Text(msg)).await.is_err() {
+                    break;
+                }
+            }
+            msg = receiver.next() => {
+                // incoming messages are ignored, only used to detect closed connections
+                match msg {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+    debug!("Event websocket subscriber disconnected");
+}
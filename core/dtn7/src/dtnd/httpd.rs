@@ -1,3 +1,4 @@
+use crate::client::SendOptions;
 use crate::core::application_agent::ApplicationAgent;
 use crate::core::application_agent::SimpleApplicationAgent;
 use crate::core::bundlepack::Constraint;
@@ -5,43 +6,47 @@ use crate::core::helpers::get_complete_digest;
 use crate::core::helpers::get_digest_of_bids;
 use crate::core::helpers::is_valid_service_name;
 use crate::core::helpers::rnd_peer;
+use crate::core::journal::JournalEntry;
 use crate::core::peer::PeerType;
 use crate::core::store::BundleStore;
+use crate::dtnd::auth::{
+    AdminAccess, ClientAccess, Grant, ReadAccess, Require, SendAccess, StatusAccess,
+};
+use crate::node::NodeExecutor;
 use crate::peers_add;
 use crate::peers_remove;
 use crate::routing_cmd;
 use crate::routing_get_data;
-use crate::store_remove;
 use crate::CONFIG;
 use crate::DTNCORE;
 use crate::PEERS;
 use crate::STATS;
+use crate::STATUS_REPORTS;
 use crate::STORE;
 use crate::{cla_names, peers_count};
 use crate::{DtnConfig, PeerAddress};
 use anyhow::Result;
-use async_trait::async_trait;
 use axum::extract::ws::WebSocketUpgrade;
 use axum::extract::DefaultBodyLimit;
 use axum::extract::Query;
 use axum::response::Html;
 use axum::{
-    extract::{self, connect_info::ConnectInfo, RequestParts},
+    extract::{self, connect_info::ConnectInfo},
     middleware::from_extractor,
     routing::{get, post},
     Router,
 };
+use bp7::administrative_record::TRANSMISSION_CANCELED;
 use bp7::dtntime::CreationTimestamp;
-use bp7::flags::BlockControlFlags;
-use bp7::flags::BundleControlFlags;
 use bp7::helpers::rnd_bundle;
 use bp7::EndpointID;
 use http::StatusCode;
 use humansize::format_size;
 use humansize::DECIMAL;
-use log::{trace, debug, info, warn};
+use hyper::server::conn::AddrIncoming;
+use log::{debug, error, info, trace, warn};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::{TryFrom, TryInto};
 use std::fmt::Write;
 use std::net::SocketAddr;
@@ -60,34 +65,24 @@ async fn ws_application_agent(
 }
 */
 
-struct RequireLocalhost;
-
-#[async_trait]
-impl<B> extract::FromRequest<B> for RequireLocalhost
-where
-    B: Send,
-{
-    type Rejection = StatusCode;
-
-    async fn from_request(conn: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
-        if CONFIG.lock().unsafe_httpd {
-            return Ok(Self);
-        }
-        if let Some(ConnectInfo(addr)) = conn.extensions().get::<ConnectInfo<SocketAddr>>() {
-            if addr.ip().is_loopback() {
-                return Ok(Self);
-            } else if let std::net::IpAddr::V6(ipv6) = addr.ip() {
-                // workaround for bug in std when handling IPv4 in IPv6 addresses
-                if let Some(ipv4) = ipv6.to_ipv4() {
-                    if ipv4.is_loopback() {
-                        return Ok(Self);
-                    }
+/// Checks whether a request originates from localhost or if unsafe httpd access is enabled.
+pub(crate) fn is_local_request(extensions: &http::Extensions) -> bool {
+    if CONFIG.lock().unsafe_httpd {
+        return true;
+    }
+    if let Some(ConnectInfo(addr)) = extensions.get::<ConnectInfo<SocketAddr>>() {
+        if addr.ip().is_loopback() {
+            return true;
+        } else if let std::net::IpAddr::V6(ipv6) = addr.ip() {
+            // workaround for bug in std when handling IPv4 in IPv6 addresses
+            if let Some(ipv4) = ipv6.to_ipv4() {
+                if ipv4.is_loopback() {
+                    return true;
                 }
             }
         }
-
-        Err(StatusCode::FORBIDDEN)
     }
+    false
 }
 
 // Begin of web UI specific structs
@@ -297,6 +292,45 @@ async fn status_peers() -> String {
     let peers = &(*PEERS.lock()).clone();
     serde_json::to_string_pretty(&peers).unwrap()
 }
+//#[get("/status/peers/quarantine")]
+async fn status_peers_quarantine() -> String {
+    serde_json::to_string_pretty(&crate::ipnd::security::quarantined_peers()).unwrap()
+}
+//#[get("/status/reports")]
+async fn status_reports() -> String {
+    serde_json::to_string_pretty(&(*STATUS_REPORTS.lock()).bundles()).unwrap()
+}
+//#[get("/status/reports/<bid>")]
+async fn status_reports_bundle(
+    extract::Path(bid): extract::Path<String>,
+) -> Result<String, (StatusCode, &'static str)> {
+    let bid = bid.trim_start_matches('/');
+    if let Some(timeline) = (*STATUS_REPORTS.lock()).timeline(bid) {
+        Ok(serde_json::to_string_pretty(&timeline).unwrap())
+    } else {
+        Err((StatusCode::NOT_FOUND, "No status reports for bundle"))
+    }
+}
+//#[get("/status/journal")]
+async fn status_journal() -> String {
+    let journals: BTreeMap<String, Vec<JournalEntry>> = (*STORE.lock())
+        .bundles()
+        .into_iter()
+        .map(|bp| (bp.id, bp.journal))
+        .collect();
+    serde_json::to_string_pretty(&journals).unwrap()
+}
+//#[get("/status/journal/<bid>")]
+async fn status_journal_bundle(
+    extract::Path(bid): extract::Path<String>,
+) -> Result<String, (StatusCode, &'static str)> {
+    let bid = bid.trim_start_matches('/');
+    if let Some(bp) = (*STORE.lock()).get_metadata(bid) {
+        Ok(serde_json::to_string_pretty(&bp.journal).unwrap())
+    } else {
+        Err((StatusCode::NOT_FOUND, "Bundle not found"))
+    }
+}
 //#[get("/status/info")]
 async fn status_info() -> String {
     STATS.lock().update_node_stats();
@@ -329,21 +363,23 @@ async fn debug_rnd_peer() -> String {
 
 async fn http_routing_cmd(
     Query(params): Query<HashMap<String, String>>,
-) -> Result<String, (StatusCode, &'static str)> {
+) -> Result<String, (StatusCode, String)> {
     if let Some(cmd) = params.get("c") {
-        if routing_cmd(cmd.to_string()).await.is_ok() {
-            Ok("Sent command to routing agent.".into())
-        } else {
-            Err((
+        match routing_cmd(cmd.to_string()).await {
+            Ok(()) => Ok("Sent command to routing agent.".into()),
+            Err(err) if err.is::<crate::routing::CommandError>() => {
+                Err((StatusCode::BAD_REQUEST, err.to_string()))
+            }
+            Err(_) => Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "error sending cmd to routing agent",
-            ))
+                "error sending cmd to routing agent".into(),
+            )),
         }
     } else {
         //anyhow::bail!("missing filter criteria");
         Err((
             StatusCode::BAD_REQUEST,
-            "missing routing command parameter cmd",
+            "missing routing command parameter cmd".into(),
         ))
     }
 }
@@ -367,8 +403,13 @@ async fn http_peers_add(
 ) -> Result<String, (StatusCode, &'static str)> {
     if let Some(peer_str) = params.get("p") {
         let peer_type =
-            PeerType::try_from(params.get("p_t").unwrap_or(&"DYNAMIC".to_owned()).as_str())
-                .unwrap();
+            match PeerType::try_from(params.get("p_t").map_or("DYNAMIC", |p_t| p_t.as_str())) {
+                // scheduled peers are only added by the contact plan
+                Ok(PeerType::Scheduled) | Err(_) => {
+                    return Err((StatusCode::BAD_REQUEST, "Invalid peer type p_t"))
+                }
+                Ok(peer_type) => peer_type,
+            };
         let mut peer = if let Ok(parsed_peer) = crate::core::helpers::parse_peer_url(peer_str) {
             parsed_peer
         } else {
@@ -426,7 +467,7 @@ async fn insert_get(extract::RawQuery(query): extract::RawQuery) -> Result<Strin
                     bndl.id(),
                     bndl.primary.destination
                 );
-                if bndl.primary.source.node() == (*CONFIG.lock()).host_eid.node() {
+                if bndl.primary.source.node() == CONFIG.lock().host_eid.node() {
                     STATS.lock().node.bundles.bundles_created += 1;
                 }
                 crate::core::processing::send_bundle(bndl).await;
@@ -454,7 +495,7 @@ async fn insert_post(body: bytes::Bytes) -> Result<String, (StatusCode, &'static
             bndl.primary.destination
         );
 
-        if bndl.primary.source.node() == (*CONFIG.lock()).host_eid.node() {
+        if bndl.primary.source.node() == CONFIG.lock().host_eid.node() {
             STATS.lock().node.bundles.bundles_created += 1;
         }
 
@@ -465,63 +506,36 @@ async fn insert_post(body: bytes::Bytes) -> Result<String, (StatusCode, &'static
     }
 }
 
+/// Constructs a new bundle originating from this node from the given `/send` query parameters.
+pub(crate) fn new_bundle_from_params(
+    query_params: &[(String, String)],
+    payload: Vec<u8>,
+) -> Result<bp7::Bundle, &'static str> {
+    let dst: EndpointID = query_params
+        .iter()
+        .find(|(k, _)| k == "dst")
+        .ok_or("Missing destination endpoint id!")?
+        .1
+        .as_str()
+        .try_into()
+        .map_err(|_| "Invalid destination endpoint id!")?;
+    let opts = SendOptions::from_query(query_params)?;
+    let src = CONFIG.lock().host_eid.clone();
+
+    Ok(opts.build_bundle(src, dst, CreationTimestamp::now(), payload))
+}
+
 //#[post("/send", guard = "fn_guard_localhost")]
 async fn send_post(
-    query_params: extract::Query<HashMap<String, String>>,
+    query_params: extract::Query<Vec<(String, String)>>,
     body: bytes::Bytes,
 ) -> Result<String, (StatusCode, &'static str)> {
-    let mut dst: EndpointID = EndpointID::none();
-    let mut lifetime = std::time::Duration::from_secs(60 * 60);
-    let mut flags = BundleControlFlags::BUNDLE_MUST_NOT_FRAGMENTED;
-    //    | BundleControlFlags::BUNDLE_STATUS_REQUEST_DELIVERY;
-
-    for (k, v) in query_params.iter() {
-        if k == "dst" {
-            dst = v.as_str().try_into().unwrap();
-        } else if k == "lifetime" {
-            if let Ok(dur) = humantime::parse_duration(v) {
-                lifetime = dur;
-            }
-        } else if k == "flags" {
-            let param_flags: u64 = v.as_str().parse().unwrap_or(0);
-            if let Some(bpcf) = BundleControlFlags::from_bits(param_flags) {
-                flags = bpcf;
-            } else {
-                return Err((
-                    StatusCode::BAD_REQUEST,
-                    "Invalid Bundle Processing Control Flags!",
-                ));
-            }
-        }
-    }
-    if dst == EndpointID::none() {
-        return Err((StatusCode::BAD_REQUEST, "Missing destination endpoint id!"));
-    }
-    let src = CONFIG.lock().host_eid.clone();
-
-    let pblock = bp7::primary::PrimaryBlockBuilder::default()
-        .bundle_control_flags(flags.bits())
-        .destination(dst)
-        .source(src.clone())
-        .report_to(src)
-        .creation_timestamp(CreationTimestamp::now())
-        .lifetime(lifetime)
-        .build()
-        .unwrap();
-
     let bytes = body.to_vec();
 
     let b_len = bytes.len();
     debug!("Received for sending: {:?}", b_len);
-    let mut bndl = bp7::bundle::BundleBuilder::default()
-        .primary(pblock)
-        .canonicals(vec![
-            bp7::canonical::new_payload_block(BlockControlFlags::empty(), bytes.to_vec()),
-            bp7::canonical::new_hop_count_block(2, BlockControlFlags::empty(), 32),
-        ])
-        .build()
-        .unwrap();
-    bndl.set_crc(bp7::crc::CRC_NO);
+    let bndl = new_bundle_from_params(&query_params, bytes)
+        .map_err(|err| (StatusCode::BAD_REQUEST, err))?;
 
     debug!(
         "Sending bundle {} to {}",
@@ -535,16 +549,20 @@ async fn send_post(
 }
 
 //#[post("/push")]
-async fn push_post(body: bytes::Bytes) -> Result<String, (StatusCode, String)> {
+async fn push_post(
+    remote: Option<ConnectInfo<SocketAddr>>,
+    body: bytes::Bytes,
+) -> Result<String, (StatusCode, String)> {
     let b_len = body.len();
     trace!("received via /push: {:?} bytes", b_len);
     if let Ok(bndl) = bp7::Bundle::try_from(body.as_ref()) {
         //trace!("received bundle {}", bndl.id());
         info!("Received bundle: {}", bndl.id());
         let bid = bndl.id();
-        //tokio::spawn(async move {
+        //crate::node::spawn(async move {
         let now = Instant::now();
-        if let Err(err) = crate::core::processing::receive(bndl).await {
+        let peer = remote.map(|ConnectInfo(addr)| addr.to_string());
+        if let Err(err) = crate::core::processing::receive_from(bndl, "http", peer).await {
             warn!("Error processing bundle: {}", err);
         }
         let elapsed = now.elapsed();
@@ -562,6 +580,7 @@ async fn push_post(body: bytes::Bytes) -> Result<String, (StatusCode, String)> {
 
 //#[get("/register", guard = "fn_guard_localhost")]
 async fn register(
+    grant: Grant,
     extract::RawQuery(query): extract::RawQuery,
 ) -> Result<String, (StatusCode, &'static str)> {
     if query.is_none() {
@@ -574,10 +593,16 @@ async fn register(
         let eid = host_eid
             .new_endpoint(&path)
             .expect("Error constructing new endpoint");
+        if !grant.may_receive(&eid.to_string()) {
+            return Err((StatusCode::FORBIDDEN, "Access to endpoint not permitted"));
+        }
         (*DTNCORE.lock())
             .register_application_agent(SimpleApplicationAgent::with(eid.clone()).into());
         Ok(format!("Registered {}", eid))
     } else if let Ok(eid) = EndpointID::try_from(path) {
+        if !grant.may_receive(&eid.to_string()) {
+            return Err((StatusCode::FORBIDDEN, "Access to endpoint not permitted"));
+        }
         // fully qualified EID, can be non-singleton endpoint
         (*DTNCORE.lock())
             .register_application_agent(SimpleApplicationAgent::with(eid.clone()).into());
@@ -592,6 +617,7 @@ async fn register(
 
 //#[get("/unregister", guard = "fn_guard_localhost")]
 async fn unregister(
+    grant: Grant,
     extract::RawQuery(query): extract::RawQuery,
 ) -> Result<String, (StatusCode, &'static str)> {
     if query.is_none() {
@@ -603,11 +629,17 @@ async fn unregister(
         let eid = host_eid
             .new_endpoint(&path)
             .expect("Error constructing new endpoint");
+        if !grant.may_receive(&eid.to_string()) {
+            return Err((StatusCode::FORBIDDEN, "Access to endpoint not permitted"));
+        }
 
         (*DTNCORE.lock())
             .unregister_application_agent(SimpleApplicationAgent::with(eid.clone()).into());
         Ok(format!("Unregistered {}", eid))
     } else if let Ok(eid) = EndpointID::try_from(path) {
+        if !grant.may_receive(&eid.to_string()) {
+            return Err((StatusCode::FORBIDDEN, "Access to endpoint not permitted"));
+        }
         (*DTNCORE.lock())
             .unregister_application_agent(SimpleApplicationAgent::with(eid.clone()).into());
         Ok(format!("Unregistered URI: {}", eid))
@@ -621,6 +653,7 @@ async fn unregister(
 
 //#[get("/endpoint", guard = "fn_guard_localhost")]
 async fn endpoint(
+    grant: Grant,
     extract::RawQuery(query): extract::RawQuery,
 ) -> Result<Vec<u8>, (StatusCode, &'static str)> {
     if query.is_none() {
@@ -632,6 +665,9 @@ async fn endpoint(
         let eid = host_eid
             .new_endpoint(&path)
             .expect("Error constructing new endpoint"); // TODO: support non-node-specific EIDs
+        if !grant.may_receive(&eid.to_string()) {
+            return Err((StatusCode::FORBIDDEN, "Access to endpoint not permitted"));
+        }
         if let Some(aa) = (*DTNCORE.lock()).get_endpoint_mut(&eid) {
             if let Some(mut bundle) = aa.pop() {
                 let cbor_bundle = bundle.to_cbor();
@@ -643,6 +679,9 @@ async fn endpoint(
             Err((StatusCode::NOT_FOUND, "No such endpoint registered!"))
         }
     } else if let Ok(eid) = EndpointID::try_from(path) {
+        if !grant.may_receive(&eid.to_string()) {
+            return Err((StatusCode::FORBIDDEN, "Access to endpoint not permitted"));
+        }
         if let Some(aa) = (*DTNCORE.lock()).get_endpoint_mut(&eid) {
             if let Some(mut bundle) = aa.pop() {
                 let cbor_bundle = bundle.to_cbor();
@@ -663,6 +702,7 @@ async fn endpoint(
 
 //#[get("/endpoint.hex", guard = "fn_guard_localhost")]
 async fn endpoint_hex(
+    grant: Grant,
     extract::RawQuery(query): extract::RawQuery,
 ) -> Result<String, (StatusCode, &'static str)> {
     if query.is_none() {
@@ -674,6 +714,9 @@ async fn endpoint_hex(
         let eid = host_eid
             .new_endpoint(&path)
             .expect("Error constructing new endpoint");
+        if !grant.may_receive(&eid.to_string()) {
+            return Err((StatusCode::FORBIDDEN, "Access to endpoint not permitted"));
+        }
         // TODO: support non-node-specific EIDs
         if let Some(aa) = (*DTNCORE.lock()).get_endpoint_mut(&eid) {
             if let Some(mut bundle) = aa.pop() {
@@ -685,6 +728,9 @@ async fn endpoint_hex(
             Err((StatusCode::NOT_FOUND, "No such endpoint registered!"))
         }
     } else if let Ok(eid) = EndpointID::try_from(path) {
+        if !grant.may_receive(&eid.to_string()) {
+            return Err((StatusCode::FORBIDDEN, "Access to endpoint not permitted"));
+        }
         if let Some(aa) = (*DTNCORE.lock()).get_endpoint_mut(&eid) {
             if let Some(mut bundle) = aa.pop() {
                 Ok(bp7::helpers::hexify(&bundle.to_cbor()))
@@ -708,7 +754,10 @@ async fn delete(
 ) -> Result<Vec<u8>, (StatusCode, &'static str)> {
     if let Some(bid) = query {
         info!("Requested deleting of bundle {}", bid);
-        if store_remove(&bid).is_ok() {
+        if crate::core::processing::remove(&bid, TRANSMISSION_CANCELED)
+            .await
+            .is_ok()
+        {
             Ok(format!("Deleted {}", bid).as_bytes().to_vec())
         } else {
             Err((StatusCode::NOT_FOUND, "Bundle not found"))
@@ -748,67 +797,78 @@ async fn download_hex(
     }
 }
 
-pub async fn spawn_httpd() -> Result<()> {
+/// All routes of the web interface, including the versioned JSON API
+pub fn router() -> Router {
     let cors = CorsLayer::new()
         // allow `GET` and `POST` when accessing the resource
         .allow_methods([http::Method::GET, http::Method::POST, http::Method::DELETE])
         // allow requests from any origin
         .allow_origin(Any);
 
-    let mut app_local_only = Router::new()
+    let mut app_admin = Router::new()
         .route("/peers/add", get(http_peers_add))
         .route("/peers/del", get(http_peers_delete))
         .route("/routing/cmd", get(http_routing_cmd).post(http_routing_cmd))
-        .route("/routing/getdata", get(http_routing_getdata))
-        .route("/send", post(send_post))
-        .layer(DefaultBodyLimit::disable())
         .route("/delete", get(delete).delete(delete))
-        .route("/register", get(register))
-        .route("/unregister", get(unregister))
-        .route("/endpoint", get(endpoint))
-        .route("/insert", get(insert_get).post(insert_post))
-        .layer(DefaultBodyLimit::disable())
-        .route("/endpoint.hex", get(endpoint_hex))
-        .route("/cts", get(get_creation_timestamp))
-        .route(
-            "/ws",
-            get(|ws: WebSocketUpgrade| async move {
-                ws.max_message_size(128 * 1024 * 1024)
-                    .max_frame_size(128 * 1024 * 1024)
-                    .on_upgrade(super::ws::handle_socket)
-            }),
-        )
         .route("/debug/rnd_bundle", get(debug_rnd_bundle))
-        .route("/debug/rnd_peer", get(debug_rnd_peer))
-        .layer(from_extractor::<RequireLocalhost>())
-        .layer(cors.clone());
+        .route("/debug/rnd_peer", get(debug_rnd_peer));
 
     if CONFIG.lock().routing == "external" {
-        app_local_only = app_local_only.route(
+        app_admin = app_admin.route(
             "/ws/erouting",
             get(|ws: WebSocketUpgrade| async move {
-                ws.on_upgrade(crate::routing::erouting::processing::handle_connection)
+                let node = crate::node::current();
+                ws.on_upgrade(move |socket| {
+                    crate::node::scope(
+                        node,
+                        crate::routing::erouting::processing::handle_connection(socket),
+                    )
+                })
             }),
         )
     }
 
     if CONFIG.lock().ecla_enable {
-        app_local_only = app_local_only.route(
+        app_admin = app_admin.route(
             "/ws/ecla",
             get(|ws: WebSocketUpgrade| async move {
-                ws.on_upgrade(crate::cla::ecla::ws::handle_connection)
+                let node = crate::node::current();
+                ws.on_upgrade(move |socket| {
+                    crate::node::scope(node, crate::cla::ecla::ws::handle_connection(socket))
+                })
             }),
         )
     }
 
-    let app = app_local_only
+    let app_send = Router::new()
+        .route("/send", post(send_post))
+        .layer(DefaultBodyLimit::disable())
+        .route("/insert", get(insert_get).post(insert_post))
+        .layer(DefaultBodyLimit::disable())
+        .route("/cts", get(get_creation_timestamp));
+
+    // endpoint specific permissions are checked by the handlers
+    let app_client = Router::new()
+        .route("/register", get(register))
+        .route("/unregister", get(unregister))
+        .route("/endpoint", get(endpoint))
+        .route("/endpoint.hex", get(endpoint_hex))
+        .route(
+            "/ws",
+            get(|grant: Grant, ws: WebSocketUpgrade| async move {
+                let node = crate::node::current();
+                ws.max_message_size(128 * 1024 * 1024)
+                    .max_frame_size(128 * 1024 * 1024)
+                    .on_upgrade(move |socket| {
+                        crate::node::scope(node, super::ws::handle_socket(socket, grant))
+                    })
+            }),
+        );
+
+    let app_status = Router::new()
         .route("/", get(index))
         .route("/peers", get(web_peers))
         .route("/bundles", get(web_bundles))
-        .route("/download.hex", get(download_hex))
-        .route("/download", get(download))
-        .route("/push", post(push_post))
-        .layer(DefaultBodyLimit::disable())
         .route("/status/nodeid", get(status_node_id))
         .route("/status/eids", get(status_eids))
         .route("/status/bundles", get(status_bundles))
@@ -821,22 +881,67 @@ pub async fn spawn_httpd() -> Result<()> {
         .route("/status/bundles/digest", get(status_bundles_digest))
         .route("/status/store", get(status_store))
         .route("/status/peers", get(status_peers))
+        .route("/status/peers/quarantine", get(status_peers_quarantine))
         .route("/status/info", get(status_info))
-        .layer(cors.clone());
+        .route("/status/reports", get(status_reports))
+        .route("/status/journal", get(status_journal))
+        .route("/status/journal/*bid", get(status_journal_bundle))
+        .route("/status/reports/*bid", get(status_reports_bundle))
+        .route("/events", get(super::events::sse_handler))
+        .route("/ws/events", get(super::events::ws_handler));
+
+    let app_read = Router::new().route("/routing/getdata", get(http_routing_getdata));
+
+    // used by the http convergence layers of other nodes, thus always public
+    let app_public = Router::new()
+        .route("/download.hex", get(download_hex))
+        .route("/download", get(download))
+        .route("/push", post(push_post))
+        .layer(DefaultBodyLimit::disable());
+
+    Router::new()
+        .merge(app_admin.layer(from_extractor::<Require<AdminAccess>>()))
+        .merge(app_send.layer(from_extractor::<Require<SendAccess>>()))
+        .merge(app_client.layer(from_extractor::<Require<ClientAccess>>()))
+        .merge(app_status.layer(from_extractor::<Require<StatusAccess>>()))
+        .merge(app_read.layer(from_extractor::<Require<ReadAccess>>()))
+        .merge(app_public)
+        .nest("/api/v1", super::api::router())
+        .layer(cors)
+}
 
+/// Binds the configured web port, connections are served as part of the running node
+fn bind() -> Result<hyper::server::Builder<AddrIncoming, NodeExecutor>> {
     let port = CONFIG.lock().webport;
 
     let v4 = CONFIG.lock().v4;
     let v6 = CONFIG.lock().v6;
     //debug!("starting webserver");
-    let server = if v4 && !v6 {
-        hyper::Server::bind(&format!("0.0.0.0:{}", port).parse()?)
+    let addr = if v4 && !v6 {
+        format!("0.0.0.0:{}", port)
     } else if !v4 && v6 {
-        hyper::Server::bind(&format!("[::1]:{}", port).parse()?)
+        format!("[::1]:{}", port)
     } else {
-        hyper::Server::bind(&format!("[::]:{}", port).parse()?)
-    }
-    .serve(app.into_make_service_with_connect_info::<SocketAddr>());
+        format!("[::]:{}", port)
+    };
+    Ok(hyper::Server::try_bind(&addr.parse()?)?.executor(NodeExecutor))
+}
+
+pub async fn spawn_httpd() -> Result<()> {
+    let app = router();
+    let server = bind()?.serve(app.into_make_service_with_connect_info::<SocketAddr>());
     server.await?;
     Ok(())
 }
+
+/// Binds the web port and serves the httpd in a task of the running node
+pub async fn bind_httpd() -> Result<()> {
+    let app = router();
+    let server = bind()?.serve(app.into_make_service_with_connect_info::<SocketAddr>());
+    crate::node::spawn(async move {
+        if let Err(err) = server.await {
+            error!("Error serving httpd: {}", err);
+        }
+    });
+    Ok(())
+}
@@ -6,19 +6,14 @@ async fn janitor() {
     trace!("cleaning up peers");
     crate::core::process_peers().await;
 
-    // handled in forwarding
-    //debug!("cleaning up store");
-    //crate::store_delete_expired();
+    trace!("cleaning up store");
+    crate::store_delete_expired().await;
 
-    trace!("reprocessing bundles");
-    /*if let Err(err) = crate::core::process_bundles().await {
-        error!("Processing bundles failed: {}", err);
-    }*/
-    crate::core::process_bundles().await;
+    // bundles waiting for forwarding are retried by the event-driven scheduler
 }
 
 pub fn spawn_janitor() {
-    tokio::spawn(crate::dtnd::cron::spawn_timer(
+    crate::node::spawn(crate::dtnd::cron::spawn_timer(
         crate::CONFIG.lock().janitor_interval,
         janitor,
     ));
@@ -1,5 +1,8 @@
+pub mod api;
+pub mod auth;
 pub mod cron;
 pub mod daemon;
+pub mod events;
 pub mod httpd;
 pub mod janitor;
 pub mod ws;
@@ -1,4 +1,5 @@
 use crate::core::application_agent::ApplicationAgent;
+use crate::dtnd::auth::Grant;
 use crate::CONFIG;
 use crate::DTNCORE;
 use crate::STATS;
@@ -52,16 +53,18 @@ pub struct WsAASession {
     /// receive either complete bundles or data and construct bundle server side
     mode: WsReceiveMode,
     tx: mpsc::Sender<BundleDelivery>,
+    /// permissions of the connected client
+    grant: Grant,
 }
 
-pub async fn handle_socket(socket: WebSocket) {
-    let (session, mut rx_bd) = WsAASession::new();
+pub async fn handle_socket(socket: WebSocket, grant: Grant) {
+    let (session, mut rx_bd) = WsAASession::new(grant);
     let (mut sender, mut receiver) = socket.split();
 
     let session = Arc::new(Mutex::new(session));
     let (tx, mut rx) = mpsc::channel::<Message>(100);
 
-    let mut send_task = tokio::spawn(async move {
+    let mut send_task = crate::node::spawn(async move {
         while let Some(msg) = rx.recv().await {
             // In any websocket error, break loop.
             if sender.send(msg).await.is_err() {
@@ -71,7 +74,7 @@ pub async fn handle_socket(socket: WebSocket) {
     });
     let tx2 = tx.clone();
     let session2 = session.clone();
-    let mut recv_task = tokio::spawn(async move {
+    let mut recv_task = crate::node::spawn(async move {
         while let Some(Ok(msg)) = receiver.next().await {
             if session2
                 .lock()
@@ -86,7 +89,7 @@ pub async fn handle_socket(socket: WebSocket) {
     });
     let tx2 = tx.clone();
     let session2 = session.clone();
-    let mut hb_task = tokio::spawn(async move {
+    let mut hb_task = crate::node::spawn(async move {
         // wait before sending first heartbeat
         let mut task = interval(CLIENT_TIMEOUT);
         task.tick().await;
@@ -112,7 +115,7 @@ pub async fn handle_socket(socket: WebSocket) {
 
     let tx2 = tx.clone();
     let session2 = session.clone();
-    let mut br_task = tokio::spawn(async move {
+    let mut br_task = crate::node::spawn(async move {
         while let Some(bndl_delivery) = rx_bd.recv().await {
             debug!("Received bundle delivery for {}", bndl_delivery.0.id());
             if session2
@@ -128,7 +131,7 @@ pub async fn handle_socket(socket: WebSocket) {
     });
     let tx2 = tx.clone();
     let session2 = session.clone();
-    let mut reflush_task = tokio::spawn(async move {
+    let mut reflush_task = crate::node::spawn(async move {
         let mut task = interval(CHECK_INTERVAL);
         loop {
             task.tick().await;
@@ -165,7 +168,7 @@ macro_rules! ws_reply_text {
 }
 
 impl WsAASession {
-    pub fn new() -> (WsAASession, mpsc::Receiver<BundleDelivery>) {
+    pub fn new(grant: Grant) -> (WsAASession, mpsc::Receiver<BundleDelivery>) {
         let (tx, rx) = mpsc::channel(32);
         (
             WsAASession {
@@ -173,6 +176,7 @@ impl WsAASession {
                 endpoints: None,
                 mode: WsReceiveMode::Data(DataReceiveFormat::JSON),
                 tx,
+                grant,
             },
             rx,
         )
@@ -269,7 +273,9 @@ impl WsAASession {
                         "/subscribe" => {
                             if v.len() == 2 {
                                 if let Ok(eid) = EndpointID::try_from(v[1]) {
-                                    if (*DTNCORE.lock()).is_in_endpoints(&eid) {
+                                    if !self.grant.may_receive(&eid.to_string()) {
+                                        ws_reply_text!(socket, "403 forbidden endpoint");
+                                    } else if (*DTNCORE.lock()).is_in_endpoints(&eid) {
                                         debug!("subscribed to endpoint: {}", eid);
                                         if self.endpoints.is_none() {
                                             self.endpoints = Some(HashSet::new());
@@ -294,7 +300,9 @@ impl WsAASession {
                                 } else {
                                     let this_host: EndpointID = CONFIG.lock().host_eid.clone();
                                     if let Ok(eid) = this_host.new_endpoint(v[1]) {
-                                        if (*DTNCORE.lock()).get_endpoint(&eid).is_none() {
+                                        if !self.grant.may_receive(&eid.to_string()) {
+                                            ws_reply_text!(socket, "403 forbidden endpoint");
+                                        } else if (*DTNCORE.lock()).get_endpoint(&eid).is_none() {
                                             debug!(
                                                 "Attempted to subscribe to unknown endpoint: {}",
                                                 eid
@@ -338,6 +346,10 @@ impl WsAASession {
             }
 
             Message::Binary(bin) => {
+                if !self.grant.may_send() {
+                    ws_reply_text!(socket, "403 sending not permitted");
+                    return Ok(());
+                }
                 match self.mode {
                     WsReceiveMode::Bundle => {
                         if let Ok(bndl) = serde_cbor::from_slice::<bp7::Bundle>(&bin) {
@@ -349,10 +361,9 @@ impl WsAASession {
                             // TODO: turn into channel
                             //                            crate::core::processing::send_bundle(bndl);
                             //crate::core::processing::send_through_task(bndl);
-                            let rt = tokio::runtime::Handle::current();
-                            rt.spawn(
-                                async move { crate::core::processing::send_bundle(bndl).await },
-                            );
+                            crate::node::spawn(async move {
+                                crate::core::processing::send_bundle(bndl).await
+                            });
                             debug!("sent bundle");
                             STATS.lock().node.bundles.bundles_created += 1;
 
@@ -419,10 +430,9 @@ impl WsAASession {
                             );
                             //let mut rt = tokio::runtime::Runtime::new().unwrap();
                             //rt.block_on(async { crate::core::processing::send_bundle(bndl).await });
-                            let rt = tokio::runtime::Handle::current();
-                            rt.spawn(
-                                async move { crate::core::processing::send_bundle(bndl).await },
-                            );
+                            crate::node::spawn(async move {
+                                crate::core::processing::send_bundle(bndl).await
+                            });
                             debug!("sent bundle");
                             STATS.lock().node.bundles.bundles_created += 1;
 
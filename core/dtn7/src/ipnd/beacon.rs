@@ -4,8 +4,9 @@ use serde::ser::{SerializeSeq, Serializer};
 use serde::{de, Deserialize, Deserializer, Serialize};
 use std::{fmt::Debug, time::Duration};
 
-use crate::ipnd::services::ServiceBlock;
+use crate::ipnd::{security::Key, services::ServiceBlock};
 use bp7::{bundle::Block, ByteBuffer, EndpointID};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
 
 // Draft IPND version is 0x04
 // This implementation uses more enhanced and additional features, also the bundle protocol version
@@ -138,6 +139,56 @@ impl Beacon {
         self.beacon_sequence_number = bsn;
     }
 
+    /// Returns the canonical encoding of all signed fields, i.e., everything except the flags
+    /// and the signature itself
+    pub fn signing_payload(&self) -> Vec<u8> {
+        serde_cbor::to_vec(&(
+            self.version,
+            &self.eid,
+            self.beacon_sequence_number,
+            self.service_block.clas(),
+            self.service_block.unsigned_services(),
+            self.beacon_period.map(|period| period.as_secs()),
+        ))
+        .expect("Error encoding beacon signing payload")
+    }
+
+    /// Signs the beacon with the Ed25519 secret key seed of this node
+    ///
+    /// Has to be called again after any change to the beacon, e.g., of the beacon_sequence_number
+    pub fn sign(&mut self, seed: &Key) {
+        let signature = SigningKey::from_bytes(seed).sign(&self.signing_payload());
+        self.service_block.set_signature(&signature.to_bytes());
+        self.add_flags(SERVICE_BLOCK_PRESENT);
+    }
+
+    /// Returns the signing time in milliseconds since the Unix epoch (if present)
+    pub fn timestamp(&self) -> Option<u64> {
+        self.service_block.timestamp()
+    }
+
+    /// Sets the signing time, receivers only accept signed beacons newer than the last one
+    pub fn set_timestamp(&mut self, timestamp: u64) {
+        self.service_block.set_timestamp(timestamp);
+        self.add_flags(SERVICE_BLOCK_PRESENT);
+    }
+
+    /// Returns the signature of the beacon (if present)
+    pub fn signature(&self) -> Option<&Vec<u8>> {
+        self.service_block.signature()
+    }
+
+    /// Checks if the beacon carries a valid signature of the given public key
+    pub fn verify(&self, public_key: &Key) -> bool {
+        let (Some(signature), Ok(key)) = (self.signature(), VerifyingKey::from_bytes(public_key))
+        else {
+            return false;
+        };
+        Signature::from_slice(signature)
+            .and_then(|signature| key.verify_strict(&self.signing_payload(), &signature))
+            .is_ok()
+    }
+
     /// This method adds a cla to the corresponding vector of the ServiceBlock
     pub fn add_cla(&mut self, name: &str, port: &Option<u16>) {
         self.service_block.add_cla(name, port);
@@ -159,9 +210,9 @@ impl Beacon {
 impl std::fmt::Display for Beacon {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let temp = format!("{:010b}", self.flags);
-        let output = if self.beacon_period.is_some() {
+        let output = if let Some(beacon_period) = self.beacon_period {
             format!("Version: {:#x}\tFlags: {}\tBeaconSequenceNumber: {}\nEID: {}\nServiceBlock:\n{}\nBeaconPeriod: {:#?}",
-        self.version, temp, self.beacon_sequence_number, self.eid, self.service_block, self.beacon_period.unwrap())
+        self.version, temp, self.beacon_sequence_number, self.eid, self.service_block, beacon_period)
         } else {
             format!("Version: {:#x}\tFlags: {}\tBeaconSequenceNumber: {}\nEID: {}\nServiceBlock:\n{}\nBeaconPeriod: None",
         self.version, temp, self.beacon_sequence_number, self.eid, self.service_block)
@@ -202,8 +253,8 @@ impl Serialize for Beacon {
         if !self.service_block.is_empty() {
             seq.serialize_element(&self.service_block)?;
         }
-        if self.beacon_period.is_some() {
-            let period_number = self.beacon_period.unwrap().as_secs();
+        if let Some(beacon_period) = self.beacon_period {
+            let period_number = beacon_period.as_secs();
             seq.serialize_element(&period_number)?;
         }
         seq.end()
@@ -0,0 +1,695 @@
+use crate::cla::ConvergenceLayerAgent;
+use crate::core::peer::PeerAddress;
+use crate::core::{DtnPeer, PeerType};
+use crate::ipnd::neighbour_discovery::peer_discovered;
+use crate::ipnd::security::{BeaconSecurityConfig, BeaconVerdict, UnauthenticatedBeacons};
+use crate::routing::RoutingNotifcation;
+use crate::{peers_get_for_node, peers_remove, routing_notify, CLAS, CONFIG, DTNCORE};
+use anyhow::{bail, Result};
+use bp7::EndpointID;
+use log::{debug, error, info, trace};
+use socket2::{Domain, Socket, Type};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+use tokio::time::interval;
+
+/*
+    DNS-SD over multicast DNS (RFC 6762, RFC 6763) as an alternative to IPND beacons.
+
+    Every node advertises the service instance `<node>._dtn._tcp.local` and one instance per
+    convergence layer, e.g., `<node>._dtn-mtcp._tcp.local`. The TXT records carry the node ID,
+    the CLA ports, the announcement period and the node's services. Other nodes are found by
+    browsing for `_dtn._tcp.local`, the resulting peers take the same path as peers discovered
+    by IPND.
+
+    Only the small subset of DNS needed for this is implemented: uncompressed names on encoding,
+    PTR, SRV, TXT, A and AAAA records and no known-answer suppression.
+*/
+
+pub const MDNS_PORT: u16 = 5353;
+pub const MDNS_V4: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+pub const MDNS_V6: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 0xfb);
+
+/// Service type browsed for and advertised by every node
+pub const SERVICE_TYPE: &str = "_dtn._tcp.local";
+
+pub const TYPE_A: u16 = 1;
+pub const TYPE_PTR: u16 = 12;
+pub const TYPE_TXT: u16 = 16;
+pub const TYPE_AAAA: u16 = 28;
+pub const TYPE_SRV: u16 = 33;
+pub const TYPE_ANY: u16 = 255;
+
+const CLASS_IN: u16 = 1;
+/// Unique records of the announcing node replace cached ones
+const CACHE_FLUSH: u16 = 0x8000;
+const FLAGS_RESPONSE: u16 = 0x8400;
+
+/// Minimum time between two responses to queries
+const RESPONSE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Service type of a convergence layer, e.g., `_dtn-mtcp._tcp.local`
+pub fn cla_service_type(cla: &str) -> String {
+    let proto = if cla == "udp" { "_udp" } else { "_tcp" };
+    format!("_dtn-{}.{}.local", cla, proto)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordData {
+    Ptr(String),
+    Srv {
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: String,
+    },
+    Txt(Vec<String>),
+    A(Ipv4Addr),
+    Aaaa(Ipv6Addr),
+    Other(Vec<u8>),
+}
+
+impl RecordData {
+    pub fn rtype(&self) -> Option<u16> {
+        match self {
+            RecordData::Ptr(_) => Some(TYPE_PTR),
+            RecordData::Srv { .. } => Some(TYPE_SRV),
+            RecordData::Txt(_) => Some(TYPE_TXT),
+            RecordData::A(_) => Some(TYPE_A),
+            RecordData::Aaaa(_) => Some(TYPE_AAAA),
+            RecordData::Other(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub name: String,
+    pub ttl: u32,
+    pub data: RecordData,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Question {
+    pub name: String,
+    pub qtype: u16,
+}
+
+/// A DNS message, records of the answer, authority and additional sections are merged
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Message {
+    pub response: bool,
+    pub questions: Vec<Question>,
+    pub records: Vec<Record>,
+}
+
+fn encode_name(buf: &mut Vec<u8>, name: &str) -> Result<()> {
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            bail!("invalid label in dns name: {}", name);
+        }
+        buf.push(label.len() as u8);
+        buf.extend_from_slice(label.as_bytes());
+    }
+    buf.push(0);
+    Ok(())
+}
+
+fn read_u16(buf: &[u8], pos: usize) -> Result<u16> {
+    match buf.get(pos..pos + 2) {
+        Some(b) => Ok(u16::from_be_bytes([b[0], b[1]])),
+        None => bail!("truncated dns message"),
+    }
+}
+
+fn read_u32(buf: &[u8], pos: usize) -> Result<u32> {
+    match buf.get(pos..pos + 4) {
+        Some(b) => Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]])),
+        None => bail!("truncated dns message"),
+    }
+}
+
+/// Decodes a possibly compressed name, returns the name and the position after it
+fn decode_name(buf: &[u8], mut pos: usize) -> Result<(String, usize)> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut end = None;
+    let mut jumps = 0;
+    loop {
+        let len = *buf
+            .get(pos)
+            .ok_or_else(|| anyhow::anyhow!("truncated dns name"))? as usize;
+        if len == 0 {
+            pos += 1;
+            break;
+        }
+        if len & 0xc0 == 0xc0 {
+            jumps += 1;
+            if jumps > 16 {
+                bail!("too many compression pointers in dns name");
+            }
+            let target = (read_u16(buf, pos)? & 0x3fff) as usize;
+            end.get_or_insert(pos + 2);
+            pos = target;
+            continue;
+        }
+        let label = buf
+            .get(pos + 1..pos + 1 + len)
+            .ok_or_else(|| anyhow::anyhow!("truncated dns label"))?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        pos += 1 + len;
+    }
+    Ok((labels.join("."), end.unwrap_or(pos)))
+}
+
+impl Message {
+    pub fn query(name: &str, qtype: u16) -> Message {
+        Message {
+            response: false,
+            questions: vec![Question {
+                name: name.into(),
+                qtype,
+            }],
+            records: Vec::new(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::with_capacity(512);
+        // mDNS messages use id 0
+        buf.extend_from_slice(&0u16.to_be_bytes());
+        let flags = if self.response { FLAGS_RESPONSE } else { 0 };
+        buf.extend_from_slice(&flags.to_be_bytes());
+        buf.extend_from_slice(&(self.questions.len() as u16).to_be_bytes());
+        buf.extend_from_slice(&(self.records.len() as u16).to_be_bytes());
+        buf.extend_from_slice(&[0, 0, 0, 0]);
+        for q in &self.questions {
+            encode_name(&mut buf, &q.name)?;
+            buf.extend_from_slice(&q.qtype.to_be_bytes());
+            buf.extend_from_slice(&CLASS_IN.to_be_bytes());
+        }
+        for r in &self.records {
+            let rtype = match r.data.rtype() {
+                Some(rtype) => rtype,
+                None => bail!("cannot encode unknown record type"),
+            };
+            encode_name(&mut buf, &r.name)?;
+            buf.extend_from_slice(&rtype.to_be_bytes());
+            let class = if rtype == TYPE_PTR {
+                CLASS_IN
+            } else {
+                CLASS_IN | CACHE_FLUSH
+            };
+            buf.extend_from_slice(&class.to_be_bytes());
+            buf.extend_from_slice(&r.ttl.to_be_bytes());
+            let mut rdata = Vec::new();
+            match &r.data {
+                RecordData::Ptr(target) => encode_name(&mut rdata, target)?,
+                RecordData::Srv {
+                    priority,
+                    weight,
+                    port,
+                    target,
+                } => {
+                    rdata.extend_from_slice(&priority.to_be_bytes());
+                    rdata.extend_from_slice(&weight.to_be_bytes());
+                    rdata.extend_from_slice(&port.to_be_bytes());
+                    encode_name(&mut rdata, target)?;
+                }
+                RecordData::Txt(entries) => {
+                    for entry in entries {
+                        if entry.len() > 255 {
+                            bail!("txt entry too long: {}", entry);
+                        }
+                        rdata.push(entry.len() as u8);
+                        rdata.extend_from_slice(entry.as_bytes());
+                    }
+                    if entries.is_empty() {
+                        rdata.push(0);
+                    }
+                }
+                RecordData::A(addr) => rdata.extend_from_slice(&addr.octets()),
+                RecordData::Aaaa(addr) => rdata.extend_from_slice(&addr.octets()),
+                RecordData::Other(_) => unreachable!(),
+            }
+            buf.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+            buf.extend_from_slice(&rdata);
+        }
+        Ok(buf)
+    }
+
+    pub fn from_bytes(buf: &[u8]) -> Result<Message> {
+        let flags = read_u16(buf, 2)?;
+        let qdcount = read_u16(buf, 4)?;
+        let rrcount =
+            read_u16(buf, 6)? as usize + read_u16(buf, 8)? as usize + read_u16(buf, 10)? as usize;
+        let mut pos = 12;
+        let mut msg = Message {
+            response: flags & 0x8000 != 0,
+            ..Default::default()
+        };
+        for _ in 0..qdcount {
+            let (name, next) = decode_name(buf, pos)?;
+            let qtype = read_u16(buf, next)?;
+            pos = next + 4;
+            msg.questions.push(Question { name, qtype });
+        }
+        for _ in 0..rrcount {
+            let (name, next) = decode_name(buf, pos)?;
+            let rtype = read_u16(buf, next)?;
+            let ttl = read_u32(buf, next + 4)?;
+            let len = read_u16(buf, next + 8)? as usize;
+            let start = next + 10;
+            let rdata = buf
+                .get(start..start + len)
+                .ok_or_else(|| anyhow::anyhow!("truncated dns record"))?;
+            let data = match rtype {
+                TYPE_PTR => RecordData::Ptr(decode_name(buf, start)?.0),
+                TYPE_SRV => RecordData::Srv {
+                    priority: read_u16(buf, start)?,
+                    weight: read_u16(buf, start + 2)?,
+                    port: read_u16(buf, start + 4)?,
+                    target: decode_name(buf, start + 6)?.0,
+                },
+                TYPE_TXT => {
+                    let mut entries = Vec::new();
+                    let mut i = 0;
+                    while i < rdata.len() {
+                        let l = rdata[i] as usize;
+                        if let Some(entry) = rdata.get(i + 1..i + 1 + l) {
+                            if !entry.is_empty() {
+                                entries.push(String::from_utf8_lossy(entry).into_owned());
+                            }
+                        }
+                        i += 1 + l;
+                    }
+                    RecordData::Txt(entries)
+                }
+                TYPE_A if len == 4 => {
+                    RecordData::A(Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3]))
+                }
+                TYPE_AAAA if len == 16 => {
+                    let octets: [u8; 16] = rdata.try_into().unwrap();
+                    RecordData::Aaaa(Ipv6Addr::from(octets))
+                }
+                _ => RecordData::Other(rdata.to_vec()),
+            };
+            msg.records.push(Record { name, ttl, data });
+            pos = start + len;
+        }
+        Ok(msg)
+    }
+}
+
+/// What a node advertises about itself via DNS-SD
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Advertisement {
+    pub eid: EndpointID,
+    pub addr: Option<IpAddr>,
+    pub period: Option<Duration>,
+    pub cla_list: Vec<(String, Option<u16>)>,
+    pub services: HashMap<u8, String>,
+}
+
+fn instance_name(node: &str, service_type: &str) -> String {
+    format!("{}.{}", node, service_type)
+}
+
+impl Advertisement {
+    /// All records announcing this node
+    pub fn records(&self, ttl: u32) -> Vec<Record> {
+        let node = self.eid.node().unwrap_or_default();
+        let host = format!("{}.local", node);
+
+        let mut txt = vec![format!("node={}", self.eid)];
+        for (cla, port) in &self.cla_list {
+            match port {
+                Some(port) => txt.push(format!("cla={}:{}", cla, port)),
+                None => txt.push(format!("cla={}", cla)),
+            }
+        }
+        if let Some(period) = self.period {
+            txt.push(format!("period={}", period.as_secs()));
+        }
+        let mut services: Vec<_> = self.services.iter().collect();
+        services.sort();
+        for (tag, payload) in services {
+            txt.push(format!("service={}:{}", tag, payload));
+        }
+
+        let first_port = self
+            .cla_list
+            .iter()
+            .find_map(|(_, port)| *port)
+            .unwrap_or(0);
+        let mut service_ports = vec![(SERVICE_TYPE.to_string(), first_port)];
+        for (cla, port) in &self.cla_list {
+            service_ports.push((cla_service_type(cla), port.unwrap_or(0)));
+        }
+
+        let mut records = Vec::new();
+        for (service_type, port) in service_ports {
+            let instance = instance_name(&node, &service_type);
+            records.push(Record {
+                name: service_type,
+                ttl,
+                data: RecordData::Ptr(instance.clone()),
+            });
+            records.push(Record {
+                name: instance.clone(),
+                ttl,
+                data: RecordData::Srv {
+                    priority: 0,
+                    weight: 0,
+                    port,
+                    target: host.clone(),
+                },
+            });
+            records.push(Record {
+                name: instance,
+                ttl,
+                data: RecordData::Txt(txt.clone()),
+            });
+        }
+        match self.addr {
+            Some(IpAddr::V4(addr)) => records.push(Record {
+                name: host,
+                ttl,
+                data: RecordData::A(addr),
+            }),
+            Some(IpAddr::V6(addr)) => records.push(Record {
+                name: host,
+                ttl,
+                data: RecordData::Aaaa(addr),
+            }),
+            None => {}
+        }
+        records
+    }
+
+    /// Parses the TXT entries of a `_dtn._tcp` service instance
+    pub fn from_txt(entries: &[String]) -> Option<Advertisement> {
+        let mut eid = None;
+        let mut adv_period = None;
+        let mut cla_list = Vec::new();
+        let mut services = HashMap::new();
+        for entry in entries {
+            let (key, value) = match entry.split_once('=') {
+                Some(kv) => kv,
+                None => continue,
+            };
+            match key {
+                "node" => eid = EndpointID::try_from(value).ok(),
+                "cla" => match value.split_once(':') {
+                    Some((cla, port)) => cla_list.push((cla.to_string(), port.parse().ok())),
+                    None => cla_list.push((value.to_string(), None)),
+                },
+                "period" => adv_period = value.parse().ok().map(Duration::from_secs),
+                "service" => {
+                    if let Some((tag, payload)) = value.split_once(':') {
+                        if let Ok(tag) = tag.parse() {
+                            services.insert(tag, payload.to_string());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some(Advertisement {
+            eid: eid?,
+            addr: None,
+            period: adv_period,
+            cla_list,
+            services,
+        })
+    }
+
+    /// Finds all nodes announced in a message, the address is taken from A/AAAA records if present.
+    ///
+    /// Nodes with a TTL of 0 said goodbye and are returned with `None` as TTL.
+    pub fn from_message(msg: &Message) -> Vec<(Advertisement, Option<u32>)> {
+        let mut found = Vec::new();
+        for ptr in &msg.records {
+            let instance = match &ptr.data {
+                RecordData::Ptr(instance) if ptr.name == SERVICE_TYPE => instance,
+                _ => continue,
+            };
+            let txt = msg.records.iter().find_map(|r| match &r.data {
+                RecordData::Txt(entries) if &r.name == instance => Some(entries),
+                _ => None,
+            });
+            let mut adv = match txt.and_then(|entries| Advertisement::from_txt(entries)) {
+                Some(adv) => adv,
+                None => continue,
+            };
+            let target = msg.records.iter().find_map(|r| match &r.data {
+                RecordData::Srv { target, .. } if &r.name == instance => Some(target),
+                _ => None,
+            });
+            adv.addr = msg.records.iter().find_map(|r| match &r.data {
+                RecordData::A(addr) if Some(&r.name) == target => Some(IpAddr::V4(*addr)),
+                RecordData::Aaaa(addr) if Some(&r.name) == target => Some(IpAddr::V6(*addr)),
+                _ => None,
+            });
+            let ttl = if ptr.ttl == 0 { None } else { Some(ptr.ttl) };
+            found.push((adv, ttl));
+        }
+        found
+    }
+
+    /// Peer for an advertisement received from `src`
+    pub fn to_peer(&self, src: IpAddr) -> DtnPeer {
+        DtnPeer::new(
+            self.eid.clone(),
+            self.addr.unwrap_or(src).into(),
+            PeerType::Dynamic,
+            self.period,
+            self.cla_list.clone(),
+            self.services.clone(),
+        )
+    }
+}
+
+/// Advertisement of the local node
+fn own_advertisement(addr: Option<IpAddr>) -> Advertisement {
+    let (eid, period) = {
+        let config = CONFIG.lock();
+        let period = if config.enable_period {
+            Some(config.announcement_interval)
+        } else {
+            None
+        };
+        (config.host_eid.clone(), period)
+    };
+    let cla_list = (*CLAS.lock())
+        .iter()
+        .map(|cla| (cla.name().to_string(), Some(cla.port())))
+        .collect();
+    let services = DTNCORE
+        .lock()
+        .service_list
+        .iter()
+        .map(|(tag, payload)| (*tag, payload.clone()))
+        .collect();
+    Advertisement {
+        eid,
+        addr,
+        period,
+        cla_list,
+        services,
+    }
+}
+
+/// Address of the interface used to reach the multicast group
+fn local_addr(group: SocketAddr) -> Option<IpAddr> {
+    let bind: SocketAddr = if group.is_ipv4() {
+        "0.0.0.0:0".parse().unwrap()
+    } else {
+        "[::]:0".parse().unwrap()
+    };
+    let socket = std::net::UdpSocket::bind(bind).ok()?;
+    socket.connect(group).ok()?;
+    Some(socket.local_addr().ok()?.ip()).filter(|ip| !ip.is_unspecified())
+}
+
+/// Time records stay valid in caches of other nodes
+fn record_ttl() -> u32 {
+    let config = CONFIG.lock();
+    let timeout = config
+        .peer_timeout
+        .max(config.announcement_interval * 2)
+        .as_secs();
+    timeout.clamp(1, 4500) as u32
+}
+
+async fn announce(socket: &UdpSocket, group: SocketAddr) {
+    let msg = Message {
+        response: true,
+        questions: Vec::new(),
+        records: own_advertisement(local_addr(group)).records(record_ttl()),
+    };
+    match msg.to_bytes() {
+        Ok(bytes) => match socket.send_to(&bytes, group).await {
+            Ok(amt) => debug!("sent mdns announcement (len={}) to {}", amt, group),
+            Err(err) => error!("Sending mdns announcement failed: {}", err),
+        },
+        Err(err) => error!("Encoding mdns announcement failed: {}", err),
+    }
+}
+
+fn asks_for_us(msg: &Message, node: &str) -> bool {
+    msg.questions.iter().any(|q| {
+        ((q.qtype == TYPE_PTR || q.qtype == TYPE_ANY)
+            && (q.name == SERVICE_TYPE || q.name.starts_with("_dtn-")))
+            || q.name.starts_with(&format!("{}.", node))
+    })
+}
+
+/// The dynamic peer a goodbye advertisement received from `src` removes.
+///
+/// Goodbyes are checked like announcements and are only accepted from the address the
+/// peer is known by, so other hosts can not evict peers.
+pub fn leaving_peer(
+    adv: &Advertisement,
+    src: IpAddr,
+    security: &BeaconSecurityConfig,
+) -> Option<DtnPeer> {
+    match security.check_unsigned(&adv.eid.node().unwrap_or_default()) {
+        BeaconVerdict::Unauthenticated
+            if security.unauthenticated == UnauthenticatedBeacons::Accept => {}
+        verdict => {
+            debug!(
+                "Ignoring mdns goodbye of {} from {}: {:?}",
+                adv.eid, src, verdict
+            );
+            return None;
+        }
+    }
+    if !CONFIG.lock().admission.admit_peer(&mut adv.to_peer(src)) {
+        debug!(
+            "Ignoring mdns goodbye refused by admission policy: {}",
+            adv.eid
+        );
+        return None;
+    }
+    let peer = peers_get_for_node(&adv.eid).filter(|peer| peer.con_type == PeerType::Dynamic)?;
+    if peer.addr != PeerAddress::Ip(src) {
+        debug!(
+            "Ignoring mdns goodbye of {} from {}, the peer is known at {}",
+            adv.eid, src, peer.addr
+        );
+        return None;
+    }
+    Some(peer)
+}
+
+async fn receiver(socket: UdpSocket, group: SocketAddr) {
+    let mut buf: Vec<u8> = vec![0; 9000];
+    let nodeid = CONFIG.lock().host_eid.clone();
+    let node = nodeid.node().unwrap_or_default();
+    let security = CONFIG.lock().beacon_security.clone();
+    let mut last_response = Instant::now() - RESPONSE_INTERVAL;
+    loop {
+        let (size, src) = match socket.recv_from(&mut buf).await {
+            Ok(res) => res,
+            Err(err) => {
+                error!("Error receiving mdns message: {}", err);
+                continue;
+            }
+        };
+        let msg = match Message::from_bytes(&buf[..size]) {
+            Ok(msg) => msg,
+            Err(err) => {
+                trace!("Ignoring invalid mdns message from {}: {}", src, err);
+                continue;
+            }
+        };
+        if !msg.response {
+            if asks_for_us(&msg, &node) && last_response.elapsed() >= RESPONSE_INTERVAL {
+                trace!("Answering mdns query from {}", src);
+                last_response = Instant::now();
+                announce(&socket, group).await;
+            }
+            continue;
+        }
+        for (adv, ttl) in Advertisement::from_message(&msg) {
+            if adv.eid == nodeid {
+                continue;
+            }
+            if ttl.is_none() {
+                if let Some(peer) = leaving_peer(&adv, src.ip(), &security) {
+                    info!("Peer said goodbye via mdns: {}", peer.eid);
+                    peers_remove(&peer.node_name());
+                    if let Err(err) =
+                        routing_notify(RoutingNotifcation::DroppedPeer(peer.eid)).await
+                    {
+                        info!("Error while dropped peer notification: {}", err);
+                    }
+                }
+                continue;
+            }
+            let verdict = security.check_unsigned(&adv.eid.node().unwrap_or_default());
+            let origin = format!("{} (mdns)", src);
+            peer_discovered(adv.to_peer(src.ip()), verdict, &security, &origin).await;
+        }
+    }
+}
+
+async fn browser(socket: UdpSocket, group: SocketAddr) {
+    let mut task = interval(CONFIG.lock().announcement_interval);
+    loop {
+        task.tick().await;
+        announce(&socket, group).await;
+        match Message::query(SERVICE_TYPE, TYPE_PTR).to_bytes() {
+            Ok(bytes) => {
+                if let Err(err) = socket.send_to(&bytes, group).await {
+                    error!("Sending mdns query failed: {}", err);
+                }
+            }
+            Err(err) => error!("Encoding mdns query failed: {}", err),
+        }
+    }
+}
+
+pub async fn spawn_mdns_discovery() -> Result<()> {
+    let v4 = CONFIG.lock().v4;
+    let v6 = CONFIG.lock().v6;
+    if v4 {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, None)?;
+        socket.set_reuse_address(true)?;
+        let addr: SocketAddr = format!("0.0.0.0:{}", MDNS_PORT).parse()?;
+        socket.bind(&addr.into())?;
+        socket.set_nonblocking(true)?;
+        // other nodes on the same host have to see our announcements
+        socket.set_multicast_loop_v4(true)?;
+        socket.set_multicast_ttl_v4(255)?;
+        socket.join_multicast_v4(&MDNS_V4, &Ipv4Addr::UNSPECIFIED)?;
+
+        let group = SocketAddr::new(MDNS_V4.into(), MDNS_PORT);
+        let socket1 = UdpSocket::from_std(socket.try_clone()?.into())?;
+        let socket2 = UdpSocket::from_std(socket.into())?;
+        info!("mDNS discovery listening on {}", socket1.local_addr()?);
+        crate::node::spawn(receiver(socket1, group));
+        crate::node::spawn(browser(socket2, group));
+    }
+    if v6 {
+        let socket = Socket::new(Domain::IPV6, Type::DGRAM, None)?;
+        socket.set_reuse_address(true)?;
+        socket.set_only_v6(true)?;
+        let addr: SocketAddr = format!("[::]:{}", MDNS_PORT).parse()?;
+        socket.bind(&addr.into())?;
+        socket.set_nonblocking(true)?;
+        socket.set_multicast_loop_v6(true)?;
+        socket.join_multicast_v6(&MDNS_V6, 0)?;
+
+        let group = SocketAddr::new(MDNS_V6.into(), MDNS_PORT);
+        let socket1 = UdpSocket::from_std(socket.try_clone()?.into())?;
+        let socket2 = UdpSocket::from_std(socket.into())?;
+        info!("mDNS discovery listening on {}", socket1.local_addr()?);
+        crate::node::spawn(receiver(socket1, group));
+        crate::node::spawn(browser(socket2, group));
+    }
+    Ok(())
+}
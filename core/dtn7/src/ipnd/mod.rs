@@ -1,4 +1,40 @@
 #![forbid(unsafe_code)]
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
 pub mod beacon;
+pub mod mdns;
 pub mod neighbour_discovery;
+pub mod security;
 pub mod services;
+
+/// Mechanism used to discover neighbours
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiscoveryBackend {
+    /// IPND beacons via UDP broadcast and multicast
+    Ipnd,
+    /// DNS-SD via multicast DNS
+    Mdns,
+}
+
+impl FromStr for DiscoveryBackend {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ipnd" => Ok(Self::Ipnd),
+            "mdns" => Ok(Self::Mdns),
+            _ => Err(format!("unknown discovery backend: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for DiscoveryBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ipnd => write!(f, "ipnd"),
+            Self::Mdns => write!(f, "mdns"),
+        }
+    }
+}
@@ -1,24 +1,27 @@
 use crate::cla::ConvergenceLayerAgent;
 use crate::core::{DtnPeer, PeerType};
+use crate::ipnd::security::{self, BeaconSecurityConfig, BeaconVerdict, UnauthenticatedBeacons};
 use crate::ipnd::{beacon::Beacon, services::*};
 use crate::routing::RoutingNotifcation;
-use crate::{peers_add, routing_notify, CLAS, CONFIG};
-use crate::{peers_touch, DTNCORE};
+use crate::DTNCORE;
+use crate::{peers_update, routing_notify, CLAS, CONFIG};
 use anyhow::Result;
-use log::{debug, error, info, trace};
+use log::{debug, error, info, trace, warn};
 use socket2::{Domain, Socket, Type};
 use std::collections::HashMap;
 use std::io;
 use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::net::UdpSocket;
 use tokio::time::interval;
 
 async fn receiver(socket: UdpSocket) -> Result<(), io::Error> {
     let mut buf: Vec<u8> = vec![0; 1024 * 64];
     let nodeid = CONFIG.lock().host_eid.clone();
+    let security = CONFIG.lock().beacon_security.clone();
     loop {
         if let Ok((size, peer)) = socket.recv_from(&mut buf).await {
-            trace!("received {} bytes", size);
+            trace!("received {} bytes from {}", size, peer);
             let deserialized: Beacon = match serde_cbor::from_slice(&buf[..size]) {
                 Ok(pkt) => pkt,
                 Err(e) => {
@@ -40,39 +43,94 @@ async fn receiver(socket: UdpSocket) -> Result<(), io::Error> {
                 debug!("Received beacon from myself, ignoring");
                 continue;
             }
-            if peers_add(dtnpeer) {
-                info!(
-                    "New peer discovered: {} @ {} (len={})",
-                    deserialized.eid(),
-                    peer,
-                    size
-                );
-            } else {
+            trace!("{}", deserialized);
+            let verdict = security.authenticate(&deserialized);
+            peer_discovered(dtnpeer, verdict, &security, &peer.to_string()).await;
+        }
+    }
+}
+
+/// Adds or updates a peer found by one of the discovery mechanisms and notifies routing.
+///
+/// Peers refused by the admission policy are ignored, CLAs it denies are removed from the peer.
+/// Peers that cannot be authenticated are handled according to the configured policy.
+pub async fn peer_discovered(
+    mut dtnpeer: DtnPeer,
+    verdict: BeaconVerdict,
+    security: &BeaconSecurityConfig,
+    origin: &str,
+) {
+    let eid = dtnpeer.eid.clone();
+    if !CONFIG.lock().admission.admit_peer(&mut dtnpeer) {
+        debug!("Peer refused by admission policy: {} @ {}", eid, origin);
+        return;
+    }
+    match verdict {
+        BeaconVerdict::Trusted => {
+            if security::release(&dtnpeer.node_name()).is_some() {
+                info!("Releasing authenticated peer from quarantine: {}", eid);
+            }
+        }
+        BeaconVerdict::Invalid => {
+            warn!(
+                "Dropping beacon with missing or invalid signature: {} @ {}",
+                eid, origin
+            );
+            return;
+        }
+        BeaconVerdict::Replayed => {
+            warn!(
+                "Dropping replayed or outdated signed beacon: {} @ {}",
+                eid, origin
+            );
+            return;
+        }
+        BeaconVerdict::Unauthenticated => match security.unauthenticated {
+            UnauthenticatedBeacons::Accept => {}
+            UnauthenticatedBeacons::Ignore => {
+                debug!("Ignoring unauthenticated beacon: {} @ {}", eid, origin);
+                return;
+            }
+            UnauthenticatedBeacons::Quarantine => {
                 debug!(
-                    "Beacon from known peer: {} @ {} (len={})",
-                    deserialized.eid(),
-                    peer,
-                    size
+                    "Quarantining peer of unauthenticated beacon: {} @ {}",
+                    eid, origin
                 );
-                // TODO: check if any fields have changed and update not only timestamp
-                if let Err(e) = peers_touch(deserialized.eid().node().unwrap().as_ref()) {
-                    error!("Failed to touch peer: {}", e);
-                }
+                security::quarantine(dtnpeer);
+                return;
             }
-            trace!("{}", deserialized);
-            if let Err(err) = routing_notify(RoutingNotifcation::EncounteredPeer(
-                deserialized.eid().clone(),
+        },
+    }
+    match peers_update(dtnpeer) {
+        None => {
+            info!("New peer discovered: {} @ {}", eid, origin);
+        }
+        Some(changes) if changes.is_empty() => {
+            debug!("Beacon from known peer: {} @ {}", eid, origin);
+        }
+        Some(changes) => {
+            info!(
+                "Known peer changed: {} @ {} (changed={:?})",
+                eid, origin, changes
+            );
+            if let Err(err) = routing_notify(RoutingNotifcation::PeerChanged(
+                eid.clone(),
+                changes.iter().map(|c| c.to_string()).collect(),
             ))
             .await
             {
-                info!("Error while encountered peer notification: {}", err);
+                info!("Error while peer changed notification: {}", err);
             }
         }
     }
+    if let Err(err) = routing_notify(RoutingNotifcation::EncounteredPeer(eid)).await {
+        info!("Error while encountered peer notification: {}", err);
+    }
 }
 
 async fn announcer(socket: UdpSocket, _v6: bool) {
     let mut task = interval(crate::CONFIG.lock().announcement_interval);
+    let mut last_timestamp = 0;
     loop {
         task.tick().await;
 
@@ -98,6 +156,8 @@ async fn announcer(socket: UdpSocket, _v6: bool) {
         //let nodeid = format!("dtn://{}", (*DTNCORE.lock()).nodeid);
         //let addr = "127.0.0.1:3003".parse().unwrap();
 
+        let signing_key = CONFIG.lock().beacon_security.signing_key;
+
         let mut destinations: HashMap<SocketAddr, u32> = HashMap::new();
         CONFIG
             .lock()
@@ -109,6 +169,16 @@ async fn announcer(socket: UdpSocket, _v6: bool) {
         for (destination, bsn) in destinations {
             (*CONFIG.lock()).update_beacon_sequence_number(&destination.to_string());
             pkt.set_beacon_sequence_number(bsn);
+            if let Some(key) = &signing_key {
+                // strictly increasing, receivers drop beacons not newer than the last one
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64;
+                last_timestamp = now.max(last_timestamp + 1);
+                pkt.set_timestamp(last_timestamp);
+                pkt.sign(key);
+            }
 
             if destination.ip().is_multicast() {
                 trace!(
@@ -136,6 +206,12 @@ async fn announcer(socket: UdpSocket, _v6: bool) {
     }
 }
 pub async fn spawn_neighbour_discovery() -> Result<()> {
+    if let Some(key) = CONFIG.lock().beacon_security.signing_key {
+        info!(
+            "Signing beacons, public key: {}",
+            security::encode_key(&security::public_key(&key))
+        );
+    }
     let v4 = CONFIG.lock().v4;
     let v6 = CONFIG.lock().v6;
     let port = CONFIG.lock().discovery_listen_port;
@@ -179,9 +255,9 @@ pub async fn spawn_neighbour_discovery() -> Result<()> {
 
         info!("Listening on {}", socket1.local_addr()?);
 
-        tokio::spawn(receiver(socket1));
+        crate::node::spawn(receiver(socket1));
 
-        tokio::spawn(announcer(socket2, false));
+        crate::node::spawn(announcer(socket2, false));
     }
     if v6 {
         let addr: SocketAddr = format!("[::1]:{}", port).parse()?;
@@ -222,8 +298,8 @@ pub async fn spawn_neighbour_discovery() -> Result<()> {
 
         info!("Listening on {}", socket1.local_addr()?);
 
-        tokio::spawn(receiver(socket1));
-        tokio::spawn(announcer(socket2, true));
+        crate::node::spawn(receiver(socket1));
+        crate::node::spawn(announcer(socket2, true));
     }
 
     Ok(())
@@ -0,0 +1,191 @@
+use crate::core::DtnPeer;
+use crate::ipnd::beacon::Beacon;
+use crate::node::node_local;
+use base64::prelude::*;
+use ed25519_dalek::{SigningKey, PUBLIC_KEY_LENGTH};
+use log::debug;
+use parking_lot::Mutex;
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+/*
+    Authentication of discovery beacons.
+
+    Nodes with a signing key attach an Ed25519 signature to every beacon. Beacons from nodes
+    with a trusted public key must carry a valid signature, everything else is unauthenticated
+    and handled according to the configured policy. Quarantined peers are kept aside without
+    being used for forwarding until a valid beacon is received from them.
+
+    Signatures authenticate the content of a beacon, not the address it was sent from. Signed
+    beacons carry their signing time, a beacon of a trusted node is only accepted if it is
+    strictly newer than the last one accepted from that node, so recorded beacons cannot be
+    replayed to redirect traffic.
+*/
+
+/// Maximum number of peers kept in quarantine
+pub const MAX_QUARANTINED_PEERS: usize = 256;
+
+pub type Key = [u8; PUBLIC_KEY_LENGTH];
+
+/// Handling of beacons that cannot be authenticated
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnauthenticatedBeacons {
+    /// Add peers as usual, the default for compatibility with nodes that do not sign beacons
+    #[default]
+    Accept,
+    /// Drop the beacon
+    Ignore,
+    /// Keep the peer in quarantine without using it for forwarding
+    Quarantine,
+}
+
+impl FromStr for UnauthenticatedBeacons {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "accept" => Ok(Self::Accept),
+            "ignore" => Ok(Self::Ignore),
+            "quarantine" => Ok(Self::Quarantine),
+            _ => Err(format!("unknown policy for unauthenticated beacons: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for UnauthenticatedBeacons {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Accept => "accept",
+            Self::Ignore => "ignore",
+            Self::Quarantine => "quarantine",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Settings of the `[discovery.security]` config section
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct BeaconSecurityConfig {
+    /// Secret key seed used to sign own beacons
+    #[serde(skip)]
+    pub signing_key: Option<Key>,
+    /// Public keys of trusted nodes by node name
+    #[serde(serialize_with = "serialize_keys")]
+    pub trusted_keys: BTreeMap<String, Key>,
+    pub unauthenticated: UnauthenticatedBeacons,
+}
+
+fn serialize_keys<S: Serializer>(keys: &BTreeMap<String, Key>, s: S) -> Result<S::Ok, S::Error> {
+    let encoded: BTreeMap<&String, String> = keys
+        .iter()
+        .map(|(node, key)| (node, encode_key(key)))
+        .collect();
+    encoded.serialize(s)
+}
+
+/// Parses a base64 encoded 32 byte key
+pub fn parse_key(encoded: &str) -> Result<Key, String> {
+    let bytes = BASE64_STANDARD
+        .decode(encoded.trim())
+        .map_err(|err| format!("invalid base64: {}", err))?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| {
+        format!("expected {} bytes, got {}", PUBLIC_KEY_LENGTH, bytes.len())
+    })
+}
+
+/// Derives the public key belonging to a secret key seed
+pub fn public_key(seed: &Key) -> Key {
+    SigningKey::from_bytes(seed).verifying_key().to_bytes()
+}
+
+pub fn encode_key(key: &Key) -> String {
+    BASE64_STANDARD.encode(key)
+}
+
+/// Result of checking the authenticity of a beacon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeaconVerdict {
+    /// Valid signature of a trusted node
+    Trusted,
+    /// No trusted key known for the sender
+    Unauthenticated,
+    /// Sender has a trusted key but the signature is missing or wrong
+    Invalid,
+    /// Valid signature of a trusted node, but not newer than the last accepted beacon
+    Replayed,
+}
+
+impl BeaconSecurityConfig {
+    /// Verdict for a node discovered by a mechanism without signatures, e.g., mDNS
+    pub fn check_unsigned(&self, node: &str) -> BeaconVerdict {
+        if self.trusted_keys.contains_key(node) {
+            BeaconVerdict::Invalid
+        } else {
+            BeaconVerdict::Unauthenticated
+        }
+    }
+    pub fn check(&self, beacon: &Beacon) -> BeaconVerdict {
+        let node = beacon.eid().node().unwrap_or_default();
+        match self.trusted_keys.get(&node) {
+            Some(key) if beacon.verify(key) => BeaconVerdict::Trusted,
+            Some(_) => BeaconVerdict::Invalid,
+            None => BeaconVerdict::Unauthenticated,
+        }
+    }
+    /// Checks a received beacon like [`check`](Self::check) and additionally rejects
+    /// replays of beacons from trusted nodes. The signing time of an accepted beacon is
+    /// remembered as the minimum for the next one.
+    pub fn authenticate(&self, beacon: &Beacon) -> BeaconVerdict {
+        let verdict = self.check(beacon);
+        if verdict != BeaconVerdict::Trusted {
+            return verdict;
+        }
+        let node = beacon.eid().node().unwrap_or_default();
+        match beacon.timestamp() {
+            Some(timestamp) if accept_timestamp(&node, timestamp) => BeaconVerdict::Trusted,
+            _ => BeaconVerdict::Replayed,
+        }
+    }
+}
+
+node_local! {
+    static ref QUARANTINE: Mutex<BTreeMap<String, DtnPeer>> = Mutex::new(BTreeMap::new());
+    static ref LAST_TIMESTAMPS: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
+}
+
+/// Records the signing time of a trusted beacon if it is newer than the last accepted one
+fn accept_timestamp(node: &str, timestamp: u64) -> bool {
+    let mut last = LAST_TIMESTAMPS.lock();
+    match last.get(node) {
+        Some(previous) if *previous >= timestamp => false,
+        _ => {
+            last.insert(node.to_string(), timestamp);
+            true
+        }
+    }
+}
+
+/// Puts a peer of an unauthenticated beacon into quarantine, replacing older information
+pub fn quarantine(peer: DtnPeer) {
+    let mut quarantine = QUARANTINE.lock();
+    quarantine.retain(|_, p| p.still_valid());
+    let node = peer.node_name();
+    if quarantine.len() >= MAX_QUARANTINED_PEERS && !quarantine.contains_key(&node) {
+        debug!("Quarantine full, dropping peer {}", node);
+        return;
+    }
+    quarantine.insert(node, peer);
+}
+
+/// Removes a peer from quarantine, e.g., after receiving an authenticated beacon
+pub fn release(node: &str) -> Option<DtnPeer> {
+    QUARANTINE.lock().remove(node)
+}
+
+pub fn quarantined_peers() -> BTreeMap<String, DtnPeer> {
+    let mut quarantine = QUARANTINE.lock();
+    quarantine.retain(|_, p| p.still_valid());
+    quarantine.clone()
+}
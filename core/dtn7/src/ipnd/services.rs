@@ -4,7 +4,7 @@ use log::warn;
 use serde::de::{SeqAccess, Visitor};
 use serde::ser::{SerializeSeq, Serializer};
 use serde::{de, Deserialize, Deserializer, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
 
 /// Struct representing the ServiceBlock used in Beacons to advertise additional services
@@ -66,6 +66,8 @@ impl ServiceBlock {
                         .expect("Couldn't parse byte array into string");
                     convert.insert(*tag, message);
                 }
+                // authenticity of the beacon, not a service of the peer
+                Service::SIGNATURE | Service::TIMESTAMP => {}
                 _ => {
                     warn!("Unknown service encountered. Compare senders IPND version with this one to check for incompatibilities.");
                 }
@@ -88,6 +90,33 @@ impl ServiceBlock {
     pub fn add_custom_service(&mut self, tag: u8, service: &[u8]) {
         self.services.insert(tag, service.to_owned());
     }
+    /// Returns the Ed25519 signature of the beacon (if present)
+    pub fn signature(&self) -> Option<&Vec<u8>> {
+        self.services.get(&Service::SIGNATURE)
+    }
+    /// Sets the Ed25519 signature of the beacon
+    pub fn set_signature(&mut self, signature: &[u8]) {
+        self.services
+            .insert(Service::SIGNATURE, signature.to_owned());
+    }
+    /// Returns the signing time of the beacon in milliseconds since the Unix epoch (if present)
+    pub fn timestamp(&self) -> Option<u64> {
+        let bytes = self.services.get(&Service::TIMESTAMP)?;
+        Some(u64::from_be_bytes(bytes.as_slice().try_into().ok()?))
+    }
+    /// Sets the signing time of the beacon in milliseconds since the Unix epoch
+    pub fn set_timestamp(&mut self, timestamp: u64) {
+        self.services
+            .insert(Service::TIMESTAMP, timestamp.to_be_bytes().to_vec());
+    }
+    /// Returns the services without the signature, sorted by tag
+    pub fn unsigned_services(&self) -> BTreeMap<u8, &Vec<u8>> {
+        self.services
+            .iter()
+            .filter(|(tag, _)| **tag != Service::SIGNATURE)
+            .map(|(tag, payload)| (*tag, payload))
+            .collect()
+    }
     /// This method sets the clas vector of a ServiceBlock to the one provided
     pub fn set_clas(&mut self, clas: Vec<(String, Option<u16>)>) {
         self.clas = clas;
@@ -221,6 +250,12 @@ impl std::fmt::Display for ServiceBlock {
                     format!("{}. Tag = {} Address service. Street {}; House Number {}; Postal Number {}; City {}; Country Code {}\n",
                             counter, tag, address[0],address[1],address[2],address[3],address[4])
                 }
+                Service::SIGNATURE => {
+                    format!("{}. Tag = {} Beacon signature\n", counter, tag)
+                }
+                Service::TIMESTAMP => {
+                    format!("{}. Tag = {} Beacon signing time\n", counter, tag)
+                }
                 _ => {
                     warn!("Unknown service encountered. Compare senders IPND version with this one to check for incompatibilities.");
                     String::new()
@@ -297,4 +332,8 @@ impl Service {
     pub const GEO_LOCATION: u8 = 127;
     pub const BATTERY: u8 = 191;
     pub const ADDRESS: u8 = 255;
+    /// Signing time of the beacon, protects signed beacons against replays
+    pub const TIMESTAMP: u8 = 222;
+    /// Ed25519 signature over the rest of the beacon
+    pub const SIGNATURE: u8 = 223;
 }
@@ -4,12 +4,18 @@ pub mod core;
 pub mod dtnconfig;
 pub mod dtnd;
 pub mod ipnd;
+pub mod node;
 pub mod routing;
+pub mod sim;
 
 use crate::cla::CLAsAvailable;
 use crate::core::bundlepack::BundlePack;
+use crate::core::events::{NodeEvent, TimedEvent, EVENT_BUFFER_SIZE};
+use crate::core::journal::JournalEvent;
+use crate::core::scheduler::SchedulerCmd;
+use crate::core::status_reports::StatusReportLog;
 use crate::core::store::{BundleStore, InMemoryBundleStore};
-use crate::core::DtnStatistics;
+use crate::core::{DtnStatistics, PeerType};
 use crate::routing::{RoutingAgent, RoutingCmd};
 use bp7::{Bundle, EndpointID};
 use cla::{CLAEnum, ClaSenderTask};
@@ -25,12 +31,14 @@ use crate::core::peer::PeerAddress;
 use crate::core::store::BundleStoresEnum;
 use anyhow::{bail, Context, Result};
 use lazy_static::*;
+use node::node_local;
 use parking_lot::Mutex;
 use std::collections::{BTreeMap, HashMap};
-use tokio::sync::mpsc::Sender;
+use std::time::Duration;
+use tokio::sync::mpsc::{Sender, UnboundedSender};
 use tokio::sync::oneshot;
 
-lazy_static! {
+node_local! {
     pub static ref CONFIG: Mutex<DtnConfig> = Mutex::new(DtnConfig::new());
     pub static ref DTNCORE: Mutex<DtnCore> = Mutex::new(DtnCore::new());
     pub static ref PEERS: Mutex<BTreeMap<String, DtnPeer>> = Mutex::new(BTreeMap::new());
@@ -38,15 +46,28 @@ lazy_static! {
     pub static ref SENDERTASK: Mutex<Option<Sender<Bundle>>> = Mutex::new(None);
     pub static ref STORE: Mutex<BundleStoresEnum> = Mutex::new(InMemoryBundleStore::new().into());
     pub static ref CLAS: Mutex<Vec<CLAEnum>> = Mutex::new(Vec::new());
+    pub static ref EVENTS: tokio::sync::broadcast::Sender<TimedEvent> =
+        tokio::sync::broadcast::channel(EVENT_BUFFER_SIZE).0;
+    pub static ref SCHEDULER: Mutex<Option<UnboundedSender<SchedulerCmd>>> = Mutex::new(None);
+    pub static ref STATUS_REPORTS: Mutex<StatusReportLog> = Mutex::new(StatusReportLog::default());
+    /// Held exclusively while the routing agent is replaced, notifications wait for the new agent
+    static ref ROUTING_SWAP: tokio::sync::RwLock<()> = tokio::sync::RwLock::new(());
 }
 
 pub type BundleID = String;
 
 pub fn cla_add(cla: CLAEnum) {
+    let event = NodeEvent::ClaUp {
+        name: cla.name().to_string(),
+        port: cla.port(),
+    };
     (*CLAS.lock()).push(cla);
+    crate::core::events::publish(event);
+    crate::core::scheduler::wake("cla up");
 }
 pub fn cla_remove(name: String) {
-    (*CLAS.lock()).retain(|value| value.name() != name)
+    (*CLAS.lock()).retain(|value| value.name() != name);
+    crate::core::events::publish(NodeEvent::ClaDown { name });
 }
 pub fn cla_is_external(name: String) -> bool {
     return (*CLAS.lock()).iter().any(|p| match p {
@@ -107,16 +128,108 @@ pub fn get_sequence(destination: &str) -> u32 {
 /// adds a new peer to the DTN core
 /// return true if peer was seen first time
 /// return false if peer was already known
-pub fn peers_add(peer: DtnPeer) -> bool {
-    debug!("Adding peer eid={}", peer.eid.node().unwrap());
-    (*PEERS.lock())
-        .insert(peer.eid.node().unwrap(), peer)
-        .is_none()
+pub fn peers_add(mut peer: DtnPeer) -> bool {
+    let node = peer.eid.node().unwrap();
+    debug!("Adding peer eid={}", node);
+    let mut peers = PEERS.lock();
+    let is_new = match peers.get(&node) {
+        Some(known) => {
+            peer.metrics = known.metrics.clone();
+            false
+        }
+        None => {
+            crate::core::peer::restore_metrics(&mut peer);
+            true
+        }
+    };
+    peers.insert(node, peer);
+    drop(peers);
+    if is_new {
+        crate::core::scheduler::wake("peer added");
+    }
+    is_new
+}
+
+/// Adds a peer or updates the advertised attributes of a known one.
+///
+/// Only dynamic peers are updated, static and scheduled peers keep their configured
+/// attributes and are just marked as seen.
+///
+/// Returns `None` for new peers, otherwise the names of the changed fields.
+pub fn peers_update(peer: DtnPeer) -> Option<Vec<&'static str>> {
+    let node = peer.eid.node().unwrap();
+    let changed = (*PEERS.lock()).get_mut(&node).map(|known| {
+        if known.con_type == PeerType::Dynamic {
+            known.update_from(&peer)
+        } else {
+            known.touch();
+            Vec::new()
+        }
+    });
+    let changed = match changed {
+        Some(changed) => changed,
+        None => {
+            peers_add(peer);
+            return None;
+        }
+    };
+    if !changed.is_empty() {
+        debug!("Updated peer eid={}: {:?}", node, changed);
+    }
+    // a new address or CLA may make the peer reachable
+    if changed.iter().any(|c| *c == "addr" || *c == "cla_list") {
+        crate::core::scheduler::wake_node(&node, "peer changed");
+    }
+    Some(changed)
 }
 
 pub fn peers_remove(peer_id: &str) {
     debug!("Removing peer eid={}", peer_id);
-    (*PEERS.lock()).remove(peer_id);
+    if let Some(peer) = (*PEERS.lock()).remove(peer_id) {
+        crate::core::peer::archive_metrics(&peer);
+    }
+}
+
+/// Removes a peer only if it still has the given type, e.g., a scheduled peer that was
+/// replaced by a static one in the meantime is kept. Returns whether the peer was removed.
+pub fn peers_remove_if_type(peer_id: &str, con_type: PeerType) -> bool {
+    let removed = {
+        let mut peers = PEERS.lock();
+        match peers.get(peer_id) {
+            Some(peer) if peer.con_type == con_type => peers.remove(peer_id),
+            _ => None,
+        }
+    };
+    match removed {
+        Some(peer) => {
+            debug!("Removing peer eid={}", peer_id);
+            crate::core::peer::archive_metrics(&peer);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Records the outcome of a bundle transfer in the link metrics of a peer
+pub fn peers_record_transfer(
+    peer_id: &str,
+    cla: &str,
+    bytes: usize,
+    duration: Duration,
+    success: bool,
+) {
+    if let Some(peer) = (*PEERS.lock()).get_mut(peer_id) {
+        peer.metrics
+            .cla_mut(cla)
+            .record_transfer(bytes, duration, success);
+    }
+}
+
+/// Records a round-trip time measured by a CLA in the link metrics of a peer
+pub fn peers_record_rtt(peer_id: &str, cla: &str, rtt: Duration) {
+    if let Some(peer) = (*PEERS.lock()).get_mut(peer_id) {
+        peer.metrics.cla_mut(cla).record_rtt(rtt);
+    }
 }
 
 pub fn peers_count() -> usize {
@@ -178,7 +291,13 @@ pub fn store_add_bundle_if_unknown(bndl: &Bundle) -> Result<bool> {
 
 pub fn store_remove(bid: &str) -> Result<()> {
     info!("Removing bundle {}", bid);
-    if let Err(err) = (*STORE.lock()).remove(bid) {
+    let store = &mut (*STORE.lock());
+    journal_append(
+        store,
+        bid,
+        JournalEvent::constraints(&[Constraint::Deleted].into()),
+    );
+    if let Err(err) = store.remove(bid) {
         error!("store_remove: {}", err);
         return Err(err);
     }
@@ -196,7 +315,36 @@ fn store_remove_if_singleton_bundle(bundle: &Bundle) {
 }
 
 pub fn store_update_metadata(bp: &BundlePack) -> Result<()> {
-    (*STORE.lock()).update_metadata(bp)
+    let store = &mut (*STORE.lock());
+    match store.get_metadata(bp.id()) {
+        Some(stored) => {
+            // the stored journal is authoritative, the given metadata might be outdated
+            let mut meta = bp.clone();
+            meta.journal = stored.journal;
+            if stored.constraints != bp.constraints {
+                crate::core::journal::append(
+                    &mut meta.journal,
+                    JournalEvent::constraints(&bp.constraints),
+                );
+            }
+            store.update_metadata(&meta)
+        }
+        None => store.update_metadata(bp),
+    }
+}
+
+fn journal_append(store: &mut BundleStoresEnum, bid: &str, event: JournalEvent) {
+    if let Some(mut meta) = store.get_metadata(bid) {
+        crate::core::journal::append(&mut meta.journal, event);
+        if let Err(err) = store.update_metadata(&meta) {
+            error!("Error while updating journal of bundle {}: {}", bid, err);
+        }
+    }
+}
+
+/// Records a lifecycle event in the journal of a bundle
+pub fn store_journal(bid: &str, event: JournalEvent) {
+    journal_append(&mut STORE.lock(), bid, event);
 }
 pub fn store_has_item(bid: &str) -> bool {
     (*STORE.lock()).has_item(bid)
@@ -208,7 +356,7 @@ pub fn store_get_metadata(bpid: &str) -> Option<BundlePack> {
     (*STORE.lock()).get_metadata(bpid)
 }
 
-pub fn store_delete_expired() {
+pub async fn store_delete_expired() {
     let all_bids = (*STORE.lock()).bundles();
 
     let all_but_deleted: Vec<&BundlePack> = all_bids
@@ -219,20 +367,108 @@ pub fn store_delete_expired() {
     for meta in all_but_deleted {
         if meta.has_expired() {
             debug!("Bundle {} is too old, deleting it", meta.id);
-            if store_remove(&meta.id).is_err() {
-                error!("Error while deleting expired bundle {}", meta.id);
+            if let Err(err) = crate::core::processing::remove(
+                &meta.id,
+                bp7::administrative_record::LIFETIME_EXPIRED,
+            )
+            .await
+            {
+                error!("Error while deleting expired bundle {}: {}", meta.id, err);
             }
         }
     }
 }
+/// Passes a command to the routing agent.
+///
+/// If the agent rejects the command, the error is a [`routing::CommandError`](crate::routing::CommandError).
 pub async fn routing_cmd(cmd: String) -> Result<()> {
+    let (reply_tx, reply_rx) = oneshot::channel();
+
     let chan = DTNCORE.lock().routing_agent.channel();
-    if let Err(err) = chan.send(RoutingCmd::Command(cmd)).await {
-        bail!("Error while sending notification: {}", err);
+    if let Err(err) = chan.send(RoutingCmd::Command(cmd, reply_tx)).await {
+        bail!("Error while sending command to routing agent: {}", err);
+    }
+    // wait for reply or timeout
+    tokio::time::timeout(std::time::Duration::from_secs(1), reply_rx).await???;
+    // routing commands may change the routing table
+    crate::core::scheduler::wake("routing command");
+    Ok(())
+}
+
+/// Tells a newly started routing agent about the current peers and the bundles waiting for forwarding
+pub(crate) async fn routing_replay(chan: &Sender<RoutingCmd>) -> Result<()> {
+    let peers: Vec<EndpointID> = (*PEERS.lock()).values().map(|p| p.eid.clone()).collect();
+    for eid in peers {
+        chan.send(RoutingCmd::Notify(RoutingNotifcation::EncounteredPeer(eid)))
+            .await?;
+    }
+    let forwarding: Vec<String> = (*STORE.lock()).forwarding();
+    for bid in forwarding {
+        if let Some(bndl) = store_get_bundle(&bid) {
+            chan.send(RoutingCmd::Notify(RoutingNotifcation::IncomingBundle(bndl)))
+                .await?;
+        }
     }
     Ok(())
 }
 
+/// Replaces the active routing agent at runtime.
+///
+/// The given settings replace those of the respective agents. The new agent learns about
+/// the current peers and the bundles waiting for forwarding before it takes over, then the
+/// old agent is shut down and forwarding of all waiting bundles is triggered. Notifications
+/// are held back meanwhile and passed to the new agent afterwards.
+///
+/// External routing is only available if configured at startup, as its transports and
+/// endpoints are not started later on.
+pub async fn routing_replace(
+    strategy: &str,
+    settings: BTreeMap<String, HashMap<String, String>>,
+) -> Result<()> {
+    if strategy == "external" {
+        bail!("external routing can only be configured at startup");
+    }
+    let previous = {
+        let mut cfg = CONFIG.lock();
+        let previous = (cfg.routing.clone(), cfg.routing_settings.clone());
+        cfg.routing = strategy.to_string();
+        cfg.routing_settings.extend(settings);
+        previous
+    };
+    let agent = match crate::routing::try_new(strategy) {
+        Ok(agent) => agent,
+        Err(err) => {
+            let mut cfg = CONFIG.lock();
+            cfg.routing = previous.0;
+            cfg.routing_settings = previous.1;
+            return Err(err);
+        }
+    };
+
+    let swap = ROUTING_SWAP.get();
+    let swap = swap.write().await;
+    if let Err(err) = routing_replay(&agent.channel()).await {
+        drop(swap);
+        {
+            let mut cfg = CONFIG.lock();
+            cfg.routing = previous.0;
+            cfg.routing_settings = previous.1;
+        }
+        if let Err(err) = agent.channel().send(RoutingCmd::Shutdown).await {
+            debug!("New routing agent already stopped: {}", err);
+        }
+        return Err(err);
+    }
+    let old = std::mem::replace(&mut DTNCORE.lock().routing_agent, agent);
+    drop(swap);
+    info!("Replaced routing agent {} with {}", previous.0, strategy);
+    if let Err(err) = old.channel().send(RoutingCmd::Shutdown).await {
+        debug!("Old routing agent already stopped: {}", err);
+    }
+    crate::core::scheduler::wake("routing replaced");
+    Ok(())
+}
+
 pub async fn routing_get_data(param: String) -> Result<String> {
     let (reply_tx, reply_rx) = oneshot::channel();
 
@@ -247,6 +483,28 @@ pub async fn routing_get_data(param: String) -> Result<String> {
 }
 
 pub async fn routing_notify(notification: RoutingNotifcation) -> Result<()> {
+    match &notification {
+        RoutingNotifcation::EncounteredPeer(eid) => {
+            crate::core::events::publish(NodeEvent::PeerEncountered {
+                eid: eid.to_string(),
+            });
+        }
+        RoutingNotifcation::DroppedPeer(eid) => {
+            crate::core::events::publish(NodeEvent::PeerDropped {
+                eid: eid.to_string(),
+            })
+        }
+        RoutingNotifcation::PeerChanged(eid, changes) => {
+            crate::core::events::publish(NodeEvent::PeerChanged {
+                eid: eid.to_string(),
+                changes: changes.clone(),
+            })
+        }
+        _ => {}
+    }
+    crate::core::events::publish(NodeEvent::from(&notification));
+    let swap = ROUTING_SWAP.get();
+    let _swap = swap.read().await;
     let chan = DTNCORE.lock().routing_agent.channel();
     if let Err(err) = chan.send(RoutingCmd::Notify(notification)).await {
         bail!("Error while sending notification: {}", err);
@@ -0,0 +1,462 @@
+//! Independent DTN nodes within one process.
+//!
+//! The state of a node, e.g., [`CONFIG`](crate::CONFIG), [`STORE`](crate::STORE) or
+//! [`PEERS`](crate::PEERS), is declared with `node_local!` statics. Every node started
+//! with a [`DtnNode`] gets its own value of these statics, code running outside of such
+//! a node uses the default node, e.g., `dtnd` and the tests.
+//!
+//! Tasks spawned with [`spawn`] or [`spawn_blocking`] stay part of the node they were
+//! spawned from, plain `tokio::spawn` or threads have to enter the node explicitly, e.g.,
+//! with [`DtnNode::scope`] or [`DtnNode::enter`], otherwise they use the default node.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dtn7::cla::dummy::DummyConvergenceLayer;
+//! use dtn7::node::DtnNode;
+//! use dtn7::DtnConfig;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let mut cfg = DtnConfig::new();
+//! cfg.host_eid = "dtn://node1/".try_into()?;
+//! let node = DtnNode::builder()
+//!     .config(cfg)
+//!     .cla(|| DummyConvergenceLayer::new(None).into())
+//!     .routing("epidemic")
+//!     .httpd(false)
+//!     .start()
+//!     .await?;
+//! let peers = node.enter(|| dtn7::PEERS.lock().len());
+//! node.shutdown().await;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::cla::CLAEnum;
+use crate::core::store::{BundleStore, BundleStoresEnum, InMemoryBundleStore};
+use crate::routing::plugin::{register_routing_plugin, RoutingPlugin};
+use crate::routing::{RoutingAgent, RoutingCmd};
+use crate::{lazy_static, DtnConfig};
+use log::error;
+use parking_lot::{Mutex, MutexGuard};
+use std::any::Any;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, Weak};
+use std::time::Duration;
+use tokio::task::{AbortHandle, JoinHandle};
+
+/// Maximum number of `node_local!` statics
+const MAX_NODE_LOCALS: usize = 64;
+/// Time the TCPCL sessions of a node get to send their SESS_TERM on shutdown
+const SESSION_TERM_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// State of a node, the values of its `node_local!` statics and its tasks
+pub(crate) struct NodeState {
+    id: u64,
+    slots: Box<[OnceLock<Box<dyn Any + Send + Sync>>]>,
+    tasks: Mutex<Vec<AbortHandle>>,
+    stopped: AtomicBool,
+}
+
+impl NodeState {
+    fn new() -> Self {
+        static NEXT_NODE_ID: AtomicU64 = AtomicU64::new(0);
+        NodeState {
+            id: NEXT_NODE_ID.fetch_add(1, Ordering::Relaxed),
+            slots: (0..MAX_NODE_LOCALS).map(|_| OnceLock::new()).collect(),
+            tasks: Mutex::new(Vec::new()),
+            stopped: AtomicBool::new(false),
+        }
+    }
+    fn track(&self, task: AbortHandle) {
+        // tasks spawned during or after the shutdown are stopped right away
+        if self.stopped.load(Ordering::Acquire) {
+            task.abort();
+            return;
+        }
+        let mut tasks = self.tasks.lock();
+        tasks.retain(|task| !task.is_finished());
+        tasks.push(task);
+    }
+}
+
+tokio::task_local! {
+    static NODE: Arc<NodeState>;
+}
+
+lazy_static! {
+    static ref DEFAULT_NODE: Arc<NodeState> = Arc::new(NodeState::new());
+}
+
+/// The node of the running task
+pub(crate) fn current() -> Arc<NodeState> {
+    NODE.try_with(Arc::clone)
+        .unwrap_or_else(|_| DEFAULT_NODE.clone())
+}
+
+/// Runs a future as part of the given node
+pub(crate) async fn scope<F: Future>(node: Arc<NodeState>, f: F) -> F::Output {
+    NODE.scope(node, f).await
+}
+
+/// A static with a separate value for each node, initialized on first use.
+///
+/// Outside of a node the value of the default node is used. The value is borrowed with
+/// [`get`](NodeLocal::get) or, for statics holding a mutex, locked with `lock`. Both return
+/// guards keeping the node alive for as long as they are held.
+pub struct NodeLocal<T> {
+    init: fn() -> T,
+    index: OnceLock<usize>,
+}
+
+impl<T> NodeLocal<T> {
+    #[doc(hidden)]
+    pub const fn new(init: fn() -> T) -> Self {
+        NodeLocal {
+            init,
+            index: OnceLock::new(),
+        }
+    }
+    /// Slot of this static in the state of every node
+    fn index(&self) -> usize {
+        static NEXT_INDEX: AtomicUsize = AtomicUsize::new(0);
+        *self.index.get_or_init(|| {
+            let index = NEXT_INDEX.fetch_add(1, Ordering::Relaxed);
+            assert!(index < MAX_NODE_LOCALS, "too many node locals");
+            index
+        })
+    }
+}
+
+impl<T: Send + Sync + 'static> NodeLocal<T> {
+    /// The value of the current node together with the node keeping it alive
+    fn value(&self) -> (Arc<NodeState>, &'static T) {
+        let node = current();
+        // initialized on first use, the initializer may use other node locals
+        let value: &T = node.slots[self.index()]
+            .get_or_init(|| Box::new((self.init)()))
+            .downcast_ref()
+            .expect("node local of wrong type");
+        // SAFETY: the value is owned by the node state, which is only freed once the returned
+        // node and every guard holding it are dropped
+        let value = unsafe { &*(value as *const T) };
+        (node, value)
+    }
+    /// Borrows the value of the current node
+    pub fn get(&self) -> NodeRef<T> {
+        let (node, value) = self.value();
+        NodeRef { value, _node: node }
+    }
+}
+
+impl<T: Send + 'static> NodeLocal<Mutex<T>> {
+    /// Locks the value of the current node
+    pub fn lock(&self) -> NodeGuard<MutexGuard<'static, T>> {
+        let (node, value) = self.value();
+        NodeGuard {
+            guard: value.lock(),
+            _node: node,
+        }
+    }
+}
+
+impl<T: Send + 'static> NodeLocal<tokio::sync::Mutex<T>> {
+    /// Locks the value of the current node
+    pub async fn lock(&self) -> NodeGuard<tokio::sync::MutexGuard<'static, T>> {
+        let (node, value) = self.value();
+        NodeGuard {
+            guard: value.lock().await,
+            _node: node,
+        }
+    }
+}
+
+/// Borrowed value of a [`NodeLocal`], keeps the node alive
+pub struct NodeRef<T: 'static> {
+    value: &'static T,
+    _node: Arc<NodeState>,
+}
+
+impl<T> Deref for NodeRef<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+/// Locked value of a [`NodeLocal`], keeps the node alive while the lock is held
+pub struct NodeGuard<G> {
+    // dropped before the node
+    guard: G,
+    _node: Arc<NodeState>,
+}
+
+impl<G: Deref> Deref for NodeGuard<G> {
+    type Target = G::Target;
+
+    fn deref(&self) -> &G::Target {
+        &self.guard
+    }
+}
+
+impl<G: DerefMut> DerefMut for NodeGuard<G> {
+    fn deref_mut(&mut self) -> &mut G::Target {
+        &mut self.guard
+    }
+}
+
+/// Declares statics with a separate value for each node, same syntax as `lazy_static!`.
+macro_rules! node_local {
+    ($(#[$attr:meta])* $vis:vis static ref $name:ident : $t:ty = $init:expr; $($rest:tt)*) => {
+        $(#[$attr])*
+        $vis static $name: $crate::node::NodeLocal<$t> = $crate::node::NodeLocal::new({
+            fn init() -> $t {
+                $init
+            }
+            init
+        });
+        $crate::node::node_local!($($rest)*);
+    };
+    () => {};
+}
+pub(crate) use node_local;
+
+/// Spawns a task as part of the node of the running task.
+pub fn spawn<F>(f: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match NODE.try_with(Arc::clone) {
+        Ok(node) => {
+            let task = tokio::spawn(NODE.scope(node.clone(), f));
+            node.track(task.abort_handle());
+            task
+        }
+        Err(_) => tokio::spawn(f),
+    }
+}
+
+/// Runs blocking code on a separate thread as part of the node of the running task.
+pub fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    match NODE.try_with(Arc::clone) {
+        Ok(node) => tokio::task::spawn_blocking(move || NODE.sync_scope(node, f)),
+        Err(_) => tokio::task::spawn_blocking(f),
+    }
+}
+
+/// Executor for the connections of the httpd, keeping them part of the node
+#[derive(Clone, Copy)]
+pub(crate) struct NodeExecutor;
+
+impl<F> hyper::rt::Executor<F> for NodeExecutor
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    fn execute(&self, f: F) {
+        spawn(f);
+    }
+}
+
+type ClaFactory = Box<dyn FnOnce() -> CLAEnum + Send>;
+type PluginRegistration = Box<dyn FnOnce() -> anyhow::Result<()> + Send>;
+
+/// Builds and starts a [`DtnNode`].
+pub struct DtnNodeBuilder {
+    config: DtnConfig,
+    clas: Vec<ClaFactory>,
+    store: Option<BundleStoresEnum>,
+    routing: Option<String>,
+    routing_settings: Vec<(String, String, String)>,
+    plugins: Vec<PluginRegistration>,
+    httpd: bool,
+}
+
+impl DtnNodeBuilder {
+    /// Uses the given config instead of a default config with a random node id, the other
+    /// settings of the builder take precedence
+    pub fn config(mut self, config: DtnConfig) -> Self {
+        self.config = config;
+        self
+    }
+    /// Adds a convergence layer, created when the node starts
+    pub fn cla<F>(mut self, cla: F) -> Self
+    where
+        F: FnOnce() -> CLAEnum + Send + 'static,
+    {
+        self.clas.push(Box::new(cla));
+        self
+    }
+    /// Uses the given bundle store instead of the one configured by `db`
+    pub fn store(mut self, store: BundleStoresEnum) -> Self {
+        self.store = Some(store);
+        self
+    }
+    /// Sets the routing strategy, e.g., `epidemic` or the name of a routing plugin
+    pub fn routing(mut self, strategy: &str) -> Self {
+        self.routing = Some(strategy.to_string());
+        self
+    }
+    /// Sets a setting of a routing agent, e.g., `("sprayandwait", "num_copies", "4")`
+    pub fn routing_setting(mut self, agent: &str, key: &str, value: &str) -> Self {
+        self.routing_settings
+            .push((agent.to_string(), key.to_string(), value.to_string()));
+        self
+    }
+    /// Registers a routing plugin when the node starts and uses it as routing strategy,
+    /// starting fails if `name` is a built-in routing agent
+    pub fn routing_plugin<P, F>(mut self, name: &str, factory: F) -> Self
+    where
+        P: RoutingPlugin,
+        F: Fn() -> P + Send + Sync + 'static,
+    {
+        let plugin = name.to_string();
+        self.plugins
+            .push(Box::new(move || register_routing_plugin(&plugin, factory)));
+        self.routing(name)
+    }
+    /// Serves the HTTP interface on the configured web port, enabled by default
+    pub fn httpd(mut self, enabled: bool) -> Self {
+        self.httpd = enabled;
+        self
+    }
+    /// Starts the node, returns when all services are running
+    pub async fn start(self) -> anyhow::Result<DtnNode> {
+        let node = DtnNode {
+            state: Arc::new(NodeState::new()),
+        };
+        let DtnNodeBuilder {
+            mut config,
+            clas,
+            store,
+            routing,
+            routing_settings,
+            plugins,
+            httpd,
+        } = self;
+        if let Some(routing) = routing {
+            config.routing = routing;
+        }
+        for (agent, key, value) in routing_settings {
+            config
+                .routing_settings
+                .entry(agent)
+                .or_default()
+                .insert(key, value);
+        }
+        let started = node
+            .scope(async move {
+                for register in plugins {
+                    register()?;
+                }
+                let clas = clas.into_iter().map(|cla| cla()).collect();
+                crate::dtnd::daemon::init_node(config, store, clas).await?;
+                if httpd {
+                    crate::dtnd::httpd::bind_httpd().await?;
+                }
+                anyhow::Ok(())
+            })
+            .await;
+        if let Err(err) = started {
+            node.shutdown().await;
+            return Err(err);
+        }
+        Ok(node)
+    }
+}
+
+/// Handle of a DTN node with its own config, core, peers, store and CLAs.
+///
+/// The state of a node is freed once it was [shut down](DtnNode::shutdown) and all its
+/// handles are dropped.
+#[derive(Clone)]
+pub struct DtnNode {
+    state: Arc<NodeState>,
+}
+
+impl std::fmt::Debug for DtnNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "DtnNode({})", self.state.id)
+    }
+}
+
+impl DtnNode {
+    pub fn builder() -> DtnNodeBuilder {
+        DtnNodeBuilder {
+            config: DtnConfig::new(),
+            clas: Vec::new(),
+            store: None,
+            routing: None,
+            routing_settings: Vec::new(),
+            plugins: Vec::new(),
+            httpd: true,
+        }
+    }
+    /// Runs a future as part of this node, e.g., to call `dtn7::send_bundle`
+    pub async fn scope<F: Future>(&self, f: F) -> F::Output {
+        scope(self.state.clone(), f).await
+    }
+    /// Runs a closure as part of this node, e.g., to read its `PEERS`
+    pub fn enter<R>(&self, f: impl FnOnce() -> R) -> R {
+        NODE.sync_scope(self.state.clone(), f)
+    }
+    /// Spawns a task as part of this node
+    pub fn spawn<F>(&self, f: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.enter(|| spawn(f))
+    }
+    /// Handle not keeping the node alive, e.g., for the dummy network the node is attached to
+    pub(crate) fn downgrade(&self) -> WeakDtnNode {
+        WeakDtnNode(Arc::downgrade(&self.state))
+    }
+    /// Stops the node: the routing agent is stopped, TCPCL sessions are terminated, the
+    /// remaining tasks, e.g., CLAs and the httpd, are aborted and the store is flushed and
+    /// closed
+    pub async fn shutdown(&self) {
+        self.scope(async {
+            let agent = crate::DTNCORE.lock().routing_agent.channel();
+            let _ = agent.send(RoutingCmd::Shutdown).await;
+            crate::cla::tcp::close_sessions(SESSION_TERM_TIMEOUT).await;
+        })
+        .await;
+        self.state.stopped.store(true, Ordering::Release);
+        let tasks: Vec<AbortHandle> = std::mem::take(&mut *self.state.tasks.lock());
+        for task in &tasks {
+            task.abort();
+        }
+        // let the runtime drop the aborted tasks, closing their sockets
+        while tasks.iter().any(|task| !task.is_finished()) {
+            tokio::task::yield_now().await;
+        }
+        self.enter(|| {
+            let mut store = crate::STORE.lock();
+            if let Err(err) = store.flush() {
+                error!("Error flushing the store of {:?}: {}", self, err);
+            }
+            // closes persistent stores, e.g., releasing the lock of a sled database
+            *store = InMemoryBundleStore::new().into();
+        });
+    }
+}
+
+/// Handle of a node not keeping its state alive
+#[derive(Clone)]
+pub(crate) struct WeakDtnNode(Weak<NodeState>);
+
+impl WeakDtnNode {
+    /// The node, unless its state was already freed
+    pub fn upgrade(&self) -> Option<DtnNode> {
+        self.0.upgrade().map(|state| DtnNode { state })
+    }
+}
@@ -0,0 +1,430 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use super::{CommandError, RoutingAgent, RoutingAgentsEnum, RoutingCmd};
+use crate::core::bundlepack::BundlePack;
+use crate::{store_get_bundle, CONFIG};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use bp7::canonical::{CanonicalBlockType, CanonicalData};
+use bp7::Bundle;
+use glob_match::glob_match;
+use log::{debug, error, info};
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::Sender;
+
+/// Block type of the bundle priority extension block, from the range reserved for private use.
+///
+/// The block carries the priority as CBOR unsigned integer, e.g., `block=201:02` for priority 2.
+/// Bundles without this block have priority 0.
+pub const PRIORITY_BLOCK: CanonicalBlockType = 201;
+
+pub fn bundle_priority(bundle: &Bundle) -> u64 {
+    match bundle
+        .extension_block_by_type(PRIORITY_BLOCK)
+        .map(|b| b.data())
+    {
+        Some(CanonicalData::Unknown(data)) => serde_cbor::from_slice(data).unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// A rule of the composite routing agent, written as `<agent> [<condition>...]`.
+///
+/// Conditions are `src=<glob>`, `dst=<glob>`, `admin=<true|false>` as well as comparisons
+/// of the bundle size in bytes and the bundle priority, e.g., `size<=65536` or `priority>1`.
+/// A rule without conditions matches every bundle.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompositeRule {
+    /// name of the routing agent handling matching bundles
+    pub agent: String,
+    /// source eid, wildcards are allowed
+    pub src: Option<String>,
+    /// destination eid, wildcards are allowed
+    pub dst: Option<String>,
+    pub admin: Option<bool>,
+    /// inclusive bounds of the bundle size
+    pub size: (Option<u64>, Option<u64>),
+    /// inclusive bounds of the bundle priority
+    pub priority: (Option<u64>, Option<u64>),
+}
+
+/// Parses a comparison like `<=10` into inclusive bounds
+fn parse_bounds(cond: &str) -> Result<(Option<u64>, Option<u64>), String> {
+    let parse = |v: &str| {
+        v.parse::<u64>()
+            .map_err(|_| format!("invalid number: {}", cond))
+    };
+    if let Some(v) = cond.strip_prefix("<=") {
+        Ok((None, Some(parse(v)?)))
+    } else if let Some(v) = cond.strip_prefix(">=") {
+        Ok((Some(parse(v)?), None))
+    } else if let Some(v) = cond.strip_prefix('<') {
+        let v = parse(v)?;
+        if v == 0 {
+            return Err(format!("condition never matches: {}", cond));
+        }
+        Ok((None, Some(v - 1)))
+    } else if let Some(v) = cond.strip_prefix('>') {
+        Ok((Some(parse(v)? + 1), None))
+    } else if let Some(v) = cond.strip_prefix('=') {
+        let v = parse(v)?;
+        Ok((Some(v), Some(v)))
+    } else {
+        Err(format!("expected comparison: {}", cond))
+    }
+}
+
+/// Combines two bounds of the same attribute, e.g., `size>10 size<100`
+fn merge_bounds(
+    a: (Option<u64>, Option<u64>),
+    b: (Option<u64>, Option<u64>),
+) -> (Option<u64>, Option<u64>) {
+    let max = match (a.1, b.1) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    (a.0.max(b.0), max)
+}
+
+fn fmt_bounds(
+    f: &mut std::fmt::Formatter,
+    name: &str,
+    bounds: (Option<u64>, Option<u64>),
+) -> std::fmt::Result {
+    match bounds {
+        (Some(min), Some(max)) if min == max => write!(f, " {}={}", name, min),
+        (min, max) => {
+            if let Some(min) = min {
+                write!(f, " {}>={}", name, min)?;
+            }
+            if let Some(max) = max {
+                write!(f, " {}<={}", name, max)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn in_bounds(value: u64, bounds: (Option<u64>, Option<u64>)) -> bool {
+    bounds.0.is_none_or(|min| value >= min) && bounds.1.is_none_or(|max| value <= max)
+}
+
+impl FromStr for CompositeRule {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let agent = parts.next().ok_or("empty rule")?;
+        if !super::is_routing_algorithm(agent) || agent == "composite" || agent == "external" {
+            return Err(format!("invalid routing agent: {}", agent));
+        }
+        let mut rule = CompositeRule {
+            agent: agent.to_string(),
+            ..Default::default()
+        };
+        for cond in parts {
+            if let Some(glob) = cond.strip_prefix("src=") {
+                rule.src = Some(glob.to_string());
+            } else if let Some(glob) = cond.strip_prefix("dst=") {
+                rule.dst = Some(glob.to_string());
+            } else if let Some(admin) = cond.strip_prefix("admin=") {
+                rule.admin = Some(
+                    admin
+                        .parse()
+                        .map_err(|_| format!("invalid condition: {}", cond))?,
+                );
+            } else if let Some(cmp) = cond.strip_prefix("size") {
+                rule.size = merge_bounds(rule.size, parse_bounds(cmp)?);
+            } else if let Some(cmp) = cond.strip_prefix("priority") {
+                rule.priority = merge_bounds(rule.priority, parse_bounds(cmp)?);
+            } else {
+                return Err(format!("invalid condition: {}", cond));
+            }
+        }
+        Ok(rule)
+    }
+}
+
+impl Display for CompositeRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.agent)?;
+        if let Some(src) = &self.src {
+            write!(f, " src={}", src)?;
+        }
+        if let Some(dst) = &self.dst {
+            write!(f, " dst={}", dst)?;
+        }
+        if let Some(admin) = self.admin {
+            write!(f, " admin={}", admin)?;
+        }
+        fmt_bounds(f, "size", self.size)?;
+        fmt_bounds(f, "priority", self.priority)
+    }
+}
+
+impl CompositeRule {
+    /// Checks whether the rule applies to a bundle with the given priority
+    pub fn matches(&self, bp: &BundlePack, priority: u64) -> bool {
+        self.src
+            .as_ref()
+            .is_none_or(|src| glob_match(src, &bp.source.to_string()))
+            && self
+                .dst
+                .as_ref()
+                .is_none_or(|dst| glob_match(dst, &bp.destination.to_string()))
+            && self.admin.is_none_or(|admin| admin == bp.administrative)
+            && in_bounds(bp.size as u64, self.size)
+            && in_bounds(priority, self.priority)
+    }
+    fn needs_priority(&self) -> bool {
+        self.priority != (None, None)
+    }
+}
+
+/// Reads the rules from a file with one rule per line, empty lines and lines starting with `#` are ignored
+pub fn load_rules(path: &str) -> Result<Vec<CompositeRule>> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("reading rules from {}", path))?;
+    let mut rules = Vec::new();
+    for (no, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        match line.parse() {
+            Ok(rule) => rules.push(rule),
+            Err(err) => bail!("{}:{}: {}", path, no + 1, err),
+        }
+    }
+    Ok(rules)
+}
+
+/// Composition of several routing agents.
+///
+/// Each bundle is routed by the agent of the first matching rule, bundles matching no rule
+/// are not forwarded. All sub-agents receive every routing notification, so their state
+/// stays complete even for bundles they are not routing.
+#[derive(Debug)]
+pub struct CompositeRoutingAgent {
+    tx: mpsc::Sender<super::RoutingCmd>,
+}
+
+struct CompositeRoutingAgentCore {
+    rules: Vec<CompositeRule>,
+    agents: BTreeMap<String, RoutingAgentsEnum>,
+}
+
+impl CompositeRoutingAgentCore {
+    /// Creates the sub-agents used by the rules that are not running yet and returns their names
+    fn spawn_agents(&mut self) -> Vec<String> {
+        let mut started = Vec::new();
+        for rule in &self.rules {
+            if !self.agents.contains_key(&rule.agent) {
+                info!(
+                    "Starting {} routing as part of composite routing",
+                    rule.agent
+                );
+                match super::try_new(&rule.agent) {
+                    Ok(agent) => {
+                        self.agents.insert(rule.agent.clone(), agent);
+                        started.push(rule.agent.clone());
+                    }
+                    Err(err) => error!("Could not start {} routing: {:#}", rule.agent, err),
+                }
+            }
+        }
+        started
+    }
+    /// Shuts down the sub-agents no rule refers to anymore
+    async fn stop_unused_agents(&mut self) {
+        let unused: Vec<String> = self
+            .agents
+            .keys()
+            .filter(|name| !self.rules.iter().any(|rule| &rule.agent == *name))
+            .cloned()
+            .collect();
+        for name in unused {
+            info!("Stopping {} routing as part of composite routing", name);
+            if let Some(agent) = self.agents.remove(&name) {
+                let _ = agent.channel().send(RoutingCmd::Shutdown).await;
+            }
+        }
+    }
+    /// Switches to new rules, sub-agents started for them learn the current peers and bundles
+    async fn replace_rules(&mut self, rules: Vec<CompositeRule>) {
+        self.rules = rules;
+        self.stop_unused_agents().await;
+        for name in self.spawn_agents() {
+            if let Some(agent) = self.agents.get(&name) {
+                if let Err(err) = crate::routing_replay(&agent.channel()).await {
+                    error!("Error while initializing {} routing: {}", name, err);
+                }
+            }
+        }
+    }
+    fn agent_for(&self, bp: &BundlePack) -> Option<&RoutingAgentsEnum> {
+        let priority = if self.rules.iter().any(|r| r.needs_priority()) {
+            store_get_bundle(bp.id())
+                .map(|bndl| bundle_priority(&bndl))
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        let rule = self.rules.iter().find(|r| r.matches(bp, priority))?;
+        debug!("Bundle {} matches composite routing rule {}", bp.id(), rule);
+        self.agents.get(&rule.agent)
+    }
+}
+
+fn rules_file() -> Option<String> {
+    CONFIG
+        .lock()
+        .routing_settings
+        .get("composite")
+        .and_then(|settings| settings.get("rules").cloned())
+}
+
+impl Default for CompositeRoutingAgent {
+    fn default() -> Self {
+        CompositeRoutingAgent::new()
+    }
+}
+
+impl CompositeRoutingAgent {
+    pub fn new() -> Self {
+        Self::try_new().expect("invalid composite routing rules")
+    }
+    /// Creates the agent with the rules from the configured rules file
+    pub fn try_new() -> Result<Self> {
+        let rules = match rules_file() {
+            Some(path) => load_rules(&path)?,
+            None => Vec::new(),
+        };
+        for rule in &rules {
+            super::check_settings(&rule.agent)?;
+        }
+        Ok(Self::with_rules(rules))
+    }
+    pub fn with_rules(rules: Vec<CompositeRule>) -> Self {
+        let (tx, rx) = mpsc::channel(100);
+        let mut core = CompositeRoutingAgentCore {
+            rules,
+            agents: BTreeMap::new(),
+        };
+        core.spawn_agents();
+        crate::node::spawn(async move {
+            handle_routing_cmd(core, rx).await;
+        });
+        CompositeRoutingAgent { tx }
+    }
+}
+
+#[async_trait]
+impl RoutingAgent for CompositeRoutingAgent {
+    fn channel(&self) -> Sender<crate::RoutingCmd> {
+        self.tx.clone()
+    }
+}
+
+impl std::fmt::Display for CompositeRoutingAgent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "CompositeRoutingAgent")
+    }
+}
+
+async fn handle_routing_cmd(
+    mut core: CompositeRoutingAgentCore,
+    mut rx: mpsc::Receiver<RoutingCmd>,
+) {
+    while let Some(cmd) = rx.recv().await {
+        match cmd {
+            super::RoutingCmd::SenderForBundle(bp, reply) => {
+                if let Some(agent) = core.agent_for(&bp) {
+                    // the sub-agent answers directly
+                    if let Err(err) = agent
+                        .channel()
+                        .send(RoutingCmd::SenderForBundle(bp, reply))
+                        .await
+                    {
+                        error!("Error while delegating to sub-agent: {}", err);
+                    }
+                } else {
+                    debug!("No composite routing rule for bundle {}", bp.id());
+                    reply.send((vec![], false)).unwrap();
+                }
+            }
+            super::RoutingCmd::Shutdown => {
+                for agent in core.agents.values() {
+                    let _ = agent.channel().send(RoutingCmd::Shutdown).await;
+                }
+                break;
+            }
+            super::RoutingCmd::Command(cmd, reply) => {
+                // `reload` re-reads the rules, `<agent> <cmd>` is passed to a sub-agent
+                if cmd == "reload" {
+                    let res = match rules_file() {
+                        Some(path) => match load_rules(&path) {
+                            Ok(rules) => {
+                                info!("Reloading composite routing rules from {}", path);
+                                core.replace_rules(rules).await;
+                                Ok(())
+                            }
+                            Err(err) => {
+                                error!("Keeping composite routing rules: {:#}", err);
+                                Err(CommandError::Invalid(format!("{:#}", err)))
+                            }
+                        },
+                        None => Err(CommandError::Invalid("no rules file configured".into())),
+                    };
+                    let _ = reply.send(res);
+                } else if let Some((name, cmd)) = cmd.split_once(' ') {
+                    if let Some(agent) = core.agents.get(name) {
+                        // the sub-agent answers directly
+                        let _ = agent
+                            .channel()
+                            .send(RoutingCmd::Command(cmd.into(), reply))
+                            .await;
+                    } else {
+                        let _ = reply.send(Err(CommandError::NotFound(format!(
+                            "unknown sub-agent: {}",
+                            name
+                        ))));
+                    }
+                } else {
+                    let _ = reply.send(Err(CommandError::unknown(&cmd)));
+                }
+            }
+            super::RoutingCmd::GetData(param, tx) => {
+                // `<agent> [<param>]` queries a sub-agent, otherwise the rules are listed
+                let (name, param) = param.split_once(' ').unwrap_or((param.as_str(), ""));
+                if let Some(agent) = core.agents.get(name) {
+                    let _ = agent
+                        .channel()
+                        .send(RoutingCmd::GetData(param.into(), tx))
+                        .await;
+                } else {
+                    let rules_as_str = core
+                        .rules
+                        .iter()
+                        .enumerate()
+                        .fold(String::new(), |acc, (idx, r)| {
+                            acc + &format!("#{}: {}\n", idx, r)
+                        });
+                    tx.send(rules_as_str).unwrap();
+                }
+            }
+            super::RoutingCmd::Notify(notification) => {
+                for agent in core.agents.values() {
+                    if let Err(err) = agent
+                        .channel()
+                        .send(RoutingCmd::Notify(notification.clone()))
+                        .await
+                    {
+                        error!("Error while notifying sub-agent: {}", err);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -107,14 +107,16 @@ async fn handle_routing_cmd(mut rx: mpsc::Receiver<RoutingCmd>) {
                     }
                 }
 
-                tokio::spawn(async move {
+                crate::node::spawn(async move {
                     reply.send((clas, delete_afterwards)).unwrap();
                 });
             }
             super::RoutingCmd::Shutdown => {
                 break;
             }
-            super::RoutingCmd::Command(_cmd) => {}
+            super::RoutingCmd::Command(cmd, reply) => {
+                let _ = reply.send(Err(super::CommandError::unknown(&cmd)));
+            }
             super::RoutingCmd::GetData(_, tx) => {
                 tx.send(format!("{:?}", core.history)).unwrap();
             }
@@ -141,7 +143,7 @@ async fn handle_routing_cmd(mut rx: mpsc::Receiver<RoutingCmd>) {
 impl EpidemicRoutingAgent {
     pub fn new() -> EpidemicRoutingAgent {
         let (tx, rx) = mpsc::channel(100);
-        tokio::spawn(async move {
+        crate::node::spawn(async move {
             handle_routing_cmd(rx).await;
         });
 
@@ -1,20 +1,61 @@
+use crate::core::events::{EventFilter, TimedEvent};
 use crate::{peers_get_for_node, BundleID, BundlePack, DtnPeer, PeerAddress, RoutingNotifcation};
+use bp7::administrative_record::{
+    AdministrativeRecordTypeCode, StatusReportReason, NO_ROUTE_TO_DESTINATION,
+};
 use bp7::{Bundle, EndpointID};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 
 pub mod processing;
+pub mod tcp;
+#[cfg(unix)]
+pub mod unix;
 pub mod ws_client;
 
 /*
 
     The External Routing allows implementing routing algorithms externally (e.g. outside the dtn7-rs codebase).
-    It works by exposing a realtime JSON API via WebSocket. With the help of the erouting it is possible to easily
-    implement new routing algorithms in different languages. All languages that can encode / decode JSON
-    and communicate via WebSocket should in theory work.
+    It works by exposing a realtime JSON API via WebSocket, TCP or Unix sockets. With the help of the erouting it is
+    possible to easily implement new routing algorithms in different languages. All languages that can encode / decode
+    JSON and communicate via one of these transports should in theory work.
+
+    Routers announce the protocol version they speak with a Hello packet. Routers that never send
+    one are treated as version 1 and only receive the packets of the first protocol version.
 
 */
 
+/// Starts the JSON-lines transports configured with `external.tcp_port` and
+/// `external.unix_socket`, the websocket transport is part of the httpd.
+pub async fn start_transports() -> anyhow::Result<()> {
+    let settings = crate::CONFIG
+        .lock()
+        .routing_settings
+        .get("external")
+        .cloned()
+        .unwrap_or_default();
+    if let Some(port) = settings.get("tcp_port") {
+        let port: u16 = port.parse()?;
+        tcp::listen(&format!("127.0.0.1:{}", port)).await?;
+    }
+    if let Some(path) = settings.get("unix_socket") {
+        #[cfg(unix)]
+        unix::listen(std::path::Path::new(path))?;
+        #[cfg(not(unix))]
+        anyhow::bail!("Unix sockets are not supported on this platform: {}", path);
+    }
+    Ok(())
+}
+
+/// Maximum length of a JSON encoded packet received via the TCP and Unix socket transports.
+pub const MAX_PACKET_LENGTH: usize = 1024 * 1024;
+
+/// Highest version of the external routing protocol supported by dtnd.
+///
+/// Version 2 adds bundle info requests, deferring and dropping bundles, injection of
+/// administrative bundles and event subscriptions.
+pub const PROTOCOL_VERSION: u32 = 2;
+
 /// The variant of Packets that can be sent or received. The resulting JSON will have
 /// a field called type that encodes the selected variant.
 #[derive(Serialize, Deserialize, Clone)]
@@ -42,12 +83,49 @@ pub enum Packet {
     EncounteredPeer(EncounteredPeer),
     /// Packet that signals that a new peer was dropped.
     DroppedPeer(DroppedPeer),
+    /// Packet that signals that the address, CLAs, services or beacon period of a known peer changed.
+    PeerChanged(PeerChanged),
     /// Packet that contains the full initial peer state of dtnd at the point of connection.
     PeerState(PeerState),
     /// Packet that creates a new service in dtnd.
     ServiceState(ServiceState),
     /// Packet that contains the full initial service state of dtnd at the point of connection.
     ServiceAdd(AddService),
+    /// Packet announcing the protocol version of the router, answered with the negotiated version.
+    Hello(Hello),
+    /// Packet that requests the extension blocks and payload size of a stored bundle.
+    RequestBundleInfo(RequestBundleInfo),
+    /// Packet response to a RequestBundleInfo packet.
+    BundleInfo(BundleInfo),
+    /// Packet response to a RequestSenderForBundle packet that postpones the decision,
+    /// the bundle is requested again after the given delay.
+    DeferBundle(DeferBundle),
+    /// Packet that deletes a bundle from the store, also as response to a RequestSenderForBundle.
+    DropBundle(DropBundle),
+    /// Packet that sends a new bundle containing an administrative record from this node.
+    InjectAdministrativeBundle(InjectAdministrativeBundle),
+    /// Packet that subscribes to node events like received or deleted bundles,
+    /// replacing a previous subscription.
+    Subscribe(EventFilter),
+    /// Packet that contains an event matching the subscription of the router.
+    Event(Event),
+}
+
+impl Packet {
+    /// Protocol version a packet was introduced with
+    pub fn version(&self) -> u32 {
+        match self {
+            Packet::Hello(_)
+            | Packet::RequestBundleInfo(_)
+            | Packet::BundleInfo(_)
+            | Packet::DeferBundle(_)
+            | Packet::DropBundle(_)
+            | Packet::InjectAdministrativeBundle(_)
+            | Packet::Subscribe(_)
+            | Packet::Event(_) => 2,
+            _ => 1,
+        }
+    }
 }
 
 impl From<RoutingNotifcation> for Packet {
@@ -77,6 +155,12 @@ impl From<RoutingNotifcation> for Packet {
                 name: eid.node().unwrap(),
                 eid,
             }),
+            RoutingNotifcation::PeerChanged(eid, changes) => Packet::PeerChanged(PeerChanged {
+                name: eid.node().unwrap(),
+                eid: eid.clone(),
+                peer: peers_get_for_node(&eid).unwrap(),
+                changes,
+            }),
         }
     }
 }
@@ -150,6 +234,16 @@ pub struct DroppedPeer {
     pub eid: EndpointID,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PeerChanged {
+    pub name: String,
+    pub eid: EndpointID,
+    /// The updated peer
+    pub peer: DtnPeer,
+    /// Names of the changed fields, e.g., `addr` or `cla_list`
+    pub changes: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct PeerState {
     pub peers: BTreeMap<String, DtnPeer>,
@@ -165,3 +259,68 @@ pub struct AddService {
 pub struct ServiceState {
     pub service_list: HashMap<u8, String>,
 }
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Hello {
+    pub version: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RequestBundleInfo {
+    pub bid: BundleID,
+}
+
+/// Canonical block of a bundle other than the payload block
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BlockInfo {
+    pub block_type: u64,
+    pub block_number: u64,
+    pub flags: u8,
+    /// Hex encoded CBOR of the block-type-specific data
+    pub data: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BundleInfo {
+    pub bid: BundleID,
+    pub payload_size: u64,
+    pub blocks: Vec<BlockInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DeferBundle {
+    pub bp: BundlePack,
+    /// Delay before the bundle is requested again
+    pub delay_ms: u64,
+}
+
+fn default_drop_code() -> StatusReportReason {
+    NO_ROUTE_TO_DESTINATION
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DropBundle {
+    pub bid: BundleID,
+    /// Explanation for the log
+    #[serde(default)]
+    pub reason: String,
+    /// Reason code of the deletion status report, no route to destination by default
+    #[serde(default = "default_drop_code")]
+    pub code: StatusReportReason,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InjectAdministrativeBundle {
+    pub destination: EndpointID,
+    pub record_type: AdministrativeRecordTypeCode,
+    /// Hex encoded content of the administrative record
+    pub content: String,
+    /// Lifetime of the bundle, one hour by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lifetime_ms: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Event {
+    pub event: TimedEvent,
+}
@@ -1,102 +1,163 @@
-use super::{Packet, PeerState, RequestSenderForBundle, ResponseSenderForBundle, ServiceState};
+use super::{
+    BlockInfo, BundleInfo, DropBundle, Event, Hello, InjectAdministrativeBundle, Packet, PeerState,
+    RequestSenderForBundle, ResponseSenderForBundle, ServiceState, PROTOCOL_VERSION,
+};
 use crate::cla::ConvergenceLayerAgent;
+use crate::core::events::{self, EventFilter};
+use crate::core::scheduler;
+use crate::node::node_local;
 use crate::routing::erouting::Error;
 use crate::{
-    cla_names, lazy_static, service_add, BundlePack, ClaSenderTask, RoutingNotifcation, CLAS,
-    DTNCORE, PEERS,
+    cla_names, service_add, store_get_bundle, store_get_metadata, BundlePack, ClaSenderTask,
+    RoutingNotifcation, CLAS, CONFIG, DTNCORE, PEERS,
 };
 use axum::extract::ws::{Message, WebSocket};
-use futures_util::{future, SinkExt, StreamExt, TryStreamExt};
-use log::{error, info, trace};
+use bp7::administrative_record::{AdministrativeRecord, BUNDLE_STATUS_REPORT_TYPE_CODE};
+use bp7::flags::BundleControlFlags;
+use bp7::helpers::{hexify, unhexify};
+use bp7::{Bundle, CreationTimestamp, PAYLOAD_BLOCK};
+use futures_util::{future, Sink, SinkExt, Stream, StreamExt, TryStreamExt};
+use log::{error, info, trace, warn};
+use parking_lot::Mutex;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::Sender;
 use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
 use tokio::time::timeout;
 
 /// Maximum timeout for a sender_for_bundle response packet.
 const EROUTING_RESPONSE_TIMEOUT_MS: u64 = 250;
+/// Consecutive timeouts after which a router is considered unresponsive and demoted.
+const EROUTING_MAX_TIMEOUTS: u32 = 3;
 
-/// Holds the channel to send messages to the connected router.
+/// Holds the channel to send messages to a connected router.
 struct Connection {
-    tx: Sender<Message>,
+    /// Identifies the router in logs and pending requests
+    id: u64,
+    tx: Sender<String>,
+    /// Negotiated protocol version
+    version: u32,
+    /// Task passing subscribed events to the router
+    events: Option<JoinHandle<()>>,
+    /// Routing requests that timed out since the router last sent a packet
+    timeouts: u32,
+}
+
+impl Connection {
+    fn responsive(&self) -> bool {
+        self.timeouts < EROUTING_MAX_TIMEOUTS
+    }
 }
 
-type ResponseMap = Arc<Mutex<HashMap<String, oneshot::Sender<Packet>>>>;
+type ResponseMap = Mutex<HashMap<String, (u64, oneshot::Sender<Packet>)>>;
 
-lazy_static! {
-    /// Keeps track of the single router that can be connected.
-    static ref CONNECTION: Arc<Mutex<Option<Connection>>> = Arc::new(Mutex::new(None));
-    /// Tracks the response channels for SenderForBundle requests.
-    static ref RESPONSES: ResponseMap = ResponseMap::new(Mutex::new(HashMap::new()));
+node_local! {
+    /// Connected routers in the order they connected. The first responsive one is the primary
+    /// router answering routing requests, the others are standbys taking over if it disconnects
+    /// or stops answering.
+    static ref ROUTERS: Mutex<Vec<Connection>> = Mutex::new(Vec::new());
+    /// Tracks the response channels for SenderForBundle requests and the router asked.
+    static ref RESPONSES: ResponseMap = Mutex::new(HashMap::new());
 }
 
-fn send_peer_state() {
+static NEXT_ROUTER_ID: AtomicU64 = AtomicU64::new(1);
+
+fn send_peer_state(id: u64) {
     let peer_state: Packet = Packet::PeerState(PeerState {
         peers: PEERS.lock().clone(),
     });
-    send_packet(&peer_state);
+    send_packet_to(id, &peer_state);
 }
 
-fn send_service_state() {
+fn send_service_state(id: u64) {
     let service_state: Packet = Packet::ServiceState(ServiceState {
         service_list: DTNCORE.lock().service_list.clone(),
     });
-    send_packet(&service_state);
+    send_packet_to(id, &service_state);
 }
 
+/// Handles a router connected via the `/ws/erouting` websocket.
 pub async fn handle_connection(ws: WebSocket) {
-    let (tx, mut rx) = mpsc::channel(100);
-    let (mut outgoing, incoming) = ws.split();
+    let (outgoing, incoming) = ws.split();
+    let incoming = incoming.try_filter_map(|msg| {
+        future::ok(match msg {
+            Message::Text(text) => Some(text),
+            Message::Binary(data) => String::from_utf8(data).ok(),
+            _ => None,
+        })
+    });
+    let outgoing = outgoing.with(|text| future::ok::<_, axum::Error>(Message::Text(text)));
+    handle_router("websocket", incoming, Box::pin(outgoing)).await;
+}
 
-    if CONNECTION.lock().unwrap().is_some() {
-        info!("Websocket connection closed because external routing agent is already connected");
+/// Handles a connected router exchanging JSON encoded packets, independent of the transport.
+pub(crate) async fn handle_router<I, E, O>(transport: &str, incoming: I, mut outgoing: O)
+where
+    I: Stream<Item = Result<String, E>>,
+    E: Display,
+    O: Sink<String> + Unpin + Send + 'static,
+    O::Error: Display,
+{
+    let (tx, mut rx) = mpsc::channel(100);
 
-        if let Ok(data) = serde_json::to_string(&Packet::Error(Error {
-            reason: String::from("external routing agent already registered"),
-        })) {
-            if let Err(err) = outgoing.send(Message::Text(data)).await {
-                error!("Error while sending closing reason: {}", err);
+    let id = NEXT_ROUTER_ID.fetch_add(1, Ordering::Relaxed);
+    {
+        let mut routers = ROUTERS.lock();
+        info!(
+            "External router {} connected via {} as {}",
+            id,
+            transport,
+            if routers.is_empty() {
+                "primary"
+            } else {
+                "standby"
             }
-        }
-
-        if let Err(err) = outgoing.close().await {
-            error!("Error while closing websocket: {}", err);
-        }
-        return;
+        );
+        routers.push(Connection {
+            id,
+            tx,
+            version: 1,
+            events: None,
+            timeouts: 0,
+        });
     }
 
-    *CONNECTION.lock().unwrap() = Some(Connection { tx });
-
     // Send initial states to the router
-    send_peer_state();
-    send_service_state();
+    send_peer_state(id);
+    send_service_state(id);
 
     let broadcast_incoming = incoming.try_for_each(|msg| {
-        trace!(
-            "Received a external routing message: {}",
-            msg.to_text().unwrap().trim()
-        );
+        trace!("Received a external routing message: {}", msg.trim());
+        mark_responsive(id);
 
-        let packet: serde_json::Result<Packet> = serde_json::from_str(msg.to_text().unwrap());
+        let packet: serde_json::Result<Packet> = serde_json::from_str(&msg);
 
         match packet {
+            Ok(packet)
+                if packet.version() > negotiated_version(id)
+                    && !matches!(packet, Packet::Hello(_)) =>
+            {
+                send_packet_to(
+                    id,
+                    &Packet::Error(Error {
+                        reason: format!(
+                            "packet requires protocol version {}, send a Hello packet first",
+                            packet.version()
+                        ),
+                    }),
+                );
+            }
             Ok(packet) => match packet {
                 // When a ResponseSenderForBundle is received we check if a response channel for that
                 // bundle id exists and send the response on that channel.
                 Packet::ResponseSenderForBundle(packet) => {
-                    trace!(
-                        "sender_for_bundle response: {}",
-                        msg.to_text().unwrap().trim()
-                    );
+                    trace!("sender_for_bundle response: {}", msg.trim());
 
-                    if let Some(tx) = RESPONSES
-                        .lock()
-                        .unwrap()
-                        .remove(packet.bp.to_string().as_str())
-                    {
+                    if let Some(tx) = take_response_channel(id, packet.bp.id()) {
                         if tx.send(Packet::ResponseSenderForBundle(packet)).is_err() {
                             error!("sender_for_bundle response could not be passed to channel")
                         }
@@ -113,6 +174,41 @@ pub async fn handle_connection(ws: WebSocket) {
 
                     service_add(packet.tag, packet.service);
                 }
+                Packet::Hello(hello) => {
+                    let version = hello.version.clamp(1, PROTOCOL_VERSION);
+                    info!("External router {} speaks protocol version {}", id, version);
+                    if let Some(con) = ROUTERS.lock().iter_mut().find(|con| con.id == id) {
+                        con.version = version;
+                    }
+                    send_packet_to(id, &Packet::Hello(Hello { version }));
+                }
+                Packet::DeferBundle(packet) => {
+                    if let Some(tx) = take_response_channel(id, packet.bp.id()) {
+                        let _ = tx.send(Packet::DeferBundle(packet));
+                    } else {
+                        info!("defer_bundle no response channel available")
+                    }
+                }
+                Packet::DropBundle(packet) => drop_bundle(id, packet),
+                Packet::RequestBundleInfo(packet) => {
+                    send_packet_to(
+                        id,
+                        &match bundle_info(&packet.bid) {
+                            Some(info) => Packet::BundleInfo(info),
+                            None => Packet::Error(Error {
+                                reason: format!("unknown bundle {}", packet.bid),
+                            }),
+                        },
+                    );
+                }
+                Packet::InjectAdministrativeBundle(packet) => match administrative_bundle(packet) {
+                    Ok(bndl) => {
+                        info!("Injecting administrative bundle {}", bndl.id());
+                        crate::node::spawn(crate::core::processing::send_bundle(bndl));
+                    }
+                    Err(reason) => send_packet_to(id, &Packet::Error(Error { reason })),
+                },
+                Packet::Subscribe(filter) => subscribe(id, filter),
                 _ => {}
             },
             Err(err) => {
@@ -123,7 +219,7 @@ pub async fn handle_connection(ws: WebSocket) {
         future::ok(())
     });
 
-    let receive_from_others = tokio::spawn(async move {
+    let receive_from_others = crate::node::spawn(async move {
         while let Some(cmd) = rx.recv().await {
             if let Err(err) = outgoing.send(cmd).await {
                 error!("err while sending to outgoing channel: {}", err);
@@ -131,21 +227,210 @@ pub async fn handle_connection(ws: WebSocket) {
         }
     });
 
-    future::select(broadcast_incoming, receive_from_others).await;
+    if let future::Either::Left((Err(err), _)) =
+        future::select(Box::pin(broadcast_incoming), receive_from_others).await
+    {
+        info!("External router {} connection failed: {}", id, err);
+    }
+
+    info!("External router {} disconnected", id);
+    disconnect(id);
+}
+
+/// Removes a router, pending requests sent to it fail so they can be passed to the next router.
+fn disconnect(id: u64) {
+    let mut routers = ROUTERS.lock();
+    let Some(pos) = routers.iter().position(|con| con.id == id) else {
+        return;
+    };
+    let con = routers.remove(pos);
+    if let Some(events) = con.events {
+        events.abort();
+    }
+    RESPONSES.lock().retain(|_, (router, _)| *router != id);
+    if pos == 0 {
+        match routers.first() {
+            Some(next) => info!("External router {} is now primary", next.id),
+            None => info!("No external router connected"),
+        }
+    }
+}
+
+/// Counts a timed out request, a router not answering repeatedly is moved behind the others.
+///
+/// Returns true if the router was demoted.
+fn record_timeout(id: u64) -> bool {
+    let mut routers = ROUTERS.lock();
+    let Some(pos) = routers.iter().position(|con| con.id == id) else {
+        return false;
+    };
+    routers[pos].timeouts += 1;
+    if routers[pos].responsive() {
+        return false;
+    }
+    let con = routers.remove(pos);
+    warn!(
+        "External router {} did not answer {} requests, demoting it",
+        id, con.timeouts
+    );
+    routers.push(con);
+    match routers.iter().find(|con| con.responsive()) {
+        Some(next) => info!("External router {} is now primary", next.id),
+        None => info!("No responsive external router connected"),
+    }
+    true
+}
+
+/// A router sending packets again is considered responsive
+fn mark_responsive(id: u64) {
+    if let Some(con) = ROUTERS.lock().iter_mut().find(|con| con.id == id) {
+        if !con.responsive() {
+            info!("External router {} is responsive again", id);
+        }
+        con.timeouts = 0;
+    }
+}
+
+fn negotiated_version(id: u64) -> u32 {
+    ROUTERS
+        .lock()
+        .iter()
+        .find(|con| con.id == id)
+        .map_or(1, |con| con.version)
+}
+
+/// Returns true if at least one external router is connected.
+pub fn router_connected() -> bool {
+    !ROUTERS.lock().is_empty()
+}
+
+/// Ids, negotiated protocol versions and roles of the connected routers, the primary router
+/// first and demoted routers last.
+pub fn routers() -> Vec<(u64, u32, &'static str)> {
+    let mut primary = true;
+    ROUTERS
+        .lock()
+        .iter()
+        .map(|con| {
+            let role = if !con.responsive() {
+                "unresponsive"
+            } else if std::mem::take(&mut primary) {
+                "primary"
+            } else {
+                "standby"
+            };
+            (con.id, con.version, role)
+        })
+        .collect()
+}
 
-    info!("External routing disconnected");
-    disconnect();
+/// Extension blocks and payload size of a stored bundle
+fn bundle_info(bid: &str) -> Option<BundleInfo> {
+    let bndl = store_get_bundle(bid)?;
+    let payload_size = bndl.payload().map_or(0, |payload| payload.len() as u64);
+    let blocks = bndl
+        .canonicals
+        .iter()
+        .filter(|block| block.block_type != PAYLOAD_BLOCK)
+        .map(|block| BlockInfo {
+            block_type: block.block_type,
+            block_number: block.block_number,
+            flags: block.block_control_flags,
+            data: hexify(&block.data().to_cbor()),
+        })
+        .collect();
+    Some(BundleInfo {
+        bid: bid.to_string(),
+        payload_size,
+        blocks,
+    })
+}
+
+/// Deletes a bundle on request of the router, a pending routing request for it is answered
+/// without next hops.
+fn drop_bundle(id: u64, packet: DropBundle) {
+    if let Some(tx) = take_response_channel(id, &packet.bid) {
+        let _ = tx.send(Packet::DropBundle(packet.clone()));
+    }
+    let Some(bp) = store_get_metadata(&packet.bid) else {
+        send_packet_to(
+            id,
+            &Packet::Error(Error {
+                reason: format!("unknown bundle {}", packet.bid),
+            }),
+        );
+        return;
+    };
+    info!(
+        "External router dropped bundle {}: {}",
+        packet.bid, packet.reason
+    );
+    crate::node::spawn(async move {
+        if let Err(err) = crate::core::processing::delete(bp, packet.code).await {
+            warn!("Error dropping bundle {}: {}", packet.bid, err);
+        }
+    });
 }
 
-fn disconnect() {
-    (*CONNECTION.lock().unwrap()) = None;
+/// Builds a bundle from this node containing the administrative record of the packet
+fn administrative_bundle(packet: InjectAdministrativeBundle) -> Result<Bundle, String> {
+    if packet.record_type == BUNDLE_STATUS_REPORT_TYPE_CODE {
+        return Err("status reports are only generated by dtnd".into());
+    }
+    let content = unhexify(&packet.content)
+        .map_err(|err| format!("invalid administrative record content: {}", err))?;
+    let record = AdministrativeRecord::Unknown(packet.record_type, content);
+    let src = CONFIG.lock().host_eid.clone();
+    let lifetime = time::Duration::from_millis(packet.lifetime_ms.unwrap_or(3_600_000));
+    let primary = bp7::primary::PrimaryBlockBuilder::default()
+        .destination(packet.destination)
+        .source(src.clone())
+        .report_to(src)
+        .bundle_control_flags(BundleControlFlags::BUNDLE_ADMINISTRATIVE_RECORD_PAYLOAD.bits())
+        .creation_timestamp(CreationTimestamp::now())
+        .lifetime(lifetime)
+        .build()
+        .map_err(|err| err.to_string())?;
+    let mut bndl = bp7::bundle::BundleBuilder::default()
+        .primary(primary)
+        .canonicals(vec![record.to_payload()])
+        .build()
+        .map_err(|err| err.to_string())?;
+    bndl.set_crc(bp7::crc::CRC_NO);
+    Ok(bndl)
 }
 
-/// Sends a JSON encoded packet to the connected router.
-fn send_packet(p: &Packet) {
+/// Passes node events matching the filter to the router, replacing the previous subscription
+fn subscribe(id: u64, filter: EventFilter) {
+    let mut rx = events::subscribe();
+    let task = crate::node::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) if filter.matches(&event.event) => {
+                    send_packet_to(id, &Packet::Event(Event { event }));
+                }
+                Ok(_) => {}
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!("External router missed {} events", missed);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+    if let Some(con) = ROUTERS.lock().iter_mut().find(|con| con.id == id) {
+        if let Some(previous) = con.events.replace(task) {
+            previous.abort();
+        }
+    } else {
+        task.abort();
+    }
+}
+
+/// Sends a JSON encoded packet to a connected router.
+fn send_packet_to(id: u64, p: &Packet) {
     if let Ok(data) = serde_json::to_string(p) {
-        if let Some(con) = CONNECTION.lock().unwrap().as_ref() {
-            if let Err(err) = con.tx.try_send(Message::Text(data)) {
+        if let Some(con) = ROUTERS.lock().iter().find(|con| con.id == id) {
+            if let Err(err) = con.tx.try_send(data) {
                 error!("couldn't send packet {}", err)
             }
         }
@@ -153,17 +438,37 @@ fn send_packet(p: &Packet) {
 }
 
 /// Takes the RoutingNotification's, encodes them to serializable structs and then sends them
-/// to the external router if one is connected.
+/// to all connected routers, so standby routers know the current state when taking over.
 pub fn notify(notification: RoutingNotifcation) {
-    send_packet(&notification.into());
+    let packet: Packet = notification.into();
+    if let Ok(data) = serde_json::to_string(&packet) {
+        for con in ROUTERS.lock().iter() {
+            if let Err(err) = con.tx.try_send(data.clone()) {
+                error!("couldn't send packet to router {}: {}", con.id, err)
+            }
+        }
+    }
+}
+
+/// Removes the response channel of a bundle if the request was sent to the given router.
+fn take_response_channel(id: u64, bid: &str) -> Option<oneshot::Sender<Packet>> {
+    let mut responses = RESPONSES.lock();
+    match responses.get(bid) {
+        Some((router, _)) if *router == id => responses.remove(bid).map(|(_, tx)| tx),
+        _ => None,
+    }
 }
 
 fn remove_response_channel(id: &str) {
-    RESPONSES.lock().unwrap().remove(id);
+    RESPONSES.lock().remove(id);
 }
 
-fn create_response_channel(id: &str, tx: oneshot::Sender<Packet>) {
-    RESPONSES.lock().unwrap().insert(id.to_string(), tx);
+/// Registers a response channel for a request to the primary router and returns its id.
+fn create_response_channel(bid: &str, tx: oneshot::Sender<Packet>) -> Option<u64> {
+    let routers = ROUTERS.lock();
+    let primary = routers.iter().find(|con| con.responsive())?.id;
+    RESPONSES.lock().insert(bid.to_string(), (primary, tx));
+    Some(primary)
 }
 
 // Builds a list of ClaSenderTask from the information contained in the ResponseSenderForBundle packet.
@@ -187,6 +492,7 @@ fn unpack_sender_for_bundle(packet: ResponseSenderForBundle) -> (Vec<ClaSenderTa
                             dest,
                             cla_name: cla_instance.name().into(),
                             next_hop: sender.next_hop.clone(),
+                            copies: None,
                         });
                     }
                 }
@@ -200,46 +506,78 @@ fn unpack_sender_for_bundle(packet: ResponseSenderForBundle) -> (Vec<ClaSenderTa
 /// Tries to send a routing requests to the external router and waits for the response.
 /// The wait will be limited to a timeout of 250ms.
 pub async fn sender_for_bundle(bp: &BundlePack) -> (Vec<ClaSenderTask>, bool) {
-    trace!("external sender_for_bundle initiated: {}", bp);
+    try_sender_for_bundle(bp)
+        .await
+        .unwrap_or_else(|| (vec![], false))
+}
 
-    if CONNECTION.lock().unwrap().is_none() {
-        return (vec![], false);
-    }
+/// Sends a routing request to the primary router, passing it on to the next router if the
+/// primary disconnects before answering or is demoted for not answering. Returns `None` if no
+/// responsive router is left to ask.
+pub async fn try_sender_for_bundle(bp: &BundlePack) -> Option<(Vec<ClaSenderTask>, bool)> {
+    trace!("external sender_for_bundle initiated: {}", bp);
 
-    // Register a response channel for the request
-    let (tx, rx) = oneshot::channel();
-    create_response_channel(bp.to_string().as_str(), tx);
+    loop {
+        // Register a response channel for the request
+        let (tx, rx) = oneshot::channel();
+        let router = create_response_channel(bp.id(), tx)?;
+
+        // Send out the SenderForBundle packet
+        let packet: Packet = Packet::RequestSenderForBundle(RequestSenderForBundle {
+            clas: cla_names(),
+            bp: bp.clone(),
+        });
+        send_packet_to(router, &packet);
+
+        let res = timeout(
+            time::Duration::from_millis(EROUTING_RESPONSE_TIMEOUT_MS),
+            rx,
+        )
+        .await;
+        match res {
+            Ok(Ok(Packet::ResponseSenderForBundle(packet))) => {
+                if packet.bp.to_string() != bp.to_string() {
+                    error!("got a wrong bundle pack! {} != {}", bp, packet.bp);
+                    return Some((vec![], false));
+                }
 
-    // Send out the SenderForBundle packet
-    let packet: Packet = Packet::RequestSenderForBundle(RequestSenderForBundle {
-        clas: cla_names(),
-        bp: bp.clone(),
-    });
-    send_packet(&packet);
+                return Some(unpack_sender_for_bundle(packet));
+            }
+            Ok(Ok(Packet::DeferBundle(packet))) => {
+                trace!(
+                    "external router deferred {} by {}ms",
+                    bp.id(),
+                    packet.delay_ms
+                );
+                scheduler::defer(bp.id(), time::Duration::from_millis(packet.delay_ms));
+                return Some((vec![], false));
+            }
+            // the bundle is deleted by the packet handler
+            Ok(Ok(Packet::DropBundle(_))) => return Some((vec![], false)),
+            Ok(Ok(_)) => {}
+            // the channel was dropped because the router disconnected
+            Ok(Err(_)) => {
+                info!(
+                    "External router {} disconnected while routing {}",
+                    router,
+                    bp.id()
+                );
+                continue;
+            }
+            Err(_) => {}
+        }
 
-    let res = timeout(
-        time::Duration::from_millis(EROUTING_RESPONSE_TIMEOUT_MS),
-        rx,
-    )
-    .await;
-    if let Ok(Ok(Packet::ResponseSenderForBundle(packet))) = res {
-        remove_response_channel(bp.to_string().as_str());
+        // Signal to the external router that the timeout was reached and no ResponseSenderForBundle was processed.
+        // This is needed in case that the response arrived later than the timeout and the connected router thinks
+        // it successfully sends its response. Otherwise, there is no way for the router to know if its response has
+        // failed.
+        send_packet_to(router, &Packet::Timeout(super::Timeout { bp: bp.clone() }));
 
-        if packet.bp.to_string() != bp.to_string() {
-            error!("got a wrong bundle pack! {} != {}", bp, packet.bp);
-            return (vec![], false);
+        info!("timeout while waiting for sender_for_bundle");
+        remove_response_channel(bp.id());
+        if record_timeout(router) {
+            continue;
         }
-
-        return unpack_sender_for_bundle(packet);
+        return Some((vec![], false));
     }
-
-    // Signal to the external router that the timeout was reached and no ResponseSenderForBundle was processed.
-    // This is needed in case that the response arrived later than the timeout and the connected router thinks
-    // it successfully sends its response. Otherwise, there is no way for the router to know if its response has
-    // failed.
-    send_packet(&Packet::Timeout(super::Timeout { bp: bp.clone() }));
-
-    info!("timeout while waiting for sender_for_bundle");
-    remove_response_channel(bp.to_string().as_str());
-    (vec![], false)
 }
@@ -0,0 +1,79 @@
+use super::processing::handle_router;
+use super::{Error, Packet, MAX_PACKET_LENGTH};
+use crate::dtnd::auth::verify_token;
+use crate::CONFIG;
+use futures_util::{SinkExt, StreamExt};
+use log::{error, info};
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::tcp::OwnedReadHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::codec::{FramedRead, FramedWrite, LinesCodec};
+
+/// Time a router has to authenticate after connecting
+const AUTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// First line a router sends via TCP, e.g., `{"type": "Auth", "token": "..."}`
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum Login {
+    Auth { token: String },
+}
+
+/// Reads the login line, the token must grant the admin scope
+async fn authenticate(incoming: &mut FramedRead<OwnedReadHalf, LinesCodec>) -> Result<(), String> {
+    let line = match tokio::time::timeout(AUTH_TIMEOUT, incoming.next()).await {
+        Ok(Some(Ok(line))) => line,
+        Ok(Some(Err(err))) => return Err(format!("invalid login: {}", err)),
+        Ok(None) => return Err("connection closed before login".into()),
+        Err(_) => return Err("no login received".into()),
+    };
+    let Ok(Login::Auth { token }) = serde_json::from_str(&line) else {
+        return Err("expected an Auth packet".into());
+    };
+    let cfg = CONFIG.lock().auth.clone();
+    match verify_token(&cfg, token.trim()) {
+        Some(grant) if grant.is_admin() => Ok(()),
+        Some(_) => Err("token lacks the admin scope".into()),
+        None => Err("invalid token".into()),
+    }
+}
+
+/// Handles a router connected via TCP, each line is a JSON encoded packet.
+///
+/// Any local process can connect, so the router has to present an admin token first.
+async fn handle_connection(stream: TcpStream, addr: SocketAddr) {
+    info!("Incoming external routing TCP connection from: {}", addr);
+    let (incoming, outgoing) = stream.into_split();
+    let mut incoming =
+        FramedRead::new(incoming, LinesCodec::new_with_max_length(MAX_PACKET_LENGTH));
+    let mut outgoing = FramedWrite::new(outgoing, LinesCodec::new());
+    if let Err(reason) = authenticate(&mut incoming).await {
+        info!("External router from {} refused: {}", addr, reason);
+        if let Ok(data) = serde_json::to_string(&Packet::Error(Error { reason })) {
+            let _ = outgoing.send(data).await;
+        }
+        return;
+    }
+    handle_router("TCP", incoming, outgoing).await;
+}
+
+/// Listens for routers on the given TCP address and returns the bound address.
+pub async fn listen(addr: &str) -> std::io::Result<SocketAddr> {
+    let listener = TcpListener::bind(addr).await?;
+    let addr = listener.local_addr()?;
+    info!("External Routing TCP Listening on: {}", addr);
+
+    crate::node::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, addr)) => {
+                    crate::node::spawn(handle_connection(stream, addr));
+                }
+                Err(err) => error!("Error accepting external routing connection: {}", err),
+            }
+        }
+    });
+    Ok(addr)
+}
@@ -0,0 +1,49 @@
+use super::processing::handle_router;
+use super::MAX_PACKET_LENGTH;
+use log::{error, info};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::Path;
+use tokio::net::{UnixListener, UnixStream};
+use tokio_util::codec::{FramedRead, FramedWrite, LinesCodec};
+
+/// Handles a router connected via a Unix domain socket, each line is a JSON encoded packet.
+async fn handle_connection(stream: UnixStream) {
+    info!("Incoming external routing Unix socket connection");
+    let (incoming, outgoing) = stream.into_split();
+    handle_router(
+        "Unix socket",
+        FramedRead::new(incoming, LinesCodec::new_with_max_length(MAX_PACKET_LENGTH)),
+        FramedWrite::new(outgoing, LinesCodec::new()),
+    )
+    .await;
+}
+
+/// Listens for routers on a Unix domain socket at the given path.
+///
+/// A socket file left over from a previous run is replaced. Only the user running dtnd may
+/// connect to the socket.
+pub fn listen(path: &Path) -> std::io::Result<()> {
+    if let Ok(meta) = std::fs::metadata(path) {
+        if meta.file_type().is_socket() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    info!(
+        "External Routing Unix socket Listening on: {}",
+        path.display()
+    );
+
+    crate::node::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    crate::node::spawn(handle_connection(stream));
+                }
+                Err(err) => error!("Error accepting external routing connection: {}", err),
+            }
+        }
+    });
+    Ok(())
+}
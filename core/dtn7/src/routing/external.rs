@@ -1,10 +1,17 @@
-use super::erouting::processing::{notify, sender_for_bundle};
-use super::RoutingAgent;
+use super::erouting::processing::{notify, routers, try_sender_for_bundle};
+use super::{RoutingAgent, RoutingAgentsEnum};
 use crate::routing::RoutingCmd;
+use crate::CONFIG;
 use async_trait::async_trait;
+use log::{debug, error, info};
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::Sender;
 
+/// Routing by external routers connected via websocket.
+///
+/// The first connected router answers routing requests, further routers are standbys taking
+/// over when it disconnects. While no router is connected, bundles are routed by the optional
+/// fallback agent set with `external.fallback`.
 #[derive(Debug)]
 pub struct ExternalRoutingAgent {
     tx: mpsc::Sender<super::RoutingCmd>,
@@ -16,25 +23,106 @@ impl Default for ExternalRoutingAgent {
     }
 }
 
+/// Name of the configured fallback agent
+fn fallback_agent() -> Option<String> {
+    CONFIG
+        .lock()
+        .routing_settings
+        .get("external")
+        .and_then(|settings| settings.get("fallback").cloned())
+}
+
 impl ExternalRoutingAgent {
     pub fn new() -> Self {
         let (tx, mut rx) = mpsc::channel(100);
-        tokio::spawn(async move {
+        let fallback: Option<RoutingAgentsEnum> =
+            fallback_agent().and_then(|name| match super::try_new(&name) {
+                Ok(agent) => {
+                    info!(
+                        "Using {} routing while no external router is connected",
+                        name
+                    );
+                    Some(agent)
+                }
+                Err(err) => {
+                    error!("Could not start fallback routing {}: {:#}", name, err);
+                    None
+                }
+            });
+        crate::node::spawn(async move {
             while let Some(cmd) = rx.recv().await {
                 match cmd {
                     super::RoutingCmd::SenderForBundle(bp, reply) => {
-                        tokio::spawn(async move {
-                            reply.send(sender_for_bundle(&bp).await).unwrap();
+                        let fallback = fallback.as_ref().map(|agent| agent.channel());
+                        crate::node::spawn(async move {
+                            match (try_sender_for_bundle(&bp).await, fallback) {
+                                (Some(res), _) => {
+                                    let _ = reply.send(res);
+                                }
+                                (None, Some(fallback)) => {
+                                    debug!("No external router, using fallback for {}", bp.id());
+                                    if let Err(err) =
+                                        fallback.send(RoutingCmd::SenderForBundle(bp, reply)).await
+                                    {
+                                        error!("Error while delegating to fallback: {}", err);
+                                    }
+                                }
+                                (None, None) => {
+                                    let _ = reply.send((vec![], false));
+                                }
+                            }
                         });
                     }
                     super::RoutingCmd::Shutdown => {
+                        if let Some(agent) = &fallback {
+                            let _ = agent.channel().send(RoutingCmd::Shutdown).await;
+                        }
                         break;
                     }
-                    super::RoutingCmd::Command(_cmd) => {}
-                    super::RoutingCmd::GetData(_, tx) => {
-                        tx.send("unimplemented!".to_string()).unwrap();
+                    super::RoutingCmd::Command(cmd, reply) => {
+                        // commands are passed to the fallback agent
+                        if let Some(agent) = &fallback {
+                            let _ = agent.channel().send(RoutingCmd::Command(cmd, reply)).await;
+                        } else {
+                            let _ = reply.send(Err(super::CommandError::unknown(&cmd)));
+                        }
+                    }
+                    super::RoutingCmd::GetData(param, tx) => {
+                        // `fallback [<param>]` queries the fallback agent, otherwise the
+                        // connected routers are listed
+                        let (name, param) = param.split_once(' ').unwrap_or((param.as_str(), ""));
+                        match &fallback {
+                            Some(agent) if name == "fallback" => {
+                                let _ = agent
+                                    .channel()
+                                    .send(RoutingCmd::GetData(param.into(), tx))
+                                    .await;
+                            }
+                            _ => {
+                                let routers_as_str = routers().iter().fold(
+                                    String::new(),
+                                    |acc, (id, version, role)| {
+                                        acc + &format!(
+                                            "router {} (version {}): {}\n",
+                                            id, version, role
+                                        )
+                                    },
+                                );
+                                let _ = tx.send(routers_as_str);
+                            }
+                        }
                     }
                     super::RoutingCmd::Notify(notification) => {
+                        // the fallback agent keeps its state to take over at any time
+                        if let Some(agent) = &fallback {
+                            if let Err(err) = agent
+                                .channel()
+                                .send(RoutingCmd::Notify(notification.clone()))
+                                .await
+                            {
+                                error!("Error while notifying fallback: {}", err);
+                            }
+                        }
                         notify(notification);
                     }
                 }
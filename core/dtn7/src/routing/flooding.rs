@@ -22,7 +22,7 @@ impl Default for FloodingRoutingAgent {
 impl FloodingRoutingAgent {
     pub fn new() -> FloodingRoutingAgent {
         let (tx, mut rx) = mpsc::channel(100);
-        tokio::spawn(async move {
+        crate::node::spawn(async move {
             while let Some(cmd) = rx.recv().await {
                 match cmd {
                     super::RoutingCmd::SenderForBundle(_bp, reply) => {
@@ -35,14 +35,16 @@ impl FloodingRoutingAgent {
                             }
                         }
 
-                        tokio::spawn(async move {
+                        crate::node::spawn(async move {
                             reply.send((clas, false)).unwrap();
                         });
                     }
                     super::RoutingCmd::Shutdown => {
                         break;
                     }
-                    super::RoutingCmd::Command(_cmd) => {}
+                    super::RoutingCmd::Command(cmd, reply) => {
+                        let _ = reply.send(Err(super::CommandError::unknown(&cmd)));
+                    }
                     super::RoutingCmd::GetData(_, tx) => {
                         tx.send("unimplemented!".to_string()).unwrap();
                     }
@@ -1,30 +1,36 @@
+pub mod composite;
 pub mod epidemic;
 pub mod erouting;
 pub mod external;
 pub mod flooding;
+pub mod plugin;
 pub mod sink;
 pub mod sprayandwait;
 pub mod static_routing;
 
 use crate::cla::ClaSenderTask;
 use crate::core::bundlepack::BundlePack;
-use crate::BundleID;
+use crate::{BundleID, CONFIG};
+use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
 use bp7::Bundle;
 use bp7::EndpointID;
+use composite::CompositeRoutingAgent;
 use enum_dispatch::enum_dispatch;
 use epidemic::EpidemicRoutingAgent;
 use external::ExternalRoutingAgent;
 use flooding::FloodingRoutingAgent;
+use log::debug;
+use plugin::PluginRoutingAgent;
 use sink::SinkRoutingAgent;
 use sprayandwait::SprayAndWaitRoutingAgent;
 use static_routing::StaticRoutingAgent;
 use std::fmt::Debug;
 use std::fmt::Display;
-use log::debug;
+use thiserror::Error;
 use tokio::sync::{mpsc, oneshot};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum RoutingNotifcation {
     SendingFailed(BundleID, String),
     SendingSucceeded(BundleID, String),
@@ -32,15 +38,19 @@ pub enum RoutingNotifcation {
     IncomingBundleWithoutPreviousNode(BundleID, String),
     EncounteredPeer(EndpointID),
     DroppedPeer(EndpointID),
+    /// Advertised attributes of a known peer changed, e.g., its address or CLAs
+    PeerChanged(EndpointID, Vec<String>),
 }
 
 #[enum_dispatch]
 #[derive(Debug)]
 pub enum RoutingAgentsEnum {
+    CompositeRoutingAgent,
     EpidemicRoutingAgent,
     FloodingRoutingAgent,
     SinkRoutingAgent,
     ExternalRoutingAgent,
+    PluginRoutingAgent,
     SprayAndWaitRoutingAgent,
     StaticRoutingAgent,
 }
@@ -51,10 +61,31 @@ impl Display for RoutingAgentsEnum {
     }
 }
 
+/// Why a routing agent rejected a command
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    /// The command is malformed or not supported by the agent
+    #[error("{0}")]
+    Invalid(String),
+    /// The command refers to something unknown, e.g., a route index
+    #[error("{0}")]
+    NotFound(String),
+    /// The command conflicts with the current state, e.g., an existing route
+    #[error("{0}")]
+    Conflict(String),
+}
+
+impl CommandError {
+    pub fn unknown(cmd: &str) -> Self {
+        CommandError::Invalid(format!("unknown command: {}", cmd))
+    }
+}
+
 pub enum RoutingCmd {
     SenderForBundle(BundlePack, oneshot::Sender<(Vec<ClaSenderTask>, bool)>),
     Notify(RoutingNotifcation),
-    Command(String),
+    /// A command for the agent, answered with the result of its execution
+    Command(String, oneshot::Sender<Result<(), CommandError>>),
     GetData(String, oneshot::Sender<String>),
     Shutdown,
 }
@@ -81,22 +112,89 @@ pub fn routing_algorithms() -> Vec<&'static str> {
         "external",
         "sprayandwait",
         "static",
+        "composite",
     ]
 }
 
+/// Returns true for built-in agents and registered routing plugins
+pub fn is_routing_algorithm(name: &str) -> bool {
+    routing_algorithms().contains(&name) || plugin::is_registered(name)
+}
+
 pub fn routing_options() -> Vec<&'static str> {
-    vec!["sprayandwait.num_copies=<int>", "static.routes=<file>"]
+    vec![
+        "sprayandwait.num_copies=<int>",
+        "sprayandwait.binary=<bool>",
+        "static.routes=<file>",
+        "composite.rules=<file>",
+        "external.fallback=<agent>",
+        "external.tcp_port=<port>",
+        "external.unix_socket=<path>",
+    ]
 }
 
 pub fn new(routingagent: &str) -> RoutingAgentsEnum {
+    try_new(routingagent).unwrap_or_else(|err| panic!("{:#}", err))
+}
+
+/// Creates a routing agent, failing on unknown agents or settings it could not start with
+pub fn try_new(routingagent: &str) -> Result<RoutingAgentsEnum> {
     debug!("Creating routing agent {}", routingagent);
-    match routingagent {
+    check_settings(routingagent)?;
+    let agent = match routingagent {
         "flooding" => FloodingRoutingAgent::new().into(),
         "epidemic" => EpidemicRoutingAgent::new().into(),
         "sink" => SinkRoutingAgent::new().into(),
         "static" => StaticRoutingAgent::new().into(),
         "external" => ExternalRoutingAgent::new().into(),
         "sprayandwait" => SprayAndWaitRoutingAgent::new().into(),
-        _ => panic!("Unknown routing agent {}", routingagent),
+        "composite" => CompositeRoutingAgent::try_new()?.into(),
+        _ => match PluginRoutingAgent::new(routingagent) {
+            Some(agent) => agent.into(),
+            None => bail!("Unknown routing agent {}", routingagent),
+        },
+    };
+    Ok(agent)
+}
+
+/// Checks the settings an agent reads on startup
+pub(crate) fn check_settings(routingagent: &str) -> Result<()> {
+    let settings = CONFIG.lock().routing_settings.get(routingagent).cloned();
+    let settings = settings.unwrap_or_default();
+    match routingagent {
+        "sprayandwait" => {
+            if let Some(num_copies) = settings.get("num_copies") {
+                num_copies
+                    .parse::<usize>()
+                    .with_context(|| format!("invalid sprayandwait.num_copies: {}", num_copies))?;
+            }
+            if let Some(binary) = settings.get("binary") {
+                binary
+                    .parse::<bool>()
+                    .with_context(|| format!("invalid sprayandwait.binary: {}", binary))?;
+            }
+        }
+        "static" => {
+            if let Some(routes) = settings.get("routes") {
+                static_routing::load_routes(routes)?;
+            }
+        }
+        "external" => {
+            if let Some(fallback) = settings.get("fallback") {
+                if fallback == "external" || fallback == "composite" {
+                    bail!("{} routing can not be the external fallback", fallback);
+                }
+                if !is_routing_algorithm(fallback) {
+                    bail!("Unknown external fallback agent {}", fallback);
+                }
+                check_settings(fallback)?;
+            }
+            if let Some(port) = settings.get("tcp_port") {
+                port.parse::<u16>()
+                    .with_context(|| format!("invalid external.tcp_port: {}", port))?;
+            }
+        }
+        _ => {}
     }
+    Ok(())
 }
@@ -0,0 +1,155 @@
+use super::{CommandError, RoutingAgent, RoutingCmd, RoutingNotifcation};
+use crate::cla::ClaSenderTask;
+use crate::core::bundlepack::BundlePack;
+use crate::node::node_local;
+use anyhow::bail;
+use async_trait::async_trait;
+use log::debug;
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::Sender;
+
+/// Routing implemented outside of dtn7, running in-process.
+///
+/// Plugins are registered by name for the running node with [`register_routing_plugin`] and
+/// can then be used as routing strategy like the built-in agents, e.g., in the config file, as fallback of
+/// external routing or in composite routing rules. All hooks of a plugin instance are
+/// called one after another, so no locking is required for its state.
+///
+/// # Example
+///
+/// ```no_run
+/// use async_trait::async_trait;
+/// use dtn7::cla::ClaSenderTask;
+/// use dtn7::core::bundlepack::BundlePack;
+/// use dtn7::routing::plugin::{register_routing_plugin, RoutingPlugin};
+///
+/// /// Forwards all bundles directly to their destination node
+/// #[derive(Default)]
+/// struct DirectRouting;
+///
+/// #[async_trait]
+/// impl RoutingPlugin for DirectRouting {
+///     async fn sender_for_bundle(&mut self, bp: &BundlePack) -> (Vec<ClaSenderTask>, bool) {
+///         match dtn7::peers_cla_for_node(&bp.destination) {
+///             Some(cla) => (vec![cla], true),
+///             None => (vec![], false),
+///         }
+///     }
+/// }
+///
+/// register_routing_plugin("direct", || DirectRouting).unwrap();
+/// dtn7::CONFIG.lock().routing = "direct".into();
+/// ```
+#[async_trait]
+pub trait RoutingPlugin: Send + 'static {
+    /// Returns the CLAs a bundle should be forwarded with and if it should be deleted
+    /// after being forwarded successfully.
+    async fn sender_for_bundle(&mut self, bp: &BundlePack) -> (Vec<ClaSenderTask>, bool);
+    /// Called for every routing notification, e.g., new peers or finished transmissions.
+    async fn notify(&mut self, _notification: RoutingNotifcation) {}
+    /// Handles commands sent via `/routing/cmd` or the `routing/command` API, the result is
+    /// returned to the sender. By default all commands are rejected as unknown.
+    async fn command(&mut self, cmd: &str) -> Result<(), CommandError> {
+        Err(CommandError::unknown(cmd))
+    }
+    /// Returns data for `/routing/getdata` or the `routing/data` API.
+    async fn data(&mut self, _param: &str) -> String {
+        String::new()
+    }
+}
+
+type PluginFactory = Arc<dyn Fn() -> Box<dyn RoutingPlugin> + Send + Sync>;
+
+node_local! {
+    /// Routing plugins registered for the node
+    static ref PLUGINS: Mutex<BTreeMap<String, PluginFactory>> = Mutex::new(BTreeMap::new());
+}
+
+/// Registers a routing plugin for the running node, each routing agent using it gets a new
+/// instance from `factory`.
+///
+/// A plugin with the same name is replaced, names of built-in agents are refused.
+pub fn register_routing_plugin<P, F>(name: &str, factory: F) -> anyhow::Result<()>
+where
+    P: RoutingPlugin,
+    F: Fn() -> P + Send + Sync + 'static,
+{
+    if super::routing_algorithms().contains(&name) {
+        bail!("{} is a built-in routing agent", name);
+    }
+    PLUGINS.lock().insert(
+        name.to_string(),
+        Arc::new(move || Box::new(factory()) as Box<dyn RoutingPlugin>),
+    );
+    Ok(())
+}
+
+/// Names of all registered routing plugins
+pub fn routing_plugins() -> Vec<String> {
+    PLUGINS.lock().keys().cloned().collect()
+}
+
+pub(crate) fn is_registered(name: &str) -> bool {
+    PLUGINS.lock().contains_key(name)
+}
+
+/// Runs a routing plugin as routing agent.
+#[derive(Debug)]
+pub struct PluginRoutingAgent {
+    name: String,
+    tx: mpsc::Sender<RoutingCmd>,
+}
+
+impl PluginRoutingAgent {
+    /// Creates an agent for a registered plugin, `None` if there is no plugin with that name.
+    pub fn new(name: &str) -> Option<Self> {
+        let factory = PLUGINS.lock().get(name).cloned()?;
+        let (tx, rx) = mpsc::channel(100);
+        let plugin = factory();
+        crate::node::spawn(handle_routing_cmd(plugin, rx));
+        Some(PluginRoutingAgent {
+            name: name.to_string(),
+            tx,
+        })
+    }
+}
+
+async fn handle_routing_cmd(
+    mut plugin: Box<dyn RoutingPlugin>,
+    mut rx: mpsc::Receiver<RoutingCmd>,
+) {
+    while let Some(cmd) = rx.recv().await {
+        match cmd {
+            RoutingCmd::SenderForBundle(bp, reply) => {
+                let _ = reply.send(plugin.sender_for_bundle(&bp).await);
+            }
+            RoutingCmd::Shutdown => {
+                break;
+            }
+            RoutingCmd::Command(cmd, reply) => {
+                let _ = reply.send(plugin.command(&cmd).await);
+            }
+            RoutingCmd::GetData(param, tx) => {
+                let _ = tx.send(plugin.data(&param).await);
+            }
+            RoutingCmd::Notify(notification) => plugin.notify(notification).await,
+        }
+    }
+    debug!("Routing plugin stopped");
+}
+
+#[async_trait]
+impl RoutingAgent for PluginRoutingAgent {
+    fn channel(&self) -> Sender<RoutingCmd> {
+        self.tx.clone()
+    }
+}
+
+impl std::fmt::Display for PluginRoutingAgent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "PluginRoutingAgent({})", self.name)
+    }
+}
@@ -17,7 +17,7 @@ impl Default for SinkRoutingAgent {
 impl SinkRoutingAgent {
     pub fn new() -> Self {
         let (tx, mut rx) = mpsc::channel(1);
-        tokio::spawn(async move {
+        crate::node::spawn(async move {
             while let Some(cmd) = rx.recv().await {
                 match cmd {
                     super::RoutingCmd::SenderForBundle(_bp, reply) => {
@@ -26,7 +26,9 @@ impl SinkRoutingAgent {
                     super::RoutingCmd::Shutdown => {
                         break;
                     }
-                    super::RoutingCmd::Command(_cmd) => {}
+                    super::RoutingCmd::Command(cmd, reply) => {
+                        let _ = reply.send(Err(super::CommandError::unknown(&cmd)));
+                    }
                     super::RoutingCmd::GetData(_, tx) => {
                         tx.send("unimplemented!".to_string()).unwrap();
                     }
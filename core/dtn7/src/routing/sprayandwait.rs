@@ -6,13 +6,75 @@ use crate::core::bundlepack::BundlePack;
 use crate::routing::RoutingCmd;
 use crate::{RoutingNotifcation, CONFIG, PEERS};
 use async_trait::async_trait;
+use bp7::canonical::{new_canonical_block, CanonicalBlock, CanonicalBlockType, CanonicalData};
+use bp7::crc::CrcBlock;
+use bp7::flags::BlockControlFlags;
+use bp7::Bundle;
 use log::{debug, info, warn};
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::Sender;
 
+/// Block type of the copy count extension block, from the range reserved for private use.
+///
+/// The block carries the number of copies the receiving node is responsible for as
+/// CBOR unsigned integer.
+pub const COPY_COUNT_BLOCK: CanonicalBlockType = 200;
+
+pub fn new_copy_count_block(
+    block_number: u64,
+    bcf: BlockControlFlags,
+    copies: u64,
+) -> CanonicalBlock {
+    new_canonical_block(
+        COPY_COUNT_BLOCK,
+        block_number,
+        bcf.bits(),
+        CanonicalData::Unknown(serde_cbor::to_vec(&copies).unwrap()),
+    )
+}
+
+/// Copy budget carried in the copy count block of a bundle
+///
+/// Example
+///
+/// ```
+/// use bp7::helpers::rnd_bundle;
+/// use bp7::CreationTimestamp;
+/// use dtn7::routing::sprayandwait::{copy_count, set_copy_count};
+///
+/// let mut bndl = rnd_bundle(CreationTimestamp::now());
+/// assert_eq!(copy_count(&bndl), None);
+/// set_copy_count(&mut bndl, 8);
+/// set_copy_count(&mut bndl, 4);
+/// assert_eq!(copy_count(&bndl), Some(4));
+/// ```
+pub fn copy_count(bundle: &Bundle) -> Option<u64> {
+    match bundle.extension_block_by_type(COPY_COUNT_BLOCK)?.data() {
+        CanonicalData::Unknown(data) => serde_cbor::from_slice(data).ok(),
+        _ => None,
+    }
+}
+
+/// Sets the copy budget of a bundle, adding a copy count block if necessary
+pub fn set_copy_count(bundle: &mut Bundle, copies: u64) {
+    let data = CanonicalData::Unknown(serde_cbor::to_vec(&copies).unwrap());
+    if let Some(block) = bundle.extension_block_by_type_mut(COPY_COUNT_BLOCK) {
+        block.set_data(data);
+    } else {
+        let mut block = new_copy_count_block(0, BlockControlFlags::empty(), copies);
+        block.set_crc_type(bundle.primary.crc_type());
+        bundle.add_canonical_block(block);
+    }
+}
+
 /// Simple implementation of basic spray and wait routing.
 /// For each bundle only l copies are spread after which we enter a wait phase.
 /// Then only direct delivery is possible.
+///
+/// In binary mode each encountered peer receives half of the remaining copies instead of a
+/// single one. The number of copies a node is responsible for travels with the bundle in the
+/// copy count block, so relays continue spraying their share. It is set per bundle at send
+/// time with the `copies` option, otherwise the source starts with `num_copies`.
 #[derive(Debug)]
 pub struct SprayAndWaitRoutingAgent {
     tx: mpsc::Sender<super::RoutingCmd>,
@@ -22,8 +84,8 @@ pub struct SprayAndWaitRoutingAgent {
 pub struct SaWBundleData {
     /// the number of copies we have left to spread
     remaining_copies: usize,
-    /// the list of nodes that have already received the bundle
-    nodes: Vec<String>,
+    /// the names of the nodes that have already received the bundle and the number of copies handed to them
+    nodes: HashMap<String, usize>,
 }
 
 /// The default number of copies that are can be sent to peers.
@@ -32,6 +94,8 @@ const MAX_COPIES: usize = 7;
 struct SprayAndWaitRoutingAgentCore {
     /// the number of copies we have left to spread
     l: usize,
+    /// hand half of the remaining copies to each peer instead of one
+    binary: bool,
     /// for each bundle ID we store the number of copies we have left and the already nodes that already received a copy
     history: HashMap<String, SaWBundleData>,
     /// our local node ID to identify our own bundles
@@ -39,32 +103,38 @@ struct SprayAndWaitRoutingAgentCore {
 }
 
 impl SprayAndWaitRoutingAgentCore {
-    pub fn new(starting_copies: usize) -> SprayAndWaitRoutingAgentCore {
+    pub fn new(starting_copies: usize, binary: bool) -> SprayAndWaitRoutingAgentCore {
         SprayAndWaitRoutingAgentCore {
             l: starting_copies,
+            binary,
             history: HashMap::new(),
             local_node: crate::CONFIG.lock().host_eid.node_id().unwrap(),
         }
     }
     /// Prepare new bundles for spreading.
-    pub fn handle_new_bundle(&mut self, bundle_id: String) {
-        if bundle_id.starts_with(&self.local_node) {
+    pub fn handle_new_bundle(&mut self, bundle_id: String, copies: Option<u64>) {
+        if self.history.contains_key(&bundle_id) {
+            // already known, e.g., a retried bundle
+            return;
+        }
+        let remaining_copies = if let Some(copies) = copies {
+            // the copy budget carried by the bundle takes precedence
+            debug!("Adding bundle {} with {} copies", &bundle_id, copies);
+            copies as usize
+        } else if bundle_id.starts_with(&self.local_node) {
             // this is our own bundle, thus, we have l copies to spread
-            let meta = SaWBundleData {
-                remaining_copies: self.l,
-                nodes: Vec::new(),
-            };
             debug!("Adding new bundle {} from this host", &bundle_id);
-            self.history.insert(bundle_id, meta);
+            self.l
         } else {
             // this is a bundle from another host, thus, we have only one copy
-            let meta = SaWBundleData {
-                remaining_copies: 1,
-                nodes: Vec::new(),
-            };
             debug!("Adding bundle {} from foreign host", &bundle_id);
-            self.history.insert(bundle_id, meta);
-        }
+            1
+        };
+        let meta = SaWBundleData {
+            remaining_copies,
+            nodes: HashMap::new(),
+        };
+        self.history.insert(bundle_id, meta);
     }
 }
 
@@ -77,7 +147,7 @@ impl Default for SprayAndWaitRoutingAgent {
 impl SprayAndWaitRoutingAgent {
     pub fn new() -> SprayAndWaitRoutingAgent {
         let (tx, rx) = mpsc::channel(100);
-        tokio::spawn(async move {
+        crate::node::spawn(async move {
             handle_routing_cmd(rx).await;
         });
 
@@ -101,26 +171,18 @@ fn handle_notification(core: &mut SprayAndWaitRoutingAgentCore, notification: Ro
     match notification {
         RoutingNotifcation::SendingFailed(bid, next_hop_node_name) => {
             // If a transmission fails we have to remove the node from the list of already received nodes
-            // and increase the number of remaining copies again.
+            // and take back the copies handed to it.
             if let Some(meta) = core.history.get_mut(&bid) {
-                let old_size = meta.nodes.len();
-                meta.nodes
-                    .retain(|node_name| node_name.contains(&next_hop_node_name));
-                if old_size != meta.nodes.len() + 1 {
-                    warn!(
-                        "Removed {} from bid {} entry, duplicate entries, should be 1!",
-                        meta.nodes.len(),
-                        &bid
-                    );
+                if let Some(copies) = meta.nodes.remove(&next_hop_node_name) {
+                    meta.remaining_copies += copies;
                 }
-                meta.remaining_copies += 1;
             }
         }
         RoutingNotifcation::IncomingBundle(bndl) => {
-            core.handle_new_bundle(bndl.id());
+            core.handle_new_bundle(bndl.id(), copy_count(&bndl));
         }
         RoutingNotifcation::IncomingBundleWithoutPreviousNode(bid, _node_name) => {
-            core.handle_new_bundle(bid);
+            core.handle_new_bundle(bid, None);
         }
         _ => {}
     }
@@ -136,7 +198,7 @@ async fn handle_sender_for_bundle(
     if let Some(meta) = core.history.get_mut(bp.id()) {
         for (_, p) in (*PEERS.lock()).iter() {
             let peer_node_id = p.eid.node_id().unwrap();
-            if peer_node_id == core.local_node || meta.nodes.contains(&peer_node_id) {
+            if peer_node_id == core.local_node || meta.nodes.contains_key(&p.node_name()) {
                 // skip if the peer is ourself or if we already sent the bundle to this peer
                 continue;
             }
@@ -149,7 +211,8 @@ async fn handle_sender_for_bundle(
                         bp.id(),
                         p.node_name()
                     );
-                    if let Some(cla) = p.first_cla() {
+                    if let Some(mut cla) = p.first_cla() {
+                        cla.copies = Some(1);
                         delete_afterwards = true;
                         clas.clear();
                         clas.push(cla);
@@ -162,10 +225,16 @@ async fn handle_sender_for_bundle(
                 }
                 continue;
             }
-            if let Some(cla) = p.first_cla() {
+            if let Some(mut cla) = p.first_cla() {
+                let copies = if core.binary {
+                    meta.remaining_copies / 2
+                } else {
+                    1
+                };
+                cla.copies = Some(copies as u64);
                 clas.push(cla);
-                meta.remaining_copies -= 1;
-                meta.nodes.push(peer_node_id.clone());
+                meta.remaining_copies -= copies;
+                meta.nodes.insert(p.node_name(), copies);
             }
             debug!(
                 "Relaying bundle {} to {}, {} copies remaining",
@@ -177,7 +246,7 @@ async fn handle_sender_for_bundle(
     } else {
         warn!("Bundle {} not found", bp.id());
     }
-    tokio::spawn(async move {
+    crate::node::spawn(async move {
         reply.send((clas, delete_afterwards)).unwrap();
     });
 }
@@ -193,9 +262,19 @@ async fn handle_routing_cmd(mut rx: mpsc::Receiver<RoutingCmd>) {
     } else {
         MAX_COPIES
     };
-    info!("configured to allow {} copies", max_copies);
+    let binary = settings
+        .get("sprayandwait")
+        .and_then(|settings| settings.get("binary"))
+        .map(|binary| binary.parse::<bool>().expect("invalid sprayandwait.binary"))
+        .unwrap_or(false);
+    info!(
+        "configured to allow {} copies{}",
+        max_copies,
+        if binary { ", binary mode" } else { "" }
+    );
 
-    let mut core: SprayAndWaitRoutingAgentCore = SprayAndWaitRoutingAgentCore::new(max_copies);
+    let mut core: SprayAndWaitRoutingAgentCore =
+        SprayAndWaitRoutingAgentCore::new(max_copies, binary);
     while let Some(cmd) = rx.recv().await {
         match cmd {
             super::RoutingCmd::SenderForBundle(bp, reply) => {
@@ -204,7 +283,9 @@ async fn handle_routing_cmd(mut rx: mpsc::Receiver<RoutingCmd>) {
             super::RoutingCmd::Shutdown => {
                 break;
             }
-            super::RoutingCmd::Command(_cmd) => {}
+            super::RoutingCmd::Command(cmd, reply) => {
+                let _ = reply.send(Err(super::CommandError::unknown(&cmd)));
+            }
             super::RoutingCmd::GetData(_, tx) => {
                 tx.send(format!("{:?}", core.history)).unwrap();
             }
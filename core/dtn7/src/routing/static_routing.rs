@@ -1,15 +1,32 @@
 use std::fmt::Display;
+use std::str::FromStr;
 
 use crate::{RoutingNotifcation, CONFIG, PEERS};
 
-use super::{RoutingAgent, RoutingCmd};
+use super::{CommandError, RoutingAgent, RoutingCmd};
+use anyhow::Context;
 use async_trait::async_trait;
+use bp7::EndpointID;
 use glob_match::glob_match;
-use log::{debug, info};
+use log::{debug, error, info};
+use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::Sender;
 
-#[derive(Debug)]
+/*
+    Static routing with a route table loaded from a file.
+
+    Every line of the file is a route `<idx> <src> <dst> <via> [<metric>]`, lines starting
+    with `#` are comments. If several routes match a bundle, the one with the lowest metric
+    whose `via` peer is currently known is used, routes with equal metrics are tried in table
+    order.
+
+    The table can be changed at runtime with the commands `add <route>`, `insert <route>`,
+    `del <idx>` and `reload`. `add` replaces a route with the same index, `insert` refuses it.
+    Invalid routes are rejected and a reload only replaces the table if the whole file is valid.
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StaticRouteEntry {
     /// index in the routing table
     pub idx: u16,
@@ -19,6 +36,9 @@ pub struct StaticRouteEntry {
     pub dst: String,
     /// next hop eid
     pub via: String,
+    /// preference among matching routes, lower is preferred
+    #[serde(default)]
+    pub metric: u32,
 }
 
 impl Display for StaticRouteEntry {
@@ -27,8 +47,113 @@ impl Display for StaticRouteEntry {
             f,
             "#{}: route from {} to {} via {}",
             self.idx, self.src, self.dst, self.via
+        )?;
+        if self.metric != 0 {
+            write!(f, " (metric {})", self.metric)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for StaticRouteEntry {
+    type Err = String;
+    /// Parses a route in the syntax of the routes file
+    ///
+    /// ```
+    /// use dtn7::routing::static_routing::StaticRouteEntry;
+    ///
+    /// let route: StaticRouteEntry = "1 * ipn:[2-3].* ipn:2.0 10".parse().unwrap();
+    /// assert_eq!(route.metric, 10);
+    /// assert_eq!(route.to_line().parse::<StaticRouteEntry>(), Ok(route));
+    /// assert!("1 * ipn:3.*".parse::<StaticRouteEntry>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        if parts.len() < 4 || parts.len() > 5 {
+            return Err(format!(
+                "expected <idx> <src> <dst> <via> [<metric>], got '{}'",
+                s.trim()
+            ));
+        }
+        let idx = parts[0]
+            .parse::<u16>()
+            .map_err(|_| format!("invalid route index: {}", parts[0]))?;
+        let metric = match parts.get(4) {
+            Some(metric) => metric
+                .parse::<u32>()
+                .map_err(|_| format!("invalid route metric: {}", metric))?,
+            None => 0,
+        };
+        let route = StaticRouteEntry {
+            idx,
+            src: parts[1].to_string(),
+            dst: parts[2].to_string(),
+            via: parts[3].to_string(),
+            metric,
+        };
+        route.validate()?;
+        Ok(route)
+    }
+}
+
+impl StaticRouteEntry {
+    /// Route in the syntax of the routes file
+    pub fn to_line(&self) -> String {
+        format!(
+            "{} {} {} {} {}",
+            self.idx, self.src, self.dst, self.via, self.metric
         )
     }
+    /// Checks that the route can be written to a routes file and its next hop is an endpoint ID
+    pub fn validate(&self) -> Result<(), String> {
+        for pattern in [&self.src, &self.dst] {
+            if pattern.is_empty() || pattern.contains(char::is_whitespace) {
+                return Err(format!("invalid route pattern: '{}'", pattern));
+            }
+        }
+        EndpointID::try_from(self.via.as_str())
+            .map_err(|err| format!("invalid next hop {}: {}", self.via, err))?;
+        Ok(())
+    }
+}
+
+/// Parses the content of a routes file, reporting the first invalid line
+pub fn parse_routes(content: &str) -> Result<Vec<StaticRouteEntry>, String> {
+    let mut routes: Vec<StaticRouteEntry> = Vec::new();
+    for (num, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        let route: StaticRouteEntry = line
+            .parse()
+            .map_err(|err| format!("line {}: {}", num + 1, err))?;
+        if routes.iter().any(|r| r.idx == route.idx) {
+            return Err(format!(
+                "line {}: duplicate route index {}",
+                num + 1,
+                route.idx
+            ));
+        }
+        routes.push(route);
+    }
+    Ok(routes)
+}
+
+/// Loads the routes from a file
+pub fn load_routes(path: &str) -> anyhow::Result<Vec<StaticRouteEntry>> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("reading routes {}", path))?;
+    parse_routes(&content).map_err(|err| anyhow::anyhow!("{}:{}", path, err))
+}
+
+/// Routes file configured in `static.routes`
+pub fn routes_file() -> Option<String> {
+    CONFIG
+        .lock()
+        .routing_settings
+        .get("static")
+        .and_then(|settings| settings.get("routes").cloned())
 }
 
 #[derive(Debug)]
@@ -36,11 +161,69 @@ pub struct StaticRoutingAgent {
     tx: mpsc::Sender<super::RoutingCmd>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct StaticRoutingAgentCore {
     routes: Vec<StaticRouteEntry>,
 }
 
+impl StaticRoutingAgentCore {
+    /// Adds a route, a route with the same index is replaced
+    fn add(&mut self, route: StaticRouteEntry) {
+        if let Some(existing) = self.routes.iter_mut().find(|r| r.idx == route.idx) {
+            *existing = route;
+        } else {
+            self.routes.push(route);
+        }
+    }
+    fn remove(&mut self, idx: u16) -> bool {
+        let len = self.routes.len();
+        self.routes.retain(|r| r.idx != idx);
+        self.routes.len() != len
+    }
+    /// Routes ordered by preference
+    fn by_metric(&self) -> Vec<&StaticRouteEntry> {
+        let mut routes: Vec<&StaticRouteEntry> = self.routes.iter().collect();
+        routes.sort_by_key(|r| r.metric);
+        routes
+    }
+    fn command(&mut self, cmd: &str) -> Result<(), CommandError> {
+        let (name, args) = cmd.trim().split_once(' ').unwrap_or((cmd.trim(), ""));
+        match name {
+            "reload" => {
+                let path = routes_file()
+                    .ok_or_else(|| CommandError::Invalid("no routes file configured".into()))?;
+                let routes = load_routes(&path)
+                    .map_err(|err| CommandError::Invalid(format!("{:#}", err)))?;
+                info!("Reloading static routes from {}", path);
+                self.routes = routes;
+            }
+            "add" | "insert" => {
+                let route: StaticRouteEntry = args.parse().map_err(CommandError::Invalid)?;
+                if name == "insert" && self.routes.iter().any(|r| r.idx == route.idx) {
+                    return Err(CommandError::Conflict(format!(
+                        "route {} already exists",
+                        route.idx
+                    )));
+                }
+                info!("Adding static route: {}", route);
+                self.add(route);
+            }
+            "del" => {
+                let idx = args
+                    .trim()
+                    .parse::<u16>()
+                    .map_err(|_| CommandError::Invalid(format!("invalid route index: {}", args)))?;
+                if !self.remove(idx) {
+                    return Err(CommandError::NotFound(format!("route {} not found", idx)));
+                }
+                info!("Removed static route #{}", idx);
+            }
+            _ => return Err(CommandError::unknown(cmd)),
+        }
+        Ok(())
+    }
+}
+
 impl Default for StaticRoutingAgent {
     fn default() -> Self {
         StaticRoutingAgent::new()
@@ -50,7 +233,7 @@ impl Default for StaticRoutingAgent {
 impl StaticRoutingAgent {
     pub fn new() -> Self {
         let (tx, rx) = mpsc::channel(1);
-        tokio::spawn(async move {
+        crate::node::spawn(async move {
             handle_routing_cmd(rx).await;
         });
         StaticRoutingAgent { tx }
@@ -70,24 +253,6 @@ impl std::fmt::Display for StaticRoutingAgent {
     }
 }
 
-fn parse_route_from_str(s: &str) -> Option<StaticRouteEntry> {
-    let s = s.trim();
-    if s.starts_with('#') || s.is_empty() {
-        return None;
-    }
-    let mut parts = s.split_whitespace();
-    let idx = parts.next().unwrap().parse::<u16>().unwrap();
-    let src = parts.next().unwrap();
-    let dst = parts.next().unwrap();
-    let via = parts.next().unwrap();
-    Some(StaticRouteEntry {
-        idx,
-        src: src.to_string(),
-        dst: dst.to_string(),
-        via: via.to_string(),
-    })
-}
-
 async fn handle_routing_notification(notification: RoutingNotifcation) {
     debug!("Received notification: {:?}", notification);
     match notification {
@@ -102,32 +267,23 @@ async fn handle_routing_notification(notification: RoutingNotifcation) {
 }
 
 async fn handle_routing_cmd(mut rx: mpsc::Receiver<RoutingCmd>) {
-    let mut route_entries = vec![];
-    let settings = CONFIG.lock().routing_settings.clone();
-
-    if let Some(static_settings) = settings.get("static") {
-        if let Some(routes_file) = static_settings.get("routes") {
-            // open file and read routes line by line
-            let routes = std::fs::read_to_string(routes_file).unwrap();
-            for line in routes.lines() {
-                if let Some(entry) = parse_route_from_str(line) {
-                    debug!("Adding static route: {}", entry);
-                    route_entries.push(entry);
-                }
-            }
+    let mut core = StaticRoutingAgentCore::default();
+    if let Some(path) = routes_file() {
+        match load_routes(&path) {
+            Ok(routes) => core.routes = routes,
+            Err(err) => error!("Not loading static routes: {:#}", err),
         }
     }
-
-    let mut core: StaticRoutingAgentCore = StaticRoutingAgentCore {
-        routes: route_entries,
-    };
+    for route in &core.routes {
+        debug!("Adding static route: {}", route);
+    }
 
     while let Some(cmd) = rx.recv().await {
         match cmd {
             super::RoutingCmd::SenderForBundle(bp, reply) => {
                 let mut clas = vec![];
                 let mut delete_afterwards = false;
-                'route_loop: for route in &core.routes {
+                'route_loop: for route in core.by_metric() {
                     if glob_match(&route.src, &bp.source.to_string())
                         && glob_match(&route.dst, &bp.destination.to_string())
                     {
@@ -144,7 +300,7 @@ async fn handle_routing_cmd(mut rx: mpsc::Receiver<RoutingCmd>) {
                                 }
                             }
                         }
-                        debug!("No valid peer found for route {}", route)
+                        debug!("No valid peer found for route {}, trying next route", route)
                     }
                 }
                 if clas.is_empty() {
@@ -155,34 +311,23 @@ async fn handle_routing_cmd(mut rx: mpsc::Receiver<RoutingCmd>) {
             super::RoutingCmd::Shutdown => {
                 break;
             }
-            super::RoutingCmd::Command(cmd) => {
-                if cmd == "reload" {
-                    let settings = CONFIG.lock().routing_settings.clone();
-                    if let Some(static_settings) = settings.get("static") {
-                        if let Some(routes_file) = static_settings.get("routes") {
-                            info!("Reloading static routes from {}", routes_file);
-                            // open file and read routes line by line
-                            let routes = std::fs::read_to_string(routes_file).unwrap();
-                            let mut route_entries = vec![];
-                            for line in routes.lines() {
-                                if let Some(entry) = parse_route_from_str(line) {
-                                    debug!("Adding static route: {}", entry);
-                                    route_entries.push(entry);
-                                }
-                            }
-                            core.routes = route_entries;
-                        }
-                    }
-                } else {
-                    debug!("Unknown command: {}", cmd);
+            super::RoutingCmd::Command(cmd, reply) => {
+                let res = core.command(&cmd);
+                if let Err(err) = &res {
+                    error!("Static routing command failed: {}", err);
                 }
+                let _ = reply.send(res);
             }
-            super::RoutingCmd::GetData(_, tx) => {
-                let routes_as_str = core
-                    .routes
-                    .iter()
-                    .fold(String::new(), |acc, r| acc + &format!("{}\n", r));
-                tx.send(routes_as_str).unwrap();
+            super::RoutingCmd::GetData(param, tx) => {
+                // `json` lists the routes as JSON, otherwise one route per line
+                let data = if param == "json" {
+                    serde_json::to_string(&core.routes).unwrap_or_default()
+                } else {
+                    core.routes
+                        .iter()
+                        .fold(String::new(), |acc, r| acc + &format!("{}\n", r))
+                };
+                tx.send(data).unwrap();
             }
             super::RoutingCmd::Notify(notification) => {
                 handle_routing_notification(notification).await;
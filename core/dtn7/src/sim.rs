@@ -0,0 +1,348 @@
+//! In-process network simulation for tests.
+//!
+//! A [`Simulation`] runs several [`DtnNode`]s in one tokio runtime, connected by dummy CLAs
+//! sending bundles via a [`DummyNetwork`]. Links between nodes have a delay and a loss rate
+//! and are brought up and down by a schedule, nodes learn about their neighbours only via
+//! these link events.
+//!
+//! Timing is deterministic when the runtime is single threaded with paused time, e.g., with
+//! `#[tokio::test(start_paused = true)]` (requires the `test-util` feature of tokio). Time
+//! then advances as soon as all nodes are idle, so simulating hours takes milliseconds.
+//! Creation timestamps and lifetimes of bundles still follow the system clock.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dtn7::sim::Simulation;
+//! use std::time::Duration;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let secs = Duration::from_secs;
+//! let sim = Simulation::builder()
+//!     .routing("epidemic")
+//!     .nodes(&["n1", "n2", "n3"])
+//!     .link("n1", "n2", Duration::from_millis(100), 0.0)
+//!     .link("n2", "n3", Duration::from_millis(100), 0.0)
+//!     .up(secs(0), "n1", "n2")
+//!     .down(secs(10), "n1", "n2")
+//!     .up(secs(20), "n2", "n3")
+//!     .start()
+//!     .await?;
+//! sim.send("n1", "dtn://n3/incoming", b"hello").await?;
+//! sim.run_until(secs(30)).await;
+//! assert_eq!(sim.received("n3", "incoming").len(), 1);
+//! sim.shutdown().await;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::cla::dummy::{DummyConvergenceLayer, DummyLink, DummyNetwork};
+use crate::client::SendOptions;
+use crate::core::application_agent::ApplicationAgent;
+use crate::core::peer::PeerAddress;
+use crate::core::store::InMemoryBundleStore;
+use crate::core::{DtnPeer, PeerType};
+use crate::node::{DtnNode, DtnNodeBuilder};
+use crate::routing::RoutingNotifcation;
+use crate::{peers_add, peers_remove, routing_notify, DtnConfig, DTNCORE};
+use anyhow::{anyhow, bail, Result};
+use bp7::{Bundle, CreationTimestamp, EndpointID};
+use log::{debug, warn};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryInto;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+type NodeCustomizer = Box<dyn FnOnce(DtnNodeBuilder) -> DtnNodeBuilder>;
+
+/// Scheduled change of a link, links are always changed in both directions
+#[derive(Debug, Clone)]
+struct LinkEvent {
+    /// Time since the start of the simulation
+    at: Duration,
+    a: String,
+    b: String,
+    up: bool,
+}
+
+/// Builds and starts a [`Simulation`].
+pub struct SimulationBuilder {
+    seed: u64,
+    routing: String,
+    nodes: Vec<(String, Option<NodeCustomizer>)>,
+    links: Vec<(String, String, Duration, f64)>,
+    events: Vec<LinkEvent>,
+}
+
+impl SimulationBuilder {
+    /// Seed for the losses of all links, 0 by default
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+    /// Routing strategy of all nodes, `epidemic` by default
+    pub fn routing(mut self, strategy: &str) -> Self {
+        self.routing = strategy.to_string();
+        self
+    }
+    /// Adds a node with the node id `dtn://<name>/` and an `incoming` endpoint
+    pub fn node(mut self, name: &str) -> Self {
+        self.nodes.push((name.to_string(), None));
+        self
+    }
+    pub fn nodes(self, names: &[&str]) -> Self {
+        names.iter().fold(self, |sim, name| sim.node(name))
+    }
+    /// Adds a node, customizing its builder, e.g., to use another routing strategy
+    pub fn node_with<F>(mut self, name: &str, customize: F) -> Self
+    where
+        F: FnOnce(DtnNodeBuilder) -> DtnNodeBuilder + 'static,
+    {
+        self.nodes
+            .push((name.to_string(), Some(Box::new(customize))));
+        self
+    }
+    /// Adds a link between two nodes, down until brought up by the schedule
+    pub fn link(mut self, a: &str, b: &str, delay: Duration, loss: f64) -> Self {
+        self.links.push((a.to_string(), b.to_string(), delay, loss));
+        self
+    }
+    /// Brings a link up at the given time since the start of the simulation
+    pub fn up(self, at: Duration, a: &str, b: &str) -> Self {
+        self.event(at, a, b, true)
+    }
+    /// Takes a link down at the given time since the start of the simulation
+    pub fn down(self, at: Duration, a: &str, b: &str) -> Self {
+        self.event(at, a, b, false)
+    }
+    fn event(mut self, at: Duration, a: &str, b: &str, up: bool) -> Self {
+        self.events.push(LinkEvent {
+            at,
+            a: a.to_string(),
+            b: b.to_string(),
+            up,
+        });
+        self
+    }
+    /// Starts all nodes and the link schedule
+    pub async fn start(self) -> Result<Simulation> {
+        let network = DummyNetwork::new(self.seed);
+        let mut nodes = BTreeMap::new();
+        for (name, customize) in self.nodes {
+            if nodes.contains_key(&name) {
+                bail!("duplicate node {}", name);
+            }
+            let mut cfg = DtnConfig::new();
+            cfg.host_eid = format!("dtn://{}/", name).try_into()?;
+            cfg.endpoints = vec!["incoming".into()];
+            cfg.janitor_interval = Duration::ZERO;
+            cfg.disable_neighbour_discovery = true;
+            let cla_network = network.clone();
+            let cla_name = name.clone();
+            let mut builder = DtnNode::builder()
+                .config(cfg)
+                .cla(move || DummyConvergenceLayer::connected(cla_network, &cla_name).into())
+                .store(InMemoryBundleStore::new().into())
+                .routing(&self.routing)
+                .httpd(false);
+            if let Some(customize) = customize {
+                builder = customize(builder);
+            }
+            let node = builder.start().await?;
+            network.attach(&name, &node);
+            nodes.insert(name, node);
+        }
+        for (a, b, delay, loss) in self.links {
+            for name in [&a, &b] {
+                if !nodes.contains_key(name) {
+                    bail!("link to unknown node {}", name);
+                }
+            }
+            let link = DummyLink {
+                up: false,
+                delay,
+                loss,
+            };
+            network.set_link(&a, &b, link);
+            network.set_link(&b, &a, link);
+        }
+        let mut events = self.events;
+        for event in &events {
+            if network.link(&event.a, &event.b).is_none() {
+                bail!("no link between {} and {}", event.a, event.b);
+            }
+        }
+        // stable, events at the same time are applied in the order they were added
+        events.sort_by_key(|event| event.at);
+
+        let start = Instant::now();
+        // not part of any node, set_link enters the nodes it changes
+        let schedule = {
+            let network = network.clone();
+            let nodes = nodes.clone();
+            tokio::spawn(async move {
+                for event in events {
+                    tokio::time::sleep_until(start + event.at).await;
+                    set_link(&network, &nodes, &event.a, &event.b, event.up).await;
+                }
+            })
+        };
+        Ok(Simulation {
+            network,
+            nodes,
+            start,
+            schedule,
+        })
+    }
+}
+
+/// Nodes connected by an in-memory network, see the [module documentation](self).
+pub struct Simulation {
+    network: DummyNetwork,
+    nodes: BTreeMap<String, DtnNode>,
+    start: Instant,
+    schedule: JoinHandle<()>,
+}
+
+impl Simulation {
+    pub fn builder() -> SimulationBuilder {
+        SimulationBuilder {
+            seed: 0,
+            routing: "epidemic".into(),
+            nodes: Vec::new(),
+            links: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+    /// Returns the node with the given name, panics for unknown nodes
+    pub fn node(&self, name: &str) -> DtnNode {
+        self.nodes
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| panic!("unknown node {}", name))
+    }
+    pub fn network(&self) -> &DummyNetwork {
+        &self.network
+    }
+    /// Time since the start of the simulation
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+    /// Lets the simulation run until the given time since its start
+    pub async fn run_until(&self, at: Duration) {
+        tokio::time::sleep_until(self.start + at).await;
+    }
+    /// Lets the simulation run for the given time
+    pub async fn run_for(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+    /// Brings a link up immediately, independent of the schedule
+    pub async fn link_up(&self, a: &str, b: &str) -> Result<()> {
+        self.change_link(a, b, true).await
+    }
+    /// Takes a link down immediately, independent of the schedule
+    pub async fn link_down(&self, a: &str, b: &str) -> Result<()> {
+        self.change_link(a, b, false).await
+    }
+    async fn change_link(&self, a: &str, b: &str, up: bool) -> Result<()> {
+        if self.network.link(a, b).is_none() {
+            bail!("no link between {} and {}", a, b);
+        }
+        set_link(&self.network, &self.nodes, a, b, up).await;
+        Ok(())
+    }
+    /// Sends a bundle from the node id of a node to the given destination, returns the bundle id
+    pub async fn send(&self, from: &str, destination: &str, payload: &[u8]) -> Result<String> {
+        let node = self
+            .nodes
+            .get(from)
+            .ok_or_else(|| anyhow!("unknown node {}", from))?;
+        let bndl = SendOptions::default().build_bundle(
+            format!("dtn://{}/", from).try_into()?,
+            destination.try_into()?,
+            CreationTimestamp::now(),
+            payload.to_vec(),
+        );
+        let bid = bndl.id();
+        node.scope(crate::core::processing::send_bundle(bndl)).await;
+        Ok(bid)
+    }
+    /// Takes the bundles delivered to an endpoint of a node, e.g., `incoming`
+    pub fn received(&self, node: &str, endpoint: &str) -> Vec<Bundle> {
+        self.node(node).enter(|| {
+            let eid = match crate::CONFIG.lock().host_eid.new_endpoint(endpoint) {
+                Ok(eid) => eid,
+                Err(err) => {
+                    warn!("Invalid endpoint {}: {}", endpoint, err);
+                    return Vec::new();
+                }
+            };
+            let mut core = DTNCORE.lock();
+            match core.get_endpoint_mut(&eid) {
+                Some(aa) => std::iter::from_fn(|| aa.pop()).collect(),
+                None => Vec::new(),
+            }
+        })
+    }
+    /// Stops the schedule and all nodes
+    pub async fn shutdown(self) {
+        self.schedule.abort();
+        for node in self.nodes.values() {
+            node.shutdown().await;
+        }
+    }
+}
+
+/// Changes a link in both directions and lets both nodes know about the change
+async fn set_link(
+    network: &DummyNetwork,
+    nodes: &BTreeMap<String, DtnNode>,
+    a: &str,
+    b: &str,
+    up: bool,
+) {
+    debug!(
+        "Simulation: link {} <-> {} {}",
+        a,
+        b,
+        if up { "up" } else { "down" }
+    );
+    for (from, to) in [(a, b), (b, a)] {
+        if let Some(mut link) = network.link(from, to) {
+            link.up = up;
+            network.set_link(from, to, link);
+        }
+    }
+    for (local, remote) in [(a, b), (b, a)] {
+        let eid: EndpointID = match format!("dtn://{}/", remote).try_into() {
+            Ok(eid) => eid,
+            Err(err) => {
+                warn!("Simulation: invalid node name {}: {:?}", remote, err);
+                continue;
+            }
+        };
+        let remote = remote.to_string();
+        nodes[local]
+            .scope(async move {
+                let notification = if up {
+                    peers_add(DtnPeer::new(
+                        eid.clone(),
+                        PeerAddress::Generic(remote),
+                        PeerType::Static,
+                        None,
+                        vec![("dummy".into(), None)],
+                        HashMap::new(),
+                    ));
+                    RoutingNotifcation::EncounteredPeer(eid)
+                } else {
+                    peers_remove(&remote);
+                    RoutingNotifcation::DroppedPeer(eid)
+                };
+                if let Err(err) = routing_notify(notification).await {
+                    warn!("Simulation: error notifying routing agent: {}", err);
+                }
+            })
+            .await;
+    }
+}
@@ -0,0 +1,263 @@
+mod common;
+
+use bp7::CreationTimestamp;
+use dtn7::client::SendOptions;
+use dtn7::core::admission::{Action, AddrRange, AdmissionPolicy, AdmissionRule, Candidate};
+use dtn7::core::helpers::parse_peer_url;
+use dtn7::{peers_add, peers_known, store_has_item, DtnConfig, CONFIG};
+use hyper::{Method, StatusCode};
+use std::convert::TryInto;
+use std::io::Write;
+use std::net::SocketAddr;
+
+fn rule(
+    action: Action,
+    node: Option<&str>,
+    addr: Option<&str>,
+    cla: Option<&str>,
+) -> AdmissionRule {
+    AdmissionRule {
+        action,
+        node: node.map(|node| node.into()),
+        addr: addr.map(|addr| addr.parse().unwrap()),
+        cla: cla.map(|cla| cla.into()),
+    }
+}
+
+#[test]
+fn addr_range_test() {
+    let range: AddrRange = "10.1.0.0/16".parse().unwrap();
+    assert_eq!(range.to_string(), "10.1.0.0/16");
+    assert!(range.contains("10.1.255.1".parse().unwrap()));
+    assert!(!range.contains("10.0.255.1".parse().unwrap()));
+
+    let single: AddrRange = "192.168.1.7".parse().unwrap();
+    assert_eq!(single.to_string(), "192.168.1.7/32");
+    assert!(single.contains("192.168.1.7".parse().unwrap()));
+    assert!(!single.contains("192.168.1.8".parse().unwrap()));
+
+    let any: AddrRange = "0.0.0.0/0".parse().unwrap();
+    assert!(any.contains("8.8.8.8".parse().unwrap()));
+    assert!(!any.contains("::1".parse().unwrap()));
+
+    let v6: AddrRange = "fe80::/10".parse().unwrap();
+    assert!(v6.contains("fe80::1".parse().unwrap()));
+    assert!(v6.contains("febf::1".parse().unwrap()));
+    assert!(!v6.contains("fec0::1".parse().unwrap()));
+
+    assert!("10.0.0.0/33".parse::<AddrRange>().is_err());
+    assert!("fe80::/129".parse::<AddrRange>().is_err());
+    assert!("node1".parse::<AddrRange>().is_err());
+}
+
+#[test]
+fn admission_policy_test() {
+    let policy = AdmissionPolicy {
+        default: Action::Deny,
+        rules: vec![
+            rule(Action::Deny, Some("gs*"), None, Some("http")),
+            rule(Action::Allow, Some("gs*"), Some("10.42.0.0/16"), None),
+            rule(Action::Allow, None, None, Some("mtcp")),
+        ],
+    };
+    let candidate = |node, addr: Option<&str>, cla| Candidate {
+        node,
+        addr: addr.map(|addr| addr.parse().unwrap()),
+        cla,
+    };
+    assert!(policy.admits(&candidate(Some("gs1"), Some("10.42.0.1"), Some("tcp"))));
+    assert!(!policy.admits(&candidate(Some("gs1"), Some("10.42.0.1"), Some("http"))));
+    assert!(!policy.admits(&candidate(Some("gs1"), Some("10.43.0.1"), Some("tcp"))));
+    // unknown address never matches an address condition
+    assert!(!policy.admits(&candidate(Some("gs1"), None, Some("tcp"))));
+    assert!(policy.admits(&candidate(Some("node1"), None, Some("mtcp"))));
+    assert!(!policy.admits(&candidate(Some("node1"), None, None)));
+    assert_eq!(
+        AdmissionPolicy::default().action(&Candidate::default()),
+        Action::Allow
+    );
+
+    let mut peer = parse_peer_url("mtcp://10.43.0.1:16162/gs2").unwrap();
+    peer.cla_list.push(("http".into(), None));
+    assert!(policy.admit_peer(&mut peer));
+    assert_eq!(peer.cla_list, vec![("mtcp".to_string(), Some(16162))]);
+
+    let mut peer = parse_peer_url("http://10.43.0.1/gs2").unwrap();
+    assert!(!policy.admit_peer(&mut peer));
+}
+
+#[test]
+fn admission_config_test() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        "nodeid = \"node1\" # keep me\n\n[admission]\ndefault = \"deny\"\n\n[[admission.rules]]\naction = \"allow\"\nnode = \"gs*\"\naddr = \"10.42.0.0/16\"\n\n[[admission.rules]]\naction = \"allow\"\ncla = \"mtcp\"\n"
+    )
+    .unwrap();
+    let cfg = DtnConfig::from(file.path().to_path_buf());
+    assert_eq!(cfg.config_file.as_deref(), Some(file.path()));
+    assert_eq!(
+        cfg.admission,
+        AdmissionPolicy {
+            default: Action::Deny,
+            rules: vec![
+                rule(Action::Allow, Some("gs*"), Some("10.42.0.0/16"), None),
+                rule(Action::Allow, None, None, Some("mtcp")),
+            ]
+        }
+    );
+
+    // saving keeps the rest of the file
+    let mut policy = cfg.admission.clone();
+    policy.default = Action::Allow;
+    policy.rules.remove(1);
+    policy
+        .rules
+        .insert(0, rule(Action::Deny, Some("node9"), None, None));
+    policy.persist(file.path()).unwrap();
+    let content = std::fs::read_to_string(file.path()).unwrap();
+    assert!(content.starts_with("nodeid = \"node1\" # keep me"));
+    let cfg = DtnConfig::from(file.path().to_path_buf());
+    assert_eq!(cfg.admission, policy);
+}
+
+#[test]
+#[should_panic]
+fn admission_config_invalid_rule_test() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        "[[admission.rules]]\naction = \"deny\"\naddr = \"10.0.0.0/42\"\n"
+    )
+    .unwrap();
+    let _ = DtnConfig::from(file.path().to_path_buf());
+}
+
+async fn request(
+    addr: SocketAddr,
+    method: Method,
+    path: &str,
+    body: Option<&str>,
+) -> (StatusCode, AdmissionPolicy) {
+    let (status, body) = common::request(addr, method, path, body).await;
+    (status, serde_json::from_slice(&body).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn admission_runtime_test() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    CONFIG.lock().config_file = Some(file.path().to_path_buf());
+
+    let addr = common::spawn_api().await;
+    for url in ["http://10.0.0.2:3000/node2", "mtcp://10.9.1.1:16162/node9"] {
+        peers_add(parse_peer_url(url).unwrap());
+    }
+
+    let (status, policy) = request(
+        addr,
+        Method::POST,
+        "/admission/rules",
+        Some(r#"{"action": "deny", "cla": "http"}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(policy.rules.len(), 1);
+    // known peers are checked against the changed policy
+    assert!(!peers_known("node2"));
+    assert!(peers_known("node9"));
+    let (status, policy) = request(
+        addr,
+        Method::POST,
+        "/admission/rules?position=0",
+        Some(r#"{"action": "deny", "node": "node9", "addr": "10.9.0.0/16"}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(
+        policy.rules[0],
+        rule(Action::Deny, Some("node9"), Some("10.9.0.0/16"), None)
+    );
+    assert!(!peers_known("node9"));
+    let (status, _) = request(
+        addr,
+        Method::POST,
+        "/admission/rules?position=5",
+        Some(r#"{"action": "deny"}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = request(
+        addr,
+        Method::POST,
+        "/admission/rules",
+        Some(r#"{"action": "deny", "addr": "nowhere"}"#),
+    )
+    .await;
+    assert!(status.is_client_error());
+
+    // changes are applied and persisted
+    let (status, current) = request(addr, Method::GET, "/admission", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(current, policy);
+    assert_eq!(CONFIG.lock().admission, policy);
+    let persisted = DtnConfig::from(file.path().to_path_buf()).admission;
+    assert_eq!(persisted, policy);
+
+    // bundles from denied nodes are dropped on reception
+    let bndl = SendOptions::default().build_bundle(
+        "dtn://node9/app".try_into().unwrap(),
+        "dtn://node1/incoming".try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    );
+    dtn7::core::processing::receive_from(bndl.clone(), "mtcp", Some("10.9.1.1:16162".into()))
+        .await
+        .unwrap();
+    assert!(!store_has_item(&bndl.id()));
+
+    let (status, _) = request(addr, Method::DELETE, "/admission/rules/2", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, policy) = request(addr, Method::DELETE, "/admission/rules/0", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        policy.rules,
+        vec![rule(Action::Deny, None, None, Some("http"))]
+    );
+
+    let (status, policy) = request(
+        addr,
+        Method::PUT,
+        "/admission",
+        Some(r#"{"default": "deny", "rules": []}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(policy.default, Action::Deny);
+    assert_eq!(DtnConfig::from(file.path().to_path_buf()).admission, policy);
+
+    // bundles pushed via http are checked against the address of the sender
+    let (status, _) = request(
+        addr,
+        Method::PUT,
+        "/admission",
+        Some(r#"{"default": "allow", "rules": [{"action": "deny", "addr": "127.0.0.0/8"}]}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let server = hyper::Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(dtn7::dtnd::httpd::router().into_make_service_with_connect_info::<SocketAddr>());
+    let httpd = server.local_addr();
+    tokio::spawn(server);
+    let bndl = SendOptions::default().build_bundle(
+        "dtn://node2/app".try_into().unwrap(),
+        "dtn://node1/incoming".try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    );
+    let req = hyper::Request::post(format!("http://{}/push", httpd))
+        .body(hyper::Body::from(bndl.clone().to_cbor()))
+        .unwrap();
+    let res = hyper::Client::new().request(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(!store_has_item(&bndl.id()));
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use bp7::CreationTimestamp;
+use common::{request, spawn_api};
+use dtn7::client::SendOptions;
+use dtn7::core::helpers::parse_peer_url;
+use dtn7::dtnd::api::{openapi, paths, ApiError, EndpointResource, NodeResource};
+use hyper::{Method, StatusCode};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::convert::TryInto;
+use std::io::Write;
+
+#[test]
+fn openapi_test() {
+    let doc = openapi();
+    assert_eq!(doc["openapi"], "3.0.3");
+    let described: BTreeSet<String> = doc["paths"].as_object().unwrap().keys().cloned().collect();
+    let routed: BTreeSet<String> = paths().into_iter().collect();
+    assert_eq!(described, routed);
+    assert!(doc["components"]["schemas"]["Error"].is_object());
+}
+
+#[tokio::test]
+async fn openapi_methods_test() {
+    let addr = spawn_api().await;
+    let doc = openapi();
+    for path in paths() {
+        let uri = path
+            .replace("{bid}", "dtn%3A%2F%2Fnone%2F-1-0")
+            .replace(['{', '}'], "");
+        for method in [Method::GET, Method::POST, Method::PUT, Method::DELETE] {
+            let described = doc["paths"][&path][method.as_str().to_lowercase()].is_object();
+            let (status, _) = request(addr, method.clone(), &uri, Some("{}")).await;
+            assert_eq!(
+                status != StatusCode::METHOD_NOT_ALLOWED,
+                described,
+                "{} {} is {} but routed with {}",
+                method,
+                path,
+                if described {
+                    "described"
+                } else {
+                    "not described"
+                },
+                status
+            );
+        }
+    }
+}
+
+/// Checks that all fields of a response are described by its schema
+fn check_schema(doc: &Value, schema: &Value, value: &Value, at: &str) {
+    let schema = match schema["$ref"].as_str() {
+        Some(reference) => {
+            let name = reference.trim_start_matches("#/components/schemas/");
+            &doc["components"]["schemas"][name]
+        }
+        None => schema,
+    };
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                let at = format!("{}.{}", at, key);
+                match (&schema["properties"][key], &schema["additionalProperties"]) {
+                    (Value::Null, Value::Bool(true)) => {}
+                    (Value::Null, Value::Object(_)) => {
+                        check_schema(doc, &schema["additionalProperties"], field, &at)
+                    }
+                    (Value::Null, _) if schema["properties"].is_object() => {
+                        panic!("{} is not described", at)
+                    }
+                    (Value::Null, _) => {}
+                    (property, _) => check_schema(doc, property, field, &at),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                check_schema(doc, &schema["items"], item, &format!("{}[{}]", at, i));
+            }
+        }
+        _ => {}
+    }
+}
+
+#[tokio::test]
+async fn openapi_schemas_test() {
+    let addr = spawn_api().await;
+    let doc = openapi();
+
+    // static routing serves all routing resources
+    let mut routes = tempfile::NamedTempFile::new().unwrap();
+    writeln!(routes, "1 ** dtn://node9/* dtn://node2/ 10").unwrap();
+    let settings = BTreeMap::from([(
+        "static".to_string(),
+        HashMap::from([(
+            "routes".to_string(),
+            routes.path().to_str().unwrap().to_string(),
+        )]),
+    )]);
+    dtn7::routing_replace("static", settings).await.unwrap();
+    dtn7::peers_add(parse_peer_url("mtcp://192.168.2.1:2342/node2").unwrap());
+    let bndl = SendOptions::default().build_bundle(
+        dtn7::CONFIG.lock().host_eid.clone(),
+        "dtn://node2/incoming".try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    );
+    dtn7::store_push_bundle(&bndl).unwrap();
+    let bid = bndl.id().replace(':', "%3A").replace('/', "%2F");
+
+    for (path, item) in doc["paths"].as_object().unwrap() {
+        let schema = &item["get"]["responses"]["200"]["content"]["application/json"]["schema"];
+        if schema.is_null() {
+            continue;
+        }
+        let uri = path.replace("{bid}", &bid).replace("{node}", "node2");
+        let (status, body) = request(addr, Method::GET, &uri, None).await;
+        // no status reports were received for the bundle
+        if path == "/bundles/{bid}/reports" {
+            assert_eq!(status, StatusCode::NOT_FOUND);
+            continue;
+        }
+        assert_eq!(status, StatusCode::OK, "GET {}", path);
+        let value: Value = serde_json::from_slice(&body).unwrap();
+        check_schema(&doc, schema, &value, path);
+    }
+}
+
+#[tokio::test]
+async fn api_resources_test() {
+    let addr = spawn_api().await;
+
+    let (status, body) = request(addr, Method::GET, "/node", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let node: NodeResource = serde_json::from_slice(&body).unwrap();
+    assert_eq!(node.node_id, dtn7::CONFIG.lock().host_eid.to_string());
+
+    let (status, body) = request(
+        addr,
+        Method::POST,
+        "/endpoints",
+        Some(r#"{"endpoint": "incoming"}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let endpoint: EndpointResource = serde_json::from_slice(&body).unwrap();
+    assert!(endpoint.eid.ends_with("/incoming"));
+
+    let (status, _) = request(addr, Method::DELETE, "/endpoints/incoming", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, body) = request(addr, Method::GET, "/bundles?limit=0", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let err: ApiError = serde_json::from_slice(&body).unwrap();
+    assert_eq!(err.status, 400);
+
+    let (status, body) = request(addr, Method::GET, "/bundles/dtn%3A%2F%2Fnone%2F-1-0", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let err: ApiError = serde_json::from_slice(&body).unwrap();
+    assert_eq!(err.error, "not_found");
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use base64::prelude::*;
+use dtn7::dtnd::auth::{issue_token, verify_token, AuthConfig, Scope, StaticToken, TokenClaims};
+use hmac::{Hmac, Mac};
+use hyper::{Method, StatusCode};
+use sha2::Sha256;
+use std::net::SocketAddr;
+
+#[test]
+fn scope_parsing_test() {
+    assert_eq!("read-status".parse::<Scope>().unwrap(), Scope::ReadStatus);
+    assert_eq!(
+        "receive:dtn://node1/*".parse::<Scope>().unwrap(),
+        Scope::Receive("dtn://node1/*".into())
+    );
+    assert_eq!(
+        Scope::Receive("dtn://node1/*".into()).to_string(),
+        "receive:dtn://node1/*"
+    );
+    assert!("receive:".parse::<Scope>().is_err());
+    assert!("root".parse::<Scope>().is_err());
+}
+
+#[test]
+fn token_test() {
+    let cfg = AuthConfig {
+        enabled: true,
+        hmac_secret: Some("secret".into()),
+        tokens: vec![StaticToken {
+            name: "dashboard".into(),
+            token: "static-token".into(),
+            scopes: vec![Scope::ReadStatus],
+        }],
+        ..Default::default()
+    };
+
+    let grant = verify_token(&cfg, "static-token").unwrap();
+    assert!(grant.has(&Scope::ReadStatus));
+    assert!(!grant.may_send());
+    assert!(verify_token(&cfg, "static-tokem").is_none());
+
+    let claims = TokenClaims {
+        sub: Some("app".into()),
+        scopes: vec![Scope::Send, Scope::Receive("dtn://node1/app/*".into())],
+        exp: None,
+    };
+    let token = issue_token("secret", &claims);
+    let grant = verify_token(&cfg, &token).unwrap();
+    assert!(grant.may_send());
+    assert!(grant.may_receive("dtn://node1/app/incoming"));
+    assert!(!grant.may_receive("dtn://node1/other"));
+
+    // wrong secret and expired tokens are rejected
+    assert!(verify_token(&cfg, &issue_token("other", &claims)).is_none());
+    let expired = TokenClaims {
+        exp: Some(1),
+        ..claims
+    };
+    assert!(verify_token(&cfg, &issue_token("secret", &expired)).is_none());
+}
+
+#[test]
+fn truncated_signature_test() {
+    let cfg = AuthConfig {
+        enabled: true,
+        hmac_secret: Some("secret".into()),
+        ..Default::default()
+    };
+    let payload = BASE64_URL_SAFE_NO_PAD.encode(r#"{"sub":"app","scopes":["send"]}"#);
+    let sign = |len: usize| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(payload.as_bytes());
+        let signature = BASE64_URL_SAFE_NO_PAD.encode(&mac.finalize().into_bytes()[..len]);
+        format!("{}.{}", payload, signature)
+    };
+    assert!(verify_token(&cfg, &sign(32)).unwrap().may_send());
+    // signatures of the length of HMAC-SHA1 are not accepted
+    assert!(verify_token(&cfg, &sign(20)).is_none());
+}
+
+async fn request(
+    addr: SocketAddr,
+    method: Method,
+    path: &str,
+    token: Option<&str>,
+    body: Option<&str>,
+) -> StatusCode {
+    common::request_with_token(addr, method, path, token, body)
+        .await
+        .0
+}
+
+#[tokio::test]
+async fn api_scopes_test() {
+    {
+        let mut cfg = dtn7::CONFIG.lock();
+        cfg.auth.enabled = true;
+        cfg.auth.trust_localhost = false;
+        cfg.auth.public_status = false;
+        cfg.auth.tokens = vec![
+            StaticToken {
+                name: "dashboard".into(),
+                token: "status".into(),
+                scopes: vec![Scope::ReadStatus],
+            },
+            StaticToken {
+                name: "app".into(),
+                token: "app".into(),
+                scopes: vec![Scope::Receive("*://*/app*".into())],
+            },
+        ];
+    }
+
+    let addr = common::spawn_api().await;
+
+    assert_eq!(
+        request(addr, Method::GET, "/node", None, None).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        request(addr, Method::GET, "/node", Some("wrong"), None).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        request(addr, Method::GET, "/node", Some("status"), None).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        request(
+            addr,
+            Method::POST,
+            "/routing/commands",
+            Some("status"),
+            Some(r#"{"command": "x"}"#)
+        )
+        .await,
+        StatusCode::FORBIDDEN
+    );
+
+    let endpoint = r#"{"endpoint": "app1"}"#;
+    assert_eq!(
+        request(
+            addr,
+            Method::POST,
+            "/endpoints",
+            Some("status"),
+            Some(endpoint)
+        )
+        .await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        request(
+            addr,
+            Method::POST,
+            "/endpoints",
+            Some("app"),
+            Some(endpoint)
+        )
+        .await,
+        StatusCode::CREATED
+    );
+    assert_eq!(
+        request(
+            addr,
+            Method::POST,
+            "/endpoints",
+            Some("app"),
+            Some(r#"{"endpoint": "other"}"#)
+        )
+        .await,
+        StatusCode::FORBIDDEN
+    );
+}
@@ -0,0 +1,171 @@
+use bp7::EndpointID;
+use dtn7::core::helpers::rnd_peer;
+use dtn7::core::PeerType;
+use dtn7::ipnd::beacon::Beacon;
+use dtn7::ipnd::security::{
+    self, encode_key, parse_key, public_key, BeaconSecurityConfig, BeaconVerdict,
+    UnauthenticatedBeacons,
+};
+use dtn7::ipnd::services::ServiceBlock;
+use dtn7::DtnConfig;
+use std::convert::TryFrom;
+use std::io::Write;
+use std::time::Duration;
+
+fn signed_beacon(seed: &[u8; 32]) -> Beacon {
+    signed_beacon_at("node2", seed, 1)
+}
+
+fn signed_beacon_at(node: &str, seed: &[u8; 32], timestamp: u64) -> Beacon {
+    let mut beacon = Beacon::with_config(
+        EndpointID::try_from(format!("dtn://{}/", node)).unwrap(),
+        ServiceBlock::new(),
+        Some(Duration::from_secs(2)),
+    );
+    beacon.add_cla("mtcp", &Some(16163));
+    beacon.add_custom_service(63, "hello".into());
+    beacon.set_beacon_sequence_number(7);
+    beacon.set_timestamp(timestamp);
+    beacon.sign(seed);
+    beacon
+}
+
+#[test]
+fn beacon_signature_test() {
+    let seed = [1u8; 32];
+    let beacon = signed_beacon(&seed);
+    let pk = public_key(&seed);
+    assert!(beacon.verify(&pk));
+    assert!(!beacon.verify(&public_key(&[2u8; 32])));
+
+    // the signature survives the wire format and is no regular service
+    let received: Beacon = serde_cbor::from_slice(&serde_cbor::to_vec(&beacon).unwrap()).unwrap();
+    assert!(received.verify(&pk));
+    assert_eq!(received.service_block().convert_services().len(), 1);
+    assert_eq!(received.timestamp(), Some(1));
+
+    // any change invalidates the signature
+    let mut replayed = received.clone();
+    replayed.set_beacon_sequence_number(8);
+    assert!(!replayed.verify(&pk));
+    let mut restamped = received.clone();
+    restamped.set_timestamp(2);
+    assert!(!restamped.verify(&pk));
+    let mut extended = received;
+    extended.add_cla("tcp", &Some(4556));
+    assert!(!extended.verify(&pk));
+    assert!(!Beacon::new(EndpointID::try_from("dtn://node2/").unwrap()).verify(&pk));
+}
+
+#[test]
+fn beacon_verdict_test() {
+    let seed = [3u8; 32];
+    let mut cfg = BeaconSecurityConfig::default();
+    assert_eq!(cfg.unauthenticated, UnauthenticatedBeacons::Accept);
+    assert_eq!(
+        cfg.check(&signed_beacon(&seed)),
+        BeaconVerdict::Unauthenticated
+    );
+
+    cfg.trusted_keys.insert("node2".into(), public_key(&seed));
+    assert_eq!(cfg.check(&signed_beacon(&seed)), BeaconVerdict::Trusted);
+    assert_eq!(
+        cfg.check(&signed_beacon(&[4u8; 32])),
+        BeaconVerdict::Invalid
+    );
+    let unsigned = Beacon::new(EndpointID::try_from("dtn://node2/").unwrap());
+    assert_eq!(cfg.check(&unsigned), BeaconVerdict::Invalid);
+    let other = Beacon::new(EndpointID::try_from("dtn://node3/").unwrap());
+    assert_eq!(cfg.check(&other), BeaconVerdict::Unauthenticated);
+
+    assert_eq!(
+        "Quarantine".parse::<UnauthenticatedBeacons>(),
+        Ok(UnauthenticatedBeacons::Quarantine)
+    );
+    assert!("drop".parse::<UnauthenticatedBeacons>().is_err());
+}
+
+#[test]
+fn beacon_replay_test() {
+    let seed = [6u8; 32];
+    let mut cfg = BeaconSecurityConfig::default();
+    cfg.trusted_keys
+        .insert("replayed".into(), public_key(&seed));
+
+    let first = signed_beacon_at("replayed", &seed, 1000);
+    assert_eq!(cfg.authenticate(&first), BeaconVerdict::Trusted);
+    // the same beacon sent again, e.g., from another host
+    assert_eq!(cfg.authenticate(&first), BeaconVerdict::Replayed);
+
+    let next = signed_beacon_at("replayed", &seed, 2000);
+    assert_eq!(cfg.authenticate(&next), BeaconVerdict::Trusted);
+    assert_eq!(
+        cfg.authenticate(&signed_beacon_at("replayed", &seed, 1500)),
+        BeaconVerdict::Replayed
+    );
+
+    // signed beacons of trusted nodes need a signing time
+    let mut untimed = Beacon::new(EndpointID::try_from("dtn://replayed/").unwrap());
+    untimed.sign(&seed);
+    assert_eq!(cfg.check(&untimed), BeaconVerdict::Trusted);
+    assert_eq!(cfg.authenticate(&untimed), BeaconVerdict::Replayed);
+
+    // other verdicts are not affected
+    assert_eq!(
+        cfg.authenticate(&signed_beacon_at("replayed", &[7u8; 32], 3000)),
+        BeaconVerdict::Invalid
+    );
+    assert_eq!(
+        cfg.authenticate(&signed_beacon_at("stranger", &seed, 1)),
+        BeaconVerdict::Unauthenticated
+    );
+    assert_eq!(
+        cfg.authenticate(&signed_beacon_at("replayed", &seed, 3000)),
+        BeaconVerdict::Trusted
+    );
+}
+
+#[test]
+fn beacon_security_config_test() {
+    let seed = [5u8; 32];
+    let pk = public_key(&seed);
+    assert_eq!(parse_key(&encode_key(&pk)), Ok(pk));
+    assert!(parse_key("c2hvcnQ=").is_err());
+    assert!(parse_key("not base64!").is_err());
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(
+        file,
+        "[discovery.security]\nsigning-key = \"{}\"\nunauthenticated = \"ignore\"\n\n[discovery.security.trusted-keys]\nnode2 = \"{}\"\nnode3 = \"invalid\"\n",
+        encode_key(&seed),
+        encode_key(&pk)
+    )
+    .unwrap();
+    let cfg = DtnConfig::from(file.path().to_path_buf());
+
+    assert_eq!(cfg.beacon_security.signing_key, Some(seed));
+    assert_eq!(
+        cfg.beacon_security.unauthenticated,
+        UnauthenticatedBeacons::Ignore
+    );
+    assert_eq!(cfg.beacon_security.trusted_keys.len(), 1);
+    assert_eq!(cfg.beacon_security.trusted_keys["node2"], pk);
+
+    // the secret key is never exposed
+    let json = serde_json::to_value(&cfg.beacon_security).unwrap();
+    assert!(json.get("signing_key").is_none());
+    assert_eq!(json["trusted_keys"]["node2"], encode_key(&pk));
+}
+
+#[test]
+fn quarantine_test() {
+    let mut peer = rnd_peer();
+    peer.con_type = PeerType::Dynamic;
+    peer.period = Some(Duration::from_secs(60));
+    let node = peer.node_name();
+    security::quarantine(peer);
+    assert!(security::quarantined_peers().contains_key(&node));
+    assert!(!dtn7::peers_known(&node));
+    assert!(security::release(&node).is_some());
+    assert!(!security::quarantined_peers().contains_key(&node));
+}
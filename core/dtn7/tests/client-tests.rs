@@ -0,0 +1,193 @@
+use bp7::canonical::{CanonicalData, BUNDLE_AGE_BLOCK, HOP_COUNT_BLOCK};
+use bp7::crc::CrcBlock;
+use bp7::flags::BundleControlFlags;
+use bp7::{Bundle, CreationTimestamp};
+use dtn7::client::{ClientError, DtnClient, SendOptions};
+use dtn7::dtnd::httpd::router;
+use dtn7::routing::sprayandwait::copy_count;
+use futures::StreamExt;
+use hyper::StatusCode;
+use std::convert::{TryFrom, TryInto};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+async fn spawn_httpd() -> DtnClient {
+    let server = hyper::Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(router().into_make_service_with_connect_info::<SocketAddr>());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    DtnClient::with_host_and_port("127.0.0.1", addr.port())
+}
+
+#[tokio::test]
+async fn client_status_test() {
+    let client = spawn_httpd().await;
+
+    let node_id = client.local_node_id().await.unwrap();
+    assert_eq!(node_id, dtn7::CONFIG.lock().host_eid);
+
+    let cts1 = client.creation_timestamp().await.unwrap();
+    let cts2 = client.creation_timestamp().await.unwrap();
+    assert_ne!(cts1, cts2);
+
+    assert!(client.peers().await.unwrap().is_empty());
+    assert!(client.bundles().await.unwrap().is_empty());
+    assert_eq!(client.info().await.unwrap().incoming, 0);
+
+    match client.delete_bundle("dtn://none/-1-0").await {
+        Err(ClientError::Status { status, .. }) => assert_eq!(status, StatusCode::NOT_FOUND),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // filters are encoded, they may contain reserved characters
+    let bndl = SendOptions::default().build_bundle(
+        "dtn://node2/app".try_into().unwrap(),
+        "dtn://node3/a+b&c#d".try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    );
+    dtn7::store_push_bundle(&bndl).unwrap();
+    assert_eq!(
+        client.bundles_filtered("a+b&c#d").await.unwrap(),
+        vec![bndl.id()]
+    );
+    assert!(client.bundles_filtered("a b").await.unwrap().is_empty());
+    assert_eq!(
+        client.bundles_digest(Some("a+b&c#d")).await.unwrap(),
+        dtn7::core::helpers::get_digest_of_bids(&[bndl.id()])
+    );
+}
+
+#[tokio::test]
+async fn client_endpoint_test() {
+    let client = spawn_httpd().await;
+
+    client
+        .register_application_endpoint("incoming")
+        .await
+        .unwrap();
+    let eids = client.endpoints().await.unwrap();
+    assert!(eids.iter().any(|eid| eid.ends_with("/incoming")));
+
+    assert!(client.fetch_bundle("incoming").await.unwrap().is_none());
+
+    let mut subscription = client.subscribe("incoming").await.unwrap();
+
+    // bundles arriving while a command waits for its reply are not lost
+    client.register_application_endpoint("other").await.unwrap();
+    let incoming = client
+        .local_node_id()
+        .await
+        .unwrap()
+        .new_endpoint("incoming")
+        .unwrap();
+    let mut bndl = SendOptions::default().build_bundle(
+        incoming.clone(),
+        incoming,
+        CreationTimestamp::now(),
+        b"early".to_vec(),
+    );
+    client.send_bundle(&mut bndl).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    subscription.subscribe("other").await.unwrap();
+    let bndl = tokio::time::timeout(Duration::from_secs(5), subscription.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(bndl.payload().unwrap(), b"early");
+    subscription.close().await.unwrap();
+
+    // subscribing to an unregistered endpoint is rejected by dtnd
+    assert!(matches!(
+        client.subscribe("unknown").await,
+        Err(ClientError::Protocol(_))
+    ));
+
+    client
+        .unregister_application_endpoint("incoming")
+        .await
+        .unwrap();
+    client
+        .unregister_application_endpoint("other")
+        .await
+        .unwrap();
+    let eids = client.endpoints().await.unwrap();
+    assert!(!eids.iter().any(|eid| eid.ends_with("/incoming")));
+}
+
+fn pairs(query: &str) -> Vec<(String, String)> {
+    url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect()
+}
+
+#[test]
+fn send_options_test() {
+    let defaults = SendOptions::from_query(&pairs("dst=dtn://node2/incoming")).unwrap();
+    assert_eq!(defaults, SendOptions::default());
+
+    let opts = SendOptions::from_query(&pairs(
+        "lifetime=5m&report=reception,deletion&dnf=false&ack=true&report_to=dtn://node1/reports\
+         &hop_limit=4&age=true&copies=6&block=192:cafe&block=193:&crc=32",
+    ))
+    .unwrap();
+    let flags = BundleControlFlags::from_bits(opts.flags).unwrap();
+    assert!(flags.contains(
+        BundleControlFlags::BUNDLE_STATUS_REQUEST_RECEPTION
+            | BundleControlFlags::BUNDLE_STATUS_REQUEST_DELETION
+            | BundleControlFlags::BUNDLE_REQUEST_USER_APPLICATION_ACK
+    ));
+    assert!(!flags.contains(BundleControlFlags::BUNDLE_MUST_NOT_FRAGMENTED));
+    assert_eq!(opts.lifetime, Duration::from_secs(300));
+    assert_eq!(opts.hop_limit, 4);
+    assert_eq!(opts.copies, Some(6));
+    assert_eq!(
+        opts.extension_blocks,
+        vec![(192, vec![0xca, 0xfe]), (193, vec![])]
+    );
+    assert_eq!(opts.crc, bp7::crc::CRC_32);
+
+    // the client side encoding is understood by dtnd
+    assert_eq!(SendOptions::from_query(&opts.to_query()).unwrap(), opts);
+
+    for invalid in [
+        "report=everything",
+        "hop_limit=0",
+        "copies=0",
+        "block=1:cafe",
+        "block=192:xyz",
+        "crc=64",
+        "report_to=nonsense",
+    ] {
+        assert!(
+            SendOptions::from_query(&pairs(invalid)).is_err(),
+            "{}",
+            invalid
+        );
+    }
+
+    let mut bndl = opts.build_bundle(
+        "dtn://node1/".try_into().unwrap(),
+        "dtn://node2/incoming".try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    );
+    let mut bndl = Bundle::try_from(bndl.to_cbor()).unwrap();
+    assert!(bndl.validate().is_ok());
+    assert_eq!(bndl.primary.report_to.to_string(), "dtn://node1/reports");
+    assert_eq!(bndl.payload().unwrap(), b"hello");
+    assert!(bndl.extension_block_by_type(BUNDLE_AGE_BLOCK).is_some());
+    assert_eq!(
+        bndl.extension_block_by_type(HOP_COUNT_BLOCK)
+            .unwrap()
+            .data(),
+        &CanonicalData::HopCount(4, 0)
+    );
+    assert_eq!(
+        bndl.extension_block_by_type(192).unwrap().data(),
+        &CanonicalData::Unknown(vec![0xca, 0xfe])
+    );
+    assert_eq!(copy_count(&bndl), Some(6));
+    assert!(bndl.primary.has_crc() && bndl.primary.check_crc());
+}
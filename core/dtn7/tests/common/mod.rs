@@ -0,0 +1,85 @@
+//! Helpers shared by the integration tests
+#![allow(dead_code)]
+
+use bp7::{Bundle, CreationTimestamp};
+use dtn7::client::SendOptions;
+use dtn7::dtnd::api::router;
+use futures_util::StreamExt;
+use hyper::{Body, Client, Method, Request, StatusCode};
+use serde_json::Value;
+use std::convert::TryInto;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// WebSocket connection of an external router to `/ws/erouting`
+pub type Router = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Serves the API router on an ephemeral local port
+pub async fn spawn_api() -> SocketAddr {
+    let app = axum::Router::new().nest("/api/v1", router());
+    let server = hyper::Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+/// Sends a JSON request to an API path and returns status and body of the response
+pub async fn request(
+    addr: SocketAddr,
+    method: Method,
+    path: &str,
+    body: Option<&str>,
+) -> (StatusCode, Vec<u8>) {
+    request_with_token(addr, method, path, None, body).await
+}
+
+/// Like [`request`], optionally authenticated by a bearer token
+pub async fn request_with_token(
+    addr: SocketAddr,
+    method: Method,
+    path: &str,
+    token: Option<&str>,
+    body: Option<&str>,
+) -> (StatusCode, Vec<u8>) {
+    let mut req = Request::builder()
+        .method(method)
+        .uri(format!("http://{}/api/v1{}", addr, path))
+        .header("content-type", "application/json");
+    if let Some(token) = token {
+        req = req.header("authorization", format!("Bearer {}", token));
+    }
+    let req = req
+        .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+        .unwrap();
+    let res = Client::new().request(req).await.unwrap();
+    let status = res.status();
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    (status, body.to_vec())
+}
+
+/// Builds a bundle requesting the given comma separated status reports, e.g., `"deletion,delivery"`
+pub fn bundle_from_to(src: &str, dst: &str, reports: &str, payload: &[u8]) -> Bundle {
+    let mut opts = SendOptions::default();
+    for name in reports.split(',') {
+        opts.flags |= dtn7::client::send::status_report_flag(name).unwrap().bits();
+    }
+    opts.build_bundle(
+        src.try_into().unwrap(),
+        dst.try_into().unwrap(),
+        CreationTimestamp::now(),
+        payload.to_vec(),
+    )
+}
+
+/// Receives the next JSON packet dtnd sent to an external router
+pub async fn recv(router: &mut Router) -> Value {
+    let msg = tokio::time::timeout(Duration::from_secs(5), router.next())
+        .await
+        .expect("no packet from dtnd")
+        .unwrap()
+        .unwrap();
+    serde_json::from_str(msg.to_text().unwrap()).unwrap()
+}
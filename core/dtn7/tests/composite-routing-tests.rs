@@ -0,0 +1,187 @@
+use bp7::{Bundle, CreationTimestamp};
+use dtn7::cla::dummy::DummyConvergenceLayer;
+use dtn7::client::SendOptions;
+use dtn7::core::bundlepack::{BundlePack, Constraint};
+use dtn7::core::helpers::parse_peer_url;
+use dtn7::routing::composite::{
+    bundle_priority, load_rules, CompositeRoutingAgent, CompositeRule, PRIORITY_BLOCK,
+};
+use dtn7::routing::{RoutingAgent, RoutingCmd, RoutingNotifcation};
+use dtn7::{cla_add, peers_add, CONFIG};
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::io::Write;
+use tokio::sync::oneshot;
+
+fn bundle(dst: &str, extension_blocks: Vec<(u64, Vec<u8>)>) -> Bundle {
+    let opts = SendOptions {
+        extension_blocks,
+        ..Default::default()
+    };
+    opts.build_bundle(
+        "dtn://node1/app".try_into().unwrap(),
+        dst.try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    )
+}
+
+#[test]
+fn composite_rule_test() {
+    let rule: CompositeRule = "static dst=dtn://gs*/** size<1025 size>=10 priority=2"
+        .parse()
+        .unwrap();
+    assert_eq!(rule.agent, "static");
+    assert_eq!(rule.dst.as_deref(), Some("dtn://gs*/**"));
+    assert_eq!(rule.size, (Some(10), Some(1024)));
+    assert_eq!(
+        rule.to_string(),
+        "static dst=dtn://gs*/** size>=10 size<=1024 priority=2"
+    );
+    assert_eq!(rule.to_string().parse::<CompositeRule>().unwrap(), rule);
+
+    let bp = BundlePack::from(bundle("dtn://gs1/incoming", vec![]));
+    assert!(bp.size >= 10);
+    assert!(rule.matches(&bp, 2));
+    assert!(!rule.matches(&bp, 1));
+    let bp = BundlePack::from(bundle("dtn://node2/incoming", vec![]));
+    assert!(!rule.matches(&bp, 2));
+
+    let rule: CompositeRule = "sink admin=true".parse().unwrap();
+    assert!(!rule.matches(&bp, 0));
+    let catch_all: CompositeRule = "epidemic".parse().unwrap();
+    assert!(catch_all.matches(&bp, 0));
+
+    for invalid in [
+        "",
+        "composite",
+        "external dst=*",
+        "unknown dst=*",
+        "epidemic size=large",
+        "epidemic priority<0",
+        "epidemic hops>2",
+        "epidemic admin=maybe",
+    ] {
+        assert!(invalid.parse::<CompositeRule>().is_err(), "{}", invalid);
+    }
+
+    let prio = bundle("dtn://node2/incoming", vec![(PRIORITY_BLOCK, vec![0x02])]);
+    assert_eq!(bundle_priority(&prio), 2);
+    assert_eq!(bundle_priority(&bundle("dtn://node2/incoming", vec![])), 0);
+}
+
+#[test]
+fn composite_rules_file_test() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        "# never forward administrative records\nsink admin=true\n\nstatic dst=dtn://gs*/**\nepidemic"
+    )
+    .unwrap();
+    let rules = load_rules(file.path().to_str().unwrap()).unwrap();
+    let agents: Vec<&str> = rules.iter().map(|r| r.agent.as_str()).collect();
+    assert_eq!(agents, vec!["sink", "static", "epidemic"]);
+
+    writeln!(file, "flooding size~3").unwrap();
+    let err = load_rules(file.path().to_str().unwrap()).unwrap_err();
+    assert!(err.to_string().contains(":6:"), "{}", err);
+}
+
+async fn next_hops(agent: &CompositeRoutingAgent, bndl: &Bundle) -> Vec<String> {
+    let (tx, rx) = oneshot::channel();
+    agent
+        .channel()
+        .send(RoutingCmd::SenderForBundle(BundlePack::from(bndl), tx))
+        .await
+        .unwrap();
+    let (clas, _) = rx.await.unwrap();
+    let mut nodes: Vec<String> = clas
+        .into_iter()
+        .map(|cla| cla.next_hop.node().unwrap())
+        .collect();
+    nodes.sort();
+    nodes
+}
+
+#[tokio::test]
+async fn composite_routing_test() {
+    cla_add(DummyConvergenceLayer::new(None).into());
+    for node in ["node2", "node3"] {
+        peers_add(parse_peer_url(&format!("dummy://10.0.0.1/{}", node)).unwrap());
+    }
+    let agent = CompositeRoutingAgent::with_rules(vec![
+        "sink priority>=5".parse().unwrap(),
+        "epidemic dst=dtn://node*/**".parse().unwrap(),
+    ]);
+
+    let bndl = bundle("dtn://node9/incoming", vec![]);
+    assert_eq!(next_hops(&agent, &bndl).await, vec!["node2", "node3"]);
+    assert!(next_hops(&agent, &bndl).await.is_empty());
+
+    // notifications reach the sub-agents
+    agent
+        .channel()
+        .send(RoutingCmd::Notify(RoutingNotifcation::SendingFailed(
+            bndl.id(),
+            "node3".into(),
+        )))
+        .await
+        .unwrap();
+    assert_eq!(next_hops(&agent, &bndl).await, vec!["node3"]);
+
+    // priority is read from the stored bundle, bundles matching no rule are not forwarded
+    let urgent = bundle("dtn://node9/incoming", vec![(PRIORITY_BLOCK, vec![0x05])]);
+    dtn7::store_push_bundle(&urgent).unwrap();
+    assert!(next_hops(&agent, &urgent).await.is_empty());
+    assert!(next_hops(&agent, &bundle("dtn://gs1/incoming", vec![]))
+        .await
+        .is_empty());
+
+    let (tx, rx) = oneshot::channel();
+    agent
+        .channel()
+        .send(RoutingCmd::GetData(String::new(), tx))
+        .await
+        .unwrap();
+    assert_eq!(
+        rx.await.unwrap(),
+        "#0: sink priority>=5\n#1: epidemic dst=dtn://node*/**\n"
+    );
+}
+
+#[tokio::test]
+async fn composite_reload_test() {
+    cla_add(DummyConvergenceLayer::new(None).into());
+    peers_add(parse_peer_url("dummy://10.0.0.1/node2").unwrap());
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(file, "epidemic").unwrap();
+    CONFIG.lock().routing_settings.insert(
+        "composite".into(),
+        HashMap::from([("rules".into(), file.path().to_str().unwrap().into())]),
+    );
+    let agent = CompositeRoutingAgent::try_new().unwrap();
+
+    let bndl = bundle("dtn://node2/incoming", vec![]);
+    dtn7::store_push_bundle(&bndl).unwrap();
+    let mut bp = BundlePack::from(&bndl);
+    bp.add_constraint(Constraint::ForwardPending);
+    dtn7::store_update_metadata(&bp).unwrap();
+
+    // the new sub-agent learns about the waiting bundle, the unused one is stopped
+    std::fs::write(file.path(), "sprayandwait\n").unwrap();
+    let (tx, rx) = oneshot::channel();
+    agent
+        .channel()
+        .send(RoutingCmd::Command("reload".into(), tx))
+        .await
+        .unwrap();
+    assert_eq!(rx.await.unwrap(), Ok(()));
+    assert_eq!(next_hops(&agent, &bndl).await, vec!["node2"]);
+    let (tx, rx) = oneshot::channel();
+    agent
+        .channel()
+        .send(RoutingCmd::GetData("epidemic".into(), tx))
+        .await
+        .unwrap();
+    assert_eq!(rx.await.unwrap(), "#0: sprayandwait\n");
+}
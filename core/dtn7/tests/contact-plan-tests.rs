@@ -0,0 +1,179 @@
+use dtn7::core::contact_plan::{spawn_contact_plan, ContactWindow, CronSchedule, ScheduledPeer};
+use dtn7::core::helpers::parse_peer_url;
+use dtn7::core::PeerType;
+use dtn7::dtnd::httpd::router;
+use dtn7::{peers_add, peers_get_for_node, peers_known, DtnConfig, CONFIG};
+use hyper::StatusCode;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::time::{Duration, SystemTime};
+
+// 2024-06-01T00:00:00Z, a saturday
+const JUNE_1ST: u64 = 1717200000;
+const DAY: u64 = 86400;
+
+#[test]
+fn cron_schedule_test() {
+    let cron: CronSchedule = "30 */2 * * *".parse().unwrap();
+    assert_eq!(cron.next(JUNE_1ST), Some(JUNE_1ST + 1800));
+    assert_eq!(cron.next(JUNE_1ST + 1800), Some(JUNE_1ST + 1800));
+    assert_eq!(cron.next(JUNE_1ST + 1801), Some(JUNE_1ST + 7200 + 1800));
+    assert!(cron.matches(JUNE_1ST + 1830));
+    assert!(!cron.matches(JUNE_1ST + 3600 + 1800));
+
+    // mondays at noon
+    let cron: CronSchedule = "0 12 * * 1".parse().unwrap();
+    assert_eq!(cron.next(JUNE_1ST), Some(JUNE_1ST + 2 * DAY + 12 * 3600));
+
+    // sunday as 7, lists and ranges
+    let cron: CronSchedule = "0,30 6-7 * * 7".parse().unwrap();
+    assert_eq!(cron.next(JUNE_1ST), Some(JUNE_1ST + DAY + 6 * 3600));
+    assert_eq!(
+        cron.next(JUNE_1ST + DAY + 7 * 3600 + 1),
+        Some(JUNE_1ST + DAY + 7 * 3600 + 1800)
+    );
+
+    // restricting day of month and day of week matches either, friday the 7th comes first
+    let cron: CronSchedule = "0 0 13 * 5".parse().unwrap();
+    assert_eq!(cron.next(JUNE_1ST), Some(JUNE_1ST + 6 * DAY));
+
+    // the next leap day
+    let cron: CronSchedule = "0 0 29 2 *".parse().unwrap();
+    assert_eq!(cron.next(JUNE_1ST), Some(1835395200));
+
+    // never fires
+    let cron: CronSchedule = "0 0 31 2 *".parse().unwrap();
+    assert_eq!(cron.next(JUNE_1ST), None);
+
+    assert!("61 * * * *".parse::<CronSchedule>().is_err());
+    assert!("* * * *".parse::<CronSchedule>().is_err());
+    assert!("*/0 * * * *".parse::<CronSchedule>().is_err());
+    assert!("5-1 * * * *".parse::<CronSchedule>().is_err());
+}
+
+#[test]
+fn contact_window_test() {
+    let window: ContactWindow = "2024-06-01T12:00:00Z for 10m".parse().unwrap();
+    assert_eq!(window.to_string(), "2024-06-01T12:00:00Z for 10m");
+    let start = JUNE_1ST + 12 * 3600;
+    assert_eq!(window.next_start(JUNE_1ST), Some(start));
+    assert_eq!(window.next_start(start), None);
+    assert_eq!(window.open_at(start - 1), None);
+    assert_eq!(window.open_at(start), Some(start + 600));
+    assert_eq!(window.open_at(start + 599), Some(start + 600));
+    assert_eq!(window.open_at(start + 600), None);
+
+    let window: ContactWindow = "30 */2 * * * for 8m".parse().unwrap();
+    assert_eq!(
+        window.open_at(JUNE_1ST + 1800 + 60),
+        Some(JUNE_1ST + 1800 + 480)
+    );
+    assert_eq!(window.open_at(JUNE_1ST + 1800 + 480), None);
+    assert_eq!(window.next_start(JUNE_1ST + 1800), Some(JUNE_1ST + 9000));
+
+    assert!("2024-06-01T12:00:00Z".parse::<ContactWindow>().is_err());
+    assert!("2024-06-01T12:00:00Z for 0s"
+        .parse::<ContactWindow>()
+        .is_err());
+    assert!("tomorrow for 10m".parse::<ContactWindow>().is_err());
+}
+
+#[test]
+fn scheduled_peer_test() {
+    let peer = parse_peer_url("mtcp://192.168.2.42/gs1").unwrap();
+    let scheduled = ScheduledPeer::new(
+        peer,
+        vec![
+            "2024-06-01T12:00:00Z for 10m".parse().unwrap(),
+            "2024-06-01T12:05:00Z for 10m".parse().unwrap(),
+            "2024-06-02T00:00:00Z for 1h".parse().unwrap(),
+        ],
+    );
+    assert_eq!(scheduled.peer.con_type, PeerType::Scheduled);
+    let noon = JUNE_1ST + 12 * 3600;
+
+    assert_eq!(scheduled.open_until(JUNE_1ST), None);
+    assert_eq!(scheduled.next_change(JUNE_1ST), Some(noon));
+    // overlapping windows form a single contact
+    assert_eq!(scheduled.open_until(noon + 400), Some(noon + 900));
+    assert_eq!(scheduled.next_change(noon + 600), Some(noon + 900));
+    assert_eq!(scheduled.next_change(noon + 900), Some(JUNE_1ST + DAY));
+    assert_eq!(scheduled.next_change(JUNE_1ST + DAY + 3600), None);
+}
+
+#[test]
+fn scheduled_peer_config_test() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        "[statics.scheduled.groundstation]\npeer = \"mtcp://192.168.2.42/gs1\"\nwindows = [\"2024-06-01T12:00:00Z for 10m\", \"30 */2 * * * for 8m\", \"invalid\"]"
+    )
+    .unwrap();
+    let cfg = DtnConfig::from(file.path().to_path_buf());
+    assert_eq!(cfg.scheduled.len(), 1);
+    assert_eq!(cfg.scheduled[0].peer.node_name(), "gs1");
+    assert_eq!(cfg.scheduled[0].peer.con_type, PeerType::Scheduled);
+    assert_eq!(cfg.scheduled[0].windows.len(), 2);
+
+    let json = serde_json::to_value(&cfg.scheduled).unwrap();
+    assert_eq!(json[0]["windows"][1], "30 */2 * * * for 8m");
+}
+
+#[tokio::test]
+async fn contact_plan_test() {
+    let start = SystemTime::now() + Duration::from_secs(1);
+    let window: ContactWindow = format!("{} for 3s", humantime::format_rfc3339_seconds(start))
+        .parse()
+        .unwrap();
+    let peer = parse_peer_url("mtcp://192.168.2.43/gs2").unwrap();
+    let eid = peer.eid.clone();
+    let replaced = parse_peer_url("mtcp://192.168.2.44/gs3").unwrap();
+    CONFIG.lock().scheduled = vec![
+        ScheduledPeer::new(peer, vec![window.clone()]),
+        ScheduledPeer::new(replaced.clone(), vec![window]),
+    ];
+
+    spawn_contact_plan();
+    assert!(peers_get_for_node(&eid).is_none());
+    tokio::time::sleep(Duration::from_millis(2000)).await;
+    assert_eq!(
+        peers_get_for_node(&eid).map(|peer| peer.con_type),
+        Some(PeerType::Scheduled)
+    );
+
+    // a static peer replacing a scheduled one outlives the contact window
+    let mut static_peer = replaced;
+    static_peer.con_type = PeerType::Static;
+    peers_add(static_peer.clone());
+    tokio::time::sleep(Duration::from_millis(3000)).await;
+    assert!(peers_get_for_node(&eid).is_none());
+    assert_eq!(
+        peers_get_for_node(&static_peer.eid).map(|peer| peer.con_type),
+        Some(PeerType::Static)
+    );
+}
+
+#[tokio::test]
+async fn legacy_add_scheduled_peer_test() {
+    let server = hyper::Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(router().into_make_service_with_connect_info::<SocketAddr>());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+
+    let get = |query: &'static str| async move {
+        let uri = format!(
+            "http://{}/peers/add?p=mtcp://192.168.2.45/gs4{}",
+            addr, query
+        );
+        hyper::Client::new()
+            .get(uri.parse().unwrap())
+            .await
+            .unwrap()
+            .status()
+    };
+    assert_eq!(get("&p_t=scheduled").await, StatusCode::BAD_REQUEST);
+    assert_eq!(get("&p_t=unknown").await, StatusCode::BAD_REQUEST);
+    assert!(!peers_known("gs4"));
+    assert_eq!(get("&p_t=static").await, StatusCode::OK);
+    assert!(peers_known("gs4"));
+}
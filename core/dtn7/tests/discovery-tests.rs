@@ -1,5 +1,6 @@
 use dtn7::core::helpers::rnd_peer;
-use dtn7::{peers_add, peers_clear, peers_count, peers_get_for_node};
+use dtn7::core::PeerType;
+use dtn7::{peers_add, peers_clear, peers_count, peers_get_for_node, peers_update};
 use lazy_static::*;
 use std::sync::{Arc, Mutex};
 
@@ -51,3 +52,43 @@ fn discovery_store_last_contact_test() {
     peers_clear();
     *count += 1;
 }
+
+#[test]
+fn discovery_store_update_test() {
+    let mut count = GLOBAL_ACCESS.lock().unwrap();
+    peers_clear();
+
+    let mut peer = rnd_peer();
+    peer.con_type = PeerType::Static;
+    assert_eq!(peers_update(peer.clone()), None);
+    assert_eq!(peers_update(peer.clone()), Some(vec![]));
+
+    // beacon of the restarted peer on a new address with an additional cla
+    let mut beacon = peer.clone();
+    beacon.con_type = PeerType::Dynamic;
+    beacon.addr = "10.0.0.42".parse::<std::net::IpAddr>().unwrap().into();
+    beacon.cla_list.push(("tcp".into(), Some(4224)));
+
+    // configured peers are not rewritten by beacons
+    for con_type in [PeerType::Static, PeerType::Scheduled] {
+        peer.con_type = con_type;
+        peers_add(peer.clone());
+        assert_eq!(peers_update(beacon.clone()), Some(vec![]));
+        assert_eq!(peers_count(), 1);
+        let stored = peers_get_for_node(&peer.eid).unwrap();
+        assert_eq!(stored.addr, peer.addr);
+        assert_eq!(stored.cla_list, peer.cla_list);
+        assert_eq!(stored.con_type, con_type);
+    }
+
+    // dynamic peers follow their beacons
+    peer.con_type = PeerType::Dynamic;
+    peers_add(peer.clone());
+    assert_eq!(peers_update(beacon.clone()), Some(vec!["addr", "cla_list"]));
+    let stored = peers_get_for_node(&peer.eid).unwrap();
+    assert_eq!(stored.addr, beacon.addr);
+    assert_eq!(stored.cla_list, beacon.cla_list);
+
+    peers_clear();
+    *count += 1;
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use axum::extract::WebSocketUpgrade;
+use axum::routing::get;
+use bp7::CreationTimestamp;
+use common::Router;
+use dtn7::cla::dummy::DummyConvergenceLayer;
+use dtn7::client::SendOptions;
+use dtn7::core::bundlepack::BundlePack;
+use dtn7::core::helpers::parse_peer_url;
+use dtn7::routing::erouting::processing::handle_connection;
+use dtn7::{cla_add, peers_add, routing_get_data, routing_sender_for_bundle, CONFIG, DTNCORE};
+use futures_util::SinkExt;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Starts external routing with the given fallback, as dtnd does at startup
+fn start_external(fallback: &str) -> anyhow::Result<()> {
+    {
+        let mut cfg = CONFIG.lock();
+        cfg.routing = "external".into();
+        cfg.routing_settings.insert(
+            "external".into(),
+            HashMap::from([("fallback".to_string(), fallback.to_string())]),
+        );
+    }
+    DTNCORE.lock().routing_agent = dtn7::routing::try_new("external")?;
+    Ok(())
+}
+
+async fn connect(addr: SocketAddr) -> Router {
+    let (mut router, _) = connect_async(format!("ws://{}/ws/erouting", addr))
+        .await
+        .unwrap();
+    assert_eq!(common::recv(&mut router).await["type"], "PeerState");
+    assert_eq!(common::recv(&mut router).await["type"], "ServiceState");
+    router
+}
+
+/// Waits for the next routing request, skipping notifications
+async fn request(router: &mut Router) -> Value {
+    loop {
+        let packet = common::recv(router).await;
+        if packet["type"] == "RequestSenderForBundle" {
+            return packet;
+        }
+    }
+}
+
+/// Answers the next routing request without next hops
+async fn answer(router: &mut Router) {
+    let request = request(router).await;
+    let response = json!({
+        "type": "ResponseSenderForBundle",
+        "bp": request["bp"],
+        "clas": [],
+        "delete_afterwards": true,
+    });
+    router
+        .send(Message::Text(response.to_string()))
+        .await
+        .unwrap();
+}
+
+/// Polls the routing data until the expected number of routers is connected
+async fn routers(expected: usize) -> Vec<String> {
+    for _ in 0..50 {
+        let data = routing_get_data(String::new()).await.unwrap();
+        let routers: Vec<String> = data.lines().map(String::from).collect();
+        if routers.len() == expected {
+            return routers;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("expected {} connected routers", expected);
+}
+
+fn bundle_pack() -> BundlePack {
+    let bndl = SendOptions::default().build_bundle(
+        "dtn://node1/app".try_into().unwrap(),
+        "dtn://node9/incoming".try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    );
+    dtn7::store_push_bundle(&bndl).unwrap();
+    BundlePack::from(&bndl)
+}
+
+#[tokio::test]
+async fn erouting_failover_test() {
+    cla_add(DummyConvergenceLayer::new(None).into());
+    peers_add(parse_peer_url("dummy://10.0.0.1/node2").unwrap());
+
+    for fallback in ["external", "composite", "unknown"] {
+        assert!(start_external(fallback).is_err());
+    }
+    start_external("epidemic").unwrap();
+
+    let bp = bundle_pack();
+
+    // epidemic routing while no router is connected
+    let (clas, delete_afterwards) = routing_sender_for_bundle(bp.clone()).await.unwrap();
+    assert_eq!(clas.len(), 1);
+    assert!(!delete_afterwards);
+
+    let app = axum::Router::new().route(
+        "/ws/erouting",
+        get(|ws: WebSocketUpgrade| async move { ws.on_upgrade(handle_connection) }),
+    );
+    let server = hyper::Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+
+    let mut primary = connect(addr).await;
+    let mut standby = connect(addr).await;
+    let listed = routers(2).await;
+    assert!(listed[0].ends_with("primary"));
+    assert!(listed[1].ends_with("standby"));
+
+    // the primary router answers
+    let pending = tokio::spawn(routing_sender_for_bundle(bp.clone()));
+    answer(&mut primary).await;
+    let (clas, delete_afterwards) = pending.await.unwrap().unwrap();
+    assert!(clas.is_empty());
+    assert!(delete_afterwards);
+
+    // a request pending when the primary disconnects is passed to the standby
+    let pending = tokio::spawn(routing_sender_for_bundle(bp.clone()));
+    request(&mut primary).await;
+    primary.close(None).await.unwrap();
+    answer(&mut standby).await;
+    let (clas, delete_afterwards) = pending.await.unwrap().unwrap();
+    assert!(clas.is_empty());
+    assert!(delete_afterwards);
+    assert!(routers(1).await[0].ends_with("primary"));
+
+    // a primary not answering repeatedly is demoted, the request is passed to the standby
+    let mut late = connect(addr).await;
+    routers(2).await;
+    for _ in 0..2 {
+        let pending = tokio::spawn(routing_sender_for_bundle(bp.clone()));
+        request(&mut standby).await;
+        let (clas, delete_afterwards) = pending.await.unwrap().unwrap();
+        assert!(clas.is_empty());
+        assert!(!delete_afterwards);
+    }
+    let pending = tokio::spawn(routing_sender_for_bundle(bp.clone()));
+    request(&mut standby).await;
+    answer(&mut late).await;
+    let (_, delete_afterwards) = pending.await.unwrap().unwrap();
+    assert!(delete_afterwards);
+    let listed = routers(2).await;
+    assert!(listed[0].ends_with("primary"));
+    assert!(listed[1].ends_with("unresponsive"));
+
+    // any packet from the demoted router makes it a standby again
+    standby
+        .send(Message::Text(
+            json!({"type": "Hello", "version": 2}).to_string(),
+        ))
+        .await
+        .unwrap();
+    for _ in 0..50 {
+        if routers(2).await[1].ends_with("standby") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(routers(2).await[1].ends_with("standby"));
+    late.close(None).await.unwrap();
+    routers(1).await;
+
+    // back to the fallback agent without routers, epidemic knows node2 already got the
+    // first bundle
+    standby.close(None).await.unwrap();
+    routers(0).await;
+    let (clas, _) = routing_sender_for_bundle(bp).await.unwrap();
+    assert!(clas.is_empty());
+    let (clas, _) = routing_sender_for_bundle(bundle_pack()).await.unwrap();
+    assert_eq!(clas.len(), 1);
+}
@@ -0,0 +1,212 @@
+mod common;
+
+use axum::extract::WebSocketUpgrade;
+use axum::routing::get;
+use bp7::helpers::hexify;
+use bp7::{Bundle, CreationTimestamp};
+use common::Router;
+use dtn7::client::SendOptions;
+use dtn7::core::bundlepack::BundlePack;
+use dtn7::core::store::BundleStore;
+use dtn7::routing::erouting::processing::{handle_connection, sender_for_bundle};
+use dtn7::routing::erouting::{Packet, PROTOCOL_VERSION};
+use dtn7::{store_push_bundle, CONFIG, STORE};
+use futures_util::SinkExt;
+use serde_json::{json, Value};
+use std::convert::TryInto;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+
+fn bundle(extension_blocks: Vec<(u64, Vec<u8>)>) -> Bundle {
+    let opts = SendOptions {
+        extension_blocks,
+        ..Default::default()
+    };
+    opts.build_bundle(
+        "dtn://node1/app".try_into().unwrap(),
+        "dtn://node9/incoming".try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    )
+}
+
+async fn send(router: &mut Router, packet: Value) {
+    router
+        .send(Message::Text(packet.to_string()))
+        .await
+        .unwrap();
+}
+
+/// Answers the next routing request with the given packet, the bundle pack is added
+async fn answer(router: &mut Router, mut packet: Value) {
+    let request = common::recv(router).await;
+    assert_eq!(request["type"], "RequestSenderForBundle");
+    packet["bp"] = request["bp"].clone();
+    send(router, packet).await;
+}
+
+#[test]
+fn erouting_packet_test() {
+    let packet: Packet =
+        serde_json::from_value(json!({ "type": "DropBundle", "bid": "dtn://node1/-1-0" })).unwrap();
+    assert_eq!(packet.version(), 2);
+    match packet {
+        Packet::DropBundle(drop) => assert_eq!(drop.code, 6),
+        _ => panic!("wrong packet"),
+    }
+    let packet: Packet =
+        serde_json::from_value(json!({ "type": "Subscribe", "types": "bundle_deleted" })).unwrap();
+    assert_eq!(packet.version(), 2);
+    let packet: Packet =
+        serde_json::from_value(json!({ "type": "ServiceAdd", "tag": 10, "service": "x" })).unwrap();
+    assert_eq!(packet.version(), 1);
+}
+
+#[tokio::test]
+async fn erouting_protocol_test() {
+    CONFIG.lock().host_eid = "dtn://node1/".try_into().unwrap();
+    let app = axum::Router::new().route(
+        "/ws/erouting",
+        get(|ws: WebSocketUpgrade| async move { ws.on_upgrade(handle_connection) }),
+    );
+    let server = hyper::Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+
+    let (mut router, _) = connect_async(format!("ws://{}/ws/erouting", addr))
+        .await
+        .unwrap();
+    assert_eq!(common::recv(&mut router).await["type"], "PeerState");
+    assert_eq!(common::recv(&mut router).await["type"], "ServiceState");
+
+    // without a hello the router speaks version 1
+    let bndl = bundle(vec![(201, vec![0x02])]);
+    store_push_bundle(&bndl).unwrap();
+    let bp = BundlePack::from(&bndl);
+    let request = tokio::spawn({
+        let bp = bp.clone();
+        async move { sender_for_bundle(&bp).await }
+    });
+    answer(
+        &mut router,
+        json!({ "type": "ResponseSenderForBundle", "clas": [], "delete_afterwards": true }),
+    )
+    .await;
+    let (clas, delete_afterwards) = request.await.unwrap();
+    assert!(clas.is_empty());
+    assert!(delete_afterwards);
+
+    send(
+        &mut router,
+        json!({ "type": "RequestBundleInfo", "bid": bp.id() }),
+    )
+    .await;
+    let error = common::recv(&mut router).await;
+    assert_eq!(error["type"], "Error");
+    assert!(error["reason"].as_str().unwrap().contains("version 2"));
+
+    send(&mut router, json!({ "type": "Hello", "version": 42 })).await;
+    assert_eq!(
+        common::recv(&mut router).await,
+        json!({ "type": "Hello", "version": PROTOCOL_VERSION })
+    );
+
+    // extension blocks and payload size
+    send(
+        &mut router,
+        json!({ "type": "RequestBundleInfo", "bid": bp.id() }),
+    )
+    .await;
+    let info = common::recv(&mut router).await;
+    assert_eq!(info["type"], "BundleInfo");
+    assert_eq!(info["payload_size"], 5);
+    let block = bndl.extension_block_by_type(201).unwrap();
+    assert!(info["blocks"].as_array().unwrap().contains(&json!({
+        "block_type": 201,
+        "block_number": block.block_number,
+        "flags": block.block_control_flags,
+        "data": hexify(&block.data().to_cbor()),
+    })));
+    send(
+        &mut router,
+        json!({ "type": "RequestBundleInfo", "bid": "dtn://node1/-1-0" }),
+    )
+    .await;
+    assert_eq!(common::recv(&mut router).await["type"], "Error");
+
+    // deferred decisions forward nothing
+    let request = tokio::spawn({
+        let bp = bp.clone();
+        async move { sender_for_bundle(&bp).await }
+    });
+    answer(
+        &mut router,
+        json!({ "type": "DeferBundle", "delay_ms": 1000 }),
+    )
+    .await;
+    let (clas, delete_afterwards) = request.await.unwrap();
+    assert!(clas.is_empty());
+    assert!(!delete_afterwards);
+
+    // dropped bundles are deleted and show up as events
+    send(
+        &mut router,
+        json!({ "type": "Subscribe", "types": "bundle_deleted" }),
+    )
+    .await;
+    let request = tokio::spawn({
+        let bp = bp.clone();
+        async move { sender_for_bundle(&bp).await }
+    });
+    answer(
+        &mut router,
+        json!({ "type": "DropBundle", "bid": bp.id(), "reason": "buffer full", "code": 10 }),
+    )
+    .await;
+    let (clas, _) = request.await.unwrap();
+    assert!(clas.is_empty());
+    let event = common::recv(&mut router).await;
+    assert_eq!(event["type"], "Event");
+    assert_eq!(event["event"]["type"], "bundle_deleted");
+    assert_eq!(event["event"]["bid"], bp.id());
+    assert_eq!(event["event"]["reason"], "traffic_pared");
+
+    // administrative bundles from this node
+    send(
+        &mut router,
+        json!({
+            "type": "InjectAdministrativeBundle",
+            "destination": [1, "//node2/"],
+            "record_type": 1,
+            "content": "00",
+        }),
+    )
+    .await;
+    assert_eq!(common::recv(&mut router).await["type"], "Error");
+    send(
+        &mut router,
+        json!({
+            "type": "InjectAdministrativeBundle",
+            "destination": [1, "//node2/"],
+            "record_type": 42,
+            "content": "a1616101",
+        }),
+    )
+    .await;
+    let mut injected = None;
+    for _ in 0..50 {
+        injected = (*STORE.lock())
+            .bundles()
+            .into_iter()
+            .find(|bp| bp.administrative && bp.destination.to_string() == "dtn://node2/");
+        if injected.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let injected = injected.expect("administrative bundle not injected");
+    assert_eq!(injected.source.to_string(), "dtn://node1/");
+}
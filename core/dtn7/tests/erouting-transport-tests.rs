@@ -0,0 +1,142 @@
+use bp7::CreationTimestamp;
+use dtn7::client::SendOptions;
+use dtn7::core::bundlepack::BundlePack;
+use dtn7::dtnd::auth::{Scope, StaticToken};
+use dtn7::routing::erouting::processing::sender_for_bundle;
+use dtn7::routing::erouting::{tcp, unix, MAX_PACKET_LENGTH, PROTOCOL_VERSION};
+use serde_json::{json, Value};
+use std::convert::TryInto;
+use std::net::SocketAddr;
+use std::os::unix::fs::PermissionsExt;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpStream, UnixStream};
+
+struct Router<R, W> {
+    lines: Lines<BufReader<R>>,
+    writer: W,
+}
+
+impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> Router<R, W> {
+    async fn recv(&mut self) -> Value {
+        let line = tokio::time::timeout(Duration::from_secs(5), self.lines.next_line())
+            .await
+            .expect("no packet from dtnd")
+            .unwrap()
+            .expect("connection closed");
+        serde_json::from_str(&line).unwrap()
+    }
+
+    async fn send(&mut self, packet: Value) {
+        self.writer
+            .write_all(format!("{}\n", packet).as_bytes())
+            .await
+            .unwrap();
+    }
+
+    /// Checks the initial state and negotiates the protocol version
+    async fn hello(&mut self) {
+        assert_eq!(self.recv().await["type"], "PeerState");
+        assert_eq!(self.recv().await["type"], "ServiceState");
+        self.send(json!({ "type": "Hello", "version": PROTOCOL_VERSION }))
+            .await;
+        assert_eq!(
+            self.recv().await,
+            json!({ "type": "Hello", "version": PROTOCOL_VERSION })
+        );
+    }
+}
+
+async fn connect_tcp(addr: SocketAddr) -> Router<OwnedReadHalf, OwnedWriteHalf> {
+    let (reader, writer) = TcpStream::connect(addr).await.unwrap().into_split();
+    Router {
+        lines: BufReader::new(reader).lines(),
+        writer,
+    }
+}
+
+#[tokio::test]
+async fn erouting_transport_test() {
+    dtn7::CONFIG.lock().auth.tokens = vec![
+        StaticToken {
+            name: "router".into(),
+            token: "secret".into(),
+            scopes: vec![Scope::Admin],
+        },
+        StaticToken {
+            name: "dashboard".into(),
+            token: "status".into(),
+            scopes: vec![Scope::ReadStatus],
+        },
+    ];
+    let addr = tcp::listen("127.0.0.1:0").await.unwrap();
+
+    // routers connecting via TCP have to present an admin token first
+    for login in [
+        json!({ "type": "Hello", "version": PROTOCOL_VERSION }),
+        json!({ "type": "Auth", "token": "wrong" }),
+        json!({ "type": "Auth", "token": "status" }),
+    ] {
+        let mut router = connect_tcp(addr).await;
+        router.send(login).await;
+        assert_eq!(router.recv().await["type"], "Error");
+        assert!(router.lines.next_line().await.unwrap().is_none());
+    }
+    let mut tcp_router = connect_tcp(addr).await;
+    tcp_router
+        .send(json!({ "type": "Auth", "token": "secret" }))
+        .await;
+    tcp_router.hello().await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("erouting.sock");
+    unix::listen(&path).unwrap();
+    // a stale socket file is replaced
+    unix::listen(&path).unwrap();
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    let (reader, writer) = UnixStream::connect(&path).await.unwrap().into_split();
+    let mut unix_router = Router {
+        lines: BufReader::new(reader).lines(),
+        writer,
+    };
+    unix_router.hello().await;
+
+    // the first router answers routing requests
+    let bp = BundlePack::from(SendOptions::default().build_bundle(
+        "dtn://node1/app".try_into().unwrap(),
+        "dtn://node9/incoming".try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    ));
+    let request = tokio::spawn(async move { sender_for_bundle(&bp).await });
+    let packet = tcp_router.recv().await;
+    assert_eq!(packet["type"], "RequestSenderForBundle");
+    tcp_router
+        .send(json!({
+            "type": "ResponseSenderForBundle",
+            "bp": packet["bp"],
+            "clas": [],
+            "delete_afterwards": true,
+        }))
+        .await;
+    let (clas, delete_afterwards) = request.await.unwrap();
+    assert!(clas.is_empty());
+    assert!(delete_afterwards);
+
+    // invalid lines are skipped
+    unix_router.send(json!({ "type": "Nonsense" })).await;
+    unix_router
+        .send(json!({ "type": "RequestBundleInfo", "bid": "dtn://node1/-1-0" }))
+        .await;
+    assert_eq!(unix_router.recv().await["type"], "Error");
+
+    // overlong lines close the connection
+    let line = format!("{}\n", " ".repeat(MAX_PACKET_LENGTH + 1));
+    let _ = unix_router.writer.write_all(line.as_bytes()).await;
+    let closed = tokio::time::timeout(Duration::from_secs(5), unix_router.lines.next_line())
+        .await
+        .unwrap();
+    assert!(!matches!(closed, Ok(Some(_))));
+}
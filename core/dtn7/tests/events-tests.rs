@@ -0,0 +1,77 @@
+use axum::routing::get;
+use dtn7::core::events::{publish, EventFilter, NodeEvent, TimedEvent};
+use hyper::{Client, StatusCode};
+use std::net::SocketAddr;
+
+#[test]
+fn event_filter_test() {
+    let delivered = NodeEvent::BundleDelivered {
+        bid: "dtn://node1/-1-0".into(),
+        destination: "dtn://node2/incoming".into(),
+    };
+    let peer = NodeEvent::PeerEncountered {
+        eid: "dtn://node3/".into(),
+    };
+
+    let all = EventFilter::default();
+    assert!(all.matches(&delivered) && all.matches(&peer));
+
+    let by_type = EventFilter {
+        types: Some("peer_encountered, peer_dropped".into()),
+        eid: None,
+    };
+    assert!(!by_type.matches(&delivered));
+    assert!(by_type.matches(&peer));
+
+    let by_eid = EventFilter {
+        types: None,
+        eid: Some("dtn://node2/*".into()),
+    };
+    assert!(by_eid.matches(&delivered));
+    assert!(!by_eid.matches(&peer));
+
+    let json = serde_json::to_value(TimedEvent {
+        timestamp: 1,
+        event: peer,
+    })
+    .unwrap();
+    assert_eq!(json["type"], "peer_encountered");
+    assert_eq!(json["eid"], "dtn://node3/");
+}
+
+#[tokio::test]
+async fn sse_stream_test() {
+    let app = axum::Router::new().route("/events", get(dtn7::dtnd::events::sse_handler));
+    let server = hyper::Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+
+    let uri = format!("http://{}/events?types=cla_down", addr)
+        .parse()
+        .unwrap();
+    let res = Client::new().get(uri).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // the subscription exists once the response headers arrived
+    publish(NodeEvent::ClaUp {
+        name: "ignored".into(),
+        port: 1,
+    });
+    publish(NodeEvent::ClaDown {
+        name: "mtcp".into(),
+    });
+
+    let mut body = res.into_body();
+    let chunk = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        hyper::body::HttpBody::data(&mut body),
+    )
+    .await
+    .expect("no event received")
+    .unwrap()
+    .unwrap();
+    let text = String::from_utf8(chunk.to_vec()).unwrap();
+    assert!(text.starts_with("event:cla_down\n"), "{}", text);
+    assert!(text.contains(r#""name":"mtcp""#));
+}
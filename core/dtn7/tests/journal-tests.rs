@@ -0,0 +1,145 @@
+use bp7::{Bundle, CreationTimestamp};
+use dtn7::client::{DtnClient, SendOptions};
+use dtn7::core::bundlepack::{BundlePack, Constraint};
+use dtn7::core::journal::{append, JournalEvent, MAX_JOURNAL_ENTRIES};
+use dtn7::dtnd::httpd::router;
+use dtn7::{store_get_metadata, store_journal, store_push_bundle};
+use std::convert::TryInto;
+use std::net::SocketAddr;
+
+fn new_bundle(dst: &str) -> Bundle {
+    SendOptions::default().build_bundle(
+        "dtn://node1/".try_into().unwrap(),
+        dst.try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    )
+}
+
+fn received(peer: &str) -> JournalEvent {
+    JournalEvent::Received {
+        cla: "mtcp".into(),
+        peer: Some(peer.into()),
+    }
+}
+
+#[test]
+fn journal_append_test() {
+    let mut journal = Vec::new();
+    append(&mut journal, received("node2"));
+    append(&mut journal, JournalEvent::Created);
+    append(&mut journal, JournalEvent::Created);
+    assert_eq!(journal.len(), 2);
+    assert_eq!(journal[1].count, 2);
+
+    for i in 0..MAX_JOURNAL_ENTRIES * 2 {
+        append(
+            &mut journal,
+            JournalEvent::Deleted {
+                reason: i.to_string(),
+            },
+        );
+    }
+    assert_eq!(journal.len(), MAX_JOURNAL_ENTRIES);
+    // the origin of a bundle is never dropped
+    assert_eq!(journal[0].event, received("node2"));
+    assert_eq!(
+        journal.last().unwrap().event,
+        JournalEvent::Deleted {
+            reason: (MAX_JOURNAL_ENTRIES * 2 - 1).to_string()
+        }
+    );
+}
+
+#[test]
+fn journal_serialization_test() {
+    let mut bp = BundlePack::from(new_bundle("dtn://node2/incoming"));
+    let without_journal = bp.to_cbor();
+    append(&mut bp.journal, received("node2"));
+    append(&mut bp.journal, JournalEvent::constraints(&bp.constraints));
+
+    // metadata stored before journals existed is still readable
+    let old = BundlePack::from(without_journal.as_slice());
+    assert!(old.journal.is_empty());
+
+    assert_eq!(BundlePack::from(bp.to_cbor().as_slice()), bp);
+
+    let json = serde_json::to_value(&bp.journal[0]).unwrap();
+    assert_eq!(json["event"], "received");
+    assert_eq!(json["cla"], "mtcp");
+    assert_eq!(json["peer"], "node2");
+    assert_eq!(json["count"], 1);
+}
+
+#[tokio::test]
+async fn journal_store_test() {
+    let bndl = new_bundle("dtn://node2/journal");
+    let bid = bndl.id();
+    store_push_bundle(&bndl).unwrap();
+    let mut stale = store_get_metadata(&bid).unwrap();
+
+    store_journal(&bid, received("node3"));
+    stale.add_constraint(Constraint::ForwardPending);
+    stale.sync().unwrap();
+
+    // syncing outdated metadata keeps the journal and records the constraint change
+    let journal = store_get_metadata(&bid).unwrap().journal;
+    assert_eq!(journal.len(), 2);
+    assert_eq!(journal[0].event, received("node3"));
+    assert_eq!(
+        journal[1].event,
+        JournalEvent::Constraints {
+            constraints: vec![Constraint::ForwardPending]
+        }
+    );
+
+    let server = hyper::Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(router().into_make_service_with_connect_info::<SocketAddr>());
+    let client = DtnClient::with_host_and_port("127.0.0.1", server.local_addr().port());
+    tokio::spawn(server);
+
+    assert_eq!(client.journal(&bid).await.unwrap(), journal);
+    assert_eq!(client.journals().await.unwrap()[&bid], journal);
+
+    // deleted bundles keep their journal and are counted as discarded
+    let discarded = dtn7::STATS.lock().node.error_info.discarded_bundle_count;
+    client.delete_bundle(&bid).await.unwrap();
+    assert!(dtn7::STATS.lock().node.error_info.discarded_bundle_count > discarded);
+    let journal = client.journal(&bid).await.unwrap();
+    let events: Vec<&JournalEvent> = journal.iter().map(|e| &e.event).collect();
+    assert_eq!(
+        events[2..],
+        [
+            &JournalEvent::Deleted {
+                reason: "user_request".into()
+            },
+            &JournalEvent::Constraints {
+                constraints: vec![Constraint::Deleted]
+            }
+        ]
+    );
+    assert!(client.journal("dtn://node1/-1-0").await.is_err());
+}
+
+#[cfg(feature = "store_sneakers")]
+#[test]
+fn journal_sneakers_remove_test() {
+    use dtn7::core::store::{BundleStore, SneakersBundleStore};
+
+    let workdir = tempfile::tempdir().unwrap();
+    dtn7::CONFIG.lock().workdir = workdir.path().to_path_buf();
+    let mut store = SneakersBundleStore::new();
+    let bndl = new_bundle("dtn://node2/sneakers");
+    let bid = bndl.id();
+    store.push(&bndl).unwrap();
+    let mut meta: BundlePack = store.get_metadata(&bid).unwrap();
+    append(&mut meta.journal, received("node3"));
+    store.update_metadata(&meta).unwrap();
+    assert_eq!(store.get_metadata(&bid).unwrap().journal.len(), 1);
+
+    // the journal is dropped with the bundle and does not reappear if it is received again
+    store.remove(&bid).unwrap();
+    assert!(store.get_metadata(&bid).is_none());
+    store.push(&bndl).unwrap();
+    assert!(store.get_metadata(&bid).unwrap().journal.is_empty());
+}
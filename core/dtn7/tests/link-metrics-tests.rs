@@ -0,0 +1,96 @@
+use dtn7::core::helpers::rnd_peer;
+use dtn7::core::peer::PeerMetrics;
+use dtn7::core::DtnPeer;
+use dtn7::{
+    peers_add, peers_clear, peers_get_for_node, peers_record_rtt, peers_record_transfer,
+    peers_remove,
+};
+use lazy_static::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+lazy_static! {
+    pub static ref GLOBAL_ACCESS: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+}
+
+#[test]
+fn link_metrics_transfer_test() {
+    let _guard = GLOBAL_ACCESS.lock().unwrap();
+    peers_clear();
+
+    let peer = rnd_peer();
+    let node = peer.node_name();
+    peers_add(peer.clone());
+
+    peers_record_transfer(&node, "mtcp", 4000, Duration::from_secs(2), true);
+    peers_record_transfer(&node, "mtcp", 4000, Duration::from_secs(1), true);
+    peers_record_transfer(&node, "mtcp", 4000, Duration::from_secs(1), false);
+    peers_record_rtt(&node, "tcp", Duration::from_millis(20));
+    peers_record_rtt(&node, "tcp", Duration::from_millis(60));
+
+    let stored = peers_get_for_node(&peer.eid).unwrap();
+    let mtcp = &stored.metrics.clas["mtcp"];
+    assert_eq!(mtcp.transfers_succeeded, 2);
+    assert_eq!(mtcp.transfers_failed, 1);
+    assert!((mtcp.success_ratio - 2.0 / 3.0).abs() < 1e-9);
+    assert_eq!(mtcp.bytes_sent, 8000);
+    // 2000 B/s smoothed towards 4000 B/s
+    assert_eq!(mtcp.throughput, Some(2500.0));
+    assert_eq!(mtcp.rtt_ms, None);
+    assert_eq!(stored.metrics.clas["tcp"].rtt_ms, Some(30.0));
+
+    // re-adding a known peer keeps its metrics
+    peers_add(peer.clone());
+    let stored = peers_get_for_node(&peer.eid).unwrap();
+    assert_eq!(stored.metrics.clas["mtcp"].transfers_succeeded, 2);
+
+    peers_clear();
+}
+
+#[test]
+fn link_metrics_contacts_test() {
+    let _guard = GLOBAL_ACCESS.lock().unwrap();
+    peers_clear();
+
+    let mut peer = rnd_peer();
+    let node = peer.node_name();
+    let start = peer.metrics.contacts.current_start.unwrap();
+    assert_eq!(peer.metrics.contacts.count, 1);
+    peer.last_contact = start + 30;
+    peers_add(peer.clone());
+    peers_record_transfer(&node, "mtcp", 100, Duration::from_millis(10), true);
+
+    peers_remove(&node);
+    assert!(peers_get_for_node(&peer.eid).is_none());
+
+    // the next encounter continues the history
+    let mut again = peer.clone();
+    again.metrics = PeerMetrics::default();
+    again.metrics.contacts.begin(start + 100);
+    peers_add(again);
+    let stored = peers_get_for_node(&peer.eid).unwrap();
+    assert_eq!(stored.metrics.contacts.count, 2);
+    assert_eq!(stored.metrics.contacts.current_start, Some(start + 100));
+    assert_eq!(stored.metrics.contacts.last_duration, Some(30));
+    assert_eq!(stored.metrics.contacts.mean_duration, Some(30.0));
+    assert_eq!(stored.metrics.clas["mtcp"].transfers_succeeded, 1);
+
+    peers_clear();
+}
+
+#[test]
+fn link_metrics_serialization_test() {
+    let mut peer = rnd_peer();
+    peer.metrics
+        .cla_mut("mtcp")
+        .record_transfer(10, Duration::from_millis(1), true);
+    let json = serde_json::to_value(&peer).unwrap();
+    assert_eq!(json["metrics"]["contacts"]["count"], 1);
+    assert_eq!(json["metrics"]["clas"]["mtcp"]["transfers_succeeded"], 1);
+
+    // peers announced without metrics, e.g., by an external router, are still accepted
+    let mut json = json;
+    json.as_object_mut().unwrap().remove("metrics");
+    let parsed: DtnPeer = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.metrics, PeerMetrics::default());
+}
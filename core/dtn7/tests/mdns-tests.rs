@@ -0,0 +1,196 @@
+use bp7::EndpointID;
+use dtn7::core::admission::{Action, AdmissionPolicy};
+use dtn7::core::peer::PeerAddress;
+use dtn7::core::{DtnPeer, PeerType};
+use dtn7::ipnd::mdns::{
+    cla_service_type, leaving_peer, Advertisement, Message, Record, RecordData, SERVICE_TYPE,
+    TYPE_PTR,
+};
+use dtn7::ipnd::security::{public_key, BeaconSecurityConfig, UnauthenticatedBeacons};
+use dtn7::ipnd::DiscoveryBackend;
+use dtn7::{peers_add, DtnConfig, CONFIG};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+
+fn advertisement() -> Advertisement {
+    let mut services = HashMap::new();
+    services.insert(63, "hello world".to_string());
+    Advertisement {
+        eid: EndpointID::try_from("dtn://node1/").unwrap(),
+        addr: Some(Ipv4Addr::new(10, 0, 0, 1).into()),
+        period: Some(Duration::from_secs(2)),
+        cla_list: vec![("mtcp".into(), Some(16163)), ("udp".into(), Some(4556))],
+        services,
+    }
+}
+
+#[test]
+fn mdns_message_test() {
+    let query = Message::query(SERVICE_TYPE, TYPE_PTR);
+    assert_eq!(
+        Message::from_bytes(&query.to_bytes().unwrap()).unwrap(),
+        query
+    );
+
+    let msg = Message {
+        response: true,
+        questions: Vec::new(),
+        records: advertisement().records(120),
+    };
+    let decoded = Message::from_bytes(&msg.to_bytes().unwrap()).unwrap();
+    assert_eq!(decoded, msg);
+
+    let ptrs: Vec<&RecordData> = decoded
+        .records
+        .iter()
+        .filter(|r| r.data.rtype() == Some(TYPE_PTR))
+        .map(|r| &r.data)
+        .collect();
+    assert_eq!(
+        ptrs,
+        vec![
+            &RecordData::Ptr("node1._dtn._tcp.local".into()),
+            &RecordData::Ptr("node1._dtn-mtcp._tcp.local".into()),
+            &RecordData::Ptr("node1._dtn-udp._udp.local".into()),
+        ]
+    );
+    assert_eq!(cla_service_type("tcp"), "_dtn-tcp._tcp.local");
+
+    assert!(Message::from_bytes(&[0, 0, 0x84]).is_err());
+}
+
+#[test]
+fn mdns_compressed_name_test() {
+    // response with a PTR record pointing into the question name
+    let mut buf = vec![0, 0, 0x84, 0, 0, 1, 0, 1, 0, 0, 0, 0];
+    for label in ["_dtn", "_tcp", "local"] {
+        buf.push(label.len() as u8);
+        buf.extend_from_slice(label.as_bytes());
+    }
+    buf.extend_from_slice(&[0, 0, 12, 0, 1]);
+    // name: pointer to offset 12, type PTR, class IN, ttl 120
+    buf.extend_from_slice(&[0xc0, 12, 0, 12, 0, 1, 0, 0, 0, 120]);
+    // rdata: "node2" + pointer to offset 12
+    buf.extend_from_slice(&[0, 8, 5]);
+    buf.extend_from_slice(b"node2");
+    buf.extend_from_slice(&[0xc0, 12]);
+
+    let msg = Message::from_bytes(&buf).unwrap();
+    assert_eq!(msg.questions[0].name, SERVICE_TYPE);
+    assert_eq!(
+        msg.records,
+        vec![Record {
+            name: SERVICE_TYPE.into(),
+            ttl: 120,
+            data: RecordData::Ptr("node2._dtn._tcp.local".into()),
+        }]
+    );
+
+    // a pointer to itself must not loop forever
+    let mut looping = vec![0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+    looping.extend_from_slice(&[0xc0, 12, 0, 12, 0, 1]);
+    assert!(Message::from_bytes(&looping).is_err());
+}
+
+#[test]
+fn mdns_advertisement_test() {
+    let adv = advertisement();
+    let msg = Message {
+        response: true,
+        questions: Vec::new(),
+        records: adv.records(120),
+    };
+    let found = Advertisement::from_message(&msg);
+    assert_eq!(found, vec![(adv.clone(), Some(120))]);
+
+    let src: IpAddr = Ipv4Addr::new(192, 168, 0, 7).into();
+    let peer = adv.to_peer(src);
+    assert_eq!(
+        peer.addr,
+        PeerAddress::Ip(Ipv4Addr::new(10, 0, 0, 1).into())
+    );
+    assert_eq!(peer.cla_list, adv.cla_list);
+    assert_eq!(peer.services[&63], "hello world");
+    assert_eq!(peer.period, Some(Duration::from_secs(2)));
+
+    // without address records the source of the message is used
+    let mut without_addr = adv.clone();
+    without_addr.addr = None;
+    assert_eq!(without_addr.to_peer(src).addr, PeerAddress::Ip(src));
+
+    let goodbye = Message {
+        response: true,
+        questions: Vec::new(),
+        records: adv.records(0),
+    };
+    assert_eq!(Advertisement::from_message(&goodbye)[0].1, None);
+
+    // instances without node id are ignored
+    assert_eq!(Advertisement::from_txt(&["cla=mtcp:16163".into()]), None);
+}
+
+#[test]
+fn mdns_goodbye_test() {
+    let known: IpAddr = Ipv4Addr::new(10, 0, 0, 7).into();
+    let mut adv = advertisement();
+    adv.eid = EndpointID::try_from("dtn://node7/").unwrap();
+    adv.addr = None;
+    peers_add(DtnPeer::new(
+        adv.eid.clone(),
+        PeerAddress::Ip(known),
+        PeerType::Dynamic,
+        None,
+        adv.cla_list.clone(),
+        HashMap::new(),
+    ));
+    let security = BeaconSecurityConfig::default();
+    assert_eq!(leaving_peer(&adv, known, &security).unwrap().eid, adv.eid);
+
+    // only the peer itself can say goodbye
+    let other: IpAddr = Ipv4Addr::new(10, 0, 0, 8).into();
+    assert!(leaving_peer(&adv, other, &security).is_none());
+
+    // goodbyes are checked like announcements
+    let mut trusted = security.clone();
+    trusted
+        .trusted_keys
+        .insert("node7".into(), public_key(&[7u8; 32]));
+    assert!(leaving_peer(&adv, known, &trusted).is_none());
+    let mut ignoring = security.clone();
+    ignoring.unauthenticated = UnauthenticatedBeacons::Ignore;
+    assert!(leaving_peer(&adv, known, &ignoring).is_none());
+
+    let previous = std::mem::replace(
+        &mut CONFIG.lock().admission,
+        AdmissionPolicy {
+            default: Action::Deny,
+            rules: Vec::new(),
+        },
+    );
+    assert!(leaving_peer(&adv, known, &security).is_none());
+    CONFIG.lock().admission = previous;
+}
+
+#[test]
+fn discovery_backend_config_test() {
+    assert_eq!(
+        DtnConfig::new().discovery_backends,
+        vec![DiscoveryBackend::Ipnd]
+    );
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        "[discovery]\nbackends = [\"mdns\", \"ipnd\", \"mdns\", \"carrier-pigeon\"]"
+    )
+    .unwrap();
+    let cfg = DtnConfig::from(file.path().to_path_buf());
+    assert_eq!(
+        cfg.discovery_backends,
+        vec![DiscoveryBackend::Mdns, DiscoveryBackend::Ipnd]
+    );
+    assert_eq!("MDNS".parse(), Ok(DiscoveryBackend::Mdns));
+}
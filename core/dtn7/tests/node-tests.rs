@@ -0,0 +1,191 @@
+use async_trait::async_trait;
+use bp7::CreationTimestamp;
+use dtn7::cla::dummy::DummyConvergenceLayer;
+use dtn7::cla::tcp::TcpConvergenceLayer;
+use dtn7::cla::ClaSenderTask;
+use dtn7::client::SendOptions;
+use dtn7::core::bundlepack::BundlePack;
+use dtn7::core::helpers::parse_peer_url;
+use dtn7::core::store::{BundleStore, InMemoryBundleStore};
+use dtn7::node::DtnNode;
+use dtn7::routing::plugin::RoutingPlugin;
+use dtn7::{peers_add, DtnConfig, CONFIG, PEERS, STORE};
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Routing plugin that never forwards bundles
+struct NoRouting;
+
+#[async_trait]
+impl RoutingPlugin for NoRouting {
+    async fn sender_for_bundle(&mut self, _bp: &BundlePack) -> (Vec<ClaSenderTask>, bool) {
+        (vec![], false)
+    }
+}
+
+async fn start(name: &str) -> DtnNode {
+    let mut cfg = DtnConfig::new();
+    cfg.host_eid = format!("dtn://{}/", name).as_str().try_into().unwrap();
+    cfg.janitor_interval = std::time::Duration::ZERO;
+    cfg.disable_neighbour_discovery = true;
+    DtnNode::builder()
+        .config(cfg)
+        .cla(|| DummyConvergenceLayer::new(None).into())
+        .store(InMemoryBundleStore::new().into())
+        .routing("sprayandwait")
+        .routing_setting("sprayandwait", "num_copies", "3")
+        .httpd(false)
+        .start()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn independent_nodes_test() {
+    let node1 = start("node1").await;
+    let node2 = start("node2").await;
+
+    assert_eq!(
+        node1.enter(|| CONFIG.lock().host_eid.node()).unwrap(),
+        "node1"
+    );
+    assert_eq!(
+        node2.enter(|| CONFIG.lock().host_eid.node()).unwrap(),
+        "node2"
+    );
+    assert_eq!(
+        node1.enter(|| CONFIG.lock().routing_settings["sprayandwait"]["num_copies"].clone()),
+        "3"
+    );
+    // the default node is not touched
+    assert_ne!(CONFIG.lock().host_eid.node().unwrap(), "node1");
+    assert_ne!(CONFIG.lock().host_eid.node().unwrap(), "node2");
+
+    node1.enter(|| peers_add(parse_peer_url("dummy://10.0.0.1/node3").unwrap()));
+    assert_eq!(node1.enter(|| PEERS.lock().len()), 1);
+    assert_eq!(node2.enter(|| PEERS.lock().len()), 0);
+
+    let bndl = SendOptions::default().build_bundle(
+        "dtn://node2/app".try_into().unwrap(),
+        "dtn://node9/incoming".try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    );
+    let bid = bndl.id();
+    node2.enter(|| dtn7::store_push_bundle(&bndl)).unwrap();
+    assert!(node2.enter(|| STORE.lock().has_item(&bid)));
+    assert!(!node1.enter(|| STORE.lock().has_item(&bid)));
+
+    // tasks of a node are stopped on shutdown, the other node keeps running
+    let task = node1.spawn(std::future::pending::<()>());
+    let other =
+        node2.spawn(async { tokio::time::sleep(std::time::Duration::from_millis(50)).await });
+    node1.shutdown().await;
+    assert!(task.await.unwrap_err().is_cancelled());
+    other.await.unwrap();
+    node2.shutdown().await;
+}
+
+#[tokio::test]
+async fn node_start_error_test() {
+    let err = DtnNode::builder()
+        .routing("unknown")
+        .httpd(false)
+        .start()
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("unknown"));
+}
+
+#[tokio::test]
+async fn node_builtin_plugin_name_test() {
+    let err = DtnNode::builder()
+        .routing_plugin("sink", || NoRouting)
+        .httpd(false)
+        .start()
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("built-in"));
+}
+
+#[tokio::test]
+async fn node_shutdown_test() {
+    let workdir = tempfile::tempdir().unwrap();
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut cfg = DtnConfig::new();
+    cfg.host_eid = "dtn://node1/".try_into().unwrap();
+    cfg.janitor_interval = std::time::Duration::ZERO;
+    cfg.disable_neighbour_discovery = true;
+    cfg.workdir = workdir.path().to_path_buf();
+    cfg.db = "sled".into();
+    // the factory of the plugin is part of the state of the node
+    let marker = Arc::new(());
+    let plugin_marker = marker.clone();
+    let settings = HashMap::from([
+        ("bind".to_string(), "127.0.0.1".to_string()),
+        ("port".to_string(), port.to_string()),
+    ]);
+    let node = DtnNode::builder()
+        .config(cfg.clone())
+        .cla(move || TcpConvergenceLayer::new(Some(&settings)).into())
+        .routing_plugin("none", move || {
+            let _ = &plugin_marker;
+            NoRouting
+        })
+        .httpd(false)
+        .start()
+        .await
+        .unwrap();
+    assert_eq!(Arc::strong_count(&marker), 2);
+
+    // establish a TCPCL session with the node, contact header and SESS_INIT
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream.write_all(b"dtn!\x04\x00").await.unwrap();
+    let mut header = [0; 6];
+    stream.read_exact(&mut header).await.unwrap();
+    assert_eq!(&header[..5], b"dtn!\x04");
+    let node_id = b"dtn://node2/";
+    let mut sess_init = vec![0x07];
+    sess_init.extend_from_slice(&30u16.to_be_bytes());
+    sess_init.extend_from_slice(&64000u64.to_be_bytes());
+    sess_init.extend_from_slice(&64000u64.to_be_bytes());
+    sess_init.extend_from_slice(&(node_id.len() as u16).to_be_bytes());
+    sess_init.extend_from_slice(node_id);
+    sess_init.extend_from_slice(&0u32.to_be_bytes());
+    stream.write_all(&sess_init).await.unwrap();
+    assert_eq!(stream.read_u8().await.unwrap(), 0x07);
+    let mut params = [0; 18];
+    stream.read_exact(&mut params).await.unwrap();
+    let mut remote_id = vec![0; stream.read_u16().await.unwrap() as usize];
+    stream.read_exact(&mut remote_id).await.unwrap();
+    assert_eq!(remote_id, b"dtn://node1/");
+    assert_eq!(stream.read_u32().await.unwrap(), 0);
+
+    // the session is terminated with a SESS_TERM instead of just being dropped
+    node.shutdown().await;
+    assert_eq!(stream.read_u8().await.unwrap(), 0x05);
+
+    // the store was closed, another node can open it
+    let other = DtnNode::builder()
+        .config(cfg)
+        .httpd(false)
+        .start()
+        .await
+        .unwrap();
+    other.shutdown().await;
+
+    // borrowed node locals keep the state alive
+    let config = node.enter(|| CONFIG.get());
+    drop(node);
+    assert_eq!(Arc::strong_count(&marker), 2);
+    assert_eq!(config.lock().host_eid.node().unwrap(), "node1");
+    drop(config);
+    assert_eq!(Arc::strong_count(&marker), 1);
+}
@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use bp7::CreationTimestamp;
+use dtn7::cla::dummy::DummyConvergenceLayer;
+use dtn7::cla::ClaSenderTask;
+use dtn7::client::SendOptions;
+use dtn7::core::bundlepack::BundlePack;
+use dtn7::core::helpers::parse_peer_url;
+use dtn7::routing::plugin::{register_routing_plugin, RoutingPlugin};
+use dtn7::routing::{CommandError, RoutingNotifcation};
+use dtn7::{
+    cla_add, peers_add, routing_cmd, routing_get_data, routing_replace, routing_sender_for_bundle,
+    DTNCORE,
+};
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+
+/// Forwards bundles directly to their destination and remembers the peers it encountered
+#[derive(Default)]
+struct DirectRouting {
+    peers: Vec<String>,
+    commands: Vec<String>,
+}
+
+#[async_trait]
+impl RoutingPlugin for DirectRouting {
+    async fn sender_for_bundle(&mut self, bp: &BundlePack) -> (Vec<ClaSenderTask>, bool) {
+        match dtn7::peers_cla_for_node(&bp.destination) {
+            Some(cla) => (vec![cla], true),
+            None => (vec![], false),
+        }
+    }
+    async fn notify(&mut self, notification: RoutingNotifcation) {
+        if let RoutingNotifcation::EncounteredPeer(eid) = notification {
+            self.peers.push(eid.node().unwrap());
+        }
+    }
+    async fn command(&mut self, cmd: &str) -> Result<(), CommandError> {
+        if cmd != "flush" {
+            return Err(CommandError::unknown(cmd));
+        }
+        self.commands.push(cmd.to_string());
+        Ok(())
+    }
+    async fn data(&mut self, param: &str) -> String {
+        match param {
+            "commands" => self.commands.join(","),
+            _ => self.peers.join(","),
+        }
+    }
+}
+
+fn bundle_pack(dst: &str) -> BundlePack {
+    BundlePack::from(SendOptions::default().build_bundle(
+        "dtn://node1/app".try_into().unwrap(),
+        dst.try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    ))
+}
+
+#[tokio::test]
+async fn routing_plugin_test() {
+    cla_add(DummyConvergenceLayer::new(None).into());
+    peers_add(parse_peer_url("dummy://10.0.0.1/node2").unwrap());
+
+    assert!(routing_replace("direct", BTreeMap::new()).await.is_err());
+    register_routing_plugin("direct", DirectRouting::default).unwrap();
+    routing_replace("direct", BTreeMap::new()).await.unwrap();
+    assert!(DTNCORE
+        .lock()
+        .routing_agent
+        .to_string()
+        .starts_with("PluginRoutingAgent"));
+    assert!(dtn7::routing::is_routing_algorithm("direct"));
+
+    // the plugin knows the peers from before it was started
+    assert_eq!(routing_get_data(String::new()).await.unwrap(), "node2");
+
+    let (clas, delete_afterwards) = routing_sender_for_bundle(bundle_pack("dtn://node2/in"))
+        .await
+        .unwrap();
+    assert_eq!(clas.len(), 1);
+    assert!(delete_afterwards);
+    let (clas, _) = routing_sender_for_bundle(bundle_pack("dtn://node3/in"))
+        .await
+        .unwrap();
+    assert!(clas.is_empty());
+
+    routing_cmd("flush".into()).await.unwrap();
+    let err = routing_cmd("purge".into()).await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<CommandError>(),
+        Some(&CommandError::unknown("purge"))
+    );
+    assert_eq!(routing_get_data("commands".into()).await.unwrap(), "flush");
+}
+
+#[test]
+fn routing_plugin_builtin_name_test() {
+    let err = register_routing_plugin("epidemic", DirectRouting::default).unwrap_err();
+    assert!(err.to_string().contains("built-in"));
+    assert!(!dtn7::routing::plugin::routing_plugins().contains(&"epidemic".to_string()));
+}
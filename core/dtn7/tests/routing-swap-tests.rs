@@ -0,0 +1,85 @@
+mod common;
+
+use bp7::CreationTimestamp;
+use dtn7::cla::dummy::DummyConvergenceLayer;
+use dtn7::client::SendOptions;
+use dtn7::core::bundlepack::{BundlePack, Constraint};
+use dtn7::core::helpers::parse_peer_url;
+use dtn7::dtnd::api::RoutingResource;
+use dtn7::{cla_add, peers_add, routing_replace, routing_sender_for_bundle, CONFIG, DTNCORE};
+use hyper::{Method, StatusCode};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryInto;
+use std::net::SocketAddr;
+
+async fn put_routing(addr: SocketAddr, body: &str) -> (StatusCode, Option<RoutingResource>) {
+    let (status, body) = common::request(addr, Method::PUT, "/routing", Some(body)).await;
+    (status, serde_json::from_slice(&body).ok())
+}
+
+#[tokio::test]
+async fn routing_replace_test() {
+    CONFIG.lock().host_eid = "dtn://node1/".try_into().unwrap();
+    cla_add(DummyConvergenceLayer::new(None).into());
+    for node in ["node2", "node3"] {
+        peers_add(parse_peer_url(&format!("dummy://10.0.0.1/{}", node)).unwrap());
+    }
+    let bndl = SendOptions::default().build_bundle(
+        "dtn://node1/app".try_into().unwrap(),
+        "dtn://node9/incoming".try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    );
+    dtn7::store_push_bundle(&bndl).unwrap();
+    let mut bp = BundlePack::from(&bndl);
+    bp.add_constraint(Constraint::ForwardPending);
+    dtn7::store_update_metadata(&bp).unwrap();
+
+    // the new agent knows the waiting bundle and the current peers
+    let settings = BTreeMap::from([(
+        "sprayandwait".to_string(),
+        HashMap::from([("num_copies".to_string(), "2".to_string())]),
+    )]);
+    routing_replace("sprayandwait", settings).await.unwrap();
+    assert!(DTNCORE
+        .lock()
+        .routing_agent
+        .to_string()
+        .starts_with("SprayAndWaitRoutingAgent"));
+    assert_eq!(CONFIG.lock().routing, "sprayandwait");
+    let (clas, _) = routing_sender_for_bundle(bp.clone()).await.unwrap();
+    assert_eq!(clas.len(), 1);
+
+    // invalid agents or settings keep the active agent
+    for (strategy, agent, key, value) in [
+        ("unknown", "unknown", "x", "y"),
+        ("sprayandwait", "sprayandwait", "num_copies", "many"),
+        ("static", "static", "routes", "/nonexistent/routes"),
+        ("external", "external", "tcp_port", "0"),
+    ] {
+        let settings = BTreeMap::from([(
+            agent.to_string(),
+            HashMap::from([(key.to_string(), value.to_string())]),
+        )]);
+        assert!(routing_replace(strategy, settings).await.is_err());
+        assert_eq!(CONFIG.lock().routing, "sprayandwait");
+        assert_eq!(
+            CONFIG.lock().routing_settings["sprayandwait"]["num_copies"],
+            "2"
+        );
+    }
+
+    let addr = common::spawn_api().await;
+
+    let (status, routing) = put_routing(addr, r#"{"strategy": "epidemic"}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    let routing = routing.unwrap();
+    assert!(routing.agent.starts_with("EpidemicRoutingAgent"));
+    assert_eq!(routing.strategy, "epidemic");
+    let (clas, _) = routing_sender_for_bundle(bp).await.unwrap();
+    assert_eq!(clas.len(), 2);
+
+    let (status, _) = put_routing(addr, r#"{"strategy": "nonsense"}"#).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(CONFIG.lock().routing, "epidemic");
+}
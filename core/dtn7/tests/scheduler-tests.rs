@@ -0,0 +1,114 @@
+use dtn7::core::scheduler::{RetryQueue, RETRY_BACKOFF_MAX, RETRY_BACKOFF_MIN};
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[test]
+fn backoff_test() {
+    assert_eq!(RetryQueue::backoff(1), RETRY_BACKOFF_MIN);
+    assert_eq!(RetryQueue::backoff(2), RETRY_BACKOFF_MIN * 2);
+    assert_eq!(RetryQueue::backoff(3), RETRY_BACKOFF_MIN * 4);
+    assert_eq!(RetryQueue::backoff(100), RETRY_BACKOFF_MAX);
+}
+
+#[test]
+fn retry_queue_test() {
+    let now = Instant::now();
+    let mut queue = RetryQueue::new();
+    assert!(queue.next_due().is_none());
+
+    queue.enqueue("b1", "node1", now);
+    assert_eq!(queue.park("b2", "node2", now), now + RETRY_BACKOFF_MIN);
+    assert_eq!(queue.len(), 2);
+
+    // only the enqueued bundle is due right away
+    assert_eq!(queue.pop_due(now), vec!["b1".to_string()]);
+    assert_eq!(queue.next_due(), Some(now + RETRY_BACKOFF_MIN));
+
+    // every unsuccessful attempt doubles the delay
+    let later = now + Duration::from_secs(5);
+    assert_eq!(queue.pop_due(later), vec!["b2".to_string()]);
+    assert_eq!(
+        queue.park("b2", "node2", later),
+        later + RETRY_BACKOFF_MIN * 2
+    );
+    assert_eq!(queue.attempts("b2"), Some(2));
+
+    // a new forwarding opportunity makes all parked bundles due and keeps their backoff
+    queue.park("b1", "node1", later);
+    assert_eq!(queue.wake_all(later), 2);
+    let mut due = queue.pop_due(later);
+    due.sort();
+    assert_eq!(due, vec!["b1".to_string(), "b2".to_string()]);
+    assert_eq!(queue.attempts("b2"), Some(2));
+    assert_eq!(
+        queue.park("b2", "node2", later),
+        later + RETRY_BACKOFF_MIN * 4
+    );
+
+    queue.forget("b2");
+    queue.forget("b1");
+    assert!(queue.is_empty());
+    assert!(queue.attempts("b2").is_none());
+    assert!(queue.pop_due(later + RETRY_BACKOFF_MAX).is_empty());
+}
+
+#[test]
+fn retry_queue_wake_node_test() {
+    let now = Instant::now();
+    let mut queue = RetryQueue::new();
+    queue.park("b1", "node1", now);
+    queue.park("b2", "node2", now);
+    queue.park("b3", "node2", now);
+
+    // only the bundles destined for the changed peer are woken
+    assert_eq!(queue.wake_node("node2", now), 2);
+    let mut due = queue.pop_due(now);
+    due.sort();
+    assert_eq!(due, vec!["b2".to_string(), "b3".to_string()]);
+    assert_eq!(queue.wake_node("node3", now), 0);
+    assert_eq!(queue.next_due(), Some(now + RETRY_BACKOFF_MIN));
+}
+
+#[test]
+fn retry_queue_defer_test() {
+    let now = Instant::now();
+    let mut queue = RetryQueue::new();
+
+    // a deferred bundle is due at the requested time instead of after the backoff
+    let at = now + Duration::from_secs(30);
+    queue.defer("b1", at);
+    assert_eq!(queue.park("b1", "node1", now), at);
+
+    // wakes leave it alone until the deferral expires
+    assert_eq!(queue.wake_all(now), 0);
+    assert_eq!(queue.wake_node("node1", now), 0);
+    assert!(queue.pop_due(at - Duration::from_secs(1)).is_empty());
+    assert_eq!(queue.pop_due(at), vec!["b1".to_string()]);
+    assert_eq!(queue.park("b1", "node1", at), at + RETRY_BACKOFF_MIN * 2);
+    assert_eq!(queue.wake_all(at), 1);
+
+    // deferring a parked bundle reschedules it
+    queue.park("b2", "node2", now);
+    queue.defer("b2", at);
+    assert_eq!(queue.wake_node("node2", now), 0);
+    assert_eq!(queue.pop_due(at), vec!["b1".to_string(), "b2".to_string()]);
+
+    queue.defer("b3", at);
+    queue.forget("b3");
+    assert_eq!(queue.park("b3", "node3", now), now + RETRY_BACKOFF_MIN);
+}
+
+#[test]
+fn retry_queue_transfer_failures_test() {
+    let mut queue = RetryQueue::new();
+    assert_eq!(queue.record_transfer("b1", true), 1);
+    assert_eq!(queue.record_transfer("b1", true), 2);
+    assert_eq!(queue.record_transfer("b2", true), 1);
+
+    // only consecutive failures count
+    assert_eq!(queue.record_transfer("b1", false), 0);
+    assert_eq!(queue.record_transfer("b1", true), 1);
+
+    queue.forget("b2");
+    assert_eq!(queue.record_transfer("b2", true), 1);
+}
@@ -0,0 +1,103 @@
+use dtn7::sim::Simulation;
+use dtn7::store_has_item;
+use std::time::Duration;
+
+const fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+#[tokio::test(start_paused = true)]
+async fn sim_store_carry_forward_test() {
+    let sim = Simulation::builder()
+        .routing("epidemic")
+        .nodes(&["n1", "n2", "n3"])
+        .link("n1", "n2", secs(1), 0.0)
+        .link("n2", "n3", secs(1), 0.0)
+        .up(secs(0), "n1", "n2")
+        .down(secs(10), "n1", "n2")
+        .up(secs(20), "n2", "n3")
+        .start()
+        .await
+        .unwrap();
+    let bid = sim.send("n1", "dtn://n3/incoming", b"hello").await.unwrap();
+
+    // the bundle is on its way for the delay of the link
+    sim.run_until(Duration::from_millis(500)).await;
+    assert!(!sim.node("n2").enter(|| store_has_item(&bid)));
+    sim.run_until(secs(2)).await;
+    assert!(sim.node("n2").enter(|| store_has_item(&bid)));
+
+    // n2 carries the bundle until it meets n3
+    sim.run_until(secs(15)).await;
+    assert!(sim.received("n3", "incoming").is_empty());
+    sim.run_until(secs(30)).await;
+    let received = sim.received("n3", "incoming");
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].id(), bid);
+    assert_eq!(sim.elapsed(), secs(30));
+    sim.shutdown().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn sim_link_down_during_transfer_test() {
+    let sim = Simulation::builder()
+        .nodes(&["n1", "n2"])
+        .link("n1", "n2", secs(5), 0.0)
+        .up(secs(0), "n1", "n2")
+        .down(secs(2), "n1", "n2")
+        .up(secs(10), "n1", "n2")
+        .start()
+        .await
+        .unwrap();
+    sim.send("n1", "dtn://n2/incoming", b"hello").await.unwrap();
+
+    // the link went down before the transfer finished
+    sim.run_until(secs(9)).await;
+    assert!(sim.received("n2", "incoming").is_empty());
+    // retried when the link came up again
+    sim.run_until(secs(16)).await;
+    assert_eq!(sim.received("n2", "incoming").len(), 1);
+
+    assert!(sim.link_down("n1", "n3").await.is_err());
+    sim.shutdown().await;
+}
+
+async fn lossy_run(seed: u64) -> usize {
+    let sim = Simulation::builder()
+        .seed(seed)
+        .nodes(&["n1", "n2"])
+        .link("n1", "n2", Duration::from_millis(100), 0.5)
+        .start()
+        .await
+        .unwrap();
+    sim.link_up("n1", "n2").await.unwrap();
+    for i in 0..20 {
+        sim.send("n1", "dtn://n2/incoming", format!("{}", i).as_bytes())
+            .await
+            .unwrap();
+        sim.run_for(secs(1)).await;
+    }
+    let received = sim.received("n2", "incoming").len();
+    sim.shutdown().await;
+    received
+}
+
+#[tokio::test(start_paused = true)]
+async fn sim_lossy_link_test() {
+    let received = lossy_run(7).await;
+    assert!(received > 0 && received < 20, "received {}", received);
+    // losses are reproducible with the same seed
+    assert_eq!(lossy_run(7).await, received);
+}
+
+#[tokio::test]
+async fn sim_start_error_test() {
+    let err = Simulation::builder()
+        .nodes(&["n1", "n2"])
+        .up(secs(0), "n1", "n2")
+        .start()
+        .await
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("no link"));
+}
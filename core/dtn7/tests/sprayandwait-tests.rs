@@ -0,0 +1,113 @@
+use bp7::{Bundle, CreationTimestamp};
+use dtn7::cla::dummy::DummyConvergenceLayer;
+use dtn7::client::SendOptions;
+use dtn7::core::bundlepack::BundlePack;
+use dtn7::core::helpers::parse_peer_url;
+use dtn7::routing::sprayandwait::{copy_count, SprayAndWaitRoutingAgent};
+use dtn7::routing::{RoutingAgent, RoutingCmd, RoutingNotifcation};
+use dtn7::{cla_add, peers_add, CONFIG};
+use std::collections::HashMap;
+use std::convert::TryInto;
+use tokio::sync::oneshot;
+
+fn bundle(src: &str, copies: Option<u64>) -> Bundle {
+    let opts = SendOptions {
+        copies,
+        ..Default::default()
+    };
+    opts.build_bundle(
+        src.try_into().unwrap(),
+        "dtn://node9/incoming".try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    )
+}
+
+/// Copies handed to each next hop
+async fn spray(agent: &SprayAndWaitRoutingAgent, bndl: &Bundle) -> HashMap<String, u64> {
+    let (tx, rx) = oneshot::channel();
+    agent
+        .channel()
+        .send(RoutingCmd::SenderForBundle(BundlePack::from(bndl), tx))
+        .await
+        .unwrap();
+    let (clas, delete_afterwards) = rx.await.unwrap();
+    assert!(!delete_afterwards);
+    clas.into_iter()
+        .map(|cla| (cla.next_hop.node().unwrap(), cla.copies.unwrap()))
+        .collect()
+}
+
+async fn notify(agent: &SprayAndWaitRoutingAgent, notification: RoutingNotifcation) {
+    agent
+        .channel()
+        .send(RoutingCmd::Notify(notification))
+        .await
+        .unwrap();
+}
+
+#[test]
+fn copy_count_block_test() {
+    let mut bndl = bundle("dtn://node1/", Some(6));
+    let bndl = Bundle::try_from(bndl.to_cbor()).unwrap();
+    assert_eq!(copy_count(&bndl), Some(6));
+    assert_eq!(copy_count(&bundle("dtn://node1/", None)), None);
+}
+
+#[tokio::test]
+async fn binary_spray_and_wait_test() {
+    {
+        let mut config = CONFIG.lock();
+        config.host_eid = "dtn://node1/".try_into().unwrap();
+        config.routing_settings.insert(
+            "sprayandwait".into(),
+            HashMap::from([
+                ("num_copies".into(), "4".into()),
+                ("binary".into(), "true".into()),
+            ]),
+        );
+    }
+    cla_add(DummyConvergenceLayer::new(None).into());
+    for node in ["node2", "node3", "node4"] {
+        peers_add(parse_peer_url(&format!("dummy://10.0.0.1/{}", node)).unwrap());
+    }
+    let agent = SprayAndWaitRoutingAgent::new();
+
+    // every peer receives half of the remaining copies, one copy is kept for direct delivery
+    let own = bundle("dtn://node1/", Some(8));
+    notify(&agent, RoutingNotifcation::IncomingBundle(own.clone())).await;
+    let handed = spray(&agent, &own).await;
+    let mut copies: Vec<u64> = handed.values().copied().collect();
+    copies.sort();
+    assert_eq!(copies, vec![1, 2, 4]);
+    assert!(spray(&agent, &own).await.is_empty());
+
+    // a failed transmission returns the copies, they go to the peer again
+    let (failed, _) = handed.iter().find(|(_, copies)| **copies == 4).unwrap();
+    notify(
+        &agent,
+        RoutingNotifcation::SendingFailed(own.id(), failed.clone()),
+    )
+    .await;
+    assert_eq!(
+        spray(&agent, &own).await,
+        HashMap::from([(failed.clone(), 2)])
+    );
+
+    // without a copy count block the source starts with num_copies
+    let own = bundle("dtn://node1/", None);
+    notify(&agent, RoutingNotifcation::IncomingBundle(own.clone())).await;
+    assert_eq!(spray(&agent, &own).await.values().sum::<u64>(), 3);
+
+    // relays honor the budget carried by the bundle
+    let relayed = bundle("dtn://node5/", Some(4));
+    notify(&agent, RoutingNotifcation::IncomingBundle(relayed.clone())).await;
+    let mut copies: Vec<u64> = spray(&agent, &relayed).await.into_values().collect();
+    copies.sort();
+    assert_eq!(copies, vec![1, 2]);
+
+    // a foreign bundle without budget is only delivered directly
+    let foreign = bundle("dtn://node5/", None);
+    notify(&agent, RoutingNotifcation::IncomingBundle(foreign.clone())).await;
+    assert!(spray(&agent, &foreign).await.is_empty());
+}
@@ -0,0 +1,189 @@
+mod common;
+
+use bp7::CreationTimestamp;
+use dtn7::cla::dummy::DummyConvergenceLayer;
+use dtn7::client::SendOptions;
+use dtn7::core::bundlepack::BundlePack;
+use dtn7::core::helpers::parse_peer_url;
+use dtn7::routing::static_routing::{parse_routes, StaticRouteEntry};
+use dtn7::routing::CommandError;
+use dtn7::{cla_add, peers_add, routing_cmd, routing_replace, routing_sender_for_bundle};
+use hyper::{Method, StatusCode};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryInto;
+use std::io::Write;
+use std::net::SocketAddr;
+
+#[test]
+fn static_routes_parse_test() {
+    let routes = parse_routes(
+        "# <idx> <src> <dst> <via> [<metric>]\n1 * ipn:[2-3].* ipn:2.0\n\n2 * dtn://gs*/** dtn://relay1/ 10\n",
+    )
+    .unwrap();
+    assert_eq!(routes.len(), 2);
+    assert_eq!(routes[0].metric, 0);
+    assert_eq!(
+        routes[1].to_string(),
+        "#2: route from * to dtn://gs*/** via dtn://relay1/ (metric 10)"
+    );
+
+    for (content, line) in [
+        ("1 * ipn:3.*", "line 1:"),
+        ("1 * * ipn:2.0\n1 * * ipn:3.0", "line 2:"),
+        ("# comment\nx * * ipn:2.0", "line 2:"),
+        ("1 * * ipn:2.0 high", "line 1:"),
+        ("1 * * nonsense", "line 1:"),
+        ("1 * * ipn:2.0 1 2", "line 1:"),
+    ] {
+        let err = parse_routes(content).unwrap_err();
+        assert!(err.starts_with(line), "{}: {}", content, err);
+    }
+}
+
+fn routes(data: &str) -> Vec<u16> {
+    serde_json::from_str::<Vec<StaticRouteEntry>>(data)
+        .unwrap()
+        .iter()
+        .map(|r| r.idx)
+        .collect()
+}
+
+async fn next_hop(bp: &BundlePack) -> Option<String> {
+    let (clas, _) = routing_sender_for_bundle(bp.clone()).await.unwrap();
+    clas.first().map(|cla| cla.next_hop.node().unwrap())
+}
+
+async fn request(
+    addr: SocketAddr,
+    method: Method,
+    path: &str,
+    body: Option<&str>,
+) -> (StatusCode, Vec<u16>) {
+    let path = format!("/routing/static{}", path);
+    let (status, body) = common::request(addr, method, &path, body).await;
+    let routes = serde_json::from_slice::<Vec<StaticRouteEntry>>(&body)
+        .map(|routes| routes.iter().map(|r| r.idx).collect())
+        .unwrap_or_default();
+    (status, routes)
+}
+
+#[tokio::test]
+async fn static_routing_test() {
+    cla_add(DummyConvergenceLayer::new(None).into());
+    for node in ["node2", "node3"] {
+        peers_add(parse_peer_url(&format!("dummy://10.0.0.1/{}", node)).unwrap());
+    }
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        "1 ** dtn://node9/* dtn://node4/\n2 ** dtn://node9/* dtn://node3/ 20\n3 ** dtn://node9/* dtn://node2/ 10"
+    )
+    .unwrap();
+    let settings = BTreeMap::from([(
+        "static".to_string(),
+        HashMap::from([(
+            "routes".to_string(),
+            file.path().to_str().unwrap().to_string(),
+        )]),
+    )]);
+    routing_replace("static", settings).await.unwrap();
+
+    let bp = BundlePack::from(SendOptions::default().build_bundle(
+        "dtn://node1/app".try_into().unwrap(),
+        "dtn://node9/incoming".try_into().unwrap(),
+        CreationTimestamp::now(),
+        b"hello".to_vec(),
+    ));
+    // node4 is unknown, node2 has the lower metric
+    assert_eq!(next_hop(&bp).await.as_deref(), Some("node2"));
+
+    routing_cmd("del 3".into()).await.unwrap();
+    assert_eq!(next_hop(&bp).await.as_deref(), Some("node3"));
+    routing_cmd("add 5 ** dtn://node9/* dtn://node2/ 30".into())
+        .await
+        .unwrap();
+    // invalid commands are rejected by the agent
+    let err = routing_cmd("add 6 ** dtn://node9/*".into())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<CommandError>(),
+        Some(CommandError::Invalid(_))
+    ));
+    let err = routing_cmd("del 42".into()).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<CommandError>(),
+        Some(CommandError::NotFound(_))
+    ));
+    let err = routing_cmd("insert 5 ** dtn://node9/* dtn://node3/".into())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<CommandError>(),
+        Some(CommandError::Conflict(_))
+    ));
+    assert_eq!(
+        routes(&dtn7::routing_get_data("json".into()).await.unwrap()),
+        vec![1, 2, 5]
+    );
+    assert_eq!(next_hop(&bp).await.as_deref(), Some("node3"));
+
+    // an invalid file keeps the current routes
+    writeln!(file, "7 * *").unwrap();
+    assert!(routing_cmd("reload".into()).await.is_err());
+    assert_eq!(
+        routes(&dtn7::routing_get_data("json".into()).await.unwrap()),
+        vec![1, 2, 5]
+    );
+
+    let addr = common::spawn_api().await;
+
+    let (status, _) = request(addr, Method::POST, "/reload", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, idxs) = request(
+        addr,
+        Method::POST,
+        "/routes",
+        Some(
+            r#"{"idx": 8, "src": "**", "dst": "dtn://node9/*", "via": "dtn://node2/", "metric": 5}"#,
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(idxs, vec![1, 2, 5, 8]);
+    assert_eq!(next_hop(&bp).await.as_deref(), Some("node2"));
+    let (status, _) = request(
+        addr,
+        Method::POST,
+        "/routes",
+        Some(r#"{"idx": 8, "src": "*", "dst": "*", "via": "dtn://node3/"}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = request(
+        addr,
+        Method::POST,
+        "/routes",
+        Some(r#"{"idx": 9, "src": "*", "dst": "*", "via": "node3"}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = request(addr, Method::DELETE, "/routes/9", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, idxs) = request(addr, Method::DELETE, "/routes/1", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(idxs, vec![2, 5, 8]);
+
+    // a valid file replaces all routes
+    std::fs::write(file.path(), "3 ** dtn://node9/* dtn://node2/\n").unwrap();
+    let (status, idxs) = request(addr, Method::POST, "/reload", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(idxs, vec![3]);
+    let (status, idxs) = request(addr, Method::GET, "/routes", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(idxs, vec![3]);
+
+    routing_replace("epidemic", BTreeMap::new()).await.unwrap();
+    let (status, _) = request(addr, Method::GET, "/routes", None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use dtn7::cla::dummy::DummyLink;
+use dtn7::core::journal::JournalEvent;
+use dtn7::sim::Simulation;
+use dtn7::CONFIG;
+use std::time::Duration;
+
+#[tokio::test(start_paused = true)]
+async fn traffic_pared_report_test() {
+    let sim = Simulation::builder()
+        .nodes(&["n1", "n2"])
+        .link("n1", "n2", Duration::from_millis(100), 0.0)
+        .up(Duration::ZERO, "n1", "n2")
+        .start()
+        .await
+        .unwrap();
+    sim.node("n2").enter(|| {
+        let mut config = CONFIG.lock();
+        config.generate_status_reports = true;
+        config.max_bundles = 1;
+    });
+    sim.run_for(Duration::from_secs(1)).await;
+
+    // n2 carries the first bundle for the unknown n3, the second one exceeds its store
+    let mut bids = Vec::new();
+    for payload in [b"first", b"other"] {
+        let bndl = common::bundle_from_to("dtn://n1/", "dtn://n3/incoming", "deletion", payload);
+        bids.push(bndl.id());
+        sim.node("n1")
+            .scope(dtn7::core::processing::send_bundle(bndl))
+            .await;
+        sim.run_for(Duration::from_secs(1)).await;
+    }
+    sim.run_for(Duration::from_secs(2)).await;
+
+    assert!(sim.node("n2").enter(|| dtn7::store_has_item(&bids[0])));
+    assert!(!sim.node("n2").enter(|| dtn7::store_has_item(&bids[1])));
+    let timeline = sim
+        .node("n1")
+        .enter(|| dtn7::STATUS_REPORTS.lock().timeline(&bids[1]))
+        .unwrap();
+    assert_eq!(timeline.len(), 1);
+    assert_eq!(timeline[0].status, "deleted");
+    assert_eq!(timeline[0].reason, "traffic_pared");
+    assert_eq!(timeline[0].node, "dtn://n2/");
+    assert!(sim
+        .node("n1")
+        .enter(|| dtn7::STATUS_REPORTS.lock().timeline(&bids[0]))
+        .is_none());
+    sim.shutdown().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn transfer_failures_test() {
+    let sim = Simulation::builder()
+        .nodes(&["n1", "n2"])
+        .link("n1", "n2", Duration::from_millis(100), 0.0)
+        .up(Duration::ZERO, "n1", "n2")
+        .start()
+        .await
+        .unwrap();
+    sim.node("n1")
+        .enter(|| CONFIG.lock().max_transfer_failures = 3);
+    sim.run_for(Duration::from_secs(1)).await;
+    // n2 stays a peer of n1 but every transfer fails
+    let broken = DummyLink {
+        up: false,
+        delay: Duration::from_millis(100),
+        loss: 0.0,
+    };
+    sim.network().set_link("n1", "n2", broken);
+
+    let bid = sim.send("n1", "dtn://n2/incoming", b"hello").await.unwrap();
+    sim.run_for(Duration::from_secs(2)).await;
+    assert!(sim.node("n1").enter(|| dtn7::store_has_item(&bid)));
+
+    // retried with backoff after 1s and 2s, given up after the third failure
+    sim.run_for(Duration::from_secs(5)).await;
+    assert!(!sim.node("n1").enter(|| dtn7::store_has_item(&bid)));
+    let journal = sim
+        .node("n1")
+        .enter(|| dtn7::store_get_metadata(&bid))
+        .unwrap()
+        .journal;
+    let failed = journal
+        .iter()
+        .filter(|entry| matches!(entry.event, JournalEvent::TransferFailed { .. }))
+        .count();
+    assert_eq!(failed, 3);
+    assert!(journal.iter().any(|entry| entry.event
+        == JournalEvent::Deleted {
+            reason: "no_next_node_contact".into()
+        }));
+    sim.shutdown().await;
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use bp7::administrative_record::{
+    new_status_report, DELETED_BUNDLE, DELIVERED_BUNDLE, FORWARDED_BUNDLE, HOP_LIMIT_EXCEEDED,
+    LIFETIME_EXPIRED, NO_INFORMATION, RECEIVED_BUNDLE,
+};
+use bp7::Bundle;
+use dtn7::client::{ClientError, DtnClient};
+use dtn7::core::status_reports::{
+    is_requested, RateLimiter, ReportedStatus, StatusReportConfig, StatusReportLog,
+};
+use dtn7::dtnd::httpd::router;
+use dtn7::DtnConfig;
+use hyper::StatusCode;
+use std::convert::TryInto;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+#[test]
+fn rate_limiter_test() {
+    let now = Instant::now();
+    let mut limiter = RateLimiter::new(2, Duration::from_secs(10));
+    assert!(limiter.check(DELETED_BUNDLE, LIFETIME_EXPIRED, now));
+    assert!(limiter.check(DELETED_BUNDLE, LIFETIME_EXPIRED, now));
+    assert!(!limiter.check(DELETED_BUNDLE, LIFETIME_EXPIRED, now));
+    assert_eq!(limiter.count(DELETED_BUNDLE, LIFETIME_EXPIRED), 2);
+
+    // every status and reason has its own budget
+    assert!(limiter.check(DELETED_BUNDLE, HOP_LIMIT_EXCEEDED, now));
+    assert!(limiter.check(DELIVERED_BUNDLE, NO_INFORMATION, now));
+
+    // a new window starts after the interval
+    let later = now + Duration::from_secs(10);
+    assert!(limiter.check(DELETED_BUNDLE, LIFETIME_EXPIRED, later));
+    assert_eq!(limiter.count(DELETED_BUNDLE, LIFETIME_EXPIRED), 1);
+
+    let mut unlimited = RateLimiter::new(0, Duration::from_secs(10));
+    assert!((0..1000).all(|_| unlimited.check(RECEIVED_BUNDLE, NO_INFORMATION, now)));
+}
+
+#[test]
+fn report_to_filter_test() {
+    let mut cfg = StatusReportConfig::default();
+    assert!(cfg.report_to_allowed("dtn://node1/reports"));
+
+    cfg.report_to = vec!["dtn://node1/*".into(), "ipn:23.*".into()];
+    assert!(cfg.report_to_allowed("dtn://node1/reports"));
+    assert!(cfg.report_to_allowed("ipn:23.7"));
+    assert!(!cfg.report_to_allowed("dtn://node2/reports"));
+    assert!(!cfg.report_to_allowed("ipn:42.7"));
+}
+
+fn bundle_with_reports(reports: &str) -> Bundle {
+    common::bundle_from_to("dtn://node1/", "dtn://node2/incoming", reports, b"hello")
+}
+
+#[test]
+fn requested_reports_test() {
+    let bndl = bundle_with_reports("deletion");
+    assert!(is_requested(&bndl, DELETED_BUNDLE));
+    assert!(!is_requested(&bndl, RECEIVED_BUNDLE));
+    assert!(!is_requested(&bndl, FORWARDED_BUNDLE));
+    assert!(!is_requested(&bndl, DELIVERED_BUNDLE));
+}
+
+#[test]
+fn status_report_config_test() {
+    let cfg = DtnConfig::from(PathBuf::from("../../examples/dtn7.toml.example"));
+    assert_eq!(cfg.status_reports, StatusReportConfig::default());
+    assert!(cfg.status_reports.report_to.is_empty());
+}
+
+#[test]
+fn status_report_log_test() {
+    let bndl = bundle_with_reports("forwarding,delivery");
+    let node2 = "dtn://node2/".try_into().unwrap();
+    let node3 = "dtn://node3/incoming".try_into().unwrap();
+
+    let delivered = ReportedStatus::from_report(
+        &new_status_report(&bndl, DELIVERED_BUNDLE, NO_INFORMATION),
+        &node3,
+    );
+    assert_eq!(delivered.len(), 1);
+    assert_eq!(delivered[0].status, "delivered");
+    assert_eq!(delivered[0].reason, "no_information");
+    assert_eq!(delivered[0].node, "dtn://node3/");
+    assert!(delivered[0].time.is_none());
+
+    let mut forwarded = ReportedStatus::from_report(
+        &new_status_report(&bndl, FORWARDED_BUNDLE, NO_INFORMATION),
+        &node2,
+    );
+    forwarded[0].reported = delivered[0].reported - 1;
+
+    let mut log = StatusReportLog::new(2);
+    log.record(&bndl.id(), delivered);
+    log.record(&bndl.id(), forwarded);
+    let timeline = log.timeline(&bndl.id()).unwrap();
+    let statuses: Vec<&str> = timeline.iter().map(|r| r.status.as_str()).collect();
+    assert_eq!(statuses, vec!["forwarded", "delivered"]);
+
+    // the oldest bundle is dropped once the capacity is reached
+    log.record("b2", Vec::new());
+    log.record("b3", Vec::new());
+    assert_eq!(log.len(), 2);
+    assert!(log.timeline(&bndl.id()).is_none());
+    assert_eq!(log.bundles(), vec!["b2".to_string(), "b3".to_string()]);
+}
+
+#[tokio::test]
+async fn status_reports_http_test() {
+    let server = hyper::Server::bind(&"127.0.0.1:0".parse().unwrap())
+        .serve(router().into_make_service_with_connect_info::<SocketAddr>());
+    let client = DtnClient::with_host_and_port("127.0.0.1", server.local_addr().port());
+    tokio::spawn(server);
+
+    let bndl = bundle_with_reports("deletion");
+    let report = new_status_report(&bndl, DELETED_BUNDLE, LIFETIME_EXPIRED);
+    dtn7::STATUS_REPORTS.lock().record(
+        &bndl.id(),
+        ReportedStatus::from_report(&report, &"dtn://node2/".try_into().unwrap()),
+    );
+
+    assert!(client
+        .reported_bundles()
+        .await
+        .unwrap()
+        .contains(&bndl.id()));
+    let timeline = client.status_reports(&bndl.id()).await.unwrap();
+    assert_eq!(timeline.len(), 1);
+    assert_eq!(timeline[0].status, "deleted");
+    assert_eq!(timeline[0].reason, "lifetime_expired");
+    assert_eq!(timeline[0].node, "dtn://node2/");
+
+    match client.status_reports("dtn://node1/-1-0").await {
+        Err(ClientError::Status { status, .. }) => assert_eq!(status, StatusCode::NOT_FOUND),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
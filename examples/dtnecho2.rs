@@ -1,10 +1,11 @@
-use anyhow::{bail, Result};
+use anyhow::Result;
+use bp7::flags::{BundleControlFlags, BundleValidation};
 use clap::Parser;
-use dtn7_plus::client::DtnClient;
-use dtn7_plus::client::{Message, WsRecvData, WsSendData};
+use dtn7::client::DtnClient;
+use futures::StreamExt;
 use std::io::Write;
 use std::str::from_utf8;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 /// A simple Bundle Protocol 7 Echo Service for Delay Tolerant Networking
 #[derive(Parser, Debug)]
@@ -22,22 +23,21 @@ struct Args {
     #[clap(short, long)]
     verbose: bool,
 }
-fn main() -> Result<()> {
+#[tokio::main]
+async fn main() -> Result<()> {
     let args = Args::parse();
 
     let port = if let Ok(env_port) = std::env::var("DTN_WEB_PORT") {
-        env_port // string is fine no need to parse number
+        env_port.parse::<u16>().expect("invalid port number")
     } else {
-        args.port.to_string()
+        args.port
     };
     let localhost = if args.ipv6 { "[::1]" } else { "127.0.0.1" };
 
-    let client = DtnClient::with_host_and_port(
-        localhost.into(),
-        port.parse::<u16>().expect("invalid port number"),
-    );
+    let client = DtnClient::with_host_and_port(localhost, port);
     let endpoint: String = if client
         .local_node_id()
+        .await
         .expect("failed to get local node id")
         .scheme()
         == "dtn"
@@ -46,79 +46,50 @@ fn main() -> Result<()> {
     } else {
         "7".into()
     };
-    client.register_application_endpoint(&endpoint)?;
+    client.register_application_endpoint(&endpoint).await?;
 
-    let mut wscon = client.ws()?;
+    let mut bundles = client.subscribe(&endpoint).await?;
+    println!("[*] subscribed to {}", endpoint);
 
-    wscon.write_text("/data")?;
-    let msg = wscon.read_text()?;
-    if msg.starts_with("200 tx mode: data") {
-        println!("[*] {}", msg);
-    } else {
-        bail!("[!] Failed to set mode to `data`");
-    }
+    while let Some(bndl) = bundles.next().await {
+        let bndl = bndl?;
+        let now = Instant::now();
+        let data = bndl.payload().cloned().unwrap_or_default();
 
-    wscon.write_text(&format!("/subscribe {}", endpoint))?;
-    let msg = wscon.read_text()?;
-    if msg.starts_with("200 subscribed") {
-        println!("[*] {}", msg);
-    } else {
-        bail!("[!] Failed to subscribe to service");
-    }
+        if args.verbose {
+            eprintln!(
+                "Bundle-Id: {} // From: {} / To: {}",
+                bndl.id(),
+                bndl.primary.source,
+                bndl.primary.destination
+            );
 
-    loop {
-        let msg = wscon.read_message()?;
-        match &msg {
-            Message::Text(txt) => {
-                if txt.starts_with("200") {
-                    if args.verbose {
-                        eprintln!("[<] {}", txt);
-                    }
-                } else {
-                    println!("[!] {}", txt);
-                }
-            }
-            Message::Binary(bin) => {
-                let now = Instant::now();
-                let recv_data: WsRecvData =
-                    serde_cbor::from_slice(bin).expect("Error decoding WsRecvData from server");
-
-                if args.verbose {
-                    eprintln!(
-                        "Bundle-Id: {} // From: {} / To: {}",
-                        recv_data.bid, recv_data.src, recv_data.dst
-                    );
-
-                    if let Ok(data_str) = from_utf8(&recv_data.data) {
-                        eprintln!("Data: {}", data_str);
-                    }
-                } else {
-                    print!(".");
-                    std::io::stdout().flush().unwrap();
-                }
-                // flip src and destionation
-                let src = recv_data.dst.to_owned();
-                let dst = recv_data.src.to_owned();
-                // construct response with copied payload
-                let echo_response = WsSendData {
-                    src,
-                    dst,
-                    delivery_notification: false,
-                    lifetime: 3600 * 24 * 1000,
-                    data: recv_data.data,
-                };
-                wscon
-                    .write_binary(&serde_cbor::to_vec(&echo_response)?)
-                    .expect("error sending echo response");
-                if args.verbose {
-                    println!("Processing bundle took {:?}", now.elapsed());
-                }
-            }
-            _ => {
-                if args.verbose {
-                    eprintln!("[<] Other: {:?}", msg);
-                }
+            if let Ok(data_str) = from_utf8(&data) {
+                eprintln!("Data: {}", data_str);
             }
+        } else {
+            print!(".");
+            std::io::stdout().flush().unwrap();
+        }
+        // flip src and destination and construct response with copied payload
+        let mut echo_response = bp7::bundle::new_std_payload_bundle(
+            bndl.primary.destination.clone(),
+            bndl.primary.source.clone(),
+            data,
+        );
+        echo_response
+            .primary
+            .bundle_control_flags
+            .set(BundleControlFlags::BUNDLE_MUST_NOT_FRAGMENTED);
+        echo_response.primary.creation_timestamp = client.creation_timestamp().await?;
+        echo_response.primary.lifetime = Duration::from_secs(3600 * 24);
+        bundles
+            .send_bundle(&mut echo_response)
+            .await
+            .expect("error sending echo response");
+        if args.verbose {
+            println!("Processing bundle took {:?}", now.elapsed());
         }
     }
+    Ok(())
 }
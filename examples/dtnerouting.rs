@@ -187,6 +187,18 @@ async fn serve(strategy: String, addr: &str) -> Result<()> {
                         .insert(packet.eid.node().unwrap(), packet.peer);
                     info!("Peer Encountered: {}", packet.eid.node().unwrap());
                 }
+                // If the address or CLAs of a peer changed replace the stored one.
+                Packet::PeerChanged(packet) => {
+                    PEERS
+                        .lock()
+                        .unwrap()
+                        .insert(packet.eid.node().unwrap(), packet.peer);
+                    info!(
+                        "Peer Changed: {} {:?}",
+                        packet.eid.node().unwrap(),
+                        packet.changes
+                    );
+                }
                 // If a peer is dropped remove it from the peer list.
                 Packet::DroppedPeer(packet) => {
                     PEERS
@@ -195,34 +207,24 @@ async fn serve(strategy: String, addr: &str) -> Result<()> {
                         .remove(packet.eid.node().unwrap().as_str());
                     info!("Peer Dropped: {}", packet.eid.node().unwrap());
                 }
-                Packet::SendingFailed(packet) => {
-                    if strategy == "epidemic" {
-                        epidemic_router
-                            .sending_failed(packet.bid.as_str(), packet.cla_sender.as_str());
-                    }
+                Packet::SendingFailed(packet) if strategy == "epidemic" => {
+                    epidemic_router.sending_failed(packet.bid.as_str(), packet.cla_sender.as_str());
                 }
                 Packet::Error(error) => {
                     error!("Error received: {}", error.reason);
                 }
-                Packet::Timeout(packet) => {
-                    if strategy == "epidemic" {
-                        epidemic_router.sending_timeout(packet.bp.id.as_str());
-                    }
+                Packet::Timeout(packet) if strategy == "epidemic" => {
+                    epidemic_router.sending_timeout(packet.bp.id.as_str());
                 }
-                Packet::IncomingBundle(packet) => {
-                    if strategy == "epidemic" {
-                        if let Some(eid) = packet.bndl.previous_node() {
-                            if let Some(node_name) = eid.node() {
-                                epidemic_router.incoming_bundle(&packet.bndl.id(), &node_name);
-                            }
-                        };
-                    }
+                Packet::IncomingBundle(packet) if strategy == "epidemic" => {
+                    if let Some(eid) = packet.bndl.previous_node() {
+                        if let Some(node_name) = eid.node() {
+                            epidemic_router.incoming_bundle(&packet.bndl.id(), &node_name);
+                        }
+                    };
                 }
-                Packet::IncomingBundleWithoutPreviousNode(packet) => {
-                    if strategy == "epidemic" {
-                        epidemic_router
-                            .incoming_bundle(packet.bid.as_str(), packet.node_name.as_str());
-                    }
+                Packet::IncomingBundleWithoutPreviousNode(packet) if strategy == "epidemic" => {
+                    epidemic_router.incoming_bundle(packet.bid.as_str(), packet.node_name.as_str());
                 }
                 Packet::RequestSenderForBundle(packet) => {
                     info!("got bundle pack: {}", packet.bp);
@@ -1,15 +1,15 @@
-use anyhow::{bail, Result};
+use anyhow::Result;
+use bp7::flags::{BundleControlFlags, BundleValidation};
+use bp7::EndpointID;
 use clap::Parser;
-use dtn7_plus::client::{DtnClient, DtnWsConnection};
-use dtn7_plus::client::{Message, WsRecvData, WsSendData};
+use dtn7::client::{BundleSubscription, DtnClient};
+use futures::StreamExt;
 use humantime::parse_duration;
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
-use std::net::TcpStream;
 use std::str::from_utf8;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{convert::TryInto, io::Write};
-use std::{thread, time};
 
 fn get_random_payload(length: usize) -> String {
     thread_rng()
@@ -18,27 +18,24 @@ fn get_random_payload(length: usize) -> String {
         .map(char::from)
         .collect()
 }
-#[derive(Debug, PartialEq)]
-enum PingState {
-    ReadyToSend,
-    Receiving,
-}
 
-fn send_ping(
-    wscon: &mut DtnWsConnection<TcpStream>,
+async fn send_ping(
+    client: &DtnClient,
+    bundles: &mut BundleSubscription,
     length: usize,
-    src: &str,
-    dst: &str,
+    src: &EndpointID,
+    dst: &EndpointID,
 ) -> Result<()> {
     let payload = get_random_payload(length);
-    let ping = WsSendData {
-        src: src.to_owned(),
-        dst: dst.to_owned(),
-        delivery_notification: false,
-        lifetime: 3600 * 24 * 1000,
-        data: payload.as_bytes().to_vec(),
-    };
-    wscon.write_binary(&serde_cbor::to_vec(&ping)?)
+    let mut ping =
+        bp7::bundle::new_std_payload_bundle(src.clone(), dst.clone(), payload.as_bytes().to_vec());
+    ping.primary
+        .bundle_control_flags
+        .set(BundleControlFlags::BUNDLE_MUST_NOT_FRAGMENTED);
+    ping.primary.creation_timestamp = client.creation_timestamp().await?;
+    ping.primary.lifetime = Duration::from_secs(3600 * 24);
+    bundles.send_bundle(&mut ping).await?;
+    Ok(())
 }
 
 /// A simple Bundle Protocol 7 Ping Tool for Delay Tolerant Networking
@@ -73,131 +70,72 @@ struct Args {
     #[clap(short, long, default_value = "2000y")]
     timeout: String,
 }
-fn main() -> Result<()> {
+#[tokio::main]
+async fn main() -> Result<()> {
     let args = Args::parse();
 
     let port = if let Ok(env_port) = std::env::var("DTN_WEB_PORT") {
-        env_port // string is fine no need to parse number
+        env_port.parse::<u16>().expect("invalid port number")
     } else {
-        args.port.to_string()
+        args.port
     };
     let localhost = if args.ipv6 { "[::1]" } else { "127.0.0.1" };
 
     let timeout: Duration = parse_duration(&args.timeout)?;
 
-    let _dst_eid: bp7::EndpointID = args.dst.clone().try_into()?;
+    let dst: EndpointID = args.dst.clone().try_into()?;
 
     let mut successful_pings = 0;
 
-    let client = DtnClient::with_host_and_port(
-        localhost.into(),
-        port.parse::<u16>().expect("invalid port number"),
-    );
-    let endpoint: String = if client
+    let client = DtnClient::with_host_and_port(localhost, port);
+    let local_node = client
         .local_node_id()
-        .expect("failed to get local node id")
-        .scheme()
-        == "dtn"
-    {
+        .await
+        .expect("failed to get local node id");
+    let endpoint: String = if local_node.scheme() == "dtn" {
         "ping".into()
     } else {
         "7007".into()
     };
-    client.register_application_endpoint(&endpoint)?;
+    client.register_application_endpoint(&endpoint).await?;
 
-    let stream = std::net::TcpStream::connect(format!("127.0.0.1:{}", port))?;
-    stream.set_read_timeout(Some(timeout))?;
-    let mut wscon = client.ws_custom(stream)?;
-    //let mut wscon = client.ws()?;
-
-    wscon.write_text("/data")?;
-    let msg = wscon.read_text()?;
-    if msg.starts_with("200 tx mode: data") {
-        println!("[*] {}", msg);
-    } else {
-        bail!("[!] Failed to set mode to `data`");
-    }
-
-    wscon.write_text(&format!("/subscribe {}", endpoint))?;
-    let msg = wscon.read_text()?;
-    if msg.starts_with("200 subscribed") {
-        println!("[*] {}", msg);
-    } else {
-        bail!("[!] Failed to subscribe to service");
-    }
-    let src = format!("{}{}", client.local_node_id()?, endpoint);
+    let mut bundles = client.subscribe(&endpoint).await?;
+    println!("[*] subscribed to {}", endpoint);
+    let src: EndpointID = format!("{}{}", local_node, endpoint).try_into()?;
 
     let mut seq_num: u64 = 0;
-    let mut state = PingState::ReadyToSend;
-    let mut sent_time = time::Instant::now();
-    println!("\nPING: {} -> {}", src, args.dst);
-    loop {
-        if state == PingState::ReadyToSend {
-            if args.count > 0 && seq_num == args.count as u64 {
-                break;
-            }
-            send_ping(&mut wscon, args.size, &src, &args.dst)?;
-            seq_num += 1;
-            sent_time = time::Instant::now();
-            println!("[>] #{} size={}", seq_num, args.size);
-            std::io::stdout().flush().unwrap();
-            state = PingState::Receiving;
-        }
-
-        let msg_result = wscon.read_message();
-
-        if let Err(err) = msg_result {
-            let tunstenite_err = err.downcast::<tungstenite::Error>()?;
-
-            if let tungstenite::error::Error::Io(io_err) = tunstenite_err {
-                if io_err.kind() == std::io::ErrorKind::WouldBlock {
-                    state = PingState::ReadyToSend;
-                    println!("[!] *** timeout ***");
-                    continue;
-                } else {
-                    anyhow::bail!(io_err);
-                }
-            } else {
-                anyhow::bail!(tunstenite_err);
+    println!("\nPING: {} -> {}", src, dst);
+    while args.count < 0 || seq_num < args.count as u64 {
+        send_ping(&client, &mut bundles, args.size, &src, &dst).await?;
+        seq_num += 1;
+        let sent_time = Instant::now();
+        println!("[>] #{} size={}", seq_num, args.size);
+        std::io::stdout().flush().unwrap();
+
+        let bndl = match tokio::time::timeout(timeout, bundles.next()).await {
+            Ok(Some(bndl)) => bndl?,
+            Ok(None) => anyhow::bail!("connection to dtnd closed"),
+            Err(_) => {
+                println!("[!] *** timeout ***");
+                continue;
             }
-        }
-        let msg = msg_result.unwrap();
-        match &msg {
-            Message::Text(txt) => {
-                if txt.starts_with("200") {
-                    if args.verbose {
-                        eprintln!("[<] {}", txt);
-                    }
-                } else {
-                    println!("[!] {}", txt);
-                }
-            }
-            Message::Binary(bin) => {
-                let recv_data: WsRecvData =
-                    serde_cbor::from_slice(bin).expect("Error decoding WsRecvData from server");
-
-                println!("[<] #{} : {:?}", seq_num, sent_time.elapsed());
-                successful_pings += 1;
-                if args.verbose {
-                    eprintln!(
-                        "Bundle-Id: {} // From: {} / To: {}",
-                        recv_data.bid, recv_data.src, recv_data.dst
-                    );
-
-                    if let Ok(data_str) = from_utf8(&recv_data.data) {
-                        eprintln!("Data: {}", data_str);
-                    }
-                }
-
-                thread::sleep(time::Duration::from_secs(1));
-                state = PingState::ReadyToSend;
-            }
-            _ => {
-                if args.verbose {
-                    eprintln!("[<] Other: {:?}", msg);
-                }
+        };
+        println!("[<] #{} : {:?}", seq_num, sent_time.elapsed());
+        successful_pings += 1;
+        if args.verbose {
+            eprintln!(
+                "Bundle-Id: {} // From: {} / To: {}",
+                bndl.id(),
+                bndl.primary.source,
+                bndl.primary.destination
+            );
+
+            if let Some(Ok(data_str)) = bndl.payload().map(|data| from_utf8(data)) {
+                eprintln!("Data: {}", data_str);
             }
         }
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
     }
 
     println!(